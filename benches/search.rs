@@ -0,0 +1,115 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Benchmarks for searching and splitting, run with `cargo bench`.
+//!
+//! Each operation is measured on three corpora of about 64 KiB: ASCII
+//! text, text with multibyte characters, and ASCII text with
+//! non-Unicode sequences scattered through it.  The search needles only
+//! occur at the end, so searches scan the whole corpus, while split and
+//! matches produce many pieces.  Compare runs with and without
+//! `--features simd` to evaluate the searchers.
+
+#![feature(test)]
+
+extern crate osstring_prototype;
+extern crate test;
+
+use osstring_prototype::{OsStr, OsString};
+use test::{black_box, Bencher};
+
+const CORPUS_LEN: usize = 64 * 1024;
+
+const ASCII_WORDS: &'static [&'static str] =
+    &["the ", "quick ", "brown ", "fox ", "jumps ", "over ", "the ", "lazy ", "dog\n"];
+
+const MIXED_WORDS: &'static [&'static str] =
+    &["thé ", "quïck ", "brøwn ", "fox ", "jümps ", "övér ", "☃ ", "lazy ", "dög 💩\n"];
+
+/// A non-Unicode sequence in the native encoding: an invalid byte on
+/// Unix and an unpaired surrogate on Windows.
+fn non_unicode() -> &'static OsStr {
+    OsStr::from_encoded_bytes(b"\xFF")
+        .or_else(|| OsStr::from_encoded_bytes(b"\xED\xA0\x80"))
+        .unwrap()
+}
+
+/// Repeats `words` up to `CORPUS_LEN` bytes, inserting `invalid` after
+/// every fifth word if given, and ends with `"#needle"`.
+fn build_corpus(words: &[&str], invalid: Option<&OsStr>) -> OsString {
+    let mut corpus = OsString::with_capacity(CORPUS_LEN + 16);
+    let mut i = 0;
+    while corpus.len() < CORPUS_LEN {
+        corpus.push(words[i % words.len()]);
+        if let Some(invalid) = invalid {
+            if i % 5 == 4 {
+                corpus.push(invalid);
+            }
+        }
+        i += 1;
+    }
+    corpus.push("#needle");
+    corpus
+}
+
+fn ascii_corpus() -> OsString {
+    build_corpus(ASCII_WORDS, None)
+}
+
+fn mixed_corpus() -> OsString {
+    build_corpus(MIXED_WORDS, None)
+}
+
+fn invalid_corpus() -> OsString {
+    build_corpus(ASCII_WORDS, Some(non_unicode()))
+}
+
+/// Times `f` on `corpus`, reporting throughput in corpus bytes.
+fn bench_corpus<F, R>(b: &mut Bencher, corpus: OsString, f: F)
+where F: Fn(&OsStr) -> R {
+    b.bytes = corpus.len() as u64;
+    b.iter(|| f(black_box(&corpus[..])));
+}
+
+/// Defines a module of benchmarks, one per corpus, for each operation.
+macro_rules! benches {
+    ($($name:ident: |$s:ident| $body:expr;)*) => { $(
+        mod $name {
+            use osstring_prototype::OsStr;
+            use test::Bencher;
+            use super::{ascii_corpus, bench_corpus, invalid_corpus, mixed_corpus};
+
+            #[bench]
+            fn ascii(b: &mut Bencher) {
+                bench_corpus(b, ascii_corpus(), |$s: &OsStr| $body)
+            }
+
+            #[bench]
+            fn mixed(b: &mut Bencher) {
+                bench_corpus(b, mixed_corpus(), |$s: &OsStr| $body)
+            }
+
+            #[bench]
+            fn invalid(b: &mut Bencher) {
+                bench_corpus(b, invalid_corpus(), |$s: &OsStr| $body)
+            }
+        }
+    )* }
+}
+
+benches! {
+    contains_os: |s| s.contains_os("needle");
+    find: |s| s.find("needle");
+    find_char: |s| s.find('#');
+    split: |s| s.split(' ').count();
+    split_os_str: |s| s.split(OsStr::new("fox")).count();
+    matches: |s| s.matches("fox").count();
+    to_string_lossy: |s| s.to_string_lossy().len();
+}
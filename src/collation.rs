@@ -0,0 +1,56 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Pluggable collation for OS strings.
+//!
+//! Sorting strings the way users expect depends on their locale, so
+//! this crate does not collate text itself.  Instead, an application
+//! implements `Collator` on top of a library such as ICU or an
+//! operating system service, and `OsStr::cmp_with` uses it for the
+//! Unicode parts of the strings while ordering non-Unicode data in a
+//! fixed way.
+//!
+//! Closures taking two `&str`s implement `Collator`.
+//!
+//! ```
+//! use std::cmp::Ordering;
+//! use osstring_prototype::collation::Collator;
+//! use osstring_prototype::os_str::OsStr;
+//!
+//! /// Orders text ignoring case.
+//! struct Caseless;
+//!
+//! impl Collator for Caseless {
+//!     fn compare(&self, a: &str, b: &str) -> Ordering {
+//!         a.to_lowercase().cmp(&b.to_lowercase())
+//!     }
+//! }
+//!
+//! assert_eq!(OsStr::new("apple").cmp_with("Banana", &Caseless), Ordering::Less);
+//! let by_length = |a: &str, b: &str| a.len().cmp(&b.len());
+//! assert_eq!(OsStr::new("apple").cmp_with("Banana", &by_length), Ordering::Less);
+//! ```
+
+use std::cmp::Ordering;
+
+/// Compares Unicode text, for `OsStr::cmp_with`.
+pub trait Collator {
+    /// Compares two strings.
+    ///
+    /// This must be a total order, but may treat different strings as
+    /// equal.
+    fn compare(&self, a: &str, b: &str) -> Ordering;
+}
+
+impl<F: Fn(&str, &str) -> Ordering> Collator for F {
+    fn compare(&self, a: &str, b: &str) -> Ordering {
+        self(a, b)
+    }
+}
@@ -0,0 +1,440 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! An `OsString` that stores short strings without allocating.
+//!
+//! A `CompactOsString` holds up to `INLINE_CAPACITY` bytes of encoded
+//! data (see `OsStr::as_encoded_bytes`) inside the value itself, and
+//! only moves to the heap when it grows past that.  This suits the
+//! many short strings, like path components, that a program handles
+//! at once.  It derefs to `OsStr`, so the whole borrowed string API is
+//! available.
+
+use std::borrow::Borrow;
+use std::cmp;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops;
+use std::string::String;
+
+use os_str::{OsStr, OsString};
+use wtf8::encode_utf8_raw;
+
+/// The number of encoded bytes a `CompactOsString` can hold without
+/// allocating.
+pub const INLINE_CAPACITY: usize = 23;
+
+/// An owned OS string that is stored inline when it is short.
+#[derive(Clone)]
+pub struct CompactOsString {
+    repr: Repr,
+}
+
+#[derive(Clone)]
+enum Repr {
+    Inline { len: u8, bytes: [u8; INLINE_CAPACITY] },
+    Heap(OsString),
+}
+
+impl CompactOsString {
+    /// Constructs a new empty `CompactOsString`.  This does not
+    /// allocate.
+    pub fn new() -> CompactOsString {
+        CompactOsString { repr: Repr::Inline { len: 0, bytes: [0; INLINE_CAPACITY] } }
+    }
+
+    /// Constructs a new empty `CompactOsString` that can hold at least
+    /// `capacity` bytes without reallocating.  This only allocates if
+    /// `capacity` is greater than `INLINE_CAPACITY`.
+    pub fn with_capacity(capacity: usize) -> CompactOsString {
+        if capacity <= INLINE_CAPACITY {
+            CompactOsString::new()
+        } else {
+            CompactOsString { repr: Repr::Heap(OsString::with_capacity(capacity)) }
+        }
+    }
+
+    /// Returns true if the string is stored inline.
+    pub fn is_inline(&self) -> bool {
+        match self.repr {
+            Repr::Inline { .. } => true,
+            Repr::Heap(_) => false,
+        }
+    }
+
+    /// Returns the number of bytes the string can hold without
+    /// reallocating.
+    pub fn capacity(&self) -> usize {
+        match self.repr {
+            Repr::Inline { .. } => INLINE_CAPACITY,
+            Repr::Heap(ref string) => string.capacity(),
+        }
+    }
+
+    /// Reserves capacity for at least `additional` more bytes, moving
+    /// the string to the heap if it no longer fits inline.
+    pub fn reserve(&mut self, additional: usize) {
+        if self.len() + additional > self.capacity() {
+            self.make_heap(additional).reserve(additional);
+        }
+    }
+
+    /// Moves the string back inline if it fits, and otherwise shrinks
+    /// its heap buffer to fit.
+    pub fn shrink_to_fit(&mut self) {
+        let inline = match self.repr {
+            Repr::Inline { .. } => return,
+            Repr::Heap(ref mut string) => {
+                if string.len() > INLINE_CAPACITY {
+                    string.shrink_to_fit();
+                    return;
+                }
+                CompactOsString::from(&**string)
+            }
+        };
+        *self = inline;
+    }
+
+    /// Coerces to an `OsStr` slice.
+    pub fn as_os_str(&self) -> &OsStr {
+        match self.repr {
+            // The inline bytes are always a whole string in the
+            // platform encoding.
+            Repr::Inline { len, ref bytes } => unsafe {
+                OsStr::from_encoded_bytes_unchecked(&bytes[..len as usize])
+            },
+            Repr::Heap(ref string) => string,
+        }
+    }
+
+    /// Converts to an `OsString`, allocating if the string is stored
+    /// inline.
+    pub fn into_os_string(self) -> OsString {
+        match self.repr {
+            Repr::Inline { .. } => self.as_os_str().to_os_string(),
+            Repr::Heap(string) => string,
+        }
+    }
+
+    /// Extends the string with the given `&OsStr` slice.
+    pub fn push<T: AsRef<OsStr>>(&mut self, s: T) {
+        let s = s.as_ref();
+        if let Repr::Inline { ref mut len, ref mut bytes } = self.repr {
+            let start = *len as usize;
+            let end = start + s.len();
+            if end <= INLINE_CAPACITY && !may_join_surrogates(&bytes[..start], s) {
+                bytes[start..end].copy_from_slice(s.as_encoded_bytes());
+                *len = end as u8;
+                return;
+            }
+        }
+        self.make_heap(s.len()).push(s)
+    }
+
+    /// Extends the string with the given `&str` slice.
+    pub fn push_str(&mut self, s: &str) {
+        self.push(OsStr::new(s))
+    }
+
+    /// Appends the given `char` to the end of the string.
+    pub fn push_char(&mut self, c: char) {
+        let mut bytes = [0; 4];
+        let len = encode_utf8_raw(c as u32, &mut bytes).unwrap();
+        self.push(OsStr::from_encoded_bytes(&bytes[..len]).unwrap())
+    }
+
+    /// Shortens the string to `new_len` bytes, without reallocating.
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as `OsString::truncate`.
+    pub fn truncate(&mut self, new_len: usize) {
+        if let Repr::Heap(ref mut string) = self.repr {
+            return string.truncate(new_len);
+        }
+        assert!(new_len <= self.len(), "new_len is greater than the length");
+        assert!(self.get(0..new_len).is_some(), "new_len is not on a code point boundary");
+        if let Repr::Inline { ref mut len, .. } = self.repr {
+            *len = new_len as u8;
+        }
+    }
+
+    /// Truncates the string to zero length.  A heap buffer is kept for
+    /// reuse, as by `OsString::clear`.
+    pub fn clear(&mut self) {
+        match self.repr {
+            Repr::Inline { ref mut len, .. } => *len = 0,
+            Repr::Heap(ref mut string) => string.clear(),
+        }
+    }
+
+    /// Moves the string to the heap, with room for `additional` more
+    /// bytes.
+    fn make_heap(&mut self, additional: usize) -> &mut OsString {
+        if self.is_inline() {
+            let mut string = OsString::with_capacity(self.len() + additional);
+            string.push(self.as_os_str());
+            self.repr = Repr::Heap(string);
+        }
+        match self.repr {
+            Repr::Heap(ref mut string) => string,
+            Repr::Inline { .. } => unreachable!(),
+        }
+    }
+}
+
+/// Returns true if pushing `s` onto `existing` could join an unpaired
+/// lead surrogate at its end with an unpaired trail surrogate at the
+/// start of `s`, which `OsString::push` handles.  This is conservative.
+fn may_join_surrogates(existing: &[u8], s: &OsStr) -> bool {
+    cfg!(any(windows, feature = "force-wtf8")) &&
+        existing.len() >= 3 && existing[existing.len() - 3] == 0xED &&
+        s.as_encoded_bytes().first() == Some(&0xED)
+}
+
+impl Default for CompactOsString {
+    fn default() -> CompactOsString {
+        CompactOsString::new()
+    }
+}
+
+impl<'a> From<&'a OsStr> for CompactOsString {
+    fn from(s: &'a OsStr) -> CompactOsString {
+        let mut string = CompactOsString::with_capacity(s.len());
+        string.push(s);
+        string
+    }
+}
+
+impl<'a> From<&'a str> for CompactOsString {
+    fn from(s: &'a str) -> CompactOsString {
+        CompactOsString::from(OsStr::new(s))
+    }
+}
+
+/// Moves a short string inline, freeing its buffer.
+impl From<OsString> for CompactOsString {
+    fn from(s: OsString) -> CompactOsString {
+        if s.len() <= INLINE_CAPACITY {
+            CompactOsString::from(&*s)
+        } else {
+            CompactOsString { repr: Repr::Heap(s) }
+        }
+    }
+}
+
+impl From<String> for CompactOsString {
+    fn from(s: String) -> CompactOsString {
+        CompactOsString::from(OsString::from(s))
+    }
+}
+
+impl From<CompactOsString> for OsString {
+    fn from(s: CompactOsString) -> OsString {
+        s.into_os_string()
+    }
+}
+
+impl ops::Deref for CompactOsString {
+    type Target = OsStr;
+
+    fn deref(&self) -> &OsStr {
+        self.as_os_str()
+    }
+}
+
+impl AsRef<OsStr> for CompactOsString {
+    fn as_ref(&self) -> &OsStr {
+        self
+    }
+}
+
+impl Borrow<OsStr> for CompactOsString {
+    fn borrow(&self) -> &OsStr {
+        self
+    }
+}
+
+impl fmt::Debug for CompactOsString {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_os_str(), formatter)
+    }
+}
+
+impl PartialEq for CompactOsString {
+    fn eq(&self, other: &CompactOsString) -> bool {
+        self.as_os_str() == other.as_os_str()
+    }
+}
+
+impl Eq for CompactOsString {}
+
+impl PartialEq<OsStr> for CompactOsString {
+    fn eq(&self, other: &OsStr) -> bool {
+        self.as_os_str() == other
+    }
+}
+
+impl PartialEq<OsString> for CompactOsString {
+    fn eq(&self, other: &OsString) -> bool {
+        self.as_os_str() == other.as_os_str()
+    }
+}
+
+impl PartialEq<str> for CompactOsString {
+    fn eq(&self, other: &str) -> bool {
+        self.as_os_str() == other
+    }
+}
+
+impl<'a> PartialEq<&'a str> for CompactOsString {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_os_str() == *other
+    }
+}
+
+impl PartialOrd for CompactOsString {
+    fn partial_cmp(&self, other: &CompactOsString) -> Option<cmp::Ordering> {
+        self.as_os_str().partial_cmp(other.as_os_str())
+    }
+}
+
+impl Ord for CompactOsString {
+    fn cmp(&self, other: &CompactOsString) -> cmp::Ordering {
+        self.as_os_str().cmp(other.as_os_str())
+    }
+}
+
+impl Hash for CompactOsString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_os_str().hash(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::prelude::v1::*;
+    use std::iter;
+    use std::mem;
+    use super::*;
+    use os_str::{OsStr, OsString};
+
+    fn repeat(c: char, count: usize) -> String {
+        iter::repeat(c).take(count).collect()
+    }
+
+    fn non_unicode_osstring() -> OsString {
+        if cfg!(any(windows, feature = "force-wtf8")) {
+            OsStr::from_encoded_bytes(b"\xED\xA0\x80").unwrap().to_os_string()
+        } else {
+            OsStr::from_encoded_bytes(b"\xFF").unwrap().to_os_string()
+        }
+    }
+
+    #[test]
+    fn size() {
+        assert!(mem::size_of::<CompactOsString>() <= 32);
+    }
+
+    #[test]
+    fn inline() {
+        let mut string = CompactOsString::new();
+        assert!(string.is_empty());
+        assert!(string.is_inline());
+        assert_eq!(string.capacity(), INLINE_CAPACITY);
+
+        string.push("dir");
+        string.push_char('/');
+        string.push_str("é");
+        string.push(&non_unicode_osstring());
+        assert!(string.is_inline());
+        let mut expected = OsString::from("dir/é");
+        expected.push(&non_unicode_osstring());
+        assert_eq!(string, expected);
+        assert_eq!(string.into_os_string(), expected);
+
+        let string = CompactOsString::from(repeat('x', INLINE_CAPACITY));
+        assert!(string.is_inline());
+        assert_eq!(string.find('x'), Some(0));
+    }
+
+    #[test]
+    fn spill() {
+        let mut string = CompactOsString::from(repeat('x', INLINE_CAPACITY));
+        string.push_char('y');
+        assert!(!string.is_inline());
+        assert_eq!(string.len(), INLINE_CAPACITY + 1);
+        assert!(string.ends_with('y'));
+
+        string.truncate(3);
+        assert_eq!(string, "xxx");
+        assert!(!string.is_inline());
+        string.shrink_to_fit();
+        assert!(string.is_inline());
+        assert_eq!(string, "xxx");
+
+        let mut string = CompactOsString::with_capacity(100);
+        assert!(!string.is_inline());
+        assert!(string.capacity() >= 100);
+        string.push("a");
+        string.clear();
+        assert!(string.is_empty());
+
+        let mut string = CompactOsString::new();
+        string.reserve(INLINE_CAPACITY);
+        assert!(string.is_inline());
+        string.reserve(INLINE_CAPACITY + 1);
+        assert!(!string.is_inline());
+    }
+
+    #[test]
+    fn conversions() {
+        let long = OsString::from(repeat('a', 40));
+        let string = CompactOsString::from(long.clone());
+        assert!(!string.is_inline());
+        assert_eq!(OsString::from(string), long);
+
+        let string = CompactOsString::from(OsString::from("short"));
+        assert!(string.is_inline());
+        assert_eq!(&*string, OsStr::new("short"));
+        assert_eq!(format!("{:?}", string), format!("{:?}", OsStr::new("short")));
+        assert_eq!(CompactOsString::from("a") < CompactOsString::from("b"), true);
+    }
+
+    #[test]
+    fn join_surrogates() {
+        if !cfg!(any(windows, feature = "force-wtf8")) {
+            return;
+        }
+        let lead = OsStr::from_encoded_bytes(b"\xED\xA0\xBD").unwrap();
+        let trail = OsStr::from_encoded_bytes(b"\xED\xB2\xA9").unwrap();
+        let mut string = CompactOsString::from(lead);
+        string.push(trail);
+        let mut expected = lead.to_os_string();
+        expected.push(trail);
+        assert_eq!(string, expected);
+        assert_eq!(string.to_str(), Some("💩"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn truncate_past_end() {
+        CompactOsString::from("abc").truncate(4);
+    }
+
+    #[test]
+    fn truncate_inline() {
+        let mut string = CompactOsString::from("aé");
+        string.truncate(3);
+        assert_eq!(string, "aé");
+        string.truncate(1);
+        assert_eq!(string, "a");
+    }
+}
@@ -0,0 +1,230 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Helpers for reading environment variables as OS strings.
+
+use std::prelude::v1::*;
+use std::env;
+use std::error;
+use std::ffi;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::string::ParseError;
+
+use os_str::{OsStr, OsString, OsStrUtf8Error};
+
+/// Parses a value from an `OsStr`.
+///
+/// This is the analogue of `FromStr` for values that can be read from
+/// non-Unicode data.
+pub trait FromOsStr: Sized {
+    /// The error returned if parsing fails.
+    type Err;
+
+    /// Parses `s` into a value of this type.
+    fn from_os_str(s: &OsStr) -> Result<Self, Self::Err>;
+}
+
+/// The error returned when a `FromStr` type is parsed from an `OsStr`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseOsStrError<E> {
+    /// The string is not valid Unicode.
+    NotUnicode(OsStrUtf8Error),
+    /// The string is Unicode, but `FromStr` rejected it.
+    Invalid(E),
+}
+
+impl<E: fmt::Display> fmt::Display for ParseOsStrError<E> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseOsStrError::NotUnicode(ref err) => fmt::Display::fmt(err, formatter),
+            ParseOsStrError::Invalid(ref err) => fmt::Display::fmt(err, formatter),
+        }
+    }
+}
+
+impl<E: error::Error> error::Error for ParseOsStrError<E> {
+    fn description(&self) -> &str {
+        match *self {
+            ParseOsStrError::NotUnicode(ref err) => err.description(),
+            ParseOsStrError::Invalid(ref err) => err.description(),
+        }
+    }
+}
+
+macro_rules! from_os_str_via_from_str {
+    ($($t:ty)*) => { $(
+        impl FromOsStr for $t {
+            type Err = ParseOsStrError<<$t as FromStr>::Err>;
+
+            fn from_os_str(s: &OsStr) -> Result<$t, Self::Err> {
+                let s = try!(s.try_to_str().map_err(ParseOsStrError::NotUnicode));
+                s.parse().map_err(ParseOsStrError::Invalid)
+            }
+        }
+    )* }
+}
+
+from_os_str_via_from_str!{i8 i16 i32 i64 isize u8 u16 u32 u64 usize f32 f64 bool char String}
+from_os_str_via_from_str!{IpAddr Ipv4Addr Ipv6Addr SocketAddr}
+
+impl FromOsStr for OsString {
+    type Err = ParseError;
+
+    fn from_os_str(s: &OsStr) -> Result<OsString, ParseError> {
+        Ok(s.to_os_string())
+    }
+}
+
+impl FromOsStr for ffi::OsString {
+    type Err = ParseError;
+
+    fn from_os_str(s: &OsStr) -> Result<ffi::OsString, ParseError> {
+        Ok(s.to_os_string().into())
+    }
+}
+
+impl FromOsStr for PathBuf {
+    type Err = ParseError;
+
+    fn from_os_str(s: &OsStr) -> Result<PathBuf, ParseError> {
+        Ok(ffi::OsString::from(s.to_os_string()).into())
+    }
+}
+
+/// Fetches the environment variable `key` and parses it.
+///
+/// Returns `None` if the variable is not set.
+///
+/// # Example
+///
+/// ```
+/// use osstring_prototype::env;
+///
+/// std::env::set_var("OSSTRING_EXAMPLE_THREADS", "4");
+/// let threads = env::var_os_parsed::<usize, _>("OSSTRING_EXAMPLE_THREADS");
+/// assert_eq!(threads, Some(Ok(4)));
+/// ```
+pub fn var_os_parsed<T, K>(key: K) -> Option<Result<T, T::Err>>
+where T: FromOsStr, K: AsRef<ffi::OsStr> {
+    env::var_os(key).map(|value| T::from_os_str(&OsString::from(value)))
+}
+
+/// Splits the `PATH` environment variable into its entries, following
+/// the platform's rules as `OsStr::split_search_paths` does.
+///
+/// Returns an empty vector if `PATH` is not set.
+pub fn split_path_var() -> Vec<PathBuf> {
+    match env::var_os("PATH") {
+        Some(value) => {
+            OsString::from(value).split_search_paths()
+                .map(|path| PathBuf::from(ffi::OsString::from(path)))
+                .collect()
+        }
+        None => Vec::new(),
+    }
+}
+
+/// Returns an iterator over the environment variables of the current
+/// process as `os_str::OsString` pairs.
+///
+/// This is `std::env::vars_os` with keys and values converted so that
+/// the string methods of this crate can be used on them directly.
+pub fn vars_os() -> VarsOs {
+    VarsOs { inner: env::vars_os() }
+}
+
+/// An iterator over the environment variables of the current process.
+///
+/// This is created by `env::vars_os`.
+pub struct VarsOs {
+    inner: env::VarsOs,
+}
+
+impl Iterator for VarsOs {
+    type Item = (OsString, OsString);
+
+    fn next(&mut self) -> Option<(OsString, OsString)> {
+        self.inner.next().map(|(key, value)| (key.into(), value.into()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::prelude::v1::*;
+    use std::env;
+    use std::path::PathBuf;
+    use super::*;
+    use os_str::{OsStr, OsString};
+
+    #[cfg(unix)]
+    fn non_unicode_osstring() -> OsString {
+        use unix::OsStringExt;
+        OsString::from_vec(vec![b'4', 0xFF])
+    }
+
+    #[cfg(windows)]
+    fn non_unicode_osstring() -> OsString {
+        use windows::OsStringExt;
+        OsString::from_wide(&[0x34, 0xD800])
+    }
+
+    #[test]
+    fn from_os_str() {
+        assert_eq!(u16::from_os_str(OsStr::new("443")), Ok(443));
+        assert_eq!(bool::from_os_str(OsStr::new("true")), Ok(true));
+        assert!(match u16::from_os_str(OsStr::new("x")) {
+            Err(ParseOsStrError::Invalid(_)) => true,
+            _ => false,
+        });
+        assert_eq!(OsString::from_os_str(OsStr::new("a b")), Ok(OsString::from("a b")));
+        assert_eq!(PathBuf::from_os_str(OsStr::new("a/b")), Ok(PathBuf::from("a/b")));
+
+        let string = non_unicode_osstring();
+        let err = u16::from_os_str(&string).unwrap_err();
+        assert_eq!(err, ParseOsStrError::NotUnicode(string.try_to_str().unwrap_err()));
+    }
+
+    #[test]
+    fn var_os_parsed() {
+        env::set_var("OSSTRING_PROTOTYPE_TEST_PARSED", "8080");
+        assert_eq!(super::var_os_parsed::<u16, _>("OSSTRING_PROTOTYPE_TEST_PARSED"),
+                   Some(Ok(8080)));
+        assert!(super::var_os_parsed::<bool, _>("OSSTRING_PROTOTYPE_TEST_PARSED")
+                .unwrap().is_err());
+        assert_eq!(super::var_os_parsed::<u16, _>("OSSTRING_PROTOTYPE_TEST_UNSET"), None);
+    }
+
+    #[test]
+    fn split_path_var() {
+        let expected = match env::var_os("PATH") {
+            Some(path) => env::split_paths(&path).collect::<Vec<_>>(),
+            None => Vec::new(),
+        };
+        assert_eq!(super::split_path_var(), expected);
+    }
+
+    #[test]
+    fn vars_os() {
+        env::set_var("OSSTRING_PROTOTYPE_TEST_VARS", "a,b");
+        let (_, value) = super::vars_os()
+            .find(|&(ref key, _)| {
+                key.strip_prefix("OSSTRING_PROTOTYPE_") == Some(OsStr::new("TEST_VARS"))
+            })
+            .unwrap();
+        assert_eq!(value.split(',').collect::<Vec<_>>(), ["a", "b"]);
+    }
+}
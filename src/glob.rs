@@ -0,0 +1,216 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Shell-style wildcard matching on encoded OS strings.
+//!
+//! Both the pattern and the name are split into units: a code point,
+//! or a single byte of non-Unicode data.  `?` matches any one unit,
+//! `*` matches any sequence of units, and `[...]` matches one unit
+//! from a set of units and ranges, negated by a leading `!` or `^`.
+//! A `]` first in a set and a `-` first or last in a set are
+//! literals.  There are no escapes, but `[*]`, `[?]` and `[[]` match
+//! the literal characters.  An unterminated `[` is a literal.
+
+use std::str;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Unit {
+    Char(u32),
+    Byte(u8),
+}
+
+/// Splits the first unit off the nonempty `bytes`, returning it and
+/// its length.  Surrogates are only code points in WTF-8.
+fn next_unit(bytes: &[u8], wtf8: bool) -> (Unit, usize) {
+    let first = bytes[0];
+    let width = match first {
+        0x00...0x7F => return (Unit::Char(first as u32), 1),
+        0xC2...0xDF => 2,
+        0xE0...0xEF => 3,
+        0xF0...0xF4 => 4,
+        _ => return (Unit::Byte(first), 1),
+    };
+    if bytes.len() < width { return (Unit::Byte(first), 1); }
+    if let Ok(s) = str::from_utf8(&bytes[..width]) {
+        return (Unit::Char(s.chars().next().unwrap() as u32), width);
+    }
+    if wtf8 && first == 0xED && bytes[1] & 0xE0 == 0xA0 && bytes[2] & 0xC0 == 0x80 {
+        let surrogate = 0xD000 | (bytes[1] as u32 & 0x3F) << 6 | bytes[2] as u32 & 0x3F;
+        return (Unit::Char(surrogate), 3);
+    }
+    (Unit::Byte(first), 1)
+}
+
+fn in_range(unit: Unit, low: Unit, high: Unit) -> bool {
+    match (unit, low, high) {
+        (Unit::Char(c), Unit::Char(low), Unit::Char(high)) => low <= c && c <= high,
+        (Unit::Byte(b), Unit::Byte(low), Unit::Byte(high)) => low <= b && b <= high,
+        _ => false,
+    }
+}
+
+/// Matches `unit` against the bracket expression at the start of
+/// `pattern`.  Returns whether it matched and the length of the
+/// expression, or `None` if the expression is not terminated.
+fn match_bracket(pattern: &[u8], unit: Unit, wtf8: bool) -> Option<(bool, usize)> {
+    let mut i = 1;
+    let negated = i < pattern.len() && (pattern[i] == b'!' || pattern[i] == b'^');
+    if negated { i += 1; }
+
+    let mut matched = false;
+    let set_start = i;
+    loop {
+        if i == pattern.len() { return None; }
+        if pattern[i] == b']' && i != set_start {
+            return Some((matched != negated, i + 1));
+        }
+        let (low, len) = next_unit(&pattern[i..], wtf8);
+        i += len;
+        let mut high = low;
+        if i + 1 < pattern.len() && pattern[i] == b'-' && pattern[i + 1] != b']' {
+            let (end, len) = next_unit(&pattern[i + 1..], wtf8);
+            high = end;
+            i += 1 + len;
+        }
+        matched |= in_range(unit, low, high);
+    }
+}
+
+/// Returns true if all of `name` matches `pattern`.  `wtf8` selects
+/// the Windows encoding.
+pub fn matches(pattern: &[u8], name: &[u8], wtf8: bool) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // The positions just after the most recent `*` and where it
+    // stopped matching
+    let mut star = None;
+    loop {
+        if p < pattern.len() {
+            if pattern[p] == b'*' {
+                p += 1;
+                star = Some((p, n));
+                continue;
+            }
+            if n < name.len() {
+                let (unit, len) = next_unit(&name[n..], wtf8);
+                let matched = match pattern[p] {
+                    b'?' => Some(1),
+                    b'[' => match match_bracket(&pattern[p..], unit, wtf8) {
+                        Some((true, pattern_len)) => Some(pattern_len),
+                        Some((false, _)) => None,
+                        None if unit == Unit::Char('[' as u32) => Some(1),
+                        None => None,
+                    },
+                    _ => {
+                        let (pattern_unit, pattern_len) = next_unit(&pattern[p..], wtf8);
+                        if pattern_unit == unit { Some(pattern_len) } else { None }
+                    }
+                };
+                if let Some(pattern_len) = matched {
+                    p += pattern_len;
+                    n += len;
+                    continue;
+                }
+            }
+        } else if n == name.len() {
+            return true;
+        }
+
+        // Mismatch.  Let the last `*` match one more unit and retry.
+        match star {
+            Some((star_p, star_n)) if star_n < name.len() => {
+                let (_, len) = next_unit(&name[star_n..], wtf8);
+                star = Some((star_p, star_n + len));
+                p = star_p;
+                n = star_n + len;
+            }
+            _ => return false,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::matches;
+
+    fn glob(pattern: &str, name: &str) -> bool {
+        let result = matches(pattern.as_bytes(), name.as_bytes(), false);
+        assert_eq!(matches(pattern.as_bytes(), name.as_bytes(), true), result);
+        result
+    }
+
+    #[test]
+    fn glob_literal() {
+        assert!(glob("", ""));
+        assert!(!glob("", "a"));
+        assert!(!glob("a", ""));
+        assert!(glob("Makefile", "Makefile"));
+        assert!(!glob("Makefile", "makefile"));
+        assert!(glob("é", "é"));
+    }
+
+    #[test]
+    fn glob_wildcards() {
+        assert!(glob("*", ""));
+        assert!(glob("*", "anything"));
+        assert!(glob("*.rs", "lib.rs"));
+        assert!(!glob("*.rs", "lib.rs.bak"));
+        assert!(glob("*.tar.*", "x.tar.gz"));
+        assert!(glob("a*b*c", "aXbYbZc"));
+        assert!(!glob("a*b*c", "aXbYbZ"));
+        assert!(glob("**a", "bba"));
+        assert!(glob("?", "é"));
+        assert!(!glob("?", "é!"));
+        assert!(glob("??", "é!"));
+        assert!(glob("file?.txt", "file1.txt"));
+        assert!(!glob("file?.txt", "file.txt"));
+    }
+
+    #[test]
+    fn glob_brackets() {
+        assert!(glob("[abc]", "b"));
+        assert!(!glob("[abc]", "d"));
+        assert!(glob("[a-c]x", "cx"));
+        assert!(glob("[!a-c]", "d"));
+        assert!(glob("[^a-c]", "d"));
+        assert!(!glob("[!a-c]", "a"));
+        assert!(glob("[]]", "]"));
+        assert!(glob("[!]]", "a"));
+        assert!(glob("[a-]", "-"));
+        assert!(glob("[-a]", "-"));
+        assert!(glob("[*?[]", "*"));
+        assert!(glob("[*?[]", "["));
+        assert!(glob("[à-ö]", "é"));
+        assert!(!glob("[à-ö]", "e"));
+        assert!(glob("[", "["));
+        assert!(glob("[ab", "[ab"));
+        assert!(!glob("[ab", "a"));
+        assert!(glob("*.[ch]", "main.c"));
+    }
+
+    #[test]
+    fn glob_non_unicode() {
+        assert!(matches(b"a?b", b"a\xFFb", false));
+        assert!(matches(b"a*", b"a\xFF\xFE", false));
+        assert!(matches(b"[\xFE-\xFF]", b"\xFF", false));
+        assert!(!matches(b"[a-z]", b"\xFF", false));
+        assert!(matches(b"?", b"\xC3", false));
+        assert!(!matches(b"?", b"\xC3(", false));
+        assert!(matches(b"??", b"\xC3(", false));
+
+        // An unpaired surrogate is a code point in WTF-8, but three
+        // bytes on Unix.
+        let surrogate = b"\xED\xA0\x80";
+        assert!(matches(b"?", surrogate, true));
+        assert!(!matches(b"?", surrogate, false));
+        assert!(matches(b"???", surrogate, false));
+        assert!(matches(b"[\xED\xA0\x80-\xED\xBF\xBF]", surrogate, true));
+    }
+}
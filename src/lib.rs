@@ -4,6 +4,7 @@
 #![feature(ascii)]
 #![feature(box_syntax)]
 #![feature(decode_utf16)]
+#![feature(fused)]
 #![feature(iter_arith)]
 #![feature(pattern)]
 #![feature(slice_patterns)]
@@ -13,31 +14,173 @@
 #![feature(copy_from_slice)]
 
 #![cfg_attr(test, feature(osstring_simple_functions))]
+#![cfg_attr(not(any(feature = "std", test)), feature(alloc, collections, unicode))]
 
 // This somewhat silly looking sequence is avoid the automatic prelude
 // import so that code behaves more like it does in libstd itself.
 #![no_std]
+#[cfg(any(feature = "std", test))]
 #[macro_use]
 extern crate std;
 
+#[cfg(not(any(feature = "std", test)))]
+extern crate alloc;
+#[cfg(not(any(feature = "std", test)))]
+#[macro_use]
+extern crate collections;
+#[cfg(not(any(feature = "std", test)))]
+extern crate rustc_unicode;
+
+// Without libstd, the `std::` paths used throughout the crate resolve
+// to this facade over the crates libstd is built from.
+#[cfg(not(any(feature = "std", test)))]
+mod std {
+    pub use core::{cell, cmp, hash, iter, mem, ops, option, result};
+    pub use alloc::{boxed, rc};
+    pub use collections::{borrow, fmt, slice, str, string, vec};
+    pub use rustc_unicode::char;
+
+    pub mod sync {
+        pub use alloc::arc::Arc;
+    }
+
+    pub mod ascii {
+        use collections::vec::Vec;
+
+        /// A copy of libstd's `AsciiExt`.
+        pub trait AsciiExt {
+            type Owned;
+            fn is_ascii(&self) -> bool;
+            fn to_ascii_uppercase(&self) -> Self::Owned;
+            fn to_ascii_lowercase(&self) -> Self::Owned;
+            fn eq_ignore_ascii_case(&self, other: &Self) -> bool;
+            fn make_ascii_uppercase(&mut self);
+            fn make_ascii_lowercase(&mut self);
+        }
+
+        impl AsciiExt for u8 {
+            type Owned = u8;
+            fn is_ascii(&self) -> bool {
+                *self < 0x80
+            }
+            fn to_ascii_uppercase(&self) -> u8 {
+                let mut byte = *self;
+                byte.make_ascii_uppercase();
+                byte
+            }
+            fn to_ascii_lowercase(&self) -> u8 {
+                let mut byte = *self;
+                byte.make_ascii_lowercase();
+                byte
+            }
+            fn eq_ignore_ascii_case(&self, other: &u8) -> bool {
+                self.to_ascii_lowercase() == other.to_ascii_lowercase()
+            }
+            fn make_ascii_uppercase(&mut self) {
+                if let b'a'...b'z' = *self { *self -= b'a' - b'A' }
+            }
+            fn make_ascii_lowercase(&mut self) {
+                if let b'A'...b'Z' = *self { *self += b'a' - b'A' }
+            }
+        }
+
+        impl AsciiExt for [u8] {
+            type Owned = Vec<u8>;
+            fn is_ascii(&self) -> bool {
+                self.iter().all(|b| b.is_ascii())
+            }
+            fn to_ascii_uppercase(&self) -> Vec<u8> {
+                self.iter().map(|b| b.to_ascii_uppercase()).collect()
+            }
+            fn to_ascii_lowercase(&self) -> Vec<u8> {
+                self.iter().map(|b| b.to_ascii_lowercase()).collect()
+            }
+            fn eq_ignore_ascii_case(&self, other: &[u8]) -> bool {
+                self.len() == other.len() &&
+                    self.iter().zip(other).all(|(a, b)| a.eq_ignore_ascii_case(b))
+            }
+            fn make_ascii_uppercase(&mut self) {
+                for byte in self {
+                    byte.make_ascii_uppercase();
+                }
+            }
+            fn make_ascii_lowercase(&mut self) {
+                for byte in self {
+                    byte.make_ascii_lowercase();
+                }
+            }
+        }
+    }
+
+    pub mod prelude {
+        pub mod v1 {
+            pub use core::prelude::v1::*;
+            pub use alloc::boxed::Box;
+            pub use collections::borrow::ToOwned;
+            pub use collections::string::{String, ToString};
+            pub use collections::vec::Vec;
+        }
+    }
+}
+
+#[cfg(feature = "aho-corasick")]
+extern crate aho_corasick;
+#[cfg(feature = "simd")]
+extern crate memchr;
+#[cfg(feature = "arbitrary")]
+extern crate quickcheck;
+#[cfg(feature = "regex")]
+extern crate regex;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "unicode-segmentation")]
+extern crate unicode_segmentation;
+#[cfg(feature = "unicode-width")]
+extern crate unicode_width;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+
 mod sys_common;
 
+#[macro_use]
+pub mod os_write;
+
+#[cfg(all(feature = "std", any(windows, all(unix, not(feature = "force-wtf8")))))]
+pub mod env;
+
+pub mod collation;
+pub mod compact_os_string;
+mod glob;
+pub mod os_pattern;
+pub mod portable;
 pub mod slice_concat_ext;
 mod slice_searcher;
 mod split_bytes;
+// The std integration reinterprets some of libstd's OS strings as this
+// crate's, so it is only built where both use the same representation.
+#[cfg(all(feature = "std", any(windows, all(unix, not(feature = "force-wtf8")))))]
 pub mod std_integration;
 mod str;
 pub mod os_str;
+#[cfg(feature = "aho-corasick")]
+pub mod os_str_set;
+pub mod os_string_builder;
 pub mod unix;
 mod utf8_sections;
 pub mod windows;
 mod wtf8;
 
 pub use os_str::{OsStr, OsString};
-pub use std_integration::{OsStrPrototyping, OsStringPrototyping, OsStrSection};
+pub use wtf8::{Wtf8, Wtf8Buf, CodePoint, Wtf8Error};
+pub use utf8_sections::{Utf8Event, Utf8Scanner};
+#[cfg(all(feature = "std", any(windows, all(unix, not(feature = "force-wtf8")))))]
+pub use std_integration::{OsStrPrototyping, OsStringPrototyping, PathPrototyping, OsStrSection, Popped};
 
 pub mod prelude {
-    pub use super::OsStrSection;
-    pub use super::{OsStrPrototyping, OsStringPrototyping};
+    #[cfg(all(feature = "std", any(windows, all(unix, not(feature = "force-wtf8")))))]
+    pub use super::{OsStrSection, Popped};
+    #[cfg(all(feature = "std", any(windows, all(unix, not(feature = "force-wtf8")))))]
+    pub use super::{OsStrPrototyping, OsStringPrototyping, PathPrototyping};
+    pub use super::os_write::OsWrite;
     pub use super::slice_concat_ext::LocalSliceConcatExt;
 }
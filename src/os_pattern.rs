@@ -0,0 +1,574 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Patterns for searching OS strings.
+//!
+//! This is the `OsStr` analogue of `core::str::pattern`.  The `str`
+//! patterns of libstd (`char`, `&str`, `&String`, `&&str`, `&[char]` and
+//! closures) are `OsPattern`s, matching only within the Unicode sections
+//! of the haystack.  `&OsStr` and
+//! `&OsString` are also `OsPattern`s, and match the encoded data
+//! directly, so they can match non-Unicode data as well.
+//!
+//! Searchers work on the encoded bytes of the haystack (see
+//! `OsStr::as_encoded_bytes`) and report matches as byte ranges,
+//! which are always valid arguments to `OsStr::split_at`.
+//!
+//! # Custom patterns
+//!
+//! Other crates can implement `OsPattern` for their own types, which
+//! then work with `find`, `split`, `matches` and the other pattern
+//! methods of `OsStr`.
+//!
+//! ```
+//! use osstring_prototype::os_pattern::{OsPattern, OsSearcher};
+//! use osstring_prototype::os_str::OsStr;
+//!
+//! /// Matches runs of ASCII digits.
+//! struct Digits;
+//!
+//! struct DigitsSearcher<'a> {
+//!     haystack: &'a [u8],
+//!     position: usize,
+//! }
+//!
+//! impl<'a> OsPattern<'a> for Digits {
+//!     type Searcher = DigitsSearcher<'a>;
+//!
+//!     fn into_os_searcher(self, haystack: &'a [u8]) -> DigitsSearcher<'a> {
+//!         DigitsSearcher { haystack: haystack, position: 0 }
+//!     }
+//! }
+//!
+//! impl<'a> OsSearcher<'a> for DigitsSearcher<'a> {
+//!     fn haystack(&self) -> &'a [u8] {
+//!         self.haystack
+//!     }
+//!
+//!     fn next_match(&mut self) -> Option<(usize, usize)> {
+//!         let is_digit = |b: &u8| b'0' <= *b && *b <= b'9';
+//!         let start = match self.haystack[self.position..].iter().position(&is_digit) {
+//!             Some(index) => self.position + index,
+//!             None => return None,
+//!         };
+//!         let len = self.haystack[start..].iter().take_while(|b| is_digit(b)).count();
+//!         self.position = start + len;
+//!         Some((start, self.position))
+//!     }
+//! }
+//!
+//! let name = OsStr::new("IMG_0042-v2.jpg");
+//! assert_eq!(name.find(Digits), Some(4));
+//! assert_eq!(name.split(Digits).collect::<Vec<_>>(), ["IMG_", "-v", ".jpg"]);
+//! assert_eq!(name.matches(Digits).collect::<Vec<_>>(), ["0042", "2"]);
+//! ```
+
+use core::str::pattern::{DoubleEndedSearcher, Pattern, ReverseSearcher, SearchStep, Searcher};
+use std::string::String;
+
+use slice_searcher::{self, ByteSearcher};
+use utf8_sections::Utf8Sections;
+
+/// A pattern that can be searched for in an `OsStr`.
+pub trait OsPattern<'a>: Sized {
+    /// The searcher for this pattern.
+    type Searcher: OsSearcher<'a>;
+
+    /// Creates a searcher for `self` in the encoded bytes of an
+    /// `OsStr`.
+    fn into_os_searcher(self, haystack: &'a [u8]) -> Self::Searcher;
+
+    /// Returns the byte this pattern matches if it only matches a
+    /// single ASCII byte.
+    ///
+    /// ASCII bytes are never part of a non-Unicode section, so such
+    /// patterns can be found with a byte scan over the whole haystack
+    /// instead of searching each section.
+    fn as_ascii_byte(&self) -> Option<u8> {
+        None
+    }
+}
+
+/// A searcher for an `OsPattern`, finding non-overlapping matches
+/// from the front of the haystack.
+///
+/// Matches must start and end on code point boundaries (see
+/// `OsStr::get`), since the `OsStr` methods slice the haystack at
+/// them.  On Unix every index is a boundary.
+pub trait OsSearcher<'a> {
+    /// Returns the haystack being searched.
+    fn haystack(&self) -> &'a [u8];
+
+    /// Returns the byte range of the next match, if any.
+    fn next_match(&mut self) -> Option<(usize, usize)>;
+
+    /// Returns the end of a match starting at the beginning of the
+    /// haystack, if there is one.  This must be called on a new
+    /// searcher, and leaves it in an unspecified state.
+    fn match_prefix(&mut self) -> Option<usize> {
+        match self.next_match() {
+            Some((0, end)) => Some(end),
+            _ => None,
+        }
+    }
+}
+
+/// A searcher that can also find matches from the back of the
+/// haystack.
+pub trait ReverseOsSearcher<'a>: OsSearcher<'a> {
+    /// Returns the byte range of the next match from the back, if
+    /// any.
+    fn next_match_back(&mut self) -> Option<(usize, usize)>;
+
+    /// Returns the start of a match ending at the end of the
+    /// haystack, if there is one.  This must be called on a new
+    /// searcher, and leaves it in an unspecified state.
+    fn match_suffix(&mut self) -> Option<usize> {
+        let len = self.haystack().len();
+        match self.next_match_back() {
+            Some((start, end)) if end == len => Some(start),
+            _ => None,
+        }
+    }
+}
+
+/// A marker for reverse searchers that find the same matches as
+/// forward searches, so they can be used from both ends at once.
+pub trait DoubleEndedOsSearcher<'a>: ReverseOsSearcher<'a> {}
+
+
+impl<'a> OsPattern<'a> for char {
+    type Searcher = UnicodeSearcher<'a, char>;
+
+    fn into_os_searcher(self, haystack: &'a [u8]) -> UnicodeSearcher<'a, char> {
+        UnicodeSearcher::new(haystack, self.as_ascii_byte(), self)
+    }
+
+    fn as_ascii_byte(&self) -> Option<u8> {
+        if (*self as u32) < 0x80 { Some(*self as u8) } else { None }
+    }
+}
+
+impl<'a, 'b> OsPattern<'a> for &'b str {
+    type Searcher = UnicodeSearcher<'a, &'b str>;
+
+    fn into_os_searcher(self, haystack: &'a [u8]) -> UnicodeSearcher<'a, &'b str> {
+        UnicodeSearcher::new(haystack, self.as_ascii_byte(), self)
+    }
+
+    fn as_ascii_byte(&self) -> Option<u8> {
+        // A single byte of UTF-8 is always ASCII.
+        if self.len() == 1 { Some(self.as_bytes()[0]) } else { None }
+    }
+}
+
+// The other `str` patterns always search each section.
+macro_rules! unicode_pattern {
+    ($([$($params:tt)*] $ty:ty where [$($bounds:tt)*];)*) => { $(
+        impl<'a, $($params)*> OsPattern<'a> for $ty where $($bounds)* {
+            type Searcher = UnicodeSearcher<'a, $ty>;
+
+            fn into_os_searcher(self, haystack: &'a [u8]) -> UnicodeSearcher<'a, $ty> {
+                UnicodeSearcher::new(haystack, None, self)
+            }
+        }
+    )* }
+}
+
+unicode_pattern!{
+    ['b] &'b String where [];
+    ['b, 'c] &'b &'c str where [];
+    ['b] &'b [char] where [];
+    [F] F where [F: FnMut(char) -> bool + Clone];
+}
+
+/// The searcher for `str` patterns, which searches each Unicode
+/// section of the haystack separately.
+pub struct UnicodeSearcher<'a, P> where P: Pattern<'a> {
+    haystack: &'a [u8],
+    ascii_byte: Option<u8>,
+    // Replaces all the other state for ASCII byte patterns
+    byte_searcher: Option<ByteSearcher<'a>>,
+    sections: Utf8Sections<'a>,
+    pat: P,
+    front_searcher: Option<P::Searcher>,
+    back_searcher: Option<P::Searcher>,
+    front_section: (usize, &'a str),
+    back_section: (usize, &'a str),
+}
+
+impl<'a, P> Clone for UnicodeSearcher<'a, P>
+where P: Pattern<'a> + Clone, P::Searcher: Clone {
+    fn clone(&self) -> Self {
+        UnicodeSearcher {
+            haystack: self.haystack,
+            ascii_byte: self.ascii_byte,
+            byte_searcher: self.byte_searcher.clone(),
+            sections: self.sections.clone(),
+            pat: self.pat.clone(),
+            front_searcher: self.front_searcher.clone(),
+            back_searcher: self.back_searcher.clone(),
+            front_section: self.front_section,
+            back_section: self.back_section,
+        }
+    }
+}
+
+impl<'a, P> UnicodeSearcher<'a, P> where P: Pattern<'a> + Clone {
+    fn new(haystack: &'a [u8], ascii_byte: Option<u8>, pat: P) -> Self {
+        let mut searcher = UnicodeSearcher {
+            haystack: haystack,
+            ascii_byte: ascii_byte,
+            byte_searcher: ascii_byte.map(|byte| ByteSearcher::new(haystack, byte)),
+            sections: Utf8Sections::new(haystack),
+            pat: pat,
+            front_searcher: None,
+            back_searcher: None,
+            front_section: (0, ""),
+            back_section: (haystack.len(), ""),
+        };
+        if haystack.is_empty() {
+            // The ends already meet, in the only (empty) section.
+            searcher.front_searcher = Some(searcher.pat.clone().into_searcher(""));
+        }
+        searcher
+    }
+}
+
+impl<'a, P> OsSearcher<'a> for UnicodeSearcher<'a, P> where P: Pattern<'a> + Clone {
+    fn haystack(&self) -> &'a [u8] {
+        self.haystack
+    }
+
+    fn next_match(&mut self) -> Option<(usize, usize)> {
+        if let Some(ref mut searcher) = self.byte_searcher {
+            return searcher.next_match().map(|start| (start, start + 1));
+        }
+        loop {
+            if self.front_section.0 == self.back_section.0 {
+                // Last section.  Both directions use the same searcher.
+                let (searcher, offset) =
+                    match (self.front_searcher.as_mut(), self.back_searcher.as_mut()) {
+                        (Some(front), None) => (front, self.front_section.0),
+                        (None, Some(back)) => (back, self.back_section.0),
+                        (None, None) => return None,
+                        (Some(_), Some(_)) => unreachable!(),
+                    };
+                return searcher.next_match().map(|(start, end)| (offset + start, offset + end));
+            }
+            if self.front_searcher.is_none() {
+                if let Some(section) = self.sections.next() {
+                    // New section
+                    self.front_section = section;
+                    self.front_searcher = Some(self.pat.clone().into_searcher(section.1));
+                } else {
+                    // Starting the last section.  Go back to the top.
+                    self.front_section = self.back_section;
+                    continue;
+                }
+            }
+
+            if let Some((start, end)) = self.front_searcher.as_mut().unwrap().next_match() {
+                let offset = self.front_section.0;
+                return Some((offset + start, offset + end));
+            }
+
+            // Finished this section
+            self.front_searcher = None;
+        }
+    }
+
+    fn match_prefix(&mut self) -> Option<usize> {
+        if let Some(byte) = self.ascii_byte {
+            return if self.haystack.first() == Some(&byte) { Some(1) } else { None };
+        }
+        let (_, section) = Utf8Sections::new(self.haystack).next().unwrap();
+        match self.pat.clone().into_searcher(section).next() {
+            SearchStep::Match(0, end) => Some(end),
+            _ => None,
+        }
+    }
+}
+
+impl<'a, P> ReverseOsSearcher<'a> for UnicodeSearcher<'a, P>
+where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
+    fn next_match_back(&mut self) -> Option<(usize, usize)> {
+        if let Some(ref mut searcher) = self.byte_searcher {
+            return searcher.next_match_back().map(|start| (start, start + 1));
+        }
+        loop {
+            if self.front_section.0 == self.back_section.0 {
+                // Last section.  Both directions use the same searcher.
+                let (searcher, offset) =
+                    match (self.front_searcher.as_mut(), self.back_searcher.as_mut()) {
+                        (Some(front), None) => (front, self.front_section.0),
+                        (None, Some(back)) => (back, self.back_section.0),
+                        (None, None) => return None,
+                        (Some(_), Some(_)) => unreachable!(),
+                    };
+                return searcher.next_match_back()
+                    .map(|(start, end)| (offset + start, offset + end));
+            }
+            if self.back_searcher.is_none() {
+                if let Some(section) = self.sections.next_back() {
+                    // New section
+                    self.back_section = section;
+                    self.back_searcher = Some(self.pat.clone().into_searcher(section.1));
+                } else {
+                    // Starting the last section.  Go back to the top.
+                    self.back_section = self.front_section;
+                    continue;
+                }
+            }
+
+            if let Some((start, end)) = self.back_searcher.as_mut().unwrap().next_match_back() {
+                let offset = self.back_section.0;
+                return Some((offset + start, offset + end));
+            }
+
+            // Finished this section
+            self.back_searcher = None;
+        }
+    }
+
+    fn match_suffix(&mut self) -> Option<usize> {
+        if let Some(byte) = self.ascii_byte {
+            return if self.haystack.last() == Some(&byte) {
+                Some(self.haystack.len() - 1)
+            } else {
+                None
+            };
+        }
+        let (offset, section) = Utf8Sections::new(self.haystack).next_back().unwrap();
+        match self.pat.clone().into_searcher(section).next_back() {
+            SearchStep::Match(start, end) if end == section.len() => Some(offset + start),
+            _ => None,
+        }
+    }
+}
+
+impl<'a, P> DoubleEndedOsSearcher<'a> for UnicodeSearcher<'a, P>
+where P: Pattern<'a> + Clone, P::Searcher: DoubleEndedSearcher<'a> {}
+
+
+/// The searcher for `OsStr` needles, which compares the encoded
+/// data directly.
+///
+/// An empty needle matches at every code point boundary.
+#[derive(Clone)]
+pub struct EncodedSearcher<'a, 'b> {
+    haystack: &'a [u8],
+    needle: &'b [u8],
+    // The part of the haystack that has not been searched
+    start: usize,
+    end: usize,
+    // Set once an empty needle has matched where the two ends meet
+    finished: bool,
+}
+
+impl<'a, 'b> EncodedSearcher<'a, 'b> {
+    #[doc(hidden)]
+    pub fn new(haystack: &'a [u8], needle: &'b [u8]) -> EncodedSearcher<'a, 'b> {
+        EncodedSearcher {
+            haystack: haystack,
+            needle: needle,
+            start: 0,
+            end: haystack.len(),
+            finished: false,
+        }
+    }
+
+    fn is_boundary(&self, index: usize) -> bool {
+        is_empty_match_boundary(self.haystack, index)
+    }
+}
+
+/// Returns true if an empty needle matches at `index` in the encoded
+/// bytes `haystack`: at either end, or anywhere that is not a UTF-8
+/// continuation byte.
+#[doc(hidden)]
+pub fn is_empty_match_boundary(haystack: &[u8], index: usize) -> bool {
+    index == haystack.len() || haystack[index] & 0xC0 != 0x80
+}
+
+impl<'a, 'b> OsSearcher<'a> for EncodedSearcher<'a, 'b> {
+    fn haystack(&self) -> &'a [u8] {
+        self.haystack
+    }
+
+    fn next_match(&mut self) -> Option<(usize, usize)> {
+        if self.needle.is_empty() {
+            while !self.finished {
+                let position = self.start;
+                if position == self.end {
+                    self.finished = true;
+                } else {
+                    self.start += 1;
+                }
+                if self.is_boundary(position) {
+                    return Some((position, position));
+                }
+            }
+            return None;
+        }
+
+        match slice_searcher::find(&self.haystack[self.start..self.end], self.needle) {
+            Some(index) => {
+                let check = self.start + index;
+                self.start = check + self.needle.len();
+                Some((check, self.start))
+            }
+            None => {
+                self.start = self.end;
+                None
+            }
+        }
+    }
+
+    fn match_prefix(&mut self) -> Option<usize> {
+        if self.haystack.starts_with(self.needle) {
+            Some(self.needle.len())
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, 'b> ReverseOsSearcher<'a> for EncodedSearcher<'a, 'b> {
+    fn next_match_back(&mut self) -> Option<(usize, usize)> {
+        if self.needle.is_empty() {
+            while !self.finished {
+                let position = self.end;
+                if position == self.start {
+                    self.finished = true;
+                } else {
+                    self.end -= 1;
+                }
+                if self.is_boundary(position) {
+                    return Some((position, position));
+                }
+            }
+            return None;
+        }
+
+        match slice_searcher::rfind(&self.haystack[self.start..self.end], self.needle) {
+            Some(index) => {
+                let check = self.start + index;
+                self.end = check;
+                Some((check, check + self.needle.len()))
+            }
+            None => {
+                self.end = self.start;
+                None
+            }
+        }
+    }
+
+    fn match_suffix(&mut self) -> Option<usize> {
+        if self.haystack.ends_with(self.needle) {
+            Some(self.haystack.len() - self.needle.len())
+        } else {
+            None
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::prelude::v1::*;
+    use super::{OsPattern, OsSearcher, ReverseOsSearcher, EncodedSearcher};
+
+    fn forward<'a, S: OsSearcher<'a>>(mut searcher: S) -> Vec<(usize, usize)> {
+        let mut result = Vec::new();
+        while let Some(m) = searcher.next_match() {
+            result.push(m);
+        }
+        result
+    }
+
+    fn backward<'a, S: ReverseOsSearcher<'a>>(mut searcher: S) -> Vec<(usize, usize)> {
+        let mut result = Vec::new();
+        while let Some(m) = searcher.next_match_back() {
+            result.push(m);
+        }
+        result
+    }
+
+    #[test]
+    fn unicode_searcher() {
+        let haystack: &[u8] = b"ab\xFFba\xFFb";
+        assert_eq!(forward('b'.into_os_searcher(haystack)), [(1, 2), (3, 4), (6, 7)]);
+        assert_eq!(backward('b'.into_os_searcher(haystack)), [(6, 7), (3, 4), (1, 2)]);
+        assert_eq!(forward("ba".into_os_searcher(haystack)), [(3, 5)]);
+        assert_eq!(forward("b\u{FF}".into_os_searcher(haystack)), []);
+        assert_eq!('a'.into_os_searcher(haystack).match_prefix(), Some(1));
+        assert_eq!('b'.into_os_searcher(haystack).match_prefix(), None);
+        assert_eq!('b'.into_os_searcher(haystack).match_suffix(), Some(6));
+        assert_eq!('a'.into_os_searcher(haystack).match_suffix(), None);
+
+        // Mixing directions
+        let mut searcher = 'b'.into_os_searcher(haystack);
+        assert_eq!(searcher.next_match(), Some((1, 2)));
+        assert_eq!(searcher.next_match_back(), Some((6, 7)));
+        assert_eq!(searcher.next_match_back(), Some((3, 4)));
+        assert_eq!(searcher.next_match(), None);
+    }
+
+    #[test]
+    fn unicode_searcher_single_byte() {
+        let haystack = "a/é\u{2F}/".as_bytes();
+        assert_eq!(forward("/".into_os_searcher(haystack)), [(1, 2), (4, 5), (5, 6)]);
+        assert_eq!(backward('/'.into_os_searcher(haystack)), [(5, 6), (4, 5), (1, 2)]);
+        assert_eq!(forward('é'.into_os_searcher(haystack)), [(2, 4)]);
+        assert_eq!(forward("a/".into_os_searcher(haystack)), [(0, 2)]);
+        assert_eq!("a".into_os_searcher(haystack).match_prefix(), Some(1));
+        assert_eq!('/'.into_os_searcher(haystack).match_prefix(), None);
+        assert_eq!('/'.into_os_searcher(haystack).match_suffix(), Some(5));
+        assert_eq!("a".into_os_searcher(haystack).match_suffix(), None);
+        assert_eq!(forward('/'.into_os_searcher(b"")), []);
+        assert_eq!('/'.into_os_searcher(b"").match_prefix(), None);
+    }
+
+    #[test]
+    fn encoded_searcher() {
+        let haystack: &[u8] = b"a\xFFaa\xFFaa";
+        let searcher = |needle: &'static [u8]| EncodedSearcher::new(haystack, needle);
+        assert_eq!(forward(searcher(b"a\xFF")), [(0, 2), (3, 5)]);
+        assert_eq!(forward(searcher(b"aa")), [(2, 4), (5, 7)]);
+        assert_eq!(backward(searcher(b"aa")), [(5, 7), (2, 4)]);
+        assert_eq!(forward(searcher(b"b")), []);
+        assert_eq!(searcher(b"a\xFF").match_prefix(), Some(2));
+        assert_eq!(searcher(b"\xFF").match_prefix(), None);
+        assert_eq!(searcher(b"\xFFaa").match_suffix(), Some(4));
+        assert_eq!(searcher(b"\xFF").match_suffix(), None);
+
+        let mut both = searcher(b"a");
+        assert_eq!(both.next_match(), Some((0, 1)));
+        assert_eq!(both.next_match_back(), Some((6, 7)));
+        assert_eq!(forward(both), [(2, 3), (3, 4), (5, 6)]);
+    }
+
+    #[test]
+    fn encoded_searcher_empty_needle() {
+        let haystack = "aé".as_bytes();
+        assert_eq!(forward(EncodedSearcher::new(haystack, b"")), [(0, 0), (1, 1), (3, 3)]);
+        assert_eq!(backward(EncodedSearcher::new(haystack, b"")), [(3, 3), (1, 1), (0, 0)]);
+
+        let mut both = EncodedSearcher::new(haystack, b"");
+        assert_eq!(both.next_match(), Some((0, 0)));
+        assert_eq!(both.next_match_back(), Some((3, 3)));
+        assert_eq!(both.next_match_back(), Some((1, 1)));
+        assert_eq!(both.next_match(), None);
+        assert_eq!(both.next_match_back(), None);
+
+        assert_eq!(forward(EncodedSearcher::new(b"", b"")), [(0, 0)]);
+    }
+}
@@ -8,7 +8,18 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-#[cfg(unix)]
-pub use unix::os_str::{OsStr, OsString, SplitUnicode, OsStrSection, SplitWhitespace, Lines, Split, RSplit, SplitTerminator, RSplitTerminator, SplitN, RSplitN, Matches, RMatches};
-#[cfg(windows)]
-pub use windows::os_str::{OsStr, OsString, SplitUnicode, OsStrSection, SplitWhitespace, Lines, Split, RSplit, SplitTerminator, RSplitTerminator, SplitN, RSplitN, Matches, RMatches};
+// Targets other than Windows use byte strings, unless the `force-wtf8`
+// feature selects the Windows implementation everywhere.  Only Unix and
+// Windows hosts are tested.
+#[cfg(not(any(windows, feature = "force-wtf8")))]
+pub use unix::os_str::{OsStr, OsString, SplitUnicode, Utf8Chunks, InvalidSections, OsStrSection, Popped, SplitWhitespace, Lines, Split, RSplit, SplitTerminator, RSplitTerminator, SplitN, RSplitN, Matches, RMatches, CharsLossy, StrChunksLossy, MatchIndicesOs, RMatchIndicesOs, Display, EscapeDefault, EscapeDebug, ToCStringError, IntoStringError, OsStrUtf8Error, SplitSearchPaths, JoinSearchPathsError, SplitArgsError, join_args, split_args, PortableHash, NaturalSortKey, cmp_natural};
+#[cfg(all(feature = "regex", not(any(windows, feature = "force-wtf8"))))]
+pub use unix::os_str::{RegexMatches, RegexSplit};
+#[cfg(all(feature = "unicode-segmentation", not(any(windows, feature = "force-wtf8"))))]
+pub use unix::os_str::Graphemes;
+#[cfg(any(windows, feature = "force-wtf8"))]
+pub use windows::os_str::{OsStr, OsString, SplitUnicode, Utf8Chunks, InvalidSections, OsStrSection, Popped, SplitWhitespace, Lines, Split, RSplit, SplitTerminator, RSplitTerminator, SplitN, RSplitN, Matches, RMatches, CharsLossy, StrChunksLossy, MatchIndicesOs, RMatchIndicesOs, Display, EscapeDefault, EscapeDebug, ToCStringError, IntoStringError, OsStrUtf8Error, SplitSearchPaths, JoinSearchPathsError, SplitArgsError, join_args, split_args, PortableHash, NaturalSortKey, cmp_natural};
+#[cfg(all(feature = "regex", any(windows, feature = "force-wtf8")))]
+pub use windows::os_str::{RegexMatches, RegexSplit};
+#[cfg(all(feature = "unicode-segmentation", any(windows, feature = "force-wtf8")))]
+pub use windows::os_str::Graphemes;
@@ -38,22 +38,43 @@
 use core::str::pattern::{DoubleEndedSearcher, Pattern, ReverseSearcher};
 
 use std::borrow::{Borrow, Cow, ToOwned};
-use std::ffi::CString;
+use std::boxed::Box;
+use std::ascii::AsciiExt;
+#[cfg(feature = "std")]
+use std::error;
+#[cfg(feature = "std")]
+use std::ffi::{CStr, CString};
 use std::fmt::{self, Debug};
-use std::iter::{Filter, Map};
+use std::char::{ToLowercase, ToUppercase};
+use std::iter::{Filter, FlatMap, FusedIterator, Map};
 use std::mem;
+#[cfg(feature = "rc")]
+use std::rc::Rc;
+#[cfg(feature = "rc")]
+use std::sync::Arc;
 use slice_concat_ext::LocalSliceConcatExt;
-use std::string::String;
+use collation::Collator;
+use os_pattern::{DoubleEndedOsSearcher, EncodedSearcher, OsPattern, OsSearcher, ReverseOsSearcher};
+use os_pattern::is_empty_match_boundary;
+use portable::{DecodeError, Encoding};
+use glob;
+use slice_searcher::{self, ReverseSliceSearcher, SliceSearcher};
+use utf8_sections;
+use std::str::FromStr;
+use std::string::{ParseError, String};
 use std::ops;
+#[cfg(feature = "std")]
+use std::os::raw::c_char;
 use std::cmp;
 use std::hash::{Hash, Hasher};
 use std::vec::Vec;
+use wtf8::encode_utf8_raw;
 
 // #[cfg(unix)]
 // use unix::{self as inner, Buf, Slice};
 // #[cfg(windows)]
 // use windows::{self as inner, Buf, Slice};
-use sys_common::{AsInner, IntoInner, FromInner};
+use sys_common::{AsInner, AsInnerMut, IntoInner, FromInner};
 
 /// Owned, mutable OS strings.
 #[derive(Clone)]
@@ -79,8 +100,11 @@ impl OsString {
     /// On Unix systems, any byte sequence can be successfully
     /// converted into an `OsString`.
     ///
-    /// On Windows system, only UTF-8 byte sequences will successfully
-    /// convert; non UTF-8 data will produce `None`.
+    /// On Windows systems, only well-formed WTF-8 will successfully
+    /// convert, and the vector is then reused without copying.  This
+    /// accepts all UTF-8, as well as the encoding of unpaired
+    /// surrogates produced by `as_encoded_bytes`.  Other data will
+    /// produce `None`.
     pub fn from_bytes<B>(bytes: B) -> Option<OsString> where B: Into<Vec<u8>> {
         Self::_from_bytes(bytes.into())
     }
@@ -92,11 +116,115 @@ impl OsString {
                 Some(OsString::from_vec(vec))
             }
             windows {
-                String::from_utf8(vec).ok().map(OsString::from)
+                use wtf8::Wtf8Buf;
+                Wtf8Buf::from_bytes(vec).ok().map(|inner| OsString { inner: Buf { inner: inner } })
             }
         }
     }
 
+    /// Decodes an `OsString` from the portable encoding produced by
+    /// `OsStr::to_portable_bytes`.
+    ///
+    /// Unicode data decodes on every platform.  Non-Unicode data only
+    /// decodes on a platform with the same native encoding; otherwise
+    /// `DecodeError::Incompatible` is returned.
+    pub fn from_portable_bytes(bytes: &[u8]) -> Result<OsString, DecodeError> {
+        let (&tag, payload) = match bytes.split_first() {
+            Some(split) => split,
+            None => return Err(DecodeError::MissingTag),
+        };
+        let encoding = match Encoding::from_tag(tag) {
+            Some(encoding) => encoding,
+            None => return Err(DecodeError::UnknownTag(tag)),
+        };
+        let native = OsStr::native_encoding();
+        // UTF-8 is valid in every native encoding.
+        match OsStr::from_encoded_bytes(payload) {
+            Some(s) if encoding == native || s.to_str().is_some() => Ok(s.to_owned()),
+            _ if encoding == Encoding::Unicode || encoding == native =>
+                Err(DecodeError::Malformed(encoding)),
+            _ => Err(DecodeError::Incompatible { found: encoding, native: native }),
+        }
+    }
+
+    /// Decodes a string produced by `OsStr::percent_encode`.
+    ///
+    /// Characters other than `%` are taken literally.  Returns `None`
+    /// if an escape is not followed by two hexadecimal digits, or if
+    /// the decoded bytes are not valid in the platform encoding (see
+    /// `OsStr::from_encoded_bytes`).
+    pub fn from_percent_encoded(s: &str) -> Option<OsString> {
+        fn hex_value(digit: u8) -> Option<u8> {
+            match digit {
+                b'0'...b'9' => Some(digit - b'0'),
+                b'a'...b'f' => Some(digit - b'a' + 10),
+                b'A'...b'F' => Some(digit - b'A' + 10),
+                _ => None,
+            }
+        }
+
+        let mut bytes = Vec::with_capacity(s.len());
+        let mut iter = s.bytes();
+        while let Some(byte) = iter.next() {
+            if byte != b'%' {
+                bytes.push(byte);
+                continue;
+            }
+            let high = iter.next().and_then(hex_value);
+            let low = iter.next().and_then(hex_value);
+            match (high, low) {
+                (Some(high), Some(low)) => bytes.push(high << 4 | low),
+                _ => return None,
+            }
+        }
+        OsStr::from_encoded_bytes(&bytes).map(|s| s.to_owned())
+    }
+
+    /// Converts a `CString`, without the nul terminator, into an
+    /// `OsString`, reusing its allocation where possible.
+    ///
+    /// This has the same platform behavior as `OsString::from_bytes`.
+    #[cfg(feature = "std")]
+    pub fn from_cstring(s: CString) -> Option<OsString> {
+        Self::_from_bytes(s.into_bytes())
+    }
+
+    /// Joins paths into a search path variable like `PATH`.  This is
+    /// the inverse of `OsStr::split_search_paths`.
+    ///
+    /// # Platform behavior
+    ///
+    /// On Unix systems, paths are joined with `:`, and a path
+    /// containing `:` is an error.
+    ///
+    /// On Windows systems, paths are joined with `;`, paths containing
+    /// `;` are quoted, and a path containing `"` is an error.
+    pub fn join_search_paths<I, T>(paths: I) -> Result<OsString, JoinSearchPathsError>
+    where I: IntoIterator<Item = T>, T: AsRef<OsStr> {
+        let separator = if is_windows!() { b';' } else { b':' };
+        let mut joined = OsString::new();
+        for (index, path) in paths.into_iter().enumerate() {
+            let path = path.as_ref();
+            if index > 0 {
+                joined.push_char(separator as char);
+            }
+            if is_windows!() && path.contains_byte(b'"') {
+                return Err(JoinSearchPathsError { index: index });
+            }
+            if path.contains_byte(separator) {
+                if !is_windows!() {
+                    return Err(JoinSearchPathsError { index: index });
+                }
+                joined.push_char('"');
+                joined.push(path);
+                joined.push_char('"');
+            } else {
+                joined.push(path);
+            }
+        }
+        Ok(joined)
+    }
+
     /// Creates a new `OsString` with the given capacity. The string will be able
     /// to hold exactly `capacity` bytes without reallocating. If `capacity` is 0,
     /// the string will not allocate.
@@ -131,18 +259,43 @@ impl OsString {
         self.inner.reserve_exact(additional)
     }
 
+    /// Shrinks the capacity of the `OsString` to match its length.
+    pub fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit()
+    }
+
+    /// Shrinks the capacity of the `OsString` to the larger of its
+    /// length and `min_capacity`.  Does nothing if the capacity is
+    /// already that small.
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.inner.shrink_to(min_capacity)
+    }
+
     /// Converts to an `OsStr` slice.
     pub fn as_os_str(&self) -> &OsStr {
         self
     }
 
     /// Converts the `OsString` into a `String` if it contains valid Unicode data.
+    /// The buffer is reused without copying.
     ///
     /// On failure, ownership of the original `OsString` is returned.
     pub fn into_string(self) -> Result<String, OsString> {
         self.inner.into_string().map_err(|buf| OsString { inner: buf} )
     }
 
+    /// Converts the `OsString` into a `String` if it contains valid Unicode data.
+    ///
+    /// On failure, the error describes the first non-Unicode sequence
+    /// and holds the original `OsString`.
+    pub fn try_into_string(self) -> Result<String, IntoStringError> {
+        self.into_string().map_err(|string| {
+            // The conversion only fails if there is an invalid sequence.
+            let error = string.utf8_error().unwrap();
+            IntoStringError { string: string, error: error }
+        })
+    }
+
     /// Converts an `OsString` into a `String`, avoiding a copy if possible.
     ///
     /// Any non-Unicode sequences are replaced with U+FFFD REPLACEMENT CHARACTER.
@@ -150,29 +303,255 @@ impl OsString {
         self.inner.into_string_lossy()
     }
 
+    /// Converts an `OsString` into a `String`, avoiding a copy if
+    /// the string is valid Unicode.
+    ///
+    /// Non-Unicode sequences are replaced with `rep` as by
+    /// `OsStr::to_string_with_replacement`.
+    pub fn into_string_with_replacement(self, rep: &str) -> String {
+        match self.into_string() {
+            Ok(s) => s,
+            Err(s) => s.to_string_with_replacement(rep).into_owned(),
+        }
+    }
+
     /// Extends the string with the given `&OsStr` slice.
     pub fn push<T: AsRef<OsStr>>(&mut self, s: T) {
         self.inner.push_slice(&s.as_ref().inner)
     }
 
+    /// Extends the string with the given `&str` slice.
+    pub fn push_str(&mut self, s: &str) {
+        self.inner.push_str(s)
+    }
+
+    /// Appends the given `char` to the end of the string.
+    pub fn push_char(&mut self, c: char) {
+        self.inner.push_char(c)
+    }
+
+    /// Shortens `self` to `new_len` bytes, without reallocating.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `new_len` is greater than the current length.  On
+    /// Windows, also panics if `new_len` does not lie on a code point
+    /// boundary.
+    pub fn truncate(&mut self, new_len: usize) {
+        self.inner.truncate(new_len)
+    }
+
+    /// Retains only the characters for which `keep_char` returns true
+    /// and the non-Unicode sections (as returned by `split_unicode`)
+    /// for which `keep_non_unicode` returns true.  This works in
+    /// place, without allocating.
+    ///
+    /// On Windows, removing the characters between two unpaired
+    /// surrogates can cause them to combine into a character.
+    pub fn retain<F, G>(&mut self, keep_char: F, mut keep_non_unicode: G)
+    where F: FnMut(char) -> bool, G: FnMut(&OsStr) -> bool {
+        self.inner.retain(keep_char, |s| keep_non_unicode(OsStr::from_inner(s)))
+    }
+
+    /// Removes the last character or non-Unicode unit from `self`
+    /// and returns it, or `None` if `self` is empty.
+    ///
+    /// # Platform behavior
+    ///
+    /// On Unix systems, a non-Unicode unit is a single byte.
+    ///
+    /// On Windows systems, a non-Unicode unit is an unpaired
+    /// surrogate.
+    pub fn pop(&mut self) -> Option<Popped> {
+        let (new_len, popped) = match self.slice_pop_char() {
+            Some((rest, c)) => (rest.len(), Popped::Char(c)),
+            None if self.is_empty() => return None,
+            None => {
+                // Surrogates are always 3 bytes in WTF-8
+                let new_len = self.len() - if is_windows!() { 3 } else { 1 };
+                (new_len, Popped::NonUnicode(self[new_len..].to_os_string()))
+            }
+        };
+        self.truncate(new_len);
+        Some(popped)
+    }
+
+    /// Converts this `OsString` into a boxed `OsStr`, dropping any
+    /// excess capacity.
+    pub fn into_boxed_os_str(self) -> Box<OsStr> {
+        unsafe { mem::transmute(self.inner.into_box()) }
+    }
+
+    /// Converts this `OsString` into an owned `Cow<OsStr>`.
+    pub fn into_cow<'a>(self) -> Cow<'a, OsStr> {
+        Cow::Owned(self)
+    }
+
     /// Truncates `self` to zero length.
     pub fn clear(&mut self) {
         self.inner.clear()
     }
 }
 
+/// Converts a `String` without copying it.
 impl From<String> for OsString {
     fn from(s: String) -> OsString {
         OsString { inner: Buf::from_string(s) }
     }
 }
 
+impl From<char> for OsString {
+    fn from(c: char) -> OsString {
+        let mut string = OsString::with_capacity(c.len_utf8());
+        string.push_char(c);
+        string
+    }
+}
+
+impl Default for OsString {
+    #[inline]
+    fn default() -> OsString {
+        OsString::new()
+    }
+}
+
+impl FromStr for OsString {
+    type Err = ParseError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<OsString, ParseError> {
+        Ok(OsString::from(s))
+    }
+}
+
 impl<'a, T: ?Sized + AsRef<OsStr>> From<&'a T> for OsString {
     fn from(s: &'a T) -> OsString {
         s.as_ref().to_os_string()
     }
 }
 
+impl Extend<char> for OsString {
+    fn extend<I: IntoIterator<Item=char>>(&mut self, iterable: I) {
+        let iterator = iterable.into_iter();
+        let (low, _high) = iterator.size_hint();
+        // Lower bound of one byte per char (ASCII only)
+        self.reserve(low);
+        for c in iterator {
+            self.push_char(c);
+        }
+    }
+}
+
+// The string impls below do not reserve: the size hint counts
+// strings, which may be empty, so it gives no lower bound on the
+// bytes needed.  `String`'s impls for `&str` and `String` skip it for
+// the same reason.
+impl<'a> Extend<&'a OsStr> for OsString {
+    fn extend<I: IntoIterator<Item=&'a OsStr>>(&mut self, iterable: I) {
+        for s in iterable {
+            self.push(s);
+        }
+    }
+}
+
+impl Extend<OsString> for OsString {
+    fn extend<I: IntoIterator<Item=OsString>>(&mut self, iterable: I) {
+        for s in iterable {
+            self.push(s);
+        }
+    }
+}
+
+impl<'a> Extend<Cow<'a, OsStr>> for OsString {
+    fn extend<I: IntoIterator<Item=Cow<'a, OsStr>>>(&mut self, iterable: I) {
+        for s in iterable {
+            self.push(s);
+        }
+    }
+}
+
+impl<'a> ops::Add<&'a OsStr> for OsString {
+    type Output = OsString;
+
+    #[inline]
+    fn add(mut self, other: &OsStr) -> OsString {
+        self.push(other);
+        self
+    }
+}
+
+impl<'a> ops::AddAssign<&'a OsStr> for OsString {
+    #[inline]
+    fn add_assign(&mut self, other: &OsStr) {
+        self.push(other);
+    }
+}
+
+impl From<Box<OsStr>> for OsString {
+    fn from(boxed: Box<OsStr>) -> OsString {
+        let inner: Box<Slice> = unsafe { mem::transmute(boxed) };
+        OsString { inner: Buf::from_box(inner) }
+    }
+}
+
+impl<'a> From<&'a OsStr> for Box<OsStr> {
+    fn from(s: &'a OsStr) -> Box<OsStr> {
+        s.to_os_string().into_boxed_os_str()
+    }
+}
+
+#[cfg(feature = "rc")]
+impl<'a> From<&'a OsStr> for Rc<OsStr> {
+    fn from(s: &'a OsStr) -> Rc<OsStr> {
+        let bytes: Rc<[u8]> = Rc::from(s.bytes());
+        // `OsStr` wraps its byte representation, so the pointer can be
+        // cast, keeping the length.
+        unsafe { Rc::from_raw(Rc::into_raw(bytes) as *const OsStr) }
+    }
+}
+
+#[cfg(feature = "rc")]
+impl From<OsString> for Rc<OsStr> {
+    fn from(s: OsString) -> Rc<OsStr> {
+        Rc::from(&s[..])
+    }
+}
+
+#[cfg(feature = "rc")]
+impl<'a> From<&'a OsStr> for Arc<OsStr> {
+    fn from(s: &'a OsStr) -> Arc<OsStr> {
+        let bytes: Arc<[u8]> = Arc::from(s.bytes());
+        // `OsStr` wraps its byte representation, so the pointer can be
+        // cast, keeping the length.
+        unsafe { Arc::from_raw(Arc::into_raw(bytes) as *const OsStr) }
+    }
+}
+
+#[cfg(feature = "rc")]
+impl From<OsString> for Arc<OsStr> {
+    fn from(s: OsString) -> Arc<OsStr> {
+        Arc::from(&s[..])
+    }
+}
+
+impl<'a> From<&'a OsStr> for Cow<'a, OsStr> {
+    fn from(s: &'a OsStr) -> Cow<'a, OsStr> {
+        Cow::Borrowed(s)
+    }
+}
+
+impl<'a> From<&'a OsString> for Cow<'a, OsStr> {
+    fn from(s: &'a OsString) -> Cow<'a, OsStr> {
+        Cow::Borrowed(s)
+    }
+}
+
+impl<'a> From<OsString> for Cow<'a, OsStr> {
+    fn from(s: OsString) -> Cow<'a, OsStr> {
+        Cow::Owned(s)
+    }
+}
+
 impl ops::Index<ops::RangeFull> for OsString {
     type Output = OsStr;
 
@@ -182,6 +561,40 @@ impl ops::Index<ops::RangeFull> for OsString {
     }
 }
 
+impl ops::IndexMut<ops::RangeFull> for OsString {
+    #[inline]
+    fn index_mut(&mut self, _index: ops::RangeFull) -> &mut OsStr {
+        OsStr::from_inner_mut(self.inner.as_mut_slice())
+    }
+}
+
+impl ops::Index<ops::Range<usize>> for OsString {
+    type Output = OsStr;
+
+    #[inline]
+    fn index(&self, index: ops::Range<usize>) -> &OsStr {
+        &self[..][index]
+    }
+}
+
+impl ops::Index<ops::RangeFrom<usize>> for OsString {
+    type Output = OsStr;
+
+    #[inline]
+    fn index(&self, index: ops::RangeFrom<usize>) -> &OsStr {
+        &self[..][index]
+    }
+}
+
+impl ops::Index<ops::RangeTo<usize>> for OsString {
+    type Output = OsStr;
+
+    #[inline]
+    fn index(&self, index: ops::RangeTo<usize>) -> &OsStr {
+        &self[..][index]
+    }
+}
+
 impl ops::Deref for OsString {
     type Target = OsStr;
 
@@ -191,6 +604,13 @@ impl ops::Deref for OsString {
     }
 }
 
+impl ops::DerefMut for OsString {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut OsStr {
+        &mut self[..]
+    }
+}
+
 impl Debug for OsString {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         fmt::Debug::fmt(&**self, formatter)
@@ -259,10 +679,28 @@ impl OsStr {
         s.as_ref()
     }
 
+    /// Views a byte sequence as an `OsStr` without copying.
+    ///
+    /// # Platform behavior
+    ///
+    /// On Unix systems, any byte sequence can be successfully
+    /// converted into an `OsStr`.
+    ///
+    /// On Windows systems, only well-formed WTF-8 will successfully
+    /// convert, as for `OsString::from_bytes`; other data will produce
+    /// `None`.
+    pub fn from_bytes(bytes: &[u8]) -> Option<&OsStr> {
+        OsStr::from_encoded_bytes(bytes)
+    }
+
     fn from_inner(inner: &Slice) -> &OsStr {
         unsafe { mem::transmute(inner) }
     }
 
+    fn from_inner_mut(inner: &mut Slice) -> &mut OsStr {
+        unsafe { mem::transmute(inner) }
+    }
+
     /// Checks whether `self` is empty.
     pub fn is_empty(&self) -> bool {
         self.inner.is_empty()
@@ -282,6 +720,23 @@ impl OsStr {
         self.inner.to_str()
     }
 
+    /// Yields a `&str` slice if the `OsStr` is valid unicode, or an
+    /// error locating the first non-Unicode sequence.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use osstring_prototype::os_str::OsStr;
+    /// assert_eq!(OsStr::new("Hello!").try_to_str(), Ok("Hello!"));
+    /// ```
+    pub fn try_to_str(&self) -> Result<&str, OsStrUtf8Error> {
+        match self.to_str() {
+            Some(s) => Ok(s),
+            // The conversion only fails if there is an invalid sequence.
+            None => Err(self.utf8_error().unwrap()),
+        }
+    }
+
     /// Converts an `OsStr` to a `Cow<str>`.
     ///
     /// Any non-Unicode sequences are replaced with U+FFFD REPLACEMENT CHARACTER.
@@ -289,11 +744,47 @@ impl OsStr {
         self.inner.to_string_lossy()
     }
 
+    /// Converts an `OsStr` to a `Cow<str>`, replacing each
+    /// non-Unicode sequence with `rep`.  An empty `rep` drops
+    /// non-Unicode data.
+    ///
+    /// # Platform behavior
+    ///
+    /// On Unix systems, each maximal run of invalid bytes is
+    /// replaced, as in `chars_lossy`.
+    ///
+    /// On Windows systems, each unpaired surrogate is replaced.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use osstring_prototype::os_str::OsStr;
+    /// assert_eq!(OsStr::new("Hello!").to_string_with_replacement("?"), "Hello!");
+    /// ```
+    pub fn to_string_with_replacement(&self, rep: &str) -> Cow<str> {
+        if let Some(s) = self.to_str() { return Cow::Borrowed(s); }
+        let mut result = String::with_capacity(self.len());
+        for section in self.split_unicode() {
+            match section {
+                OsStrSection::Unicode(s) => result.push_str(s),
+                OsStrSection::NonUnicode(s) => {
+                    for _ in s.chars_lossy() { result.push_str(rep); }
+                }
+            }
+        }
+        Cow::Owned(result)
+    }
+
     /// Copies the slice into an owned `OsString`.
     pub fn to_os_string(&self) -> OsString {
         OsString { inner: self.inner.to_owned() }
     }
 
+    /// Borrows the slice as a `Cow<OsStr>`.
+    pub fn to_cow(&self) -> Cow<OsStr> {
+        Cow::Borrowed(self)
+    }
+
     /// Yields this `OsStr` as a byte slice.
     ///
     /// # Platform behavior
@@ -318,832 +809,4879 @@ impl OsStr {
     /// This is a convenience for creating a `CString` from
     /// `self.to_bytes()`, and inherits the platform behavior of the
     /// `to_bytes` method.
+    #[cfg(feature = "std")]
     pub fn to_cstring(&self) -> Option<CString> {
-        self.to_bytes().and_then(|b| CString::new(b).ok())
+        self.try_to_cstring().ok()
     }
 
-    /// Gets the underlying byte representation.
+    /// Creates a `CString` containing this `OsStr` data, reporting
+    /// why the conversion failed if it is not possible.
     ///
-    /// Note: it is *crucial* that this API is private, to avoid
-    /// revealing the internal, platform-specific encodings.
-    fn bytes(&self) -> &[u8] {
-        unsafe { mem::transmute(&self.inner) }
+    /// See `to_cstring`.
+    #[cfg(feature = "std")]
+    pub fn try_to_cstring(&self) -> Result<CString, ToCStringError> {
+        let bytes = try!(self.to_cstring_bytes());
+        Ok(unsafe { CString::from_vec_unchecked(bytes.to_vec()) })
     }
 
-    /// Returns an iterator over the Unicode and non-Unicode sections
-    /// of the string.  Sections will always be nonempty and Unicode
-    /// and non-Unicode sections will always alternate.
-    ///
-    /// # Example
+    /// Writes this `OsStr` data followed by a nul into `buf`, replacing
+    /// its previous contents, and returns a `CStr` view of it.
     ///
-    /// ```
-    /// use std::ffi::OsStr;
-    /// use osstring_prototype::prelude::*;
-    /// let string = OsStr::new("Hello!");
-    /// match string.split_unicode().next().unwrap() {
-    ///     OsStrSection::Unicode(s) => assert_eq!(s, "Hello!"),
-    ///     OsStrSection::NonUnicode(s) => panic!("Got non-Unicode: {:?}", s),
-    /// }
-    /// ```
-    pub fn split_unicode<'a>(&'a self) -> SplitUnicode<'a> {
-        SplitUnicode(self.inner.split_unicode())
+    /// Reusing the same buffer for many conversions avoids allocating
+    /// a new `CString` each time.  Fails in the same cases as
+    /// `try_to_cstring`.
+    #[cfg(feature = "std")]
+    pub fn as_cstr_with_nul<'a>(&self, buf: &'a mut Vec<u8>)
+                                -> Result<&'a CStr, ToCStringError> {
+        let bytes = try!(self.to_cstring_bytes());
+        buf.clear();
+        buf.extend_from_slice(bytes);
+        buf.push(0);
+        Ok(unsafe { CStr::from_ptr(buf.as_ptr() as *const c_char) })
+    }
+
+    /// Locates the first sequence that is not valid Unicode, if there
+    /// is one.
+    fn utf8_error(&self) -> Option<OsStrUtf8Error> {
+        let bytes = self.bytes();
+        match ::std::str::from_utf8(bytes) {
+            Ok(_) => None,
+            Err(err) => {
+                let start = err.valid_up_to();
+                // Unpaired surrogates are always three bytes.
+                let len = if is_windows!() { 3 } else { utf8_sections::invalid_len(&bytes[start..]) };
+                Some(OsStrUtf8Error { valid_up_to: start, error_len: len })
+            }
+        }
     }
 
-    /// Returns true if `needle` is a substring of `self`.
-    pub fn contains_os<S: AsRef<OsStr>>(&self, needle: S) -> bool {
-        self.inner.contains_os(&needle.as_ref().inner)
+    /// Returns the bytes for a C string, checked not to contain nuls.
+    #[cfg(feature = "std")]
+    fn to_cstring_bytes(&self) -> Result<&[u8], ToCStringError> {
+        match self.to_bytes() {
+            Some(bytes) => match bytes.iter().position(|&b| b == 0) {
+                Some(pos) => Err(ToCStringError::InteriorNul(pos)),
+                None => Ok(bytes),
+            },
+            None => {
+                let valid_up_to = match self.split_unicode().next() {
+                    Some(OsStrSection::Unicode(s)) => s.len(),
+                    _ => 0,
+                };
+                Err(ToCStringError::NotUnicode(valid_up_to))
+            }
+        }
     }
 
-    /// Returns true if `needle` is a prefix of `self`.
-    pub fn starts_with_os<S: AsRef<OsStr>>(&self, needle: S) -> bool {
-        self.inner.starts_with_os(&needle.as_ref().inner)
+    /// Views the contents of a `CStr`, without the nul terminator, as
+    /// an `OsStr` without copying.
+    ///
+    /// This has the same platform behavior as `OsStr::from_bytes`.
+    #[cfg(feature = "std")]
+    pub fn from_cstr(s: &CStr) -> Option<&OsStr> {
+        OsStr::from_bytes(s.to_bytes())
     }
 
-    /// Returns true if `needle` is a suffix of `self`.
-    pub fn ends_with_os<S: AsRef<OsStr>>(&self, needle: S) -> bool {
-        self.inner.ends_with_os(&needle.as_ref().inner)
+    /// Divides one `OsStr` into two at a byte index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid` is past the end of the string.  On Windows,
+    /// also panics if `mid` does not lie on a code point boundary.
+    pub fn split_at(&self, mid: usize) -> (&OsStr, &OsStr) {
+        assert!(mid <= self.len(), "index {} out of bounds when splitting `{:?}`",
+                mid, self);
+        match self.try_split_at(mid) {
+            Some(halves) => halves,
+            None => panic!("index {} in `{:?}` does not lie on a code point boundary",
+                           mid, self),
+        }
     }
 
-    /// Replaces all occurrences of one string with another.
-    pub fn replace<T: AsRef<OsStr>, U: AsRef<OsStr>>(&self, from: T, to: U) -> OsString {
-        OsString::from_inner(self.inner.replace(&from.as_ref().inner,
-                                                &to.as_ref().inner))
+    /// Divides one `OsStr` into two at a byte index, returning `None`
+    /// if `split_at` would panic.
+    pub fn try_split_at(&self, mid: usize) -> Option<(&OsStr, &OsStr)> {
+        self.inner.split_at(mid).map(|(a, b)| (OsStr::from_inner(a), OsStr::from_inner(b)))
     }
 
-    /// An iterator over the non-empty substrings of `self` that
-    /// contain no whitespace and are separated by whitespace.
-    pub fn split_whitespace<'a>(&'a self) -> SplitWhitespace<'a> {
-        SplitWhitespace::new(self)
+    /// Returns the substring at the byte range `range`, or `None` if
+    /// the range is out of bounds.  On Windows, also returns `None` if
+    /// either end of the range does not lie on a code point boundary.
+    pub fn get(&self, range: ops::Range<usize>) -> Option<&OsStr> {
+        self.try_split_at(range.end)
+            .and_then(|(head, _)| head.try_split_at(range.start))
+            .map(|(_, substring)| substring)
     }
 
-    /// An iterator over the lines of `self`, separated by `\n` or
-    /// `\r\n`.  This does not return an empty string after a trailing
-    /// `\n`.
-    pub fn lines<'a>(&'a self) -> Lines<'a> {
-        Lines::new(self)
+    /// Checks if all characters in this string are within the ASCII range.
+    ///
+    /// This is cheaper than `to_str`, since no UTF-8 validation is
+    /// needed, and an ASCII string is always valid Unicode.
+    pub fn is_ascii(&self) -> bool {
+        self.bytes().is_ascii()
     }
 
-    /// Returns true if `self` matches `pat`.
+    /// Checks that two strings are an ASCII case-insensitive match.
     ///
-    /// Note that patterns can only match Unicode sections of the `OsStr`.
-    pub fn contains<'a, P>(&'a self, pat: P) -> bool where P: Pattern<'a> + Clone {
-        self.inner.utf8_sections().any(|s| s.1.contains(pat.clone()))
+    /// Same as comparing `to_ascii_lowercase` of both strings, but
+    /// without allocating.  Non-ASCII data must match exactly.
+    pub fn eq_ignore_ascii_case<S: AsRef<OsStr>>(&self, other: S) -> bool {
+        // ASCII is encoded identically on all platforms, and non-ASCII
+        // bytes are left alone by the case folding.
+        self.bytes().eq_ignore_ascii_case(other.as_ref().bytes())
     }
 
-    /// Returns true if the beginning of `self` matches `pat`.
+    /// Checks that two strings match ignoring case, using Unicode case
+    /// folding.
     ///
-    /// Note that patterns can only match Unicode sections of the `OsStr`.
-    pub fn starts_with<'a, P>(&'a self, pat: P) -> bool where P: Pattern<'a> {
-        self.inner.utf8_sections().next().unwrap().1.starts_with(pat)
+    /// Unicode sections are compared after full case folding, so
+    /// `"Straße"` matches `"STRASSE"`, and non-Unicode data must match
+    /// exactly.  Characters other than dotless i are folded by mapping
+    /// them to lower case, upper case and lower case again, which
+    /// closely follows the Unicode case folding tables.  The
+    /// folding is not locale-aware, and does not reproduce the simpler
+    /// rules some filesystems use for case-insensitive names.
+    pub fn eq_fold<S: AsRef<OsStr>>(&self, other: S) -> bool {
+        self.folded_bytes().eq(other.as_ref().folded_bytes())
+    }
+
+    /// Compares two strings ignoring case, using Unicode case folding.
+    ///
+    /// This is consistent with `eq_fold`.  The strings are ordered as
+    /// their case-folded forms would be by `Ord`.
+    pub fn cmp_fold<S: AsRef<OsStr>>(&self, other: S) -> cmp::Ordering {
+        self.folded_bytes().cmp(other.as_ref().folded_bytes())
     }
 
-    /// Returns true if the end of `self` matches `pat`.
+    /// Compares two strings using `collator`, such as a locale-aware
+    /// collation supplied by the application.
     ///
-    /// Note that patterns can only match Unicode sections of the `OsStr`.
-    pub fn ends_with<'a, P>(&'a self, pat: P) -> bool
-            where P: Pattern<'a>, P::Searcher: ReverseSearcher<'a> {
-        self.inner.utf8_sections().next_back().unwrap().1.ends_with(pat)
+    /// The strings are split into Unicode and non-Unicode sections (see
+    /// `split_unicode`), which are compared in turn until they differ.
+    /// Unicode sections are compared by `collator`, so a string that is
+    /// entirely Unicode is passed to it whole.  Non-Unicode sections are
+    /// compared by their encoded bytes.  Where one string has a Unicode
+    /// section and the other a non-Unicode one, the Unicode section
+    /// sorts first, and a string that runs out of sections sorts before
+    /// the other.
+    ///
+    /// This returns `Equal` only if every pair of sections compares
+    /// equal, which for a collator that ignores some differences does
+    /// not imply that the strings are equal.
+    pub fn cmp_with<S, C>(&self, other: S, collator: &C) -> cmp::Ordering
+    where S: AsRef<OsStr>, C: Collator + ?Sized {
+        let mut sections = self.split_unicode();
+        let mut other_sections = other.as_ref().split_unicode();
+        loop {
+            let order = match (sections.next(), other_sections.next()) {
+                (Some(OsStrSection::Unicode(a)), Some(OsStrSection::Unicode(b))) => {
+                    collator.compare(a, b)
+                }
+                (Some(OsStrSection::NonUnicode(a)), Some(OsStrSection::NonUnicode(b))) => {
+                    a.bytes().cmp(b.bytes())
+                }
+                (Some(OsStrSection::Unicode(_)), Some(OsStrSection::NonUnicode(_))) |
+                (None, Some(_)) => cmp::Ordering::Less,
+                (Some(OsStrSection::NonUnicode(_)), Some(OsStrSection::Unicode(_))) |
+                (Some(_), None) => cmp::Ordering::Greater,
+                (None, None) => return cmp::Ordering::Equal,
+            };
+            if order != cmp::Ordering::Equal {
+                return order;
+            }
+        }
     }
 
-    /// An iterator over substrings of `self` separated by characters
-    /// matched by a pattern.  See `str::split` for details.
-    ///
-    /// Note that patterns can only match Unicode sections of the `OsStr`.
-    pub fn split<'a, P>(&'a self, pat: P) -> Split<'a, P>
-    where P: Pattern<'a> + Clone {
-        Split { inner: self.inner.split(pat) }
+    /// Returns an iterator over the encoded bytes of the case-folded
+    /// form of this string.
+    fn folded_bytes(&self) -> FoldedBytes {
+        FoldedBytes {
+            sections: self.split_unicode(),
+            chars: fold_chars(""),
+            raw: [].iter(),
+            encoded: [0; 4],
+            encoded_pos: 0,
+            encoded_len: 0,
+        }
     }
 
-    /// An iterator over substrings of `self` separated by characters
-    /// matched by a pattern, in reverse order.  See `str::rsplit` for
-    /// details.
-    ///
-    /// Note that patterns can only match Unicode sections of the `OsStr`.
-    pub fn rsplit<'a, P>(&'a self, pat: P) -> RSplit<'a, P>
-    where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
-        RSplit { inner: self.inner.rsplit(pat) }
+    /// Returns true if `needle` is a substring of `self`, ignoring
+    /// ASCII case.  See `find_ignore_ascii_case`.
+    pub fn contains_ignore_ascii_case<S: AsRef<OsStr>>(&self, needle: S) -> bool {
+        self.find_ignore_ascii_case(needle).is_some()
     }
 
-    /// Equivalent to `split`, except the trailing substring is
-    /// skipped if empty.  See `str::split_terminator` for details.
+    /// Returns the byte index of the first occurrence of `needle` in
+    /// `self`, ignoring ASCII case, or `None` if there is none.
+    /// Non-ASCII data must match exactly.
     ///
-    /// Note that patterns can only match Unicode sections of the `OsStr`.
-    pub fn split_terminator<'a, P>(&'a self, pat: P) -> SplitTerminator<'a, P>
-    where P: Pattern<'a> + Clone {
-        SplitTerminator { inner: self.inner.split_terminator(pat) }
+    /// # Platform behavior
+    ///
+    /// On Windows systems, a surrogate at either end of `needle` will
+    /// not match half of a surrogate pair in `self`, as with `find_os`.
+    pub fn find_ignore_ascii_case<S: AsRef<OsStr>>(&self, needle: S) -> Option<usize> {
+        slice_searcher::find_ignore_ascii_case(self.bytes(), needle.as_ref().bytes())
     }
 
-    /// Equivalent to `rsplit`, except the trailing substring is
-    /// skipped if empty.  See `str::rsplit_terminator` for details.
-    ///
-    /// Note that patterns can only match Unicode sections of the `OsStr`.
-    pub fn rsplit_terminator<'a, P>(&'a self, pat: P) -> RSplitTerminator<'a, P>
-    where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
-        RSplitTerminator { inner: self.inner.rsplit_terminator(pat) }
+    /// Returns true if `needle` is a prefix of `self`, ignoring ASCII
+    /// case.  See `find_ignore_ascii_case` for platform behavior.
+    pub fn starts_with_ignore_ascii_case<S: AsRef<OsStr>>(&self, needle: S) -> bool {
+        let (bytes, needle) = (self.bytes(), needle.as_ref().bytes());
+        bytes.len() >= needle.len() && bytes[..needle.len()].eq_ignore_ascii_case(needle)
     }
 
-    /// An iterator over substrings of `self` separated by characters
-    /// matched by a pattern, restricted to returning at most `count`
-    /// items.  See `str::splitn` for details.
-    ///
-    /// Note that patterns can only match Unicode sections of the `OsStr`.
-    pub fn splitn<'a, P>(&'a self, count: usize, pat: P) -> SplitN<'a, P>
-    where P: Pattern<'a> + Clone {
-        SplitN { inner: self.inner.splitn(count, pat) }
+    /// Returns true if `needle` is a suffix of `self`, ignoring ASCII
+    /// case.  See `find_ignore_ascii_case` for platform behavior.
+    pub fn ends_with_ignore_ascii_case<S: AsRef<OsStr>>(&self, needle: S) -> bool {
+        let (bytes, needle) = (self.bytes(), needle.as_ref().bytes());
+        bytes.len() >= needle.len() &&
+            bytes[bytes.len() - needle.len()..].eq_ignore_ascii_case(needle)
     }
 
-    /// An iterator over substrings of `self` separated by characters
-    /// matched by a pattern, in reverse order, restricted to returning
-    /// at most `count` items.  See `str::rsplitn` for details.
+    /// Returns true if `self` matches the shell-style wildcard
+    /// `pattern`.
     ///
-    /// Note that patterns can only match Unicode sections of the `OsStr`.
-    pub fn rsplitn<'a, P>(&'a self, count: usize, pat: P) -> RSplitN<'a, P>
-    where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
-        RSplitN { inner: self.inner.rsplitn(count, pat) }
+    /// `?` matches any one character, `*` matches any sequence of
+    /// characters, and `[...]` matches one character from a set such
+    /// as `[abc]`, `[a-z]` or `[!0-9]`.  Each byte of non-Unicode data
+    /// counts as one character, and can appear in the pattern.  There
+    /// is no special treatment of path separators or leading dots,
+    /// and no escape character, but `[*]`, `[?]` and `[[]` match the
+    /// literal characters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use osstring_prototype::os_str::OsStr;
+    /// assert!(OsStr::new("main.c").matches_glob("*.[ch]"));
+    /// assert!(!OsStr::new("main.rs").matches_glob("*.[ch]"));
+    /// ```
+    pub fn matches_glob<S: AsRef<OsStr>>(&self, pattern: S) -> bool {
+        glob::matches(pattern.as_ref().bytes(), self.bytes(), is_windows!())
     }
 
-    /// An iterator over matches of a pattern in `self`.  See
-    /// `str::matches` for details.
+    /// Converts this string to its ASCII lower case equivalent in-place.
     ///
-    /// Note that patterns can only match Unicode sections of the `OsStr`.
-    pub fn matches<'a, P>(&'a self, pat: P) -> Matches<'a, P>
-    where P: Pattern<'a> + Clone {
-        Matches { inner: self.inner.matches(pat) }
+    /// Non-ASCII data is left unchanged.
+    pub fn make_ascii_lowercase(&mut self) {
+        self.inner.make_ascii_lowercase()
     }
 
-    /// An iterator over matches of a pattern in `self`, in reverse
-    /// order.  See `str::rmatches` for details.
+    /// Converts this string to its ASCII upper case equivalent in-place.
     ///
-    /// Note that patterns can only match Unicode sections of the `OsStr`.
-    pub fn rmatches<'a, P>(&'a self, pat: P) -> RMatches<'a, P>
-    where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
-        RMatches { inner: self.inner.rmatches(pat) }
+    /// Non-ASCII data is left unchanged.
+    pub fn make_ascii_uppercase(&mut self) {
+        self.inner.make_ascii_uppercase()
     }
 
-    /// Returns a `&OsStr` with leading and trailing whitespace removed.
-    pub fn trim(&self) -> &OsStr {
-        self.trim_matches(char::is_whitespace as fn(char) -> bool)
+    /// Returns the lowercase equivalent of this string.
+    ///
+    /// Unicode sections are mapped as by `str::to_lowercase`.
+    /// Non-Unicode sections are copied unchanged.
+    pub fn to_lowercase(&self) -> OsString {
+        self.map_unicode_sections(str::to_lowercase)
     }
 
-    /// Returns a `&OsStr` with leading whitespace removed.
-    pub fn trim_left(&self) -> &OsStr {
-        self.trim_left_matches(char::is_whitespace)
+    /// Returns the uppercase equivalent of this string.
+    ///
+    /// Unicode sections are mapped as by `str::to_uppercase`.
+    /// Non-Unicode sections are copied unchanged.
+    pub fn to_uppercase(&self) -> OsString {
+        self.map_unicode_sections(str::to_uppercase)
+    }
+
+    fn map_unicode_sections<F>(&self, mut f: F) -> OsString
+    where F: FnMut(&str) -> String {
+        let mut result = OsString::with_capacity(self.len());
+        for section in self.split_unicode() {
+            match section {
+                OsStrSection::Unicode(s) => result.push(f(s)),
+                OsStrSection::NonUnicode(s) => result.push(s),
+            }
+        }
+        result
     }
 
-    /// Returns a `&OsStr` with trailing whitespace removed.
-    pub fn trim_right(&self) -> &OsStr {
-        self.trim_right_matches(char::is_whitespace)
+    /// Yields the platform-specific encoding of this `OsStr` as a
+    /// byte slice.
+    ///
+    /// # Platform behavior
+    ///
+    /// On Unix systems, this is the raw bytes of the string.
+    ///
+    /// On Windows systems, this is the string encoded as WTF-8.
+    ///
+    /// Valid Unicode is always UTF-8 encoded on all platforms, so
+    /// code working with this representation should treat other
+    /// bytes as opaque.
+    pub fn as_encoded_bytes(&self) -> &[u8] {
+        self.bytes()
     }
 
-    /// Returns a `&OsStr` with leading and trailing matches of `pat`
-    /// repeatedly removed.
-    pub fn trim_matches<'a, P>(&'a self, pat: P) -> &'a OsStr
-    where P: Pattern<'a> + Clone, P::Searcher: DoubleEndedSearcher<'a> {
-        Self::from_inner(self.inner.trim_matches(pat))
+    /// Converts bytes in the platform-specific encoding (see
+    /// `as_encoded_bytes`) back to an `OsStr`.
+    ///
+    /// # Platform behavior
+    ///
+    /// On Unix systems, this always succeeds.
+    ///
+    /// On Windows systems, this returns `None` unless `bytes` is
+    /// well-formed WTF-8.
+    pub fn from_encoded_bytes(bytes: &[u8]) -> Option<&OsStr> {
+        Slice::from_encoded_bytes(bytes).map(OsStr::from_inner)
     }
 
-    /// Returns a `&OsStr` with leading matches of `pat` repeatedly
-    /// removed.
-    pub fn trim_left_matches<'a, P>(&'a self, pat: P) -> &'a OsStr
-    where P: Pattern<'a> {
-        Self::from_inner(self.inner.trim_left_matches(pat))
+    /// Converts bytes in the platform-specific encoding back to an
+    /// `OsStr` without checking them.
+    ///
+    /// # Safety
+    ///
+    /// `bytes` must be valid in the platform encoding, such as bytes
+    /// copied from `as_encoded_bytes`.
+    #[doc(hidden)]
+    pub unsafe fn from_encoded_bytes_unchecked(bytes: &[u8]) -> &OsStr {
+        OsStr::from_inner(Slice::from_encoded_bytes_unchecked(bytes))
     }
 
-    /// Returns a `&OsStr` with trailing matches of `pat` repeatedly
-    /// removed.
-    pub fn trim_right_matches<'a, P>(&'a self, pat: P) -> &'a OsStr
-    where P: Pattern<'a>, P::Searcher: ReverseSearcher<'a> {
-        Self::from_inner(self.inner.trim_right_matches(pat))
+    /// Encodes this string in a platform-tagged format that can be
+    /// stored or transmitted and decoded with
+    /// `OsString::from_portable_bytes`.
+    ///
+    /// See the `portable` module for a description of the format.
+    pub fn to_portable_bytes(&self) -> Vec<u8> {
+        let encoding = if self.to_str().is_some() {
+            Encoding::Unicode
+        } else {
+            OsStr::native_encoding()
+        };
+        let mut bytes = Vec::with_capacity(1 + self.len());
+        bytes.push(encoding.tag());
+        bytes.extend_from_slice(self.bytes());
+        bytes
     }
-}
 
-impl PartialEq for OsStr {
-    fn eq(&self, other: &OsStr) -> bool {
-        self.bytes().eq(other.bytes())
+    /// Feeds this string into `state` in a form that does not depend
+    /// on the platform.
+    ///
+    /// The `Hash` implementation feeds the encoded bytes (see
+    /// `as_encoded_bytes`) in a platform-specific way, so the same
+    /// string can hash differently on Unix and Windows.  This method
+    /// instead feeds the length of the WTF-8 form of the string, as a
+    /// little-endian `u64`, followed by that form.  Strings that are
+    /// valid Unicode therefore hash the same on every platform, as do
+    /// unpaired surrogates on Windows and their WTF-8 encoding on Unix.
+    /// Other non-Unicode data on Unix, which has no WTF-8 form, is fed
+    /// as its raw bytes.
+    ///
+    /// `PortableHash` wraps a string to use this as its `Hash`
+    /// implementation.
+    pub fn hash_portable<H: Hasher>(&self, state: &mut H) {
+        // Length-prefixed rather than terminated like `str`: the raw
+        // bytes fed on Unix can contain any terminator byte.  The
+        // length has a fixed width and byte order so that it does not
+        // depend on the target either.
+        let bytes = self.bytes();
+        let len = bytes.len() as u64;
+        state.write(&[len as u8, (len >> 8) as u8, (len >> 16) as u8, (len >> 24) as u8,
+                      (len >> 32) as u8, (len >> 40) as u8, (len >> 48) as u8, (len >> 56) as u8]);
+        state.write(bytes);
+    }
+
+    /// Encodes this string as ASCII, replacing every byte of its
+    /// encoded form (see `as_encoded_bytes`) other than ASCII letters,
+    /// digits, `-`, `.`, `_`, `~` and `/` with a `%XX` escape.
+    ///
+    /// The result can be decoded with `OsString::from_percent_encoded`
+    /// on the same platform.
+    pub fn percent_encode(&self) -> String {
+        const HEX: &'static [u8; 16] = b"0123456789ABCDEF";
+
+        let mut result = String::with_capacity(self.len());
+        for &byte in self.bytes() {
+            match byte {
+                b'a'...b'z' | b'A'...b'Z' | b'0'...b'9' |
+                b'-' | b'.' | b'_' | b'~' | b'/' => result.push(byte as char),
+                _ => {
+                    result.push('%');
+                    result.push(HEX[(byte >> 4) as usize] as char);
+                    result.push(HEX[(byte & 0xF) as usize] as char);
+                }
+            }
+        }
+        result
     }
-}
 
-impl PartialEq<str> for OsStr {
-    fn eq(&self, other: &str) -> bool {
-        *self == *OsStr::new(other)
+    fn native_encoding() -> Encoding {
+        if is_windows!() { Encoding::Wtf8 } else { Encoding::Bytes }
     }
-}
 
-impl PartialEq<OsStr> for str {
-    fn eq(&self, other: &OsStr) -> bool {
-        *other == *OsStr::new(self)
+    /// Gets the underlying byte representation.
+    fn bytes(&self) -> &[u8] {
+        unsafe { mem::transmute(&self.inner) }
     }
-}
 
-impl Eq for OsStr {}
+    #[inline(never)]
+    fn slice_error_fail(&self, begin: usize, end: usize) -> ! {
+        assert!(begin <= end, "begin <= end ({} <= {}) when slicing `{:?}`",
+                begin, end, self);
+        assert!(end <= self.len(), "index {} out of bounds when slicing `{:?}`",
+                end, self);
+        panic!("index {} or {} in `{:?}` does not lie on a code point boundary",
+               begin, end, self)
+    }
 
-impl PartialOrd for OsStr {
-    #[inline]
-    fn partial_cmp(&self, other: &OsStr) -> Option<cmp::Ordering> {
-        self.bytes().partial_cmp(other.bytes())
+    /// Returns an iterator over the Unicode and non-Unicode sections
+    /// of the string.  Sections will always be nonempty and Unicode
+    /// and non-Unicode sections will always alternate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::ffi::OsStr;
+    /// use osstring_prototype::prelude::*;
+    /// let string = OsStr::new("Hello!");
+    /// match string.split_unicode().next().unwrap() {
+    ///     OsStrSection::Unicode(s) => assert_eq!(s, "Hello!"),
+    ///     OsStrSection::NonUnicode(s) => panic!("Got non-Unicode: {:?}", s),
+    /// }
+    /// ```
+    pub fn split_unicode<'a>(&'a self) -> SplitUnicode<'a> {
+        SplitUnicode(self.inner.split_unicode())
     }
-    #[inline]
-    fn lt(&self, other: &OsStr) -> bool { self.bytes().lt(other.bytes()) }
-    #[inline]
-    fn le(&self, other: &OsStr) -> bool { self.bytes().le(other.bytes()) }
-    #[inline]
-    fn gt(&self, other: &OsStr) -> bool { self.bytes().gt(other.bytes()) }
-    #[inline]
-    fn ge(&self, other: &OsStr) -> bool { self.bytes().ge(other.bytes()) }
-}
 
-impl PartialOrd<str> for OsStr {
-    #[inline]
-    fn partial_cmp(&self, other: &str) -> Option<cmp::Ordering> {
-        self.partial_cmp(OsStr::new(other))
+    /// Returns an iterator over chunks of the encoded string, each a
+    /// Unicode section followed by the non-Unicode data after it.
+    /// Either half of a chunk may be empty, but not both.
+    ///
+    /// This is useful for custom lossy conversions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use osstring_prototype::os_str::OsStr;
+    /// let string = OsStr::new("Hello!");
+    /// assert_eq!(string.utf8_chunks().collect::<Vec<_>>(), [("Hello!", &b""[..])]);
+    /// ```
+    pub fn utf8_chunks<'a>(&'a self) -> Utf8Chunks<'a> {
+        Utf8Chunks(self.split_unicode())
     }
-}
 
-// FIXME (#19470): cannot provide PartialOrd<OsStr> for str until we
-// have more flexible coherence rules.
+    /// Returns an iterator over the non-Unicode sections of the
+    /// string, with their byte ranges in the encoded string.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use osstring_prototype::os_str::OsStr;
+    /// assert!(OsStr::new("Hello!").invalid_sections().next().is_none());
+    /// ```
+    pub fn invalid_sections<'a>(&'a self) -> InvalidSections<'a> {
+        InvalidSections {
+            sections: self.split_unicode(),
+            start: 0,
+            end: self.len(),
+        }
+    }
 
-impl Ord for OsStr {
-    #[inline]
-    fn cmp(&self, other: &OsStr) -> cmp::Ordering { self.bytes().cmp(other.bytes()) }
-}
+    /// Returns true if `needle` is a substring of `self`.
+    ///
+    /// # Platform behavior
+    ///
+    /// On Windows systems, a surrogate at either end of `needle` can
+    /// match half of a surrogate pair in `self`.  `contains` with the
+    /// same needle as an `OsStr` pattern does not match it there.
+    pub fn contains_os<S: AsRef<OsStr>>(&self, needle: S) -> bool {
+        self.inner.contains_os(&needle.as_ref().inner)
+    }
 
-impl Hash for OsStr {
-    #[inline]
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.bytes().hash(state)
+    /// Returns true if `self` contains the byte `byte`.  See
+    /// `find_byte` for platform behavior.
+    pub fn contains_byte(&self, byte: u8) -> bool {
+        self.find_byte(byte).is_some()
     }
-}
 
-impl Debug for OsStr {
-    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        self.inner.fmt(formatter)
+    /// Returns the byte index of the first occurrence of the byte
+    /// `byte` in `self`, or `None` if there is none.  The result is
+    /// always a valid argument to `split_at`.
+    ///
+    /// # Platform behavior
+    ///
+    /// On Unix systems, any byte can be found.
+    ///
+    /// On Windows systems, non-ASCII bytes are only parts of encoded
+    /// code points, so this returns `None` for them.
+    pub fn find_byte(&self, byte: u8) -> Option<usize> {
+        if is_windows!() && byte >= 0x80 {
+            return None;
+        }
+        self.bytes().iter().position(|&b| b == byte)
     }
-}
 
-impl Borrow<OsStr> for OsString {
-    fn borrow(&self) -> &OsStr { &self[..] }
-}
+    /// Returns the byte index of the first occurrence of `needle` in
+    /// `self`, or `None` if there is none.
+    ///
+    /// # Platform behavior
+    ///
+    /// On Windows systems, a surrogate at either end of `needle` will
+    /// not match half of a surrogate pair in `self`, since that would
+    /// not be a valid index.
+    pub fn find_os<S: AsRef<OsStr>>(&self, needle: S) -> Option<usize> {
+        self.inner.find_os(&needle.as_ref().inner)
+    }
+
+    /// Returns the byte index of the last occurrence of `needle` in
+    /// `self`, or `None` if there is none.  See `find_os` for platform
+    /// behavior.
+    pub fn rfind_os<S: AsRef<OsStr>>(&self, needle: S) -> Option<usize> {
+        self.inner.rfind_os(&needle.as_ref().inner)
+    }
+
+    /// An iterator over the non-overlapping occurrences of `needle`
+    /// in `self`, yielding the byte index of each match along with
+    /// the match itself.  An empty needle matches where an empty
+    /// `OsStr` pattern does: at each end and before every byte that
+    /// is not a UTF-8 continuation byte.  See `find_os` for platform
+    /// behavior.
+    pub fn match_indices_os<'a, 'b, S>(&'a self, needle: &'b S) -> MatchIndicesOs<'a, 'b>
+    where S: AsRef<OsStr> + ?Sized {
+        let needle = needle.as_ref();
+        MatchIndicesOs {
+            haystack: self,
+            needle_len: needle.len(),
+            searcher: SliceSearcher::new(self.bytes(), needle.bytes(), false),
+        }
+    }
 
-impl ToOwned for OsStr {
-    type Owned = OsString;
-    fn to_owned(&self) -> OsString { self.to_os_string() }
-}
+    /// An iterator over the non-overlapping occurrences of `needle`
+    /// in `self`, starting from the end.  Yields the same items as
+    /// `match_indices_os`, except when matches overlap.  See
+    /// `find_os` for platform behavior.
+    pub fn rmatch_indices_os<'a, 'b, S>(&'a self, needle: &'b S) -> RMatchIndicesOs<'a, 'b>
+    where S: AsRef<OsStr> + ?Sized {
+        let needle = needle.as_ref();
+        RMatchIndicesOs {
+            haystack: self,
+            needle_len: needle.len(),
+            searcher: ReverseSliceSearcher::new(self.bytes(), needle.bytes(), false),
+        }
+    }
 
-impl AsRef<OsStr> for OsStr {
-    fn as_ref(&self) -> &OsStr {
-        self
+    /// Returns the number of non-overlapping occurrences of `needle`
+    /// in `self`.  Equivalent to `self.match_indices_os(needle).count()`.
+    pub fn count_matches_os<S: AsRef<OsStr>>(&self, needle: S) -> usize {
+        let needle = needle.as_ref();
+        if needle.is_empty() {
+            // Needs the code point boundary checks
+            return self.match_indices_os(needle).count();
+        }
+        SliceSearcher::new(self.bytes(), needle.bytes(), false).count()
     }
-}
 
-impl AsRef<OsStr> for OsString {
-    fn as_ref(&self) -> &OsStr {
-        self
+    /// Splits `self` on the first occurrence of `needle`, returning
+    /// the parts before and after it, or `None` if there is none.
+    ///
+    /// # Platform behavior
+    ///
+    /// On Windows systems, a surrogate at either end of `needle` will
+    /// not match half of a surrogate pair in `self`, since the pair
+    /// cannot be split without allocating.
+    pub fn split_once_os<S: AsRef<OsStr>>(&self, needle: S) -> Option<(&OsStr, &OsStr)> {
+        self.inner.split_once_os(&needle.as_ref().inner)
+            .map(|(a, b)| (OsStr::from_inner(a), OsStr::from_inner(b)))
     }
-}
 
-impl AsRef<OsStr> for str {
-    fn as_ref(&self) -> &OsStr {
-        OsStr::from_inner(Slice::from_str(self))
+    /// Splits `self` on the last occurrence of `needle`, returning
+    /// the parts before and after it, or `None` if there is none.
+    /// See `split_once_os` for platform behavior.
+    pub fn rsplit_once_os<S: AsRef<OsStr>>(&self, needle: S) -> Option<(&OsStr, &OsStr)> {
+        self.inner.rsplit_once_os(&needle.as_ref().inner)
+            .map(|(a, b)| (OsStr::from_inner(a), OsStr::from_inner(b)))
     }
-}
 
-impl AsRef<OsStr> for String {
-    fn as_ref(&self) -> &OsStr {
-        (&**self).as_ref()
+    /// Returns true if `needle` is a prefix of `self`.  See
+    /// `contains_os` for platform behavior.
+    pub fn starts_with_os<S: AsRef<OsStr>>(&self, needle: S) -> bool {
+        self.inner.starts_with_os(&needle.as_ref().inner)
     }
-}
 
-impl FromInner<Buf> for OsString {
-    fn from_inner(buf: Buf) -> OsString {
-        OsString { inner: buf }
+    /// Returns true if `needle` is a suffix of `self`.  See
+    /// `contains_os` for platform behavior.
+    pub fn ends_with_os<S: AsRef<OsStr>>(&self, needle: S) -> bool {
+        self.inner.ends_with_os(&needle.as_ref().inner)
     }
-}
 
-impl IntoInner<Buf> for OsString {
-    fn into_inner(self) -> Buf {
-        self.inner
+    /// Returns an iterator over the `char`s of the string.  Each
+    /// non-Unicode sequence is replaced by a single U+FFFD REPLACEMENT
+    /// CHARACTER.
+    ///
+    /// # Platform behavior
+    ///
+    /// On Unix systems, a maximal run of invalid bytes produces one
+    /// replacement character.
+    ///
+    /// On Windows systems, each unpaired surrogate produces one
+    /// replacement character.
+    pub fn chars_lossy<'a>(&'a self) -> CharsLossy<'a> {
+        CharsLossy(self.inner.chars_lossy())
     }
-}
 
-impl AsInner<Slice> for OsStr {
-    fn as_inner(&self) -> &Slice {
-        &self.inner
+    /// Returns an iterator over pieces of the string converted as by
+    /// `chars_lossy`.  Unicode sections are borrowed and each
+    /// non-Unicode section is replaced by U+FFFD REPLACEMENT
+    /// CHARACTERs, so the string can be displayed or transcoded
+    /// without first copying all of it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use osstring_prototype::os_str::OsStr;
+    /// let string = OsStr::new("Hello!");
+    /// assert_eq!(string.to_str_chunks_lossy().collect::<Vec<_>>(), ["Hello!"]);
+    /// ```
+    pub fn to_str_chunks_lossy<'a>(&'a self) -> StrChunksLossy<'a> {
+        StrChunksLossy(self.split_unicode())
     }
-}
 
+    /// Returns an object that implements `Display` for printing the
+    /// string as by `to_string_lossy`.  Width, precision, fill and
+    /// alignment are handled as for `str`.
+    ///
+    /// Only non-Unicode strings formatted with a width or precision
+    /// are converted with an allocation; everything else is written
+    /// directly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use osstring_prototype::os_str::OsStr;
+    /// let string = OsStr::new("Hello!");
+    /// assert_eq!(format!("[{:>7.5}]", string.display()), "[  Hello]");
+    /// ```
+    pub fn display(&self) -> Display {
+        Display(self)
+    }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum OsStrSection<'a> {
-    Unicode(&'a str),
-    NonUnicode(&'a OsStr),
-}
+    /// Returns an object that implements `Display` for escaping the
+    /// string.  Unicode characters are escaped as by
+    /// `char::escape_default`.
+    ///
+    /// # Platform behavior
+    ///
+    /// On Unix systems, each invalid byte is written as `\xNN`.
+    ///
+    /// On Windows systems, each unpaired surrogate is written as
+    /// `\u{DNNN}`.
+    pub fn escape_default(&self) -> EscapeDefault {
+        EscapeDefault(self)
+    }
+
+    /// Returns an object that implements `Display` for escaping the
+    /// string.  Printable Unicode characters are written unchanged,
+    /// while quotes, backslashes and control characters are escaped.
+    /// Non-Unicode data is escaped as by `escape_default`.
+    pub fn escape_debug(&self) -> EscapeDebug {
+        EscapeDebug(self)
+    }
+
+    fn write_escaped<F>(&self, formatter: &mut fmt::Formatter, mut write_char: F) -> fmt::Result
+    where F: FnMut(&mut fmt::Formatter, char) -> fmt::Result {
+        for section in self.split_unicode() {
+            match section {
+                OsStrSection::Unicode(s) => {
+                    for c in s.chars() {
+                        try!(write_char(formatter, c));
+                    }
+                }
+                OsStrSection::NonUnicode(s) => if_unix_windows! {
+                    unix {
+                        for byte in s.bytes() {
+                            try!(write!(formatter, "\\x{:02X}", byte));
+                        }
+                    }
+                    windows {
+                        use windows::OsStrExt;
+                        for unit in s.encode_wide() {
+                            try!(write!(formatter, "\\u{{{:X}}}", unit));
+                        }
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces all occurrences of one string with another.  If there
+    /// are none, `self` is returned without allocating.
+    ///
+    /// Like `contains_os`, this matches half of a surrogate pair on
+    /// Windows systems.
+    ///
+    /// Unlike `str::replace`, an empty `from` matches between every
+    /// pair of code units, not just between characters: between bytes
+    /// on Unix, and between 16-bit code units on Windows.  Use
+    /// `replace_matches` with an empty `str` pattern to insert between
+    /// characters.
+    pub fn replace<T: AsRef<OsStr>, U: AsRef<OsStr>>(&self, from: T, to: U) -> Cow<OsStr> {
+        match self.inner.replace(&from.as_ref().inner, &to.as_ref().inner) {
+            Some(buf) => Cow::Owned(OsString::from_inner(buf)),
+            None => Cow::Borrowed(self),
+        }
+    }
+
+    /// Replaces all matches of a pattern with a string.  See
+    /// `str::replace` for details.
+    ///
+    /// This cannot be called `replace`, since that name is taken by
+    /// the version taking an `OsStr` needle.
+    ///
+    /// If nothing matches, `self` is returned without allocating.
+    ///
+    /// Note that `str` patterns never match non-Unicode data.
+    pub fn replace_matches<'a, P>(&'a self, pat: P, to: &str) -> Cow<'a, OsStr>
+    where P: OsPattern<'a> {
+        self.replacen(pat, to, usize::max_value())
+    }
+
+    /// Replaces the first `count` matches of a pattern with a string.
+    /// See `str::replacen` for details.  If nothing is replaced,
+    /// `self` is returned without allocating.
+    ///
+    /// Note that `str` patterns never match non-Unicode data.
+    pub fn replacen<'a, P>(&'a self, pat: P, to: &str, count: usize) -> Cow<'a, OsStr>
+    where P: OsPattern<'a> {
+        let mut searcher = pat.into_os_searcher(self.bytes());
+        let mut next = if count == 0 { None } else { searcher.next_match() };
+        if next.is_none() { return Cow::Borrowed(self); }
+
+        let mut result = OsString::with_capacity(self.len());
+        let mut position = 0;
+        let mut remaining = count;
+        while let Some((start, end)) = next {
+            result.push(&self[position..start]);
+            result.push(to);
+            position = end;
+            remaining -= 1;
+            next = if remaining == 0 { None } else { searcher.next_match() };
+        }
+        result.push(&self[position..]);
+        Cow::Owned(result)
+    }
+
+    /// An iterator over the non-empty substrings of `self` that
+    /// contain no whitespace and are separated by whitespace.
+    pub fn split_whitespace<'a>(&'a self) -> SplitWhitespace<'a> {
+        SplitWhitespace::new(self)
+    }
+
+    /// An iterator over the lines of `self`, separated by `\n` or
+    /// `\r\n`.  This does not return an empty string after a trailing
+    /// `\n`.
+    pub fn lines<'a>(&'a self) -> Lines<'a> {
+        Lines::new(self)
+    }
+
+    /// An iterator over the paths in a search path variable like
+    /// `PATH`.
+    ///
+    /// # Platform behavior
+    ///
+    /// On Unix systems, paths are separated by `:`.
+    ///
+    /// On Windows systems, paths are separated by `;`, except within
+    /// double quotes.  The quotes are removed from the returned paths.
+    pub fn split_search_paths<'a>(&'a self) -> SplitSearchPaths<'a> {
+        SplitSearchPaths { remaining: Some(self) }
+    }
+
+    /// Returns true if `self` matches `pat`.
+    ///
+    /// Note that `str` patterns never match non-Unicode data.
+    ///
+    /// # Platform behavior
+    ///
+    /// On Windows systems, an `OsStr` pattern never matches half of a
+    /// surrogate pair in `self`, since every match must lie on code
+    /// point boundaries.  This can differ from `contains_os` with the
+    /// same needle.
+    pub fn contains<'a, P>(&'a self, pat: P) -> bool where P: OsPattern<'a> {
+        pat.into_os_searcher(self.bytes()).next_match().is_some()
+    }
+
+    /// Returns the byte index of the first match of `pat` in `self`,
+    /// or `None` if there is none.
+    ///
+    /// Note that `str` patterns never match non-Unicode data.
+    pub fn find<'a, P>(&'a self, pat: P) -> Option<usize> where P: OsPattern<'a> {
+        pat.into_os_searcher(self.bytes()).next_match().map(|(start, _)| start)
+    }
+
+    /// Returns the byte index of the last match of `pat` in `self`,
+    /// or `None` if there is none.
+    ///
+    /// Note that `str` patterns never match non-Unicode data.
+    pub fn rfind<'a, P>(&'a self, pat: P) -> Option<usize>
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
+        pat.into_os_searcher(self.bytes()).next_match_back().map(|(start, _)| start)
+    }
+
+    /// Returns true if the beginning of `self` matches `pat`.
+    ///
+    /// Note that `str` patterns never match non-Unicode data.  See
+    /// `contains` for platform behavior.
+    pub fn starts_with<'a, P>(&'a self, pat: P) -> bool where P: OsPattern<'a> {
+        pat.into_os_searcher(self.bytes()).match_prefix().is_some()
+    }
+
+    /// Returns true if the end of `self` matches `pat`.
+    ///
+    /// Note that `str` patterns never match non-Unicode data.  See
+    /// `contains` for platform behavior.
+    pub fn ends_with<'a, P>(&'a self, pat: P) -> bool
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
+        pat.into_os_searcher(self.bytes()).match_suffix().is_some()
+    }
+
+    /// Returns the remainder of `self` after removing a match of `pat`
+    /// at the start, or `None` if `self` does not start with `pat`.
+    ///
+    /// Note that `str` patterns never match non-Unicode data.
+    pub fn strip_prefix<'a, P>(&'a self, pat: P) -> Option<&'a OsStr> where P: OsPattern<'a> {
+        pat.into_os_searcher(self.bytes()).match_prefix().map(|end| &self[end..])
+    }
+
+    /// Returns the remainder of `self` after removing a match of `pat`
+    /// at the end, or `None` if `self` does not end with `pat`.
+    ///
+    /// Note that `str` patterns never match non-Unicode data.
+    pub fn strip_suffix<'a, P>(&'a self, pat: P) -> Option<&'a OsStr>
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
+        pat.into_os_searcher(self.bytes()).match_suffix().map(|start| &self[..start])
+    }
+
+    /// Splits `self` on the first match of `pat`, returning the parts
+    /// before and after the match, or `None` if there is no match.
+    ///
+    /// Note that `str` patterns never match non-Unicode data.
+    pub fn split_once<'a, P>(&'a self, pat: P) -> Option<(&'a OsStr, &'a OsStr)>
+    where P: OsPattern<'a> {
+        pat.into_os_searcher(self.bytes()).next_match()
+            .map(|(start, end)| (&self[..start], &self[end..]))
+    }
+
+    /// Splits `self` on the last match of `pat`, returning the parts
+    /// before and after the match, or `None` if there is no match.
+    ///
+    /// Note that `str` patterns never match non-Unicode data.
+    pub fn rsplit_once<'a, P>(&'a self, pat: P) -> Option<(&'a OsStr, &'a OsStr)>
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
+        pat.into_os_searcher(self.bytes()).next_match_back()
+            .map(|(start, end)| (&self[..start], &self[end..]))
+    }
+
+    /// Returns the number of matches of `pat` in `self`.  Equivalent
+    /// to `self.matches(pat).count()`.
+    ///
+    /// Note that `str` patterns never match non-Unicode data.
+    pub fn count_matches<'a, P>(&'a self, pat: P) -> usize where P: OsPattern<'a> {
+        let mut searcher = pat.into_os_searcher(self.bytes());
+        let mut count = 0;
+        while searcher.next_match().is_some() {
+            count += 1;
+        }
+        count
+    }
+
+    /// Returns the last character of `self` and the rest of the
+    /// string, or `None` if `self` is empty or does not end with a
+    /// Unicode character.
+    pub fn slice_pop_char(&self) -> Option<(&OsStr, char)> {
+        let (offset, section) = self.inner.utf8_sections().next_back().unwrap();
+        section.chars().next_back().map(|c| {
+            (self.split_at(offset + section.len() - c.len_utf8()).0, c)
+        })
+    }
+
+    /// An iterator over substrings of `self` separated by characters
+    /// matched by a pattern.  See `str::split` for details.
+    ///
+    /// Note that `str` patterns never match non-Unicode data.
+    pub fn split<'a, P>(&'a self, pat: P) -> Split<'a, P>
+    where P: OsPattern<'a> {
+        Split { inner: self.inner.split(pat) }
+    }
+
+    /// An iterator over substrings of `self` separated by characters
+    /// matched by a pattern, in reverse order.  See `str::rsplit` for
+    /// details.
+    ///
+    /// Note that `str` patterns never match non-Unicode data.
+    pub fn rsplit<'a, P>(&'a self, pat: P) -> RSplit<'a, P>
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
+        RSplit { inner: self.inner.rsplit(pat) }
+    }
+
+    /// Equivalent to `split`, except the trailing substring is
+    /// skipped if empty.  See `str::split_terminator` for details.
+    ///
+    /// Note that `str` patterns never match non-Unicode data.
+    pub fn split_terminator<'a, P>(&'a self, pat: P) -> SplitTerminator<'a, P>
+    where P: OsPattern<'a> {
+        SplitTerminator { inner: self.inner.split_terminator(pat) }
+    }
+
+    /// Equivalent to `rsplit`, except the trailing substring is
+    /// skipped if empty.  See `str::rsplit_terminator` for details.
+    ///
+    /// Note that `str` patterns never match non-Unicode data.
+    pub fn rsplit_terminator<'a, P>(&'a self, pat: P) -> RSplitTerminator<'a, P>
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
+        RSplitTerminator { inner: self.inner.rsplit_terminator(pat) }
+    }
+
+    /// An iterator over substrings of `self` separated by characters
+    /// matched by a pattern, restricted to returning at most `count`
+    /// items.  See `str::splitn` for details.
+    ///
+    /// Note that `str` patterns never match non-Unicode data.
+    pub fn splitn<'a, P>(&'a self, count: usize, pat: P) -> SplitN<'a, P>
+    where P: OsPattern<'a> {
+        SplitN { inner: self.inner.splitn(count, pat) }
+    }
+
+    /// An iterator over substrings of `self` separated by characters
+    /// matched by a pattern, in reverse order, restricted to returning
+    /// at most `count` items.  See `str::rsplitn` for details.
+    ///
+    /// Note that `str` patterns never match non-Unicode data.
+    pub fn rsplitn<'a, P>(&'a self, count: usize, pat: P) -> RSplitN<'a, P>
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
+        RSplitN { inner: self.inner.rsplitn(count, pat) }
+    }
+
+    /// An iterator over substrings of `self` separated by `sep`.
+    /// Equivalent to `self.split(sep.as_ref())`, so `sep` is matched
+    /// exactly, including any non-Unicode data.  See `find_os` for
+    /// platform behavior.
+    pub fn split_os<'a, 'b, S>(&'a self, sep: &'b S) -> Split<'a, &'b OsStr>
+    where S: AsRef<OsStr> + ?Sized {
+        self.split(sep.as_ref())
+    }
+
+    /// An iterator over substrings of `self` separated by `sep`, in
+    /// reverse order.  See `split_os`.
+    pub fn rsplit_os<'a, 'b, S>(&'a self, sep: &'b S) -> RSplit<'a, &'b OsStr>
+    where S: AsRef<OsStr> + ?Sized {
+        self.rsplit(sep.as_ref())
+    }
+
+    /// An iterator over substrings of `self` separated by `sep`,
+    /// restricted to returning at most `count` items.  See `split_os`.
+    pub fn splitn_os<'a, 'b, S>(&'a self, count: usize, sep: &'b S) -> SplitN<'a, &'b OsStr>
+    where S: AsRef<OsStr> + ?Sized {
+        self.splitn(count, sep.as_ref())
+    }
+
+    /// An iterator over substrings of `self` separated by `sep`,
+    /// starting from the end and restricted to returning at most
+    /// `count` items.  See `split_os`.
+    pub fn rsplitn_os<'a, 'b, S>(&'a self, count: usize, sep: &'b S) -> RSplitN<'a, &'b OsStr>
+    where S: AsRef<OsStr> + ?Sized {
+        self.rsplitn(count, sep.as_ref())
+    }
+
+    /// An iterator over matches of a pattern in `self`.  See
+    /// `str::matches` for details.
+    ///
+    /// Note that `str` patterns never match non-Unicode data.
+    pub fn matches<'a, P>(&'a self, pat: P) -> Matches<'a, P>
+    where P: OsPattern<'a> {
+        Matches { inner: self.inner.matches(pat) }
+    }
+
+    /// An iterator over matches of a pattern in `self`, in reverse
+    /// order.  See `str::rmatches` for details.
+    ///
+    /// Note that `str` patterns never match non-Unicode data.
+    pub fn rmatches<'a, P>(&'a self, pat: P) -> RMatches<'a, P>
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
+        RMatches { inner: self.inner.rmatches(pat) }
+    }
+
+    /// An iterator over the non-overlapping occurrences of `needle` in
+    /// `self`.  Equivalent to `self.matches(needle.as_ref())`, so
+    /// `needle` is matched exactly, including any non-Unicode data.
+    /// See `find_os` for platform behavior.
+    pub fn matches_os<'a, 'b, S>(&'a self, needle: &'b S) -> Matches<'a, &'b OsStr>
+    where S: AsRef<OsStr> + ?Sized {
+        self.matches(needle.as_ref())
+    }
+
+    /// An iterator over the non-overlapping occurrences of `needle` in
+    /// `self`, in reverse order.  See `matches_os`.
+    pub fn rmatches_os<'a, 'b, S>(&'a self, needle: &'b S) -> RMatches<'a, &'b OsStr>
+    where S: AsRef<OsStr> + ?Sized {
+        self.rmatches(needle.as_ref())
+    }
+
+    /// Returns a `&OsStr` with leading and trailing whitespace removed.
+    ///
+    /// Only whitespace in the leading and trailing Unicode sections
+    /// is removed; trimming stops at the first non-Unicode data.
+    pub fn trim(&self) -> &OsStr {
+        self.trim_matches(char::is_whitespace as fn(char) -> bool)
+    }
+
+    /// Returns a `&OsStr` with leading whitespace removed.
+    ///
+    /// Only whitespace in the leading Unicode section is removed.
+    pub fn trim_start(&self) -> &OsStr {
+        self.trim_start_matches(char::is_whitespace)
+    }
+
+    /// Returns a `&OsStr` with trailing whitespace removed.
+    ///
+    /// Only whitespace in the trailing Unicode section is removed.
+    pub fn trim_end(&self) -> &OsStr {
+        self.trim_end_matches(char::is_whitespace)
+    }
+
+    /// Returns a `&OsStr` with leading whitespace removed.  Identical
+    /// to `trim_start`.
+    pub fn trim_left(&self) -> &OsStr {
+        self.trim_start()
+    }
+
+    /// Returns a `&OsStr` with trailing whitespace removed.  Identical
+    /// to `trim_end`.
+    pub fn trim_right(&self) -> &OsStr {
+        self.trim_end()
+    }
+
+    /// Returns a `&OsStr` with leading and trailing matches of `pat`
+    /// repeatedly removed.
+    ///
+    /// Note that patterns can only match Unicode sections of the `OsStr`.
+    pub fn trim_matches<'a, P>(&'a self, pat: P) -> &'a OsStr
+    where P: Pattern<'a> + Clone, P::Searcher: DoubleEndedSearcher<'a> {
+        Self::from_inner(self.inner.trim_matches(pat))
+    }
+
+    /// Returns a `&OsStr` with leading matches of `pat` repeatedly
+    /// removed.
+    ///
+    /// Note that patterns can only match Unicode sections of the `OsStr`.
+    pub fn trim_start_matches<'a, P>(&'a self, pat: P) -> &'a OsStr
+    where P: Pattern<'a> {
+        Self::from_inner(self.inner.trim_left_matches(pat))
+    }
+
+    /// Returns a `&OsStr` with trailing matches of `pat` repeatedly
+    /// removed.
+    ///
+    /// Note that patterns can only match Unicode sections of the `OsStr`.
+    pub fn trim_end_matches<'a, P>(&'a self, pat: P) -> &'a OsStr
+    where P: Pattern<'a>, P::Searcher: ReverseSearcher<'a> {
+        Self::from_inner(self.inner.trim_right_matches(pat))
+    }
+
+    /// Returns a `&OsStr` with leading matches of `pat` repeatedly
+    /// removed.  Identical to `trim_start_matches`.
+    pub fn trim_left_matches<'a, P>(&'a self, pat: P) -> &'a OsStr
+    where P: Pattern<'a> {
+        self.trim_start_matches(pat)
+    }
+
+    /// Returns a `&OsStr` with trailing matches of `pat` repeatedly
+    /// removed.  Identical to `trim_end_matches`.
+    pub fn trim_right_matches<'a, P>(&'a self, pat: P) -> &'a OsStr
+    where P: Pattern<'a>, P::Searcher: ReverseSearcher<'a> {
+        self.trim_end_matches(pat)
+    }
+}
+
+impl ops::Index<ops::Range<usize>> for OsStr {
+    type Output = OsStr;
+
+    #[inline]
+    fn index(&self, index: ops::Range<usize>) -> &OsStr {
+        match self.get(index.clone()) {
+            Some(substring) => substring,
+            None => self.slice_error_fail(index.start, index.end),
+        }
+    }
+}
+
+impl ops::Index<ops::RangeFrom<usize>> for OsStr {
+    type Output = OsStr;
+
+    #[inline]
+    fn index(&self, index: ops::RangeFrom<usize>) -> &OsStr {
+        &self[index.start..self.len()]
+    }
+}
+
+impl ops::Index<ops::RangeTo<usize>> for OsStr {
+    type Output = OsStr;
+
+    #[inline]
+    fn index(&self, index: ops::RangeTo<usize>) -> &OsStr {
+        &self[0..index.end]
+    }
+}
+
+impl PartialEq for OsStr {
+    fn eq(&self, other: &OsStr) -> bool {
+        self.bytes().eq(other.bytes())
+    }
+}
+
+impl PartialEq<str> for OsStr {
+    fn eq(&self, other: &str) -> bool {
+        *self == *OsStr::new(other)
+    }
+}
+
+impl PartialEq<OsStr> for str {
+    fn eq(&self, other: &OsStr) -> bool {
+        *other == *OsStr::new(self)
+    }
+}
+
+impl Eq for OsStr {}
+
+impl PartialOrd for OsStr {
+    #[inline]
+    fn partial_cmp(&self, other: &OsStr) -> Option<cmp::Ordering> {
+        self.bytes().partial_cmp(other.bytes())
+    }
+    #[inline]
+    fn lt(&self, other: &OsStr) -> bool { self.bytes().lt(other.bytes()) }
+    #[inline]
+    fn le(&self, other: &OsStr) -> bool { self.bytes().le(other.bytes()) }
+    #[inline]
+    fn gt(&self, other: &OsStr) -> bool { self.bytes().gt(other.bytes()) }
+    #[inline]
+    fn ge(&self, other: &OsStr) -> bool { self.bytes().ge(other.bytes()) }
+}
+
+impl PartialOrd<str> for OsStr {
+    #[inline]
+    fn partial_cmp(&self, other: &str) -> Option<cmp::Ordering> {
+        self.partial_cmp(OsStr::new(other))
+    }
+}
+
+impl PartialOrd<OsStr> for str {
+    #[inline]
+    fn partial_cmp(&self, other: &OsStr) -> Option<cmp::Ordering> {
+        OsStr::new(self).partial_cmp(other)
+    }
+}
+
+impl PartialOrd<OsString> for str {
+    #[inline]
+    fn partial_cmp(&self, other: &OsString) -> Option<cmp::Ordering> {
+        OsStr::new(self).partial_cmp(&**other)
+    }
+}
+
+impl Ord for OsStr {
+    #[inline]
+    fn cmp(&self, other: &OsStr) -> cmp::Ordering { self.bytes().cmp(other.bytes()) }
+}
+
+// Comparisons in both directions between two types that can be viewed
+// as an `OsStr`.
+macro_rules! impl_cmp {
+    ($lhs:ty, $rhs:ty) => {
+        impl<'a, 'b> PartialEq<$rhs> for $lhs {
+            #[inline]
+            fn eq(&self, other: &$rhs) -> bool {
+                <OsStr as PartialEq>::eq(self.as_ref(), other.as_ref())
+            }
+        }
+
+        impl<'a, 'b> PartialEq<$lhs> for $rhs {
+            #[inline]
+            fn eq(&self, other: &$lhs) -> bool {
+                <OsStr as PartialEq>::eq(self.as_ref(), other.as_ref())
+            }
+        }
+
+        impl<'a, 'b> PartialOrd<$rhs> for $lhs {
+            #[inline]
+            fn partial_cmp(&self, other: &$rhs) -> Option<cmp::Ordering> {
+                <OsStr as PartialOrd>::partial_cmp(self.as_ref(), other.as_ref())
+            }
+        }
+
+        impl<'a, 'b> PartialOrd<$lhs> for $rhs {
+            #[inline]
+            fn partial_cmp(&self, other: &$lhs) -> Option<cmp::Ordering> {
+                <OsStr as PartialOrd>::partial_cmp(self.as_ref(), other.as_ref())
+            }
+        }
+    }
+}
+
+impl_cmp!(OsString, OsStr);
+impl_cmp!(OsString, &'a OsStr);
+impl_cmp!(Cow<'a, OsStr>, OsStr);
+impl_cmp!(Cow<'a, OsStr>, &'b OsStr);
+impl_cmp!(Cow<'a, OsStr>, OsString);
+impl_cmp!(OsString, String);
+impl_cmp!(OsString, &'a str);
+impl_cmp!(OsStr, String);
+
+impl Hash for OsStr {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bytes().hash(state)
+    }
+}
+
+/// A string whose `Hash` implementation is `OsStr::hash_portable`.
+///
+/// Equality compares the wrapped strings as `OsStr`s, so this can be
+/// used as a `HashMap` key, for example for a filename, that hashes
+/// the same way on every platform.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PortableHash<T>(pub T);
+
+impl<T: AsRef<OsStr>> Hash for PortableHash<T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.as_ref().hash_portable(state)
+    }
+}
+
+impl<T: AsRef<OsStr>> PartialEq for PortableHash<T> {
+    fn eq(&self, other: &PortableHash<T>) -> bool {
+        self.0.as_ref() == other.0.as_ref()
+    }
+}
+
+impl<T: AsRef<OsStr>> Eq for PortableHash<T> {}
+
+/// Compares two strings in natural order, so that `"file2"` sorts
+/// before `"file10"`.
+///
+/// Runs of ASCII digits found at the same position in both strings
+/// are compared by their numeric value, of any size.  Everything else,
+/// including non-Unicode data, is compared byte by byte as by `Ord`.
+/// Strings that only differ in leading zeros, like `"a01"` and `"a1"`,
+/// are ordered by `Ord`, so this only returns `Equal` for equal
+/// strings.
+///
+/// # Example
+///
+/// ```
+/// use osstring_prototype::os_str::{OsString, cmp_natural};
+///
+/// let mut files = vec![OsString::from("file10"), OsString::from("file2"),
+///                      OsString::from("file1")];
+/// files.sort_by(|a, b| cmp_natural(a, b));
+/// assert_eq!(files, ["file1", "file2", "file10"]);
+/// ```
+pub fn cmp_natural<A: AsRef<OsStr>, B: AsRef<OsStr>>(a: A, b: B) -> cmp::Ordering {
+    fn is_digit(byte: u8) -> bool {
+        match byte {
+            b'0'...b'9' => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the ends of the digit run starting at `start` and of
+    /// its leading zeros.
+    fn digit_run(bytes: &[u8], start: usize) -> (usize, usize) {
+        let end = start + bytes[start..].iter().take_while(|&&b| is_digit(b)).count();
+        let zeros_end = start + bytes[start..end].iter().take_while(|&&b| b == b'0').count();
+        (end, zeros_end)
+    }
+
+    /// Breaks a tie in `order` by comparing bytes.
+    fn or_bytes(order: cmp::Ordering, a: &[u8], b: &[u8]) -> cmp::Ordering {
+        match order {
+            cmp::Ordering::Equal => a.cmp(b),
+            order => order,
+        }
+    }
+
+    let (a, b) = (a.as_ref().bytes(), b.as_ref().bytes());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if is_digit(a[i]) && is_digit(b[j]) {
+            let (a_end, a_start) = digit_run(a, i);
+            let (b_end, b_start) = digit_run(b, j);
+            // Without leading zeros, a longer number is larger.
+            let order = or_bytes((a_end - a_start).cmp(&(b_end - b_start)),
+                                 &a[a_start..a_end], &b[b_start..b_end]);
+            if order != cmp::Ordering::Equal {
+                return order;
+            }
+            i = a_end;
+            j = b_end;
+        } else {
+            if a[i] != b[j] {
+                return a[i].cmp(&b[j]);
+            }
+            i += 1;
+            j += 1;
+        }
+    }
+    or_bytes((a.len() - i).cmp(&(b.len() - j)), a, b)
+}
+
+/// A string whose `Ord` implementation is `cmp_natural`.
+///
+/// This can be used with `sort_by_key` or as the key of a `BTreeMap`
+/// to keep file names in the order users expect.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NaturalSortKey<T>(pub T);
+
+impl<T: AsRef<OsStr>> PartialEq for NaturalSortKey<T> {
+    fn eq(&self, other: &NaturalSortKey<T>) -> bool {
+        self.0.as_ref() == other.0.as_ref()
+    }
+}
+
+impl<T: AsRef<OsStr>> Eq for NaturalSortKey<T> {}
+
+impl<T: AsRef<OsStr>> PartialOrd for NaturalSortKey<T> {
+    fn partial_cmp(&self, other: &NaturalSortKey<T>) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: AsRef<OsStr>> Ord for NaturalSortKey<T> {
+    fn cmp(&self, other: &NaturalSortKey<T>) -> cmp::Ordering {
+        cmp_natural(self.0.as_ref(), other.0.as_ref())
+    }
+}
+
+impl<T: AsRef<OsStr>> Hash for NaturalSortKey<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.as_ref().hash(state)
+    }
+}
+
+impl Debug for OsStr {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        self.inner.fmt(formatter)
+    }
+}
+
+impl Borrow<OsStr> for OsString {
+    fn borrow(&self) -> &OsStr { &self[..] }
+}
+
+impl ToOwned for OsStr {
+    type Owned = OsString;
+    fn to_owned(&self) -> OsString { self.to_os_string() }
+}
+
+impl AsRef<OsStr> for OsStr {
+    fn as_ref(&self) -> &OsStr {
+        self
+    }
+}
+
+impl AsRef<OsStr> for OsString {
+    fn as_ref(&self) -> &OsStr {
+        self
+    }
+}
+
+impl AsRef<OsStr> for str {
+    fn as_ref(&self) -> &OsStr {
+        OsStr::from_inner(Slice::from_str(self))
+    }
+}
+
+impl AsRef<OsStr> for String {
+    fn as_ref(&self) -> &OsStr {
+        (&**self).as_ref()
+    }
+}
+
+impl<'a, 'b> OsPattern<'a> for &'b OsStr {
+    type Searcher = EncodedSearcher<'a, 'b>;
+
+    fn into_os_searcher(self, haystack: &'a [u8]) -> EncodedSearcher<'a, 'b> {
+        EncodedSearcher::new(haystack, self.bytes())
+    }
+}
+
+impl<'a, 'b> OsPattern<'a> for &'b OsString {
+    type Searcher = EncodedSearcher<'a, 'b>;
+
+    fn into_os_searcher(self, haystack: &'a [u8]) -> EncodedSearcher<'a, 'b> {
+        EncodedSearcher::new(haystack, self.as_os_str().bytes())
+    }
+}
+
+impl FromInner<Buf> for OsString {
+    fn from_inner(buf: Buf) -> OsString {
+        OsString { inner: buf }
+    }
+}
+
+impl IntoInner<Buf> for OsString {
+    fn into_inner(self) -> Buf {
+        self.inner
+    }
+}
+
+impl AsInnerMut<Buf> for OsString {
+    fn as_inner_mut(&mut self) -> &mut Buf {
+        &mut self.inner
+    }
+}
+
+impl AsInner<Slice> for OsStr {
+    fn as_inner(&self) -> &Slice {
+        &self.inner
+    }
+}
+
+
+/// The error returned by `OsStr::try_to_cstring`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToCStringError {
+    /// The string contains a nul byte at the given position.
+    InteriorNul(usize),
+    /// The string is not valid Unicode, which is required on Windows.
+    /// Holds the length of the longest valid Unicode prefix.
+    NotUnicode(usize),
+}
+
+impl fmt::Display for ToCStringError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ToCStringError::InteriorNul(pos) =>
+                write!(formatter, "nul byte found at position {}", pos),
+            ToCStringError::NotUnicode(valid_up_to) =>
+                write!(formatter, "non-Unicode data found after {} bytes", valid_up_to),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for ToCStringError {
+    fn description(&self) -> &str {
+        match *self {
+            ToCStringError::InteriorNul(..) => "nul byte found in data",
+            ToCStringError::NotUnicode(..) => "non-Unicode data",
+        }
+    }
+}
+
+/// The error returned by `OsStr::try_to_str`, locating the first
+/// sequence that is not valid Unicode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OsStrUtf8Error {
+    valid_up_to: usize,
+    error_len: usize,
+}
+
+impl OsStrUtf8Error {
+    /// Returns the length of the longest valid Unicode prefix of the
+    /// string.
+    pub fn valid_up_to(&self) -> usize {
+        self.valid_up_to
+    }
+
+    /// Returns the length of the non-Unicode sequence starting at
+    /// `valid_up_to`.
+    ///
+    /// # Platform behavior
+    ///
+    /// On Unix systems, this is the length of an incomplete UTF-8
+    /// character, or 1 for any other invalid byte.
+    ///
+    /// On Windows systems, this is always 3, the length of an unpaired
+    /// surrogate.
+    pub fn error_len(&self) -> usize {
+        self.error_len
+    }
+}
+
+impl fmt::Display for OsStrUtf8Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "invalid sequence of {} bytes found after {} bytes of Unicode",
+               self.error_len, self.valid_up_to)
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for OsStrUtf8Error {
+    fn description(&self) -> &str {
+        "non-Unicode data"
+    }
+}
+
+/// The error returned by `OsString::try_into_string`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntoStringError {
+    string: OsString,
+    error: OsStrUtf8Error,
+}
+
+impl IntoStringError {
+    /// Returns the length of the longest valid Unicode prefix of the
+    /// string.
+    pub fn valid_up_to(&self) -> usize {
+        self.error.valid_up_to()
+    }
+
+    /// Returns the length of the non-Unicode sequence starting at
+    /// `valid_up_to`.  See `OsStrUtf8Error::error_len`.
+    pub fn error_len(&self) -> usize {
+        self.error.error_len()
+    }
+
+    /// Returns the location of the first non-Unicode sequence.
+    pub fn utf8_error(&self) -> OsStrUtf8Error {
+        self.error
+    }
+
+    /// Returns the string that could not be converted.
+    pub fn as_os_str(&self) -> &OsStr {
+        &self.string
+    }
+
+    /// Returns the string that could not be converted.
+    pub fn into_os_string(self) -> OsString {
+        self.string
+    }
+}
+
+impl fmt::Display for IntoStringError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.error, formatter)
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for IntoStringError {
+    fn description(&self) -> &str {
+        "non-Unicode data"
+    }
+}
+
+/// The value removed by `OsString::pop`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Popped {
+    /// A Unicode character.
+    Char(char),
+    /// A single non-Unicode unit.  See `OsString::pop`.
+    NonUnicode(OsString),
+}
+
+type FoldChars<'a> = FlatMap<::std::str::Chars<'a>, CaseFold, fn(char) -> CaseFold>;
+
+fn fold_chars(s: &str) -> FoldChars {
+    s.chars().flat_map(fold_char as fn(char) -> CaseFold)
+}
+
+/// The case folding of a single character.
+enum CaseFold {
+    Unchanged(Option<char>),
+    Mapped(FlatMap<FlatMap<ToLowercase, ToUppercase, fn(char) -> ToUppercase>,
+                   ToLowercase, fn(char) -> ToLowercase>),
+}
+
+fn fold_char(c: char) -> CaseFold {
+    match c {
+        'A'...'Z' => CaseFold::Unchanged(Some((c as u8 + b'a' - b'A') as char)),
+        '\0'...'\x7F' => CaseFold::Unchanged(Some(c)),
+        // Dotless i has no case folding, but upper cases to I.
+        '\u{131}' => CaseFold::Unchanged(Some(c)),
+        _ => {
+            CaseFold::Mapped(c.to_lowercase()
+                             .flat_map(char::to_uppercase as fn(char) -> ToUppercase)
+                             .flat_map(char::to_lowercase as fn(char) -> ToLowercase))
+        }
+    }
+}
+
+impl Iterator for CaseFold {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        match *self {
+            CaseFold::Unchanged(ref mut c) => c.take(),
+            CaseFold::Mapped(ref mut chars) => chars.next(),
+        }
+    }
+}
+
+/// The iterator returned by `OsStr::folded_bytes`.
+struct FoldedBytes<'a> {
+    sections: SplitUnicode<'a>,
+    chars: FoldChars<'a>,
+    raw: ::std::slice::Iter<'a, u8>,
+    encoded: [u8; 4],
+    encoded_pos: usize,
+    encoded_len: usize,
+}
+
+impl<'a> Iterator for FoldedBytes<'a> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        loop {
+            if self.encoded_pos < self.encoded_len {
+                self.encoded_pos += 1;
+                return Some(self.encoded[self.encoded_pos - 1]);
+            }
+            if let Some(c) = self.chars.next() {
+                self.encoded_len = encode_utf8_raw(c as u32, &mut self.encoded).unwrap();
+                self.encoded_pos = 0;
+                continue;
+            }
+            if let Some(&byte) = self.raw.next() {
+                return Some(byte);
+            }
+            match self.sections.next() {
+                Some(OsStrSection::Unicode(s)) => self.chars = fold_chars(s),
+                Some(OsStrSection::NonUnicode(s)) => self.raw = s.bytes().iter(),
+                None => return None,
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OsStrSection<'a> {
+    Unicode(&'a str),
+    NonUnicode(&'a OsStr),
+}
+
+impl<'a> From<inner::Section<'a>> for OsStrSection<'a> {
+    fn from(x: inner::Section<'a>) -> OsStrSection<'a> {
+        match x {
+            inner::Section::Unicode(s) => OsStrSection::Unicode(s),
+            inner::Section::NonUnicode(s) => OsStrSection::NonUnicode(OsStr::from_inner(s)),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SplitUnicode<'a>(inner::SplitUnicode<'a>);
+
+impl<'a> Iterator for SplitUnicode<'a> {
+    type Item = OsStrSection<'a>;
+    fn next(&mut self) -> Option<OsStrSection<'a>> {
+        self.0.next().map(|x| x.into())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for SplitUnicode<'a> {
+    fn next_back(&mut self) -> Option<OsStrSection<'a>> {
+        self.0.next_back().map(|x| x.into())
+    }
+}
+
+impl<'a> FusedIterator for SplitUnicode<'a> {}
+
+#[derive(Clone)]
+pub struct InvalidSections<'a> {
+    sections: SplitUnicode<'a>,
+    // The offsets of the unconsumed sections
+    start: usize,
+    end: usize,
+}
+
+impl<'a> Iterator for InvalidSections<'a> {
+    type Item = (ops::Range<usize>, &'a [u8]);
+    fn next(&mut self) -> Option<(ops::Range<usize>, &'a [u8])> {
+        loop {
+            match self.sections.next() {
+                Some(OsStrSection::Unicode(s)) => self.start += s.len(),
+                Some(OsStrSection::NonUnicode(s)) => {
+                    let range = self.start..self.start + s.len();
+                    self.start = range.end;
+                    return Some((range, s.bytes()));
+                }
+                None => return None,
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Sections alternate.
+        let (low, high) = self.sections.size_hint();
+        (low / 2, high.map(|high| (high + 1) / 2))
+    }
+}
+
+impl<'a> DoubleEndedIterator for InvalidSections<'a> {
+    fn next_back(&mut self) -> Option<(ops::Range<usize>, &'a [u8])> {
+        loop {
+            match self.sections.next_back() {
+                Some(OsStrSection::Unicode(s)) => self.end -= s.len(),
+                Some(OsStrSection::NonUnicode(s)) => {
+                    let range = self.end - s.len()..self.end;
+                    self.end = range.start;
+                    return Some((range, s.bytes()));
+                }
+                None => return None,
+            }
+        }
+    }
+}
+
+impl<'a> FusedIterator for InvalidSections<'a> {}
+
+#[derive(Clone)]
+pub struct Utf8Chunks<'a>(SplitUnicode<'a>);
+
+impl<'a> Iterator for Utf8Chunks<'a> {
+    type Item = (&'a str, &'a [u8]);
+    fn next(&mut self) -> Option<(&'a str, &'a [u8])> {
+        let valid = match self.0.next() {
+            Some(OsStrSection::Unicode(s)) => s,
+            Some(OsStrSection::NonUnicode(s)) => return Some(("", s.bytes())),
+            None => return None,
+        };
+        // Sections alternate, so anything left is non-Unicode.
+        let invalid = match self.0.next() {
+            Some(OsStrSection::NonUnicode(s)) => s.bytes(),
+            _ => &[],
+        };
+        Some((valid, invalid))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (low, high) = self.0.size_hint();
+        ((low + 1) / 2, high)
+    }
+}
+
+impl<'a> FusedIterator for Utf8Chunks<'a> {}
+
+
+#[derive(Clone)]
+pub struct StrChunksLossy<'a>(SplitUnicode<'a>);
+
+impl<'a> StrChunksLossy<'a> {
+    fn replace(section: OsStrSection<'a>) -> Cow<'a, str> {
+        match section {
+            OsStrSection::Unicode(s) => Cow::Borrowed(s),
+            OsStrSection::NonUnicode(s) => {
+                let mut chars = s.chars_lossy();
+                chars.next();
+                if chars.next().is_none() {
+                    Cow::Borrowed("\u{FFFD}")
+                } else {
+                    Cow::Owned(s.chars_lossy().collect())
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for StrChunksLossy<'a> {
+    type Item = Cow<'a, str>;
+    fn next(&mut self) -> Option<Cow<'a, str>> {
+        self.0.next().map(StrChunksLossy::replace)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for StrChunksLossy<'a> {
+    fn next_back(&mut self) -> Option<Cow<'a, str>> {
+        self.0.next_back().map(StrChunksLossy::replace)
+    }
+}
+
+impl<'a> FusedIterator for StrChunksLossy<'a> {}
+
+
+#[derive(Clone)]
+pub struct CharsLossy<'a>(inner::CharsLossy<'a>);
+
+impl<'a> Iterator for CharsLossy<'a> {
+    type Item = char;
+    fn next(&mut self) -> Option<char> {
+        self.0.next()
+    }
+}
+
+
+#[derive(Clone)]
+pub struct MatchIndicesOs<'a, 'b> {
+    haystack: &'a OsStr,
+    needle_len: usize,
+    searcher: SliceSearcher<'a, 'b>,
+}
+
+impl<'a, 'b> Iterator for MatchIndicesOs<'a, 'b> {
+    type Item = (usize, &'a OsStr);
+    fn next(&mut self) -> Option<(usize, &'a OsStr)> {
+        // Only an empty needle can match away from a code point
+        // boundary.  Skip those the same way `OsStr` patterns do.
+        while let Some(start) = self.searcher.next() {
+            if self.needle_len != 0 || is_empty_match_boundary(self.haystack.bytes(), start) {
+                return Some((start, &self.haystack[start..start + self.needle_len]));
+            }
+        }
+        None
+    }
+}
+
+impl<'a, 'b> FusedIterator for MatchIndicesOs<'a, 'b> {}
+
+#[derive(Clone)]
+pub struct RMatchIndicesOs<'a, 'b> {
+    haystack: &'a OsStr,
+    needle_len: usize,
+    searcher: ReverseSliceSearcher<'a, 'b>,
+}
+
+impl<'a, 'b> Iterator for RMatchIndicesOs<'a, 'b> {
+    type Item = (usize, &'a OsStr);
+    fn next(&mut self) -> Option<(usize, &'a OsStr)> {
+        // Only an empty needle can match away from a code point
+        // boundary.  Skip those the same way `OsStr` patterns do.
+        while let Some(start) = self.searcher.next() {
+            if self.needle_len != 0 || is_empty_match_boundary(self.haystack.bytes(), start) {
+                return Some((start, &self.haystack[start..start + self.needle_len]));
+            }
+        }
+        None
+    }
+}
+
+impl<'a, 'b> FusedIterator for RMatchIndicesOs<'a, 'b> {}
+
+
+pub struct Display<'a>(&'a OsStr);
+
+impl<'a> fmt::Display for Display<'a> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(s) = self.0.to_str() {
+            return formatter.pad(s);
+        }
+        if formatter.width().is_some() || formatter.precision().is_some() {
+            // Padding depends on the length of the whole string.
+            return formatter.pad(&self.0.to_string_lossy());
+        }
+        for chunk in self.0.to_str_chunks_lossy() {
+            try!(formatter.write_str(&chunk));
+        }
+        Ok(())
+    }
+}
+
+pub struct EscapeDefault<'a>(&'a OsStr);
+
+impl<'a> fmt::Display for EscapeDefault<'a> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.0.write_escaped(formatter, |f, c| {
+            use std::fmt::Write;
+            for e in c.escape_default() {
+                try!(f.write_char(e));
+            }
+            Ok(())
+        })
+    }
+}
+
+pub struct EscapeDebug<'a>(&'a OsStr);
+
+impl<'a> fmt::Display for EscapeDebug<'a> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.0.write_escaped(formatter, |f, c| {
+            use std::fmt::Write;
+            match c {
+                '\t' | '\r' | '\n' | '\\' | '\'' | '"' => {
+                    for e in c.escape_default() {
+                        try!(f.write_char(e));
+                    }
+                    Ok(())
+                }
+                c if c.is_control() => {
+                    for e in c.escape_unicode() {
+                        try!(f.write_char(e));
+                    }
+                    Ok(())
+                }
+                c => f.write_char(c),
+            }
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct SplitWhitespace<'a>(Filter<Split<'a, fn(char) -> bool>, OsStrNonEmptyHack>);
+
+// Functions have issues with Clone sometimes
+#[derive(Clone)]
+struct OsStrNonEmptyHack;
+
+impl<'a, 'b> FnOnce<(&'b &'a OsStr,)> for OsStrNonEmptyHack {
+    type Output = bool;
+    extern "rust-call" fn call_once(mut self, args: (&&OsStr,)) -> bool { self.call_mut(args) }
+}
+
+impl<'a, 'b> FnMut<(&'b &'a OsStr,)> for OsStrNonEmptyHack {
+    extern "rust-call" fn call_mut(&mut self, (&s,): (&&OsStr,)) -> bool { !s.is_empty() }
+}
+
+impl<'a> SplitWhitespace<'a> {
+    fn new(string: &'a OsStr) -> Self {
+        SplitWhitespace(string.split(char::is_whitespace as fn(char) -> bool)
+                        .filter(OsStrNonEmptyHack))
+    }
+}
+
+impl<'a> Iterator for SplitWhitespace<'a> {
+    type Item = &'a OsStr;
+    fn next(&mut self) -> Option<&'a OsStr> { self.0.next() }
+    fn size_hint(&self) -> (usize, Option<usize>) { self.0.size_hint() }
+}
+
+impl<'a> DoubleEndedIterator for SplitWhitespace<'a> {
+    fn next_back(&mut self) -> Option<&'a OsStr> { self.0.next_back() }
+}
+
+impl<'a> FusedIterator for SplitWhitespace<'a> {}
+
+#[derive(Clone)]
+pub struct Lines<'a>(Map<SplitTerminator<'a, char>, fn(&'a OsStr) -> &'a OsStr>);
+
+impl<'a> Lines<'a> {
+    fn new(string: &'a OsStr) -> Self {
+        fn remove_cr(s: &OsStr) -> &OsStr {
+            let mut split = s.rsplitn(2, '\r');
+            if let Some(e) = split.next() {
+                if e.is_empty() {
+                    return split.next().unwrap_or(OsStr::new(""));
+                }
+            }
+            s
+        }
+        Lines(string.split_terminator('\n').map(remove_cr))
+    }
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = &'a OsStr;
+    fn next(&mut self) -> Option<&'a OsStr> { self.0.next() }
+    fn size_hint(&self) -> (usize, Option<usize>) { self.0.size_hint() }
+}
+
+impl<'a> DoubleEndedIterator for Lines<'a> {
+    fn next_back(&mut self) -> Option<&'a OsStr> { self.0.next_back() }
+}
+
+impl<'a> FusedIterator for Lines<'a> {}
+
+
+#[derive(Clone)]
+pub struct SplitSearchPaths<'a> {
+    remaining: Option<&'a OsStr>,
+}
+
+impl<'a> Iterator for SplitSearchPaths<'a> {
+    type Item = OsString;
+    fn next(&mut self) -> Option<OsString> {
+        let string = match self.remaining.take() {
+            Some(string) => string,
+            None => return None,
+        };
+        let separator = if is_windows!() { b';' } else { b':' };
+        let mut path = OsString::new();
+        let mut start = 0;
+        let mut in_quote = false;
+        // Both bytes are ASCII, so they always lie on code point
+        // boundaries.
+        for (i, &byte) in string.bytes().iter().enumerate() {
+            if is_windows!() && byte == b'"' {
+                path.push(&string[start..i]);
+                start = i + 1;
+                in_quote = !in_quote;
+            } else if byte == separator && !in_quote {
+                path.push(&string[start..i]);
+                self.remaining = Some(&string[i + 1..]);
+                return Some(path);
+            }
+        }
+        path.push(&string[start..]);
+        Some(path)
+    }
+}
+
+/// The error returned by `OsString::join_search_paths`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JoinSearchPathsError {
+    index: usize,
+}
+
+impl JoinSearchPathsError {
+    /// Returns the position of the path that could not be joined.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl fmt::Display for JoinSearchPathsError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "path {} contains a character not allowed in search paths", self.index)
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for JoinSearchPathsError {
+    fn description(&self) -> &str {
+        "path contains a character not allowed in search paths"
+    }
+}
+
+/// Joins arguments into a command line that `split_args` splits back
+/// into the same arguments.
+///
+/// # Platform behavior
+///
+/// On Unix systems, this uses POSIX shell quoting.  Arguments made up
+/// only of characters with no special meaning to the shell are added
+/// as they are, and all others are wrapped in single quotes.
+///
+/// On Windows systems, this uses the quoting understood by
+/// `CommandLineToArgvW` for the arguments after the program name.
+/// The program name itself follows different rules and should not be
+/// passed to this function.
+pub fn join_args<I, T>(args: I) -> OsString
+where I: IntoIterator<Item = T>, T: AsRef<OsStr> {
+    let mut joined = OsString::new();
+    for (index, arg) in args.into_iter().enumerate() {
+        if index > 0 {
+            joined.push_char(' ');
+        }
+        if is_windows!() {
+            push_windows_arg(&mut joined, arg.as_ref());
+        } else {
+            push_posix_arg(&mut joined, arg.as_ref());
+        }
+    }
+    joined
+}
+
+fn push_posix_arg(joined: &mut OsString, arg: &OsStr) {
+    let plain = !arg.is_empty() && arg.bytes().iter().all(|&byte| match byte {
+        b'a'...b'z' | b'A'...b'Z' | b'0'...b'9' => true,
+        b'_' | b'@' | b'%' | b'+' | b'=' | b':' | b',' | b'.' | b'/' | b'-' => true,
+        _ => false,
+    });
+    if plain {
+        joined.push(arg);
+        return;
+    }
+    // Nothing is special inside single quotes, so a quote has to end
+    // the quoted section and be escaped outside it.
+    joined.push_char('\'');
+    let mut start = 0;
+    for (i, &byte) in arg.bytes().iter().enumerate() {
+        if byte == b'\'' {
+            joined.push(&arg[start..i]);
+            joined.push_str("'\\''");
+            start = i + 1;
+        }
+    }
+    joined.push(&arg[start..]);
+    joined.push_char('\'');
+}
+
+fn push_windows_arg(joined: &mut OsString, arg: &OsStr) {
+    let quote = arg.is_empty() || arg.contains_byte(b' ') || arg.contains_byte(b'\t');
+    if quote {
+        joined.push_char('"');
+    }
+    // Backslashes are only special before a quote, where 2n of them
+    // stand for n backslashes and 2n + 1 for n and a literal quote.
+    let mut backslashes = 0;
+    let mut start = 0;
+    for (i, &byte) in arg.bytes().iter().enumerate() {
+        if byte == b'\\' {
+            backslashes += 1;
+            continue;
+        }
+        if byte == b'"' {
+            joined.push(&arg[start..i]);
+            push_backslashes(joined, backslashes + 1);
+            start = i;
+        }
+        backslashes = 0;
+    }
+    joined.push(&arg[start..]);
+    if quote {
+        push_backslashes(joined, backslashes);
+        joined.push_char('"');
+    }
+}
+
+fn push_backslashes(joined: &mut OsString, count: usize) {
+    for _ in 0..count {
+        joined.push_char('\\');
+    }
+}
+
+/// Splits a command line into arguments.  This is the inverse of
+/// `join_args`.
+///
+/// # Platform behavior
+///
+/// On Unix systems, this performs POSIX shell word splitting, without
+/// any expansions.  Words are separated by spaces, tabs and newlines,
+/// and single quotes, double quotes and backslashes are interpreted as
+/// the shell does.  An unterminated quote or a trailing backslash is
+/// an error.
+///
+/// On Windows systems, this follows the rules `CommandLineToArgvW`
+/// applies to the arguments after the program name, and never fails.
+pub fn split_args(line: &OsStr) -> Result<Vec<OsString>, SplitArgsError> {
+    if is_windows!() {
+        Ok(split_windows_args(line))
+    } else {
+        split_posix_args(line)
+    }
+}
+
+fn split_posix_args(line: &OsStr) -> Result<Vec<OsString>, SplitArgsError> {
+    fn is_special(byte: u8) -> bool {
+        match byte {
+            b' ' | b'\t' | b'\n' | b'\'' | b'"' | b'\\' => true,
+            _ => false,
+        }
+    }
+
+    let bytes = line.bytes();
+    let mut args = Vec::new();
+    let mut word = OsString::new();
+    let mut in_word = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b' ' | b'\t' | b'\n' => {
+                if in_word {
+                    args.push(mem::replace(&mut word, OsString::new()));
+                    in_word = false;
+                }
+                i += 1;
+            }
+            b'\'' => {
+                let end = match bytes[i + 1..].iter().position(|&b| b == b'\'') {
+                    Some(len) => i + 1 + len,
+                    None => return Err(SplitArgsError { position: i }),
+                };
+                word.push(&line[i + 1..end]);
+                in_word = true;
+                i = end + 1;
+            }
+            b'"' => {
+                let mut start = i + 1;
+                let mut j = i + 1;
+                loop {
+                    match bytes.get(j) {
+                        None => return Err(SplitArgsError { position: i }),
+                        Some(&b'"') => break,
+                        Some(&b'\\') => match bytes.get(j + 1) {
+                            Some(&b'$') | Some(&b'`') | Some(&b'"') | Some(&b'\\') => {
+                                word.push(&line[start..j]);
+                                start = j + 1;
+                                j += 2;
+                            }
+                            Some(&b'\n') => {
+                                word.push(&line[start..j]);
+                                start = j + 2;
+                                j += 2;
+                            }
+                            _ => j += 1,
+                        },
+                        Some(_) => j += 1,
+                    }
+                }
+                word.push(&line[start..j]);
+                in_word = true;
+                i = j + 1;
+            }
+            b'\\' => {
+                match bytes.get(i + 1) {
+                    None => return Err(SplitArgsError { position: i }),
+                    // A backslash-newline pair is a line continuation.
+                    Some(&b'\n') => {}
+                    Some(_) => {
+                        word.push(&line[i + 1..i + 2]);
+                        in_word = true;
+                    }
+                }
+                i += 2;
+            }
+            _ => {
+                let end = bytes[i..].iter().position(|&b| is_special(b))
+                    .map_or(bytes.len(), |len| i + len);
+                word.push(&line[i..end]);
+                in_word = true;
+                i = end;
+            }
+        }
+    }
+    if in_word {
+        args.push(word);
+    }
+    Ok(args)
+}
+
+fn split_windows_args(line: &OsStr) -> Vec<OsString> {
+    let bytes = line.bytes();
+    let mut args = Vec::new();
+    let mut word = OsString::new();
+    let mut in_word = false;
+    let mut in_quotes = false;
+    let mut backslashes = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => {
+                backslashes += 1;
+                in_word = true;
+                i += 1;
+            }
+            b'"' => {
+                push_backslashes(&mut word, backslashes / 2);
+                if backslashes % 2 == 1 {
+                    word.push_char('"');
+                } else if !in_quotes {
+                    in_quotes = true;
+                } else if bytes.get(i + 1) == Some(&b'"') {
+                    // A doubled quote is a literal quote and does not
+                    // end the quoted section.
+                    word.push_char('"');
+                    i += 1;
+                } else {
+                    in_quotes = false;
+                }
+                backslashes = 0;
+                in_word = true;
+                i += 1;
+            }
+            b' ' | b'\t' if !in_quotes => {
+                push_backslashes(&mut word, backslashes);
+                backslashes = 0;
+                if in_word {
+                    args.push(mem::replace(&mut word, OsString::new()));
+                    in_word = false;
+                }
+                i += 1;
+            }
+            _ => {
+                push_backslashes(&mut word, backslashes);
+                backslashes = 0;
+                // Always take at least one byte, since quoted spaces
+                // and tabs are literal.
+                let end = bytes[i + 1..].iter()
+                    .position(|&b| match b {
+                        b'\\' | b'"' | b' ' | b'\t' => true,
+                        _ => false,
+                    })
+                    .map_or(bytes.len(), |len| i + 1 + len);
+                word.push(&line[i..end]);
+                in_word = true;
+                i = end;
+            }
+        }
+    }
+    push_backslashes(&mut word, backslashes);
+    if in_word {
+        args.push(word);
+    }
+    args
+}
+
+/// The error returned by `split_args`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitArgsError {
+    position: usize,
+}
+
+impl SplitArgsError {
+    /// Returns the byte position of the unterminated quote or trailing
+    /// backslash.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl fmt::Display for SplitArgsError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "unterminated quote or escape at byte {}", self.position)
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for SplitArgsError {
+    fn description(&self) -> &str {
+        "unterminated quote or escape"
+    }
+}
+
+macro_rules! make_iterator {
+    ($forward:ident and $reverse:ident yield $map:expr => $ret:ty) => {
+        pub struct $forward<'a, P> where P: OsPattern<'a> {
+            inner: inner::$forward<'a, P>
+        }
+
+        impl<'a, P> Clone for $forward<'a, P> where P: OsPattern<'a>, P::Searcher: Clone {
+            fn clone(&self) -> Self { $forward { inner: self.inner.clone() } }
+        }
+
+        impl<'a, P> Iterator for $forward<'a, P> where P: OsPattern<'a> {
+            type Item = $ret;
+
+            fn next(&mut self) -> Option<$ret> {
+                self.inner.next().map($map)
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.inner.size_hint()
+            }
+        }
+
+        impl<'a, P> FusedIterator for $forward<'a, P> where P: OsPattern<'a> {}
+
+        pub struct $reverse<'a, P> where P: OsPattern<'a> {
+            inner: inner::$reverse<'a, P>
+        }
+
+        impl<'a, P> Clone for $reverse<'a, P> where P: OsPattern<'a>, P::Searcher: Clone {
+            fn clone(&self) -> Self { $reverse { inner: self.inner.clone() } }
+        }
+
+        impl<'a, P> Iterator for $reverse<'a, P>
+        where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
+            type Item = $ret;
+
+            fn next(&mut self) -> Option<$ret> {
+                self.inner.next().map($map)
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.inner.size_hint()
+            }
+        }
+
+        impl<'a, P> FusedIterator for $reverse<'a, P>
+        where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {}
+    };
+    ($forward:ident and $reverse:ident are double ended yield $map:expr => $ret:ty) => {
+        make_iterator!{$forward and $reverse yield $map => $ret}
+
+        impl<'a, P> DoubleEndedIterator for $forward<'a, P>
+        where P: OsPattern<'a>, P::Searcher: DoubleEndedOsSearcher<'a> {
+            fn next_back(&mut self) -> Option<$ret> {
+                self.inner.next_back().map($map)
+            }
+        }
+
+        impl<'a, P> DoubleEndedIterator for $reverse<'a, P>
+        where P: OsPattern<'a>, P::Searcher: DoubleEndedOsSearcher<'a> {
+            fn next_back(&mut self) -> Option<$ret> {
+                self.inner.next_back().map($map)
+            }
+        }
+    }
+}
+
+make_iterator!{Split and RSplit are double ended yield |s| OsStr::from_inner(s) => &'a OsStr}
+make_iterator!{SplitTerminator and RSplitTerminator are double ended
+               yield |s| OsStr::from_inner(s) => &'a OsStr}
+make_iterator!{SplitN and RSplitN yield |s| OsStr::from_inner(s) => &'a OsStr}
+make_iterator!{Matches and RMatches are double ended
+               yield |s| OsStr::from_inner(s) => &'a OsStr}
+
+macro_rules! make_remainder {
+    ($($name:ident)*) => { $(
+        impl<'a, P> $name<'a, P> where P: OsPattern<'a> {
+            /// Returns the part of the string not yet split, or `None`
+            /// if the iterator is exhausted.
+            pub fn remainder(&self) -> Option<&'a OsStr> {
+                self.inner.remainder().map(OsStr::from_inner)
+            }
+        }
+    )* }
+}
+
+make_remainder!{Split RSplit SplitTerminator RSplitTerminator SplitN RSplitN}
+
+impl<'a, P> DoubleEndedIterator for SplitN<'a, P>
+where P: OsPattern<'a>, P::Searcher: DoubleEndedOsSearcher<'a> + Clone {
+    fn next_back(&mut self) -> Option<&'a OsStr> {
+        self.inner.next_back().map(OsStr::from_inner)
+    }
+}
+
+impl<'a, P> DoubleEndedIterator for RSplitN<'a, P>
+where P: OsPattern<'a>, P::Searcher: DoubleEndedOsSearcher<'a> + Clone {
+    fn next_back(&mut self) -> Option<&'a OsStr> {
+        self.inner.next_back().map(OsStr::from_inner)
+    }
+}
+
+
+impl<S: Borrow<OsStr>> LocalSliceConcatExt<OsStr> for [S] {
+    type Output = OsString;
+
+    fn concat(&self) -> OsString {
+        if self.is_empty() {
+            return OsString::new();
+        }
+
+        let len = self.iter().map(|s| s.borrow().len()).sum();
+        let mut result = OsString::with_capacity(len);
+
+        for s in self {
+            result.push(s.borrow())
+        }
+
+        result
+    }
+
+    fn join(&self, sep: &OsStr) -> OsString {
+        if self.is_empty() {
+            return OsString::new();
+        }
+
+        // concat is faster
+        if sep.is_empty() {
+            return self.concat();
+        }
+
+        // this is wrong without the guarantee that `self` is non-empty
+        // On Windows this may be a slight overestimate, but that's OK.
+        let len = sep.len() * (self.len() - 1)
+            + self.iter().map(|s| s.borrow().len()).sum::<usize>();
+        let mut result = OsString::with_capacity(len);
+        let mut first = true;
+
+        for s in self {
+            if first {
+                first = false;
+            } else {
+                result.push(sep);
+            }
+            result.push(s.borrow());
+        }
+        result
+    }
+
+    fn connect(&self, sep: &OsStr) -> OsString {
+        self.join(sep)
+    }
+}
+
+/// Serialization is an enum with two newtype variants: `Unicode`,
+/// holding a string, for data that is valid Unicode, and `Encoded`,
+/// holding the bytes produced by `OsStr::to_portable_bytes`, for
+/// everything else.
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+    use serde::bytes::{ByteBuf, Bytes};
+    use std::string::String;
+    use super::{OsStr, OsString};
+
+    const VARIANTS: &'static [&'static str] = &["Unicode", "Encoded"];
+
+    impl Serialize for OsStr {
+        fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+            match self.to_str() {
+                Some(s) => serializer.serialize_newtype_variant("OsString", 0, VARIANTS[0], s),
+                None => {
+                    let bytes = self.to_portable_bytes();
+                    serializer.serialize_newtype_variant("OsString", 1, VARIANTS[1],
+                                                         Bytes::from(&bytes))
+                }
+            }
+        }
+    }
+
+    impl Serialize for OsString {
+        fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+            (**self).serialize(serializer)
+        }
+    }
+
+    enum Variant {
+        Unicode,
+        Encoded,
+    }
+
+    impl Deserialize for Variant {
+        fn deserialize<D: Deserializer>(deserializer: &mut D) -> Result<Variant, D::Error> {
+            struct VariantVisitor;
+
+            impl de::Visitor for VariantVisitor {
+                type Value = Variant;
+
+                fn visit_usize<E: de::Error>(&mut self, value: usize) -> Result<Variant, E> {
+                    match value {
+                        0 => Ok(Variant::Unicode),
+                        1 => Ok(Variant::Encoded),
+                        _ => Err(E::unknown_variant(&format!("{}", value))),
+                    }
+                }
+
+                fn visit_str<E: de::Error>(&mut self, value: &str) -> Result<Variant, E> {
+                    match value {
+                        "Unicode" => Ok(Variant::Unicode),
+                        "Encoded" => Ok(Variant::Encoded),
+                        _ => Err(E::unknown_variant(value)),
+                    }
+                }
+            }
+
+            deserializer.deserialize_struct_field(VariantVisitor)
+        }
+    }
+
+    impl Deserialize for OsString {
+        fn deserialize<D: Deserializer>(deserializer: &mut D) -> Result<OsString, D::Error> {
+            struct OsStringVisitor;
+
+            impl de::EnumVisitor for OsStringVisitor {
+                type Value = OsString;
+
+                fn visit<V>(&mut self, mut visitor: V) -> Result<OsString, V::Error>
+                where V: de::VariantVisitor {
+                    match try!(visitor.visit_variant()) {
+                        Variant::Unicode => {
+                            let string: String = try!(visitor.visit_newtype());
+                            Ok(OsString::from(string))
+                        }
+                        Variant::Encoded => {
+                            let bytes: ByteBuf = try!(visitor.visit_newtype());
+                            OsString::from_portable_bytes(&bytes)
+                                .map_err(|err| de::Error::custom(format!("{}", err)))
+                        }
+                    }
+                }
+            }
+
+            deserializer.deserialize_enum("OsString", VARIANTS, OsStringVisitor)
+        }
+    }
+}
+
+/// Random values for property tests.  On Unix these are byte strings
+/// mixing characters with arbitrary bytes, and on Windows sequences of
+/// 16-bit code units mixing characters with lone surrogates, so most
+/// values of any length are not valid Unicode.
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls {
+    use quickcheck::{Arbitrary, Gen};
+    use std::boxed::Box;
+    use std::iter::Iterator;
+    use std::string::ToString;
+    use std::vec::Vec;
+    use super::OsString;
+
+    impl Arbitrary for OsString {
+        fn arbitrary<G: Gen>(g: &mut G) -> OsString {
+            let size = g.size();
+            let len = g.gen_range(0, size + 1);
+            if_unix_windows! {
+                unix {
+                    use unix::OsStringExt;
+                    let mut bytes = Vec::new();
+                    for _ in 0..len {
+                        if g.gen_weighted_bool(4) {
+                            bytes.push(g.gen());
+                        } else {
+                            let c: char = Arbitrary::arbitrary(g);
+                            bytes.extend_from_slice(c.to_string().as_bytes());
+                        }
+                    }
+                    OsString::from_vec(bytes)
+                }
+                windows {
+                    use windows::OsStringExt;
+                    let mut units = Vec::new();
+                    for _ in 0..len {
+                        if g.gen_weighted_bool(4) {
+                            units.push(g.gen_range(0xD800, 0xE000));
+                        } else {
+                            let c: char = Arbitrary::arbitrary(g);
+                            units.extend(c.to_string().encode_utf16());
+                        }
+                    }
+                    OsString::from_wide(&units)
+                }
+            }
+        }
+
+        fn shrink(&self) -> Box<Iterator<Item=OsString>> {
+            if_unix_windows! {
+                unix {
+                    use unix::OsStringExt;
+                    Box::new(self.clone().into_vec().shrink().map(OsString::from_vec))
+                }
+                windows {
+                    use windows::{OsStrExt, OsStringExt};
+                    let units = self.encode_wide().collect::<Vec<_>>();
+                    Box::new(units.shrink().map(|units| OsString::from_wide(&units)))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "regex")]
+pub use self::regex_impls::{RegexMatches, RegexSplit};
+
+/// Regular expression searches.  These run a `regex::bytes::Regex`
+/// over the data returned by `as_encoded_bytes`.
+///
+/// On Windows, the data is WTF-8, where an unpaired surrogate is
+/// encoded like a UTF-8 character but is not valid UTF-8.  Unicode
+/// patterns (the default) therefore never match unpaired surrogates,
+/// while `(?-u)` patterns see their three-byte encodings.  On all
+/// platforms, matches that do not begin and end on code point
+/// boundaries (see `get`) are ignored.
+#[cfg(feature = "regex")]
+mod regex_impls {
+    use regex::bytes::{self, Regex};
+    use super::OsStr;
+
+    impl OsStr {
+        /// Returns the byte index and content of the first match of
+        /// `re` in `self`, or `None` if there is none.
+        pub fn regex_find<'a>(&'a self, re: &Regex) -> Option<(usize, &'a OsStr)> {
+            let start = self.as_encoded_bytes().as_ptr() as usize;
+            self.regex_matches(re).next()
+                .map(|part| (part.as_encoded_bytes().as_ptr() as usize - start, part))
+        }
+
+        /// An iterator over the non-overlapping matches of `re` in
+        /// `self`.
+        pub fn regex_matches<'a, 'r>(&'a self, re: &'r Regex) -> RegexMatches<'a, 'r> {
+            RegexMatches {
+                haystack: self,
+                matches: re.find_iter(self.as_encoded_bytes()),
+            }
+        }
+
+        /// An iterator over the substrings of `self` separated by
+        /// matches of `re`.
+        pub fn regex_split<'a, 'r>(&'a self, re: &'r Regex) -> RegexSplit<'a, 'r> {
+            RegexSplit {
+                haystack: self,
+                matches: re.find_iter(self.as_encoded_bytes()),
+                position: Some(0),
+            }
+        }
+    }
+
+    pub struct RegexMatches<'a, 'r> {
+        haystack: &'a OsStr,
+        matches: bytes::FindMatches<'r, 'a>,
+    }
+
+    impl<'a, 'r> Iterator for RegexMatches<'a, 'r> {
+        type Item = &'a OsStr;
+        fn next(&mut self) -> Option<&'a OsStr> {
+            while let Some((start, end)) = self.matches.next() {
+                if let Some(part) = self.haystack.get(start..end) {
+                    return Some(part);
+                }
+            }
+            None
+        }
+    }
+
+    pub struct RegexSplit<'a, 'r> {
+        haystack: &'a OsStr,
+        matches: bytes::FindMatches<'r, 'a>,
+        // The start of the next substring, or None when done
+        position: Option<usize>,
+    }
+
+    impl<'a, 'r> Iterator for RegexSplit<'a, 'r> {
+        type Item = &'a OsStr;
+        fn next(&mut self) -> Option<&'a OsStr> {
+            let position = match self.position {
+                Some(position) => position,
+                None => return None,
+            };
+            while let Some((start, end)) = self.matches.next() {
+                if self.haystack.get(start..end).is_some() {
+                    self.position = Some(end);
+                    return Some(&self.haystack[position..start]);
+                }
+            }
+            self.position = None;
+            Some(&self.haystack[position..])
+        }
+    }
+}
+
+#[cfg(feature = "unicode-segmentation")]
+pub use self::grapheme_impls::Graphemes;
+
+/// Grapheme clusters, as found by the `unicode-segmentation` crate.
+#[cfg(feature = "unicode-segmentation")]
+mod grapheme_impls {
+    use unicode_segmentation::{self, UnicodeSegmentation};
+    use super::{OsStr, OsStrSection, SplitUnicode};
+
+    impl OsStr {
+        /// Returns an iterator over the extended grapheme clusters of
+        /// `self`.
+        ///
+        /// Clusters are found within each Unicode section, and each run
+        /// of non-Unicode data is yielded as a single item.  This is
+        /// useful for truncating a string for display without splitting
+        /// a character from its combining marks.
+        ///
+        /// # Example
+        ///
+        /// ```
+        /// use osstring_prototype::os_str::OsStr;
+        /// let string = OsStr::new("cafe\u{301}!");
+        /// assert_eq!(string.graphemes().collect::<Vec<_>>(), ["c", "a", "f", "e\u{301}", "!"]);
+        /// ```
+        pub fn graphemes<'a>(&'a self) -> Graphemes<'a> {
+            Graphemes {
+                sections: self.split_unicode(),
+                front: "".graphemes(true),
+                back: "".graphemes(true),
+            }
+        }
+    }
+
+    /// The iterator returned by `OsStr::graphemes`.
+    #[derive(Clone)]
+    pub struct Graphemes<'a> {
+        sections: SplitUnicode<'a>,
+        // The clusters of the Unicode sections currently being
+        // iterated over from each end
+        front: unicode_segmentation::Graphemes<'a>,
+        back: unicode_segmentation::Graphemes<'a>,
+    }
+
+    impl<'a> Iterator for Graphemes<'a> {
+        type Item = &'a OsStr;
+        fn next(&mut self) -> Option<&'a OsStr> {
+            loop {
+                if let Some(cluster) = self.front.next() {
+                    return Some(OsStr::new(cluster));
+                }
+                match self.sections.next() {
+                    Some(OsStrSection::Unicode(s)) => self.front = s.graphemes(true),
+                    Some(OsStrSection::NonUnicode(s)) => return Some(s),
+                    None => return self.back.next().map(OsStr::new),
+                }
+            }
+        }
+    }
+
+    impl<'a> DoubleEndedIterator for Graphemes<'a> {
+        fn next_back(&mut self) -> Option<&'a OsStr> {
+            loop {
+                if let Some(cluster) = self.back.next_back() {
+                    return Some(OsStr::new(cluster));
+                }
+                match self.sections.next_back() {
+                    Some(OsStrSection::Unicode(s)) => self.back = s.graphemes(true),
+                    Some(OsStrSection::NonUnicode(s)) => return Some(s),
+                    None => return self.front.next_back().map(OsStr::new),
+                }
+            }
+        }
+    }
+}
+
+/// Display widths, as found by the `unicode-width` crate.
+#[cfg(feature = "unicode-width")]
+mod width_impls {
+    use unicode_width::UnicodeWidthStr;
+    use super::{OsStr, OsStrSection};
+
+    impl OsStr {
+        /// Returns the number of terminal columns needed to display
+        /// `self`, with non-Unicode data written as by `escape_debug`.
+        ///
+        /// The width of Unicode text is computed by `unicode-width`,
+        /// so wide East Asian characters count as two columns and
+        /// combining marks as none.
+        ///
+        /// # Platform behavior
+        ///
+        /// On Unix systems, each invalid byte counts as four columns,
+        /// for `\xNN`.
+        ///
+        /// On Windows systems, each unpaired surrogate counts as eight
+        /// columns, for `\u{DNNN}`.
+        pub fn display_width(&self) -> usize {
+            self.display_width_with(if is_windows!() { 8 } else { 4 })
+        }
+
+        /// Returns the number of terminal columns needed to display
+        /// `self`, counting `non_unicode_width` columns for each unit
+        /// of non-Unicode data: a byte on Unix, or an unpaired
+        /// surrogate on Windows.
+        ///
+        /// See `display_width`.
+        pub fn display_width_with(&self, non_unicode_width: usize) -> usize {
+            self.split_unicode().map(|section| match section {
+                OsStrSection::Unicode(s) => s.width(),
+                OsStrSection::NonUnicode(s) => {
+                    // Unpaired surrogates are always three bytes.
+                    let units = if is_windows!() { s.len() / 3 } else { s.len() };
+                    units * non_unicode_width
+                }
+            }).sum()
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::prelude::v1::*;
+    use std::borrow::Cow;
+    use super::*;
+    use slice_concat_ext::LocalSliceConcatExt;
+
+    fn unicode_str() -> &'static str { "aé 💩" }
+    fn unicode_osstring() -> OsString {
+        OsString::from(unicode_str())
+    }
+
+    fn non_unicode_osstring() -> OsString {
+        if_unix_windows! {
+            unix {
+                use unix::OsStringExt;
+                let string = OsString::from_vec(vec![0xFF]);
+                assert!(string.to_str().is_none());
+                string
+            }
+            windows {
+                use windows::OsStringExt;
+                let string = OsString::from_wide(&[0xD800]);
+                assert!(string.to_str().is_none());
+                string
+            }
+        }
+    }
+
+    fn split_char() -> (OsString, OsString) {
+        if_unix_windows! {
+            unix {
+                use unix::OsStringExt;
+                (OsString::from_vec(vec![0xC2]), OsString::from_vec(vec![0xA2]))
+            }
+            windows {
+                use windows::OsStringExt;
+                (OsString::from_wide(&[0xD83D]), OsString::from_wide(&[0xDE3A]))
+            }
+        }
+    }
+
+
+    #[test]
+    fn osstring_eq_smoke() {
+        assert_eq!(OsString::new(), OsString::new());
+        let string = OsString::from("abc");
+        assert_eq!(unicode_osstring(), unicode_osstring());
+        assert!(OsString::new() != unicode_osstring());
+        assert!(unicode_osstring() != string);
+        assert_eq!(non_unicode_osstring(), non_unicode_osstring());
+    }
+
+    #[test]
+    fn osstring_from_bytes() {
+        assert_eq!(OsString::from_bytes(unicode_str().as_bytes()),
+                   Some(OsString::from(unicode_str())));
+
+        let string = non_unicode_osstring();
+        let bytes = string.as_encoded_bytes().to_vec();
+        let ptr = bytes.as_ptr();
+        let string = OsString::from_bytes(bytes).unwrap();
+        assert_eq!(string, non_unicode_osstring());
+        assert_eq!(string.as_encoded_bytes().as_ptr(), ptr);
+        if is_windows!() {
+            assert_eq!(OsString::from_bytes(&b"\xFF"[..]), None);
+            // A surrogate pair must be encoded as a single character.
+            assert_eq!(OsString::from_bytes(&b"\xED\xA0\xBD\xED\xB2\xA9"[..]), None);
+        }
+    }
+
+    #[test]
+    fn osstr_from_bytes() {
+        assert_eq!(OsStr::from_bytes(b""), Some(OsStr::new("")));
+        assert_eq!(OsStr::from_bytes(unicode_str().as_bytes()), Some(OsStr::new(unicode_str())));
+        let bytes = b"abc";
+        assert_eq!(OsStr::from_bytes(bytes).unwrap().as_encoded_bytes().as_ptr(),
+                   bytes.as_ptr());
+        if_unix_windows! {
+            unix {
+                assert_eq!(OsStr::from_bytes(b"\xFF"), Some(&non_unicode_osstring()[..]));
+            }
+            windows {
+                assert_eq!(OsStr::from_bytes(b"\xFF"), None);
+                assert_eq!(OsStr::from_bytes(b"\xED\xA0\x80"), Some(&non_unicode_osstring()[..]));
+            }
+        }
+    }
+
+    #[test]
+    fn osstring_capacity() {
+        assert!(OsString::with_capacity(10).capacity() >= 10);
+        assert!(OsString::from("Hello").capacity() >= 5);
+    }
+
+    #[test]
+    fn osstring_reserve() {
+        let mut string = OsString::from("Hello");
+        let len = string.len();
+        let cap = string.capacity();
+        assert!(cap >= len);
+        string.reserve_exact(cap - len);
+        assert_eq!(string.capacity(), cap);
+        string.reserve_exact(cap - len + 1);
+        assert!(string.capacity() > cap);
+        let cap = string.capacity();
+        string.reserve(cap - len + 1);
+        assert!(string.capacity() > cap);
+    }
+
+    #[test]
+    fn osstring_shrink() {
+        let mut string = OsString::with_capacity(100);
+        string.push("abc");
+        string.shrink_to(200);
+        assert!(string.capacity() >= 100);
+        string.shrink_to(10);
+        assert!(string.capacity() >= 10 && string.capacity() < 100);
+        string.shrink_to(0);
+        assert!(string.capacity() >= 3 && string.capacity() < 10);
+        string.push(&non_unicode_osstring());
+        string.shrink_to_fit();
+        assert!(string.capacity() >= string.len());
+        let mut expected = OsString::from("abc");
+        expected.push(&non_unicode_osstring());
+        assert_eq!(string, expected);
+    }
+
+    #[test]
+    fn osstring_into_string() {
+        assert_eq!(unicode_osstring().into_string(), Ok(unicode_str().to_string()));
+        assert_eq!(non_unicode_osstring().into_string(), Err(non_unicode_osstring()));
+    }
+
+    #[test]
+    fn osstring_string_conversions_reuse_allocation() {
+        let mut string = String::with_capacity(100);
+        string.push_str(unicode_str());
+        let ptr = string.as_ptr();
+
+        let os_string = OsString::from(string);
+        assert_eq!(os_string.capacity(), 100);
+        assert_eq!(os_string.as_encoded_bytes().as_ptr(), ptr);
+
+        let string = os_string.into_string().unwrap();
+        assert_eq!(string.capacity(), 100);
+        assert_eq!(string.as_ptr(), ptr);
+
+        if_unix_windows! {
+            unix {
+                let bytes = Vec::from(OsString::from(string));
+                assert_eq!(bytes.capacity(), 100);
+                assert_eq!(bytes.as_ptr(), ptr);
+                assert_eq!(bytes, unicode_str().as_bytes());
+            }
+            windows {}
+        }
+    }
+
+    #[test]
+    fn osstring_try_into_string() {
+        assert_eq!(unicode_osstring().try_into_string(), Ok(unicode_str().to_string()));
+
+        let unit_len = if is_windows!() { 3 } else { 1 };
+        let err = non_unicode_osstring().try_into_string().unwrap_err();
+        assert_eq!((err.valid_up_to(), err.error_len()), (0, unit_len));
+        assert_eq!(err.as_os_str(), &non_unicode_osstring()[..]);
+        assert_eq!(err.into_os_string(), non_unicode_osstring());
+
+        let mut string = OsString::from("aé");
+        string.push(non_unicode_osstring());
+        string.push(non_unicode_osstring());
+        let err = string.clone().try_into_string().unwrap_err();
+        assert_eq!((err.valid_up_to(), err.error_len()), (3, unit_len));
+        assert_eq!(err.to_string(),
+                   format!("invalid sequence of {} bytes found after 3 bytes of Unicode",
+                           unit_len));
+        assert_eq!(err.into_os_string(), string);
+        assert_eq!(string.clone().try_into_string().unwrap_err().utf8_error(),
+                   string.try_to_str().unwrap_err());
+
+        if_unix_windows! {
+            unix {
+                use unix::OsStringExt;
+                let err = OsString::from_vec(b"a\xE2\x82b".to_vec()).try_into_string();
+                let err = err.unwrap_err();
+                assert_eq!((err.valid_up_to(), err.error_len()), (1, 2));
+            }
+            windows {
+                use windows::OsStringExt;
+                let err = OsString::from_wide(&[0x61, 0xDE3A, 0xD83D]).try_into_string();
+                let err = err.unwrap_err();
+                assert_eq!((err.valid_up_to(), err.error_len()), (1, 3));
+            }
+        }
+    }
+
+    #[test]
+    fn osstring_into_string_lossy() {
+        assert_eq!(unicode_osstring().into_string_lossy(), unicode_str());
+        assert_eq!(non_unicode_osstring().into_string_lossy(),
+                   String::from_utf8_lossy(b"\xFF"));
+
+        if_unix_windows! {
+            unix {
+                use unix::OsStringExt;
+                let cases: [&[u8]; 7] = [b"a\xFFb", b"\xF0\x9F\x98", b"\xF0\x9F\x98a\xE2\x82",
+                                         b"\xED\xA0\x80", b"\xC2\xC2\xA2\xFF\xFE",
+                                         b"\xF0\x9F\x98\x80\xF0\x9F", b"\xE2\x82\xAC\x80"];
+                for bytes in &cases {
+                    assert_eq!(OsString::from_vec(bytes.to_vec()).into_string_lossy(),
+                               String::from_utf8_lossy(bytes));
+                }
+
+                // Three-byte sequences are patched without reallocating.
+                let mut string = OsString::from_vec(b"ab\xF0\x9F\x98cd".to_vec());
+                string.shrink_to_fit();
+                let ptr = string.bytes().as_ptr();
+                let string = string.into_string_lossy();
+                assert_eq!(string, "ab\u{FFFD}cd");
+                assert_eq!(string.as_ptr(), ptr);
+            }
+            windows {}
+        }
+    }
+
+    #[test]
+    fn osstring_into_string_with_replacement() {
+        assert_eq!(unicode_osstring().into_string_with_replacement("?"), unicode_str());
+        let mut string = non_unicode_osstring();
+        string.push("a");
+        assert_eq!(string.into_string_with_replacement("?"), "?a");
+    }
+
+    #[test]
+    fn osstring_push() {
+        let mut string = OsString::new();
+        string.push("foo");
+        string.push("x");
+        string.push(unicode_osstring());
+        assert_eq!(string, OsString::from(["foox", unicode_str()].concat()));
+        string.push(non_unicode_osstring());
+        assert!(string.into_string().is_err());
+    }
+
+    #[test]
+    fn osstring_push_split_char() {
+        let (start, end) = split_char();
+        let joined = if_unix_windows! {
+            unix { "a\u{A2}b" }
+            windows { "a\u{1F63A}b" }
+        };
+
+        let mut string = OsString::from("a");
+        string.push(&start);
+        string.push(&end);
+        string.push("b");
+        assert_eq!(string, OsString::from(joined));
+        assert!(string.to_str().is_some());
+
+        let mut end_b = end.clone();
+        end_b.push("b");
+        let mut string = OsString::from("a");
+        string.push(&start);
+        string.push(&end_b);
+        assert_eq!(string, OsString::from(joined));
+
+        let mut string = OsString::from("a");
+        string.push(&start);
+        string.push("b");
+        string.push(&end);
+        assert!(string.to_str().is_none());
+        let parts = string.split('b').collect::<Vec<_>>();
+        assert_eq!(parts.len(), 2);
+        let mut rejoined = parts[0].to_owned();
+        rejoined.push("b");
+        rejoined.push(parts[1]);
+        assert_eq!(rejoined, string);
+    }
+
+    #[test]
+    fn osstring_truncate() {
+        let mut string = OsString::from("aé💩");
+        string.truncate(7);
+        assert_eq!(&string, "aé💩");
+        string.truncate(3);
+        assert_eq!(&string, "aé");
+        string.truncate(0);
+        assert_eq!(&string, "");
+
+        let mut string = non_unicode_osstring();
+        let len = string.len();
+        string.push("abc");
+        let capacity = string.capacity();
+        string.truncate(len + 1);
+        let mut expected = non_unicode_osstring();
+        expected.push("a");
+        assert_eq!(string, expected);
+        assert_eq!(string.capacity(), capacity);
+    }
+
+    #[test]
+    #[should_panic]
+    fn osstring_truncate_past_end() {
+        OsString::from("abc").truncate(4);
+    }
+
+    #[test]
+    fn osstring_retain() {
+        let mut string = OsString::from("a1é2💩3");
+        string.retain(|c| !c.is_numeric(), |_| panic!());
+        assert_eq!(&string, "aé💩");
+        string.retain(|_| false, |_| panic!());
+        assert_eq!(&string, "");
+
+        let mut string = OsString::from("a");
+        string.push(&non_unicode_osstring());
+        string.push("b");
+        let mut sections = Vec::new();
+        string.retain(|c| c == 'b', |s| { sections.push(s.to_os_string()); true });
+        assert_eq!(sections, [non_unicode_osstring()]);
+        let mut expected = non_unicode_osstring();
+        expected.push("b");
+        assert_eq!(string, expected);
+        string.retain(|_| true, |_| false);
+        assert_eq!(&string, "b");
+
+        let (start, end) = split_char();
+        let mut string = start.clone();
+        string.push("x");
+        string.push(&end);
+        string.retain(|c| c != 'x', |_| true);
+        let mut joined = start.clone();
+        joined.push(&end);
+        assert_eq!(string, joined);
+        assert_eq!(string.to_str(), joined.to_str());
+    }
+
+    #[test]
+    fn osstring_pop() {
+        let mut string = OsString::from("aé💩");
+        assert_eq!(string.pop(), Some(Popped::Char('💩')));
+        assert_eq!(string.pop(), Some(Popped::Char('é')));
+        assert_eq!(string.pop(), Some(Popped::Char('a')));
+        assert_eq!(string.pop(), None);
+        assert_eq!(&string, "");
+
+        let mut string = OsString::from("a");
+        string.push(&non_unicode_osstring());
+        assert_eq!(string.pop(), Some(Popped::NonUnicode(non_unicode_osstring())));
+        assert_eq!(&string, "a");
+
+        let (start, end) = split_char();
+        let mut string = start.clone();
+        string.push(&start);
+        assert_eq!(string.pop(), Some(Popped::NonUnicode(start.clone())));
+        assert_eq!(string, start);
+        assert_eq!(end.clone().pop(), Some(Popped::NonUnicode(end.clone())));
+    }
+
+    #[test]
+    fn osstring_push_str_char() {
+        let mut string = OsString::new();
+        string.push_str("foo");
+        string.push_char('x');
+        string.push_char('é');
+        string.push_char('💩');
+        string.push_str("");
+        assert_eq!(&string, "fooxé💩");
+
+        let (start, end) = split_char();
+        let mut string = start.clone();
+        string.push_str("a");
+        string.push(&end);
+        string.push_char('b');
+        let mut expected = start.clone();
+        expected.push("a");
+        expected.push(&end);
+        expected.push("b");
+        assert_eq!(string, expected);
+    }
+
+    #[test]
+    fn osstring_boxed_os_str() {
+        let boxed = OsString::from("aé💩").into_boxed_os_str();
+        assert_eq!(&*boxed, OsStr::new("aé💩"));
+        assert_eq!(OsString::from(boxed), OsString::from("aé💩"));
+
+        let mut string = OsString::with_capacity(100);
+        string.push(&non_unicode_osstring());
+        let boxed = string.into_boxed_os_str();
+        assert_eq!(&*boxed, &non_unicode_osstring()[..]);
+        let string = OsString::from(boxed);
+        assert_eq!(string, non_unicode_osstring());
+        assert_eq!(string.capacity(), string.len());
+
+        let boxed: Box<OsStr> = OsStr::new("").into();
+        assert!(boxed.is_empty());
+        let boxed = Box::<OsStr>::from(&non_unicode_osstring()[..]);
+        assert_eq!(&*boxed, &non_unicode_osstring()[..]);
+    }
+
+    #[cfg(feature = "rc")]
+    #[test]
+    fn osstr_shared_pointers() {
+        use std::rc::Rc;
+        use std::sync::Arc;
+
+        let rc = Rc::<OsStr>::from(OsStr::new("aé💩"));
+        assert_eq!(&*rc, "aé💩");
+        let rc: Rc<OsStr> = non_unicode_osstring().into();
+        assert_eq!(&*rc, &non_unicode_osstring()[..]);
+        let rc2 = rc.clone();
+        assert_eq!(Rc::strong_count(&rc), 2);
+        assert_eq!(&*rc2, &non_unicode_osstring()[..]);
+
+        let arc = Arc::<OsStr>::from(&non_unicode_osstring()[..]);
+        assert_eq!(&*arc, &non_unicode_osstring()[..]);
+        let arc: Arc<OsStr> = OsString::new().into();
+        assert!(arc.is_empty());
+        let arc = Arc::<OsStr>::from(unicode_osstring());
+        let shared = arc.clone();
+        let joined = ::std::thread::spawn(move || shared.to_os_string()).join().unwrap();
+        assert_eq!(joined, unicode_osstring());
+    }
+
+    #[test]
+    fn osstring_extend() {
+        let mut string = OsString::from("a");
+        string.extend("bé💩".chars());
+        assert_eq!(&string, "abé💩");
+
+        let mut string = OsString::new();
+        string.extend(vec![OsStr::new("a"), &non_unicode_osstring(), OsStr::new("b")]);
+        let mut expected = OsString::from("a");
+        expected.push(&non_unicode_osstring());
+        expected.push("b");
+        assert_eq!(string, expected);
+
+        let mut string = OsString::new();
+        string.extend(vec![OsString::from("a"), non_unicode_osstring(), OsString::from("b")]);
+        assert_eq!(string, expected);
+
+        let mut string = OsString::new();
+        string.extend(vec![Cow::Borrowed(OsStr::new("a")),
+                           Cow::Owned(non_unicode_osstring()),
+                           Cow::Borrowed(OsStr::new("b"))]);
+        assert_eq!(string, expected);
+
+        let (start, end) = split_char();
+        let mut string = OsString::new();
+        string.extend(vec![start.clone(), end.clone()]);
+        let mut joined = start.clone();
+        joined.push(&end);
+        assert_eq!(string, joined);
+    }
+
+    #[test]
+    fn osstring_add() {
+        let string = OsString::from("a") + OsStr::new("b");
+        assert_eq!(&string, "ab");
+        let mut string = string + &non_unicode_osstring();
+        string += OsStr::new("c");
+        let mut expected = OsString::from("ab");
+        expected.push(&non_unicode_osstring());
+        expected.push("c");
+        assert_eq!(string, expected);
+
+        let (start, end) = split_char();
+        let mut joined = start.clone();
+        joined.push(&end);
+        assert_eq!(start.clone() + &end, joined);
+    }
+
+    #[test]
+    fn osstring_default_from_str_char() {
+        assert_eq!(OsString::default(), OsString::new());
+        assert_eq!("aé".parse::<OsString>(), Ok(OsString::from("aé")));
+        assert_eq!(OsString::from('a'), OsString::from("a"));
+        assert_eq!(OsString::from('💩'), OsString::from("💩"));
+    }
+
+    #[test]
+    fn osstring_clear() {
+        let mut string = non_unicode_osstring();
+        string.clear();
+        assert_eq!(&string, "");
+    }
+
+    #[test]
+    fn osstr_is_empty() {
+        assert!(OsString::new().is_empty());
+        assert!(!unicode_osstring().is_empty());
+        assert!(!non_unicode_osstring().is_empty());
+    }
+
+    #[test]
+    fn osstr_len() {
+        assert_eq!(OsStr::new("").len(), 0);
+        assert_eq!(unicode_osstring().len(), unicode_str().len());
+        assert!(non_unicode_osstring().len() > 0);
+    }
+
+    #[test]
+    fn osstr_to_str() {
+        assert_eq!(unicode_osstring().to_str(), Some(unicode_str()));
+        assert_eq!(non_unicode_osstring().to_str(), None);
+    }
+
+    #[test]
+    fn osstr_try_to_str() {
+        assert_eq!(unicode_osstring().try_to_str(), Ok(unicode_str()));
+
+        let mut string = OsString::from("ab");
+        string.push(&non_unicode_osstring());
+        let err = string.try_to_str().unwrap_err();
+        assert_eq!(err.valid_up_to(), 2);
+        assert_eq!(err.error_len(), if is_windows!() { 3 } else { 1 });
+        assert_eq!(err.to_string(),
+                   format!("invalid sequence of {} bytes found after 2 bytes of Unicode",
+                           err.error_len()));
+    }
+
+    #[test]
+    fn osstr_to_string_lossy() {
+        assert_eq!(unicode_osstring().to_string_lossy(),
+                   Cow::Borrowed(unicode_str()));
+        assert_eq!(non_unicode_osstring().to_string_lossy(),
+                   String::from_utf8_lossy(b"\xFF"));
+    }
+
+    #[test]
+    fn osstr_to_string_with_replacement() {
+        assert_eq!(unicode_osstring().to_string_with_replacement("?"),
+                   Cow::Borrowed(unicode_str()));
+        assert_eq!(non_unicode_osstring().to_string_with_replacement("?"), "?");
+        assert_eq!(non_unicode_osstring().to_string_with_replacement("\u{FFFD}"),
+                   non_unicode_osstring().chars_lossy().collect::<String>());
+
+        let (start, end) = split_char();
+        let mut string = OsString::from("a");
+        string.push(&start);
+        string.push("b");
+        string.push(&end);
+        string.push(&non_unicode_osstring());
+        let expected = if is_windows!() { "a<?>b<?><?>" } else { "a<?>b<?>" };
+        assert_eq!(string.to_string_with_replacement("<?>"), expected);
+        assert_eq!(string.to_string_with_replacement(""), "ab");
+    }
+
+    #[test]
+    fn osstr_to_bytes() {
+        assert_eq!(unicode_osstring().to_bytes(), Some(unicode_str().as_bytes()));
+        if_unix_windows! {
+            unix {
+                assert_eq!(non_unicode_osstring().to_bytes(), Some(&b"\xFF"[..]));
+            }
+            windows {
+                assert_eq!(non_unicode_osstring().to_bytes(), None);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn osstr_to_cstring() {
+        use std::ffi::CString;
+
+        assert_eq!(OsStr::new("aé").to_cstring(), Some(CString::new("aé").unwrap()));
+        assert_eq!(OsStr::new("a\0b").to_cstring(), None);
+        assert_eq!(OsStr::new("aé").try_to_cstring(), Ok(CString::new("aé").unwrap()));
+        assert_eq!(OsStr::new("aé\0b").try_to_cstring(), Err(ToCStringError::InteriorNul(3)));
+
+        let mut string = OsString::from("ab");
+        string.push(&non_unicode_osstring());
+        string.push("\0");
+        if_unix_windows! {
+            unix {
+                assert_eq!(string.try_to_cstring(), Err(ToCStringError::InteriorNul(3)));
+            }
+            windows {
+                assert_eq!(string.to_cstring(), None);
+                assert_eq!(string.try_to_cstring(), Err(ToCStringError::NotUnicode(2)));
+                assert_eq!(non_unicode_osstring().try_to_cstring(),
+                           Err(ToCStringError::NotUnicode(0)));
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn osstr_cstr_conversions() {
+        #[cfg(feature = "std")]
+        use std::ffi::{CStr, CString};
+
+        let cstring = CString::new("aé").unwrap();
+        let string = OsStr::from_cstr(&cstring).unwrap();
+        assert_eq!(string, OsStr::new("aé"));
+        assert_eq!(string.as_encoded_bytes().as_ptr(), cstring.as_ptr() as *const u8);
+        assert_eq!(OsString::from_cstring(cstring.clone()), Some(OsString::from("aé")));
+
+        let mut buf = Vec::new();
+        assert_eq!(OsStr::new("aé").as_cstr_with_nul(&mut buf), Ok(&*cstring));
+        assert_eq!(buf, b"a\xC3\xA9\0");
+        assert_eq!(OsStr::new("b").as_cstr_with_nul(&mut buf).map(CStr::to_bytes),
+                   Ok(&b"b"[..]));
+        assert_eq!(OsStr::new("a\0").as_cstr_with_nul(&mut buf),
+                   Err(ToCStringError::InteriorNul(1)));
+
+        let cstring = CString::new(b"a\xFF".to_vec()).unwrap();
+        if_unix_windows! {
+            unix {
+                let mut expected = OsString::from("a");
+                expected.push(non_unicode_osstring());
+                assert_eq!(OsStr::from_cstr(&cstring), Some(&expected[..]));
+                assert_eq!(OsString::from_cstring(cstring), Some(expected.clone()));
+                assert_eq!(expected.as_cstr_with_nul(&mut buf).map(CStr::to_bytes),
+                           Ok(&b"a\xFF"[..]));
+            }
+            windows {
+                assert_eq!(OsStr::from_cstr(&cstring), None);
+                assert_eq!(OsString::from_cstring(cstring), None);
+                assert_eq!(non_unicode_osstring().as_cstr_with_nul(&mut buf),
+                           Err(ToCStringError::NotUnicode(0)));
+            }
+        }
+    }
+
+    #[test]
+    fn osstr_split_at() {
+        let string = OsStr::new("aé 💩");
+        assert_eq!(string.split_at(0), (OsStr::new(""), string));
+        assert_eq!(string.split_at(3), (OsStr::new("aé"), OsStr::new(" 💩")));
+        assert_eq!(string.split_at(string.len()), (string, OsStr::new("")));
+        assert!(string.try_split_at(string.len() + 1).is_none());
+
+        let mut string = non_unicode_osstring();
+        string.push("a");
+        assert_eq!(string.split_at(non_unicode_osstring().len()),
+                   (&non_unicode_osstring()[..], OsStr::new("a")));
+
+        let (start, end) = split_char();
+        let mut full = start.clone();
+        full.push(&end);
+        if_unix_windows! {
+            unix {
+                assert_eq!(full.try_split_at(1), Some((&start[..], &end[..])));
+            }
+            windows {
+                for i in 1..full.len() {
+                    assert!(full.try_split_at(i).is_none());
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn osstr_split_at_past_end() {
+        OsStr::new("abc").split_at(4);
+    }
+
+    #[test]
+    fn osstr_invalid_sections() {
+        let invalid = non_unicode_osstring();
+        let invalid = invalid.as_encoded_bytes();
+        let len = invalid.len();
+        assert!(OsStr::new("").invalid_sections().next().is_none());
+        assert!(OsStr::new("abc").invalid_sections().next_back().is_none());
+        assert_eq!(non_unicode_osstring().invalid_sections().collect::<Vec<_>>(),
+                   [(0..len, invalid)]);
+
+        let mut string = OsString::from("Γ");
+        string.push(&non_unicode_osstring());
+        string.push("abc");
+        string.push(&non_unicode_osstring());
+        let sections = [(2..2 + len, invalid), (5 + len..5 + 2 * len, invalid)];
+        assert_eq!(string.invalid_sections().collect::<Vec<_>>(), sections);
+        assert_eq!(string.invalid_sections().rev().collect::<Vec<_>>(),
+                   [sections[1].clone(), sections[0].clone()]);
+        for (range, bytes) in string.invalid_sections() {
+            assert_eq!(&string.as_encoded_bytes()[range], bytes);
+        }
+
+        let mut sections = string.invalid_sections();
+        assert_eq!(sections.next_back().map(|s| s.0), Some(5 + len..5 + 2 * len));
+        assert_eq!(sections.next().map(|s| s.0), Some(2..2 + len));
+        assert!(sections.next().is_none());
+        assert!(sections.next_back().is_none());
+    }
+
+    #[test]
+    fn osstr_utf8_chunks() {
+        let invalid = non_unicode_osstring();
+        let invalid = invalid.as_encoded_bytes();
+        assert!(OsStr::new("").utf8_chunks().next().is_none());
+        assert_eq!(OsStr::new("abc").utf8_chunks().collect::<Vec<_>>(),
+                   [("abc", &b""[..])]);
+        assert_eq!(non_unicode_osstring().utf8_chunks().collect::<Vec<_>>(),
+                   [("", invalid)]);
+
+        let mut string = OsString::from("Γ");
+        string.push(&non_unicode_osstring());
+        string.push("abc");
+        string.push(&non_unicode_osstring());
+        assert_eq!(string.utf8_chunks().collect::<Vec<_>>(),
+                   [("Γ", invalid), ("abc", invalid)]);
+
+        let mut string = non_unicode_osstring();
+        string.push("a");
+        assert_eq!(string.utf8_chunks().collect::<Vec<_>>(),
+                   [("", invalid), ("a", &b""[..])]);
+
+        let lossy: String = string.utf8_chunks()
+            .map(|(valid, invalid)| {
+                if invalid.is_empty() { valid.to_owned() } else { format!("{}?", valid) }
+            })
+            .collect();
+        assert_eq!(lossy, "?a");
+    }
+
+    #[test]
+    fn osstr_split_unicode() {
+        use super::OsStrSection::*;
+        assert!(OsStr::new("").split_unicode().next().is_none());
+        assert!(OsStr::new("").split_unicode().next_back().is_none());
+        assert_eq!(OsStr::new("a").split_unicode().collect::<Vec<_>>(),
+                   [Unicode("a")]);
+        assert_eq!(OsStr::new("a").split_unicode().rev().collect::<Vec<_>>(),
+                   [Unicode("a")]);
+        assert_eq!(non_unicode_osstring().split_unicode().collect::<Vec<_>>(),
+                   [NonUnicode(&non_unicode_osstring()[..])]);
+        assert_eq!(non_unicode_osstring().split_unicode().rev().collect::<Vec<_>>(),
+                   [NonUnicode(&non_unicode_osstring()[..])]);
+
+        let mut string = OsString::from("Γ");
+        string.push(&non_unicode_osstring());
+        string.push("abc");
+        assert_eq!(string.split_unicode().collect::<Vec<_>>(),
+                   [Unicode("Γ"), NonUnicode(&non_unicode_osstring()[..]), Unicode("abc")]);
+        assert_eq!(string.split_unicode().rev().collect::<Vec<_>>(),
+                   [Unicode("abc"), NonUnicode(&non_unicode_osstring()[..]), Unicode("Γ")]);
+
+        let mut split = OsStr::new("a").split_unicode();
+        assert_eq!(split.next(), Some(Unicode("a")));
+        assert_eq!(split.next_back(), None);
+        let mut split = OsStr::new("a").split_unicode();
+        assert_eq!(split.next_back(), Some(Unicode("a")));
+        assert_eq!(split.next(), None);
+
+        let mut string = non_unicode_osstring();
+        string.push("a");
+        string.push(&non_unicode_osstring());
+        let mut split = string.split_unicode();
+        assert_eq!(split.next(), Some(NonUnicode(&non_unicode_osstring()[..])));
+        assert_eq!(split.next_back(), Some(NonUnicode(&non_unicode_osstring()[..])));
+        assert_eq!(split.next_back(), Some(Unicode("a")));
+        assert_eq!(split.next_back(), None);
+        let mut split = string.split_unicode();
+        assert_eq!(split.next(), Some(NonUnicode(&non_unicode_osstring()[..])));
+        assert_eq!(split.next(), Some(Unicode("a")));
+        assert_eq!(split.next_back(), Some(NonUnicode(&non_unicode_osstring()[..])));
+        assert_eq!(split.next_back(), None);
+        let mut split = string.split_unicode();
+        assert_eq!(split.next_back(), Some(NonUnicode(&non_unicode_osstring()[..])));
+        assert_eq!(split.next(), Some(NonUnicode(&non_unicode_osstring()[..])));
+        assert_eq!(split.next(), Some(Unicode("a")));
+        assert_eq!(split.next(), None);
+        let mut split = string.split_unicode();
+        assert_eq!(split.next_back(), Some(NonUnicode(&non_unicode_osstring()[..])));
+        assert_eq!(split.next_back(), Some(Unicode("a")));
+        assert_eq!(split.next(), Some(NonUnicode(&non_unicode_osstring()[..])));
+        assert_eq!(split.next(), None);
+    }
+
+    #[test]
+    fn osstr_find_byte() {
+        assert_eq!(OsStr::new("").find_byte(b'='), None);
+        assert_eq!(OsStr::new("KEY=value=x").find_byte(b'='), Some(3));
+        assert_eq!(OsStr::new("aé:").find_byte(b':'), Some(3));
+        assert!(OsStr::new("a:b").contains_byte(b':'));
+        assert!(!OsStr::new("a:b").contains_byte(b'='));
+
+        let mut string = non_unicode_osstring();
+        string.push("=value");
+        let index = string.find_byte(b'=').unwrap();
+        assert_eq!(string.split_at(index), (&non_unicode_osstring()[..], OsStr::new("=value")));
+    }
+
+    #[test]
+    fn osstr_find_byte_non_ascii() {
+        let mut string = OsString::from("aé");
+        string.push(&non_unicode_osstring());
+        if_unix_windows! {
+            unix {
+                assert_eq!(string.find_byte(0xC3), Some(1));
+                assert_eq!(string.find_byte(0xFF), Some(3));
+                assert!(string.contains_byte(0xA9));
+                assert!(!string.contains_byte(0x80));
+            }
+            windows {
+                assert_eq!(string.find_byte(0xC3), None);
+                assert_eq!(string.find_byte(0xED), None);
+                assert!(!string.contains_byte(0xA9));
+            }
+        }
+    }
+
+    #[test]
+    fn osstr_contains_os() {
+        assert!(OsStr::new("").contains_os(""));
+        assert!(OsStr::new("aé 💩").contains_os(""));
+        assert!(OsStr::new("aé 💩").contains_os("aé"));
+        assert!(OsStr::new("aé 💩").contains_os("é "));
+        assert!(OsStr::new("aé 💩").contains_os(" 💩"));
+        assert!(OsStr::new("aé 💩").contains_os("aé 💩"));
+        assert!(!OsStr::new("aé 💩").contains_os("b"));
+        assert!(!OsStr::new("").contains_os("a"));
+
+        let (start, end) = split_char();
+        let mut full = start.to_owned();
+        full.push(&end);
+        // Sanity check
+        assert!(start.to_str().is_none() && end.to_str().is_none() &&
+                full.to_str().is_some());
+
+        assert!(!OsStr::new("").contains_os(&start));
+        assert!(!OsStr::new("").contains_os(&end));
+
+        assert!(start.contains_os(""));
+        assert!(start.contains_os(&start));
+        assert!(!start.contains_os(&end));
+        assert!(!start.contains_os(&full));
+        assert!(end.contains_os(""));
+        assert!(!end.contains_os(&start));
+        assert!(end.contains_os(&end));
+        assert!(!end.contains_os(&full));
+        assert!(full.contains_os(""));
+        assert!(full.contains_os(&start));
+        assert!(full.contains_os(&end));
+        assert!(full.contains_os(&full));
+    }
+
+    #[test]
+    fn osstr_starts_with_os() {
+        assert!(OsStr::new("").starts_with_os(""));
+        assert!(OsStr::new("aé 💩").starts_with_os(""));
+        assert!(OsStr::new("aé 💩").starts_with_os("aé"));
+        assert!(!OsStr::new("aé 💩").starts_with_os(" 💩"));
+        assert!(OsStr::new("aé 💩").starts_with_os("aé 💩"));
+        assert!(!OsStr::new("").starts_with_os("a"));
+
+        let (start, end) = split_char();
+        let mut full = start.to_owned();
+        full.push(&end);
+        // Sanity check
+        assert!(start.to_str().is_none() && end.to_str().is_none() &&
+                full.to_str().is_some());
+
+        assert!(!OsStr::new("").starts_with_os(&start));
+        assert!(!OsStr::new("").starts_with_os(&end));
+
+        assert!(start.starts_with_os(""));
+        assert!(start.starts_with_os(&start));
+        assert!(!start.starts_with_os(&end));
+        assert!(!start.starts_with_os(&full));
+        assert!(end.starts_with_os(""));
+        assert!(!end.starts_with_os(&start));
+        assert!(end.starts_with_os(&end));
+        assert!(!end.starts_with_os(&full));
+        assert!(full.starts_with_os(""));
+        assert!(full.starts_with_os(&start));
+        assert!(!full.starts_with_os(&end));
+        assert!(full.starts_with_os(&full));
+    }
+
+    #[test]
+    fn osstr_ends_with_os() {
+        assert!(OsStr::new("").ends_with_os(""));
+        assert!(OsStr::new("aé 💩").ends_with_os(""));
+        assert!(!OsStr::new("aé 💩").ends_with_os("aé"));
+        assert!(OsStr::new("aé 💩").ends_with_os(" 💩"));
+        assert!(OsStr::new("aé 💩").ends_with_os("aé 💩"));
+        assert!(!OsStr::new("").ends_with_os("a"));
+
+        let (start, end) = split_char();
+        let mut full = start.to_owned();
+        full.push(&end);
+        // Sanity check
+        assert!(start.to_str().is_none() && end.to_str().is_none() &&
+                full.to_str().is_some());
+
+        assert!(!OsStr::new("").ends_with_os(&start));
+        assert!(!OsStr::new("").ends_with_os(&end));
+
+        assert!(start.ends_with_os(""));
+        assert!(start.ends_with_os(&start));
+        assert!(!start.ends_with_os(&end));
+        assert!(!start.ends_with_os(&full));
+        assert!(end.ends_with_os(""));
+        assert!(!end.ends_with_os(&start));
+        assert!(end.ends_with_os(&end));
+        assert!(!end.ends_with_os(&full));
+        assert!(full.ends_with_os(""));
+        assert!(!full.ends_with_os(&start));
+        assert!(full.ends_with_os(&end));
+        assert!(full.ends_with_os(&full));
+    }
 
-impl<'a> From<inner::Section<'a>> for OsStrSection<'a> {
-    fn from(x: inner::Section<'a>) -> OsStrSection<'a> {
-        match x {
-            inner::Section::Unicode(s) => OsStrSection::Unicode(s),
-            inner::Section::NonUnicode(s) => OsStrSection::NonUnicode(OsStr::from_inner(s)),
-        }
+    #[test]
+    fn osstr_find_os() {
+        assert_eq!(OsStr::new("").find_os(""), Some(0));
+        assert_eq!(OsStr::new("").rfind_os(""), Some(0));
+        assert_eq!(OsStr::new("ab").rfind_os(""), Some(2));
+        assert_eq!(OsStr::new("").find_os("a"), None);
+        assert_eq!(OsStr::new("").rfind_os("a"), None);
+        assert_eq!(OsStr::new("a::é::b").find_os("::"), Some(1));
+        assert_eq!(OsStr::new("a::é::b").rfind_os("::"), Some(5));
+        assert_eq!(OsStr::new("aaa").find_os("aa"), Some(0));
+        assert_eq!(OsStr::new("aaa").rfind_os("aa"), Some(1));
+
+        let mut string = OsString::from("a");
+        string.push(&non_unicode_osstring());
+        string.push("b");
+        string.push(&non_unicode_osstring());
+        let len = non_unicode_osstring().len();
+        assert_eq!(string.find_os(&non_unicode_osstring()), Some(1));
+        assert_eq!(string.rfind_os(&non_unicode_osstring()), Some(2 + len));
     }
-}
 
-#[derive(Clone)]
-pub struct SplitUnicode<'a>(inner::SplitUnicode<'a>);
+    #[test]
+    fn osstr_match_indices_os() {
+        assert_eq!(OsStr::new("").match_indices_os("a").collect::<Vec<_>>(), []);
+        assert_eq!(OsStr::new("").match_indices_os("").collect::<Vec<_>>(),
+                   [(0, OsStr::new(""))]);
+        assert_eq!(OsStr::new("ab").match_indices_os("").map(|x| x.0).collect::<Vec<_>>(),
+                   [0, 1, 2]);
+        assert_eq!(OsStr::new("a::b::::c").match_indices_os("::").collect::<Vec<_>>(),
+                   [(1, OsStr::new("::")), (4, OsStr::new("::")), (6, OsStr::new("::"))]);
+        assert_eq!(OsStr::new("aaa").match_indices_os("aa").collect::<Vec<_>>(),
+                   [(0, OsStr::new("aa"))]);
+
+        let mut string = OsString::from("a");
+        string.push(&non_unicode_osstring());
+        string.push("b");
+        string.push(&non_unicode_osstring());
+        let len = non_unicode_osstring().len();
+        assert_eq!(string.match_indices_os(&non_unicode_osstring()).collect::<Vec<_>>(),
+                   [(1, &non_unicode_osstring()[..]), (2 + len, &non_unicode_osstring()[..])]);
 
-impl<'a> Iterator for SplitUnicode<'a> {
-    type Item = OsStrSection<'a>;
-    fn next(&mut self) -> Option<OsStrSection<'a>> {
-        self.0.next().map(|x| x.into())
+        let string = OsStr::new("ééb");
+        let empty = string.matches(OsStr::new("")).count();
+        assert_eq!(string.match_indices_os("").map(|x| x.0).collect::<Vec<_>>(), [0, 2, 4, 5]);
+        assert_eq!(string.rmatch_indices_os("").map(|x| x.0).collect::<Vec<_>>(), [5, 4, 2, 0]);
+        assert_eq!(string.count_matches_os(""), empty);
+        assert_eq!(empty, 4);
     }
-}
 
-impl<'a> DoubleEndedIterator for SplitUnicode<'a> {
-    fn next_back(&mut self) -> Option<OsStrSection<'a>> {
-        self.0.next_back().map(|x| x.into())
+    #[test]
+    fn osstr_rmatch_indices_os() {
+        assert_eq!(OsStr::new("").rmatch_indices_os("a").collect::<Vec<_>>(), []);
+        assert_eq!(OsStr::new("").rmatch_indices_os("").collect::<Vec<_>>(),
+                   [(0, OsStr::new(""))]);
+        assert_eq!(OsStr::new("ab").rmatch_indices_os("").map(|x| x.0).collect::<Vec<_>>(),
+                   [2, 1, 0]);
+        assert_eq!(OsStr::new("a::b::::c").rmatch_indices_os("::").collect::<Vec<_>>(),
+                   [(6, OsStr::new("::")), (4, OsStr::new("::")), (1, OsStr::new("::"))]);
+        assert_eq!(OsStr::new("aaa").rmatch_indices_os("aa").collect::<Vec<_>>(),
+                   [(1, OsStr::new("aa"))]);
+
+        let mut string = OsString::from("a");
+        string.push(&non_unicode_osstring());
+        string.push("b");
+        string.push(&non_unicode_osstring());
+        let len = non_unicode_osstring().len();
+        assert_eq!(string.rmatch_indices_os(&non_unicode_osstring()).collect::<Vec<_>>(),
+                   [(2 + len, &non_unicode_osstring()[..]), (1, &non_unicode_osstring()[..])]);
     }
-}
 
+    #[test]
+    fn osstr_split_once_os() {
+        assert_eq!(OsStr::new("").split_once_os(""), Some((OsStr::new(""), OsStr::new(""))));
+        assert_eq!(OsStr::new("").split_once_os("a"), None);
+        assert_eq!(OsStr::new("a::b::c").split_once_os("::"),
+                   Some((OsStr::new("a"), OsStr::new("b::c"))));
 
-#[derive(Clone)]
-pub struct SplitWhitespace<'a>(Filter<Split<'a, fn(char) -> bool>, OsStrNonEmptyHack>);
+        let mut string = OsString::from("a");
+        string.push(&non_unicode_osstring());
+        string.push("b");
+        string.push(&non_unicode_osstring());
+        assert_eq!(string.split_once_os(&non_unicode_osstring()),
+                   Some((OsStr::new("a"), &string[1 + non_unicode_osstring().len()..])));
 
-// Functions have issues with Clone sometimes
-#[derive(Clone)]
-struct OsStrNonEmptyHack;
+        let (start, end) = split_char();
+        let mut full = start.to_owned();
+        full.push(&end);
+        if_unix_windows! {
+            unix {
+                assert_eq!(full.split_once_os(&start), Some((OsStr::new(""), &end[..])));
+            }
+            windows {
+                assert_eq!(full.split_once_os(&start), None);
+            }
+        }
+    }
 
-impl<'a, 'b> FnOnce<(&'b &'a OsStr,)> for OsStrNonEmptyHack {
-    type Output = bool;
-    extern "rust-call" fn call_once(mut self, args: (&&OsStr,)) -> bool { self.call_mut(args) }
-}
+    #[test]
+    fn osstr_rsplit_once_os() {
+        assert_eq!(OsStr::new("").rsplit_once_os(""), Some((OsStr::new(""), OsStr::new(""))));
+        assert_eq!(OsStr::new("").rsplit_once_os("a"), None);
+        assert_eq!(OsStr::new("a::b::c").rsplit_once_os("::"),
+                   Some((OsStr::new("a::b"), OsStr::new("c"))));
 
-impl<'a, 'b> FnMut<(&'b &'a OsStr,)> for OsStrNonEmptyHack {
-    extern "rust-call" fn call_mut(&mut self, (&s,): (&&OsStr,)) -> bool { !s.is_empty() }
-}
+        let mut string = OsString::from("a");
+        string.push(&non_unicode_osstring());
+        string.push("b");
+        string.push(&non_unicode_osstring());
+        let len = non_unicode_osstring().len();
+        assert_eq!(string.rsplit_once_os(&non_unicode_osstring()),
+                   Some((&string[..2 + len], OsStr::new(""))));
 
-impl<'a> SplitWhitespace<'a> {
-    fn new(string: &'a OsStr) -> Self {
-        SplitWhitespace(string.split(char::is_whitespace as fn(char) -> bool)
-                        .filter(OsStrNonEmptyHack))
+        let (start, end) = split_char();
+        let mut full = start.to_owned();
+        full.push(&end);
+        if_unix_windows! {
+            unix {
+                assert_eq!(full.rsplit_once_os(&end), Some((&start[..], OsStr::new(""))));
+            }
+            windows {
+                assert_eq!(full.rsplit_once_os(&end), None);
+            }
+        }
     }
-}
 
-impl<'a> Iterator for SplitWhitespace<'a> {
-    type Item = &'a OsStr;
-    fn next(&mut self) -> Option<&'a OsStr> { self.0.next() }
-}
+    #[test]
+    fn osstr_count_matches() {
+        assert_eq!(OsStr::new("").count_matches('a'), 0);
+        assert_eq!(OsStr::new("").count_matches(""), 1);
+        assert_eq!(OsStr::new("a:b:c").count_matches(':'), 2);
+        assert_eq!(OsStr::new("aaa").count_matches("aa"), 1);
+        assert_eq!(OsStr::new("a1b22").count_matches(char::is_numeric), 3);
+
+        let mut string = OsString::from(":");
+        string.push(&non_unicode_osstring());
+        string.push("::");
+        string.push(&non_unicode_osstring());
+        assert_eq!(string.count_matches(':'), 3);
+        assert_eq!(string.count_matches(':'), string.matches(':').count());
+        assert_eq!(string.count_matches(""), string.matches("").count());
+    }
 
-impl<'a> DoubleEndedIterator for SplitWhitespace<'a> {
-    fn next_back(&mut self) -> Option<&'a OsStr> { self.0.next_back() }
-}
+    #[test]
+    fn osstr_count_matches_os() {
+        assert_eq!(OsStr::new("").count_matches_os("a"), 0);
+        assert_eq!(OsStr::new("").count_matches_os(""), 1);
+        assert_eq!(OsStr::new("ab").count_matches_os(""), 3);
+        assert_eq!(OsStr::new("a::b::::c").count_matches_os("::"), 3);
+        assert_eq!(OsStr::new("aaa").count_matches_os("aa"), 1);
 
-#[derive(Clone)]
-pub struct Lines<'a>(Map<SplitTerminator<'a, char>, fn(&'a OsStr) -> &'a OsStr>);
+        let mut string = non_unicode_osstring();
+        string.push("b");
+        string.push(&non_unicode_osstring());
+        assert_eq!(string.count_matches_os(&non_unicode_osstring()), 2);
+    }
 
-impl<'a> Lines<'a> {
-    fn new(string: &'a OsStr) -> Self {
-        fn remove_cr(s: &OsStr) -> &OsStr {
-            let mut split = s.rsplitn(2, '\r');
-            if let Some(e) = split.next() {
-                if e.is_empty() {
-                    return split.next().unwrap_or(OsStr::new(""));
-                }
-            }
-            s
-        }
-        Lines(string.split_terminator('\n').map(remove_cr))
+    #[test]
+    fn osstr_split_once() {
+        assert_eq!(OsStr::new("").split_once('='), None);
+        assert_eq!(OsStr::new("KEY").split_once('='), None);
+        assert_eq!(OsStr::new("KEY=a=b").split_once('='),
+                   Some((OsStr::new("KEY"), OsStr::new("a=b"))));
+        assert_eq!(OsStr::new("--opt==x").split_once("=="),
+                   Some((OsStr::new("--opt"), OsStr::new("x"))));
+
+        let mut string = non_unicode_osstring();
+        string.push("=");
+        string.push(&non_unicode_osstring());
+        string.push("=");
+        assert_eq!(string.split_once('='),
+                   Some((&non_unicode_osstring()[..], &string[non_unicode_osstring().len() + 1..])));
     }
-}
 
-impl<'a> Iterator for Lines<'a> {
-    type Item = &'a OsStr;
-    fn next(&mut self) -> Option<&'a OsStr> { self.0.next() }
-}
+    #[test]
+    fn osstr_rsplit_once() {
+        assert_eq!(OsStr::new("").rsplit_once('='), None);
+        assert_eq!(OsStr::new("KEY").rsplit_once('='), None);
+        assert_eq!(OsStr::new("KEY=a=b").rsplit_once('='),
+                   Some((OsStr::new("KEY=a"), OsStr::new("b"))));
 
-impl<'a> DoubleEndedIterator for Lines<'a> {
-    fn next_back(&mut self) -> Option<&'a OsStr> { self.0.next_back() }
-}
+        let mut string = OsString::from("=");
+        string.push(&non_unicode_osstring());
+        string.push("=");
+        string.push(&non_unicode_osstring());
+        assert_eq!(string.rsplit_once('='),
+                   Some((&string[..non_unicode_osstring().len() + 1], &non_unicode_osstring()[..])));
+    }
 
+    #[test]
+    fn osstr_slice_pop_char() {
+        assert_eq!(OsStr::new("").slice_pop_char(), None);
+        assert_eq!(OsStr::new("a").slice_pop_char(), Some((OsStr::new(""), 'a')));
+        assert_eq!(OsStr::new("dir/").slice_pop_char(), Some((OsStr::new("dir"), '/')));
+        assert_eq!(OsStr::new("aé💩").slice_pop_char(), Some((OsStr::new("aé"), '💩')));
 
-macro_rules! make_iterator {
-    ($forward:ident and $reverse:ident yield $map:expr => $ret:ty) => {
-        pub struct $forward<'a, P> where P: Pattern<'a> {
-            inner: inner::$forward<'a, P>
-        }
+        assert_eq!(non_unicode_osstring().slice_pop_char(), None);
+        let mut string = non_unicode_osstring();
+        string.push("é");
+        assert_eq!(string.slice_pop_char(), Some((&non_unicode_osstring()[..], 'é')));
+    }
 
-        impl<'a, P> Clone for $forward<'a, P>
-            where P: Pattern<'a> + Clone, P::Searcher: Clone {
-                fn clone(&self) -> Self { $forward { inner: self.inner.clone() } }
-            }
+    #[test]
+    fn osstr_get() {
+        let string = OsStr::new("aé💩");
+        assert_eq!(string.get(0..0), Some(OsStr::new("")));
+        assert_eq!(string.get(0..1), Some(OsStr::new("a")));
+        assert_eq!(string.get(1..3), Some(OsStr::new("é")));
+        assert_eq!(string.get(3..7), Some(OsStr::new("💩")));
+        assert_eq!(string.get(7..7), Some(OsStr::new("")));
+        assert_eq!(string.get(0..8), None);
+        assert_eq!(string.get(8..8), None);
+        assert_eq!(string.get(3..1), None);
 
-        impl<'a, P> Iterator for $forward<'a, P> where P: Pattern<'a> + Clone {
-            type Item = $ret;
+        let (start, end) = split_char();
+        let mut string = OsString::from("a");
+        string.push(&start);
+        string.push("b");
+        string.push(&end);
+        let found = 1 + start.len();
+        assert_eq!(string.get(found..found + 1), Some(OsStr::new("b")));
+        assert_eq!(string.get(1..found), Some(&start[..]));
+    }
 
-            fn next(&mut self) -> Option<$ret> {
-                self.inner.next().map($map)
-            }
-        }
+    #[test]
+    fn osstr_index_range() {
+        let string = OsString::from("aé💩");
+        assert_eq!(&string[..], OsStr::new("aé💩"));
+        assert_eq!(&string[1..3], OsStr::new("é"));
+        assert_eq!(&string[3..], OsStr::new("💩"));
+        assert_eq!(&string[..1], OsStr::new("a"));
+        assert_eq!(&string[..][7..], OsStr::new(""));
+        assert_eq!(&string[..][..0], OsStr::new(""));
 
-        pub struct $reverse<'a, P> where P: Pattern<'a> {
-            inner: inner::$reverse<'a, P>
-        }
+        let (start, end) = split_char();
+        let mut string = start.clone();
+        string.push("b");
+        string.push(&end);
+        assert_eq!(&string[..start.len()], &start[..]);
+        assert_eq!(&string[start.len() + 1..], &end[..]);
+    }
 
-        impl<'a, P> Clone for $reverse<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: Clone {
-            fn clone(&self) -> Self { $reverse { inner: self.inner.clone() } }
-        }
+    #[test]
+    #[should_panic]
+    fn osstr_index_range_past_end() {
+        let _ = &OsStr::new("abc")[1..4];
+    }
 
-        impl<'a, P> Iterator for $reverse<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
-            type Item = $ret;
+    #[test]
+    #[should_panic]
+    fn osstr_index_range_backwards() {
+        let _ = &OsStr::new("abc")[2..1];
+    }
 
-            fn next(&mut self) -> Option<$ret> {
-                self.inner.next().map($map)
-            }
+    #[test]
+    fn osstr_encoded_bytes() {
+        assert_eq!(OsStr::new("").as_encoded_bytes(), b"");
+        assert_eq!(OsStr::new("aé").as_encoded_bytes(), "aé".as_bytes());
+        assert_eq!(OsStr::from_encoded_bytes("aé".as_bytes()), Some(OsStr::new("aé")));
+
+        for string in &[unicode_osstring(), non_unicode_osstring()] {
+            assert_eq!(OsStr::from_encoded_bytes(string.as_encoded_bytes()),
+                       Some(&string[..]));
         }
-    };
-    ($forward:ident and $reverse:ident are double ended yield $map:expr => $ret:ty) => {
-        make_iterator!{$forward and $reverse yield $map => $ret}
 
-        impl<'a, P> DoubleEndedIterator for $forward<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: DoubleEndedSearcher<'a> {
-            fn next_back(&mut self) -> Option<$ret> {
-                self.inner.next_back().map($map)
+        let (start, end) = split_char();
+        let mut bytes = start.as_encoded_bytes().to_vec();
+        bytes.extend_from_slice(end.as_encoded_bytes());
+        if_unix_windows! {
+            unix {
+                assert_eq!(OsStr::from_encoded_bytes(&bytes), Some(OsStr::new("\u{A2}")));
+            }
+            windows {
+                // An unpaired lead and trail must be joined in WTF-8
+                assert_eq!(OsStr::from_encoded_bytes(&bytes), None);
             }
         }
+    }
 
-        impl<'a, P> DoubleEndedIterator for $reverse<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: DoubleEndedSearcher<'a> {
-            fn next_back(&mut self) -> Option<$ret> {
-                self.inner.next_back().map($map)
+    #[test]
+    fn osstr_wtf8_conversions() {
+        if_unix_windows! {
+            unix {}
+            windows {
+                use windows::{OsStrExt, OsStringExt};
+                use wtf8::{CodePoint, Wtf8, Wtf8Buf};
+
+                assert_eq!(OsStr::from_wtf8_slice(Wtf8::from_str("aé")), OsStr::new("aé"));
+                assert_eq!(OsStr::new("aé").as_wtf8(), Wtf8::from_str("aé"));
+
+                let mut buf = Wtf8Buf::from_str("a");
+                buf.push(CodePoint::from_u32(0xD800).unwrap());
+                let string = OsString::from_wtf8_buf(buf.clone());
+                assert_eq!(string, OsString::from_wide(&[0x61, 0xD800]));
+                assert_eq!(string.as_wtf8(), &*buf);
+                assert_eq!(string.into_wtf8_buf(), buf);
             }
         }
     }
-}
 
-make_iterator!{Split and RSplit are double ended yield |s| OsStr::from_inner(s) => &'a OsStr}
-make_iterator!{SplitTerminator and RSplitTerminator are double ended
-               yield |s| OsStr::from_inner(s) => &'a OsStr}
-make_iterator!{SplitN and RSplitN yield |s| OsStr::from_inner(s) => &'a OsStr}
-make_iterator!{Matches and RMatches are double ended yield |s| s => &'a str}
+    #[test]
+    fn osstr_from_wtf8() {
+        if_unix_windows! {
+            unix {}
+            windows {
+                use windows::{OsStrExt, OsStringExt};
+
+                assert_eq!(OsStr::from_wtf8(b"a\xC3\xA9").unwrap(), OsStr::new("aé"));
+                assert_eq!(OsStr::from_wtf8(b"a\xED\xA0\x80").unwrap(),
+                           &*OsString::from_wide(&[0x61, 0xD800]));
+                assert_eq!(OsStr::from_wtf8(b"a\xFF").unwrap_err().valid_up_to(), 1);
+
+                let string = non_unicode_osstring();
+                let bytes = string.as_encoded_bytes().to_vec();
+                assert_eq!(OsString::from_wtf8(bytes).unwrap(), string);
+                // A surrogate pair must be encoded as a single code point
+                let error = OsString::from_wtf8(b"\xED\xA0\xBD\xED\xB8\xBA".to_vec())
+                    .unwrap_err();
+                assert_eq!(error.valid_up_to(), 3);
+            }
+        }
+    }
 
+    #[test]
+    fn osstr_encode_wide() {
+        if_unix_windows! {
+            unix {}
+            windows {
+                use windows::{OsStrExt, OsStringExt};
 
-impl<S: Borrow<OsStr>> LocalSliceConcatExt<OsStr> for [S] {
-    type Output = OsString;
+                assert_eq!(OsStr::new("").encode_wide().count(), 0);
+                assert_eq!(OsStr::new("a\u{E9}\u{1F4A9}").encode_wide().collect::<Vec<_>>(),
+                           [0x61, 0xE9, 0xD83D, 0xDCA9]);
+                assert_eq!(non_unicode_osstring().encode_wide().collect::<Vec<_>>(), [0xD800]);
 
-    fn concat(&self) -> OsString {
-        if self.is_empty() {
-            return OsString::new();
+                let wide = [0x61, 0xDE3A, 0xD83D, 0xD83D, 0xDE3A];
+                assert_eq!(OsString::from_wide(&wide).encode_wide().collect::<Vec<_>>(), wide);
+            }
         }
+    }
 
-        let len = self.iter().map(|s| s.borrow().len()).sum();
-        let mut result = OsString::with_capacity(len);
+    #[test]
+    fn osstr_from_nul_terminated() {
+        if_unix_windows! {
+            unix {
+                use unix::OsStrExt;
 
-        for s in self {
-            result.push(s.borrow())
-        }
+                assert_eq!(OsStr::from_bytes_until_nul(b"ab\0cd\0"), OsStr::new("ab"));
+                assert_eq!(OsStr::from_bytes_until_nul(b"\0"), OsStr::new(""));
+                assert_eq!(OsStr::from_bytes_until_nul(b""), OsStr::new(""));
+                assert_eq!(OsStr::from_bytes_until_nul(b"\xFF"), &non_unicode_osstring()[..]);
+            }
+            windows {
+                use windows::OsStringExt;
 
-        result
+                assert_eq!(OsString::from_wide_null(&[0x61, 0x62, 0, 0x63, 0]),
+                           OsString::from("ab"));
+                assert_eq!(OsString::from_wide_null(&[0]), OsString::new());
+                assert_eq!(OsString::from_wide_null(&[]), OsString::new());
+                assert_eq!(OsString::from_wide_null(&[0xD800]), non_unicode_osstring());
+            }
+        }
     }
 
-    fn join(&self, sep: &OsStr) -> OsString {
-        if self.is_empty() {
-            return OsString::new();
+    #[test]
+    fn osstr_to_wide() {
+        if_unix_windows! {
+            unix {
+                use unix::OsStrExt;
+
+                assert_eq!(OsStr::new("").to_wide(), Some(vec![]));
+                assert_eq!(OsStr::new("a\u{E9}\u{1F4A9}").to_wide(),
+                           Some(vec![0x61, 0xE9, 0xD83D, 0xDCA9]));
+                assert_eq!(non_unicode_osstring().to_wide(), None);
+
+                let mut string = OsString::from("a");
+                string.push(non_unicode_osstring());
+                string.push("b");
+                assert_eq!(string.to_wide_lossy(), [0x61, 0xFFFD, 0x62]);
+                assert_eq!(OsStr::new("\u{1F4A9}").to_wide_lossy(), [0xD83D, 0xDCA9]);
+            }
+            windows {}
         }
+    }
 
-        // concat is faster
-        if sep.is_empty() {
-            return self.concat();
+    #[test]
+    fn osstr_compare_bytes() {
+        if_unix_windows! {
+            unix {
+                let string = non_unicode_osstring();
+                let bytes = vec![0xFF];
+                assert!(*string == *bytes && *bytes == *string);
+                assert!(*string == bytes && bytes == *string);
+                assert!(&string[..] == bytes && bytes == &string[..]);
+                assert!(string == *bytes && *bytes == string);
+                assert!(string == &bytes[..] && &bytes[..] == string);
+                assert!(string == bytes && bytes == string);
+                assert!(*OsStr::new("ab") != b"a"[..]);
+
+                assert!(*OsStr::new("a") < b"b"[..] && b"b"[..] > *OsStr::new("a"));
+                assert!(*OsStr::new("a") < bytes && bytes > *OsStr::new("a"));
+                assert!(OsStr::new("a") < bytes && bytes > OsStr::new("a"));
+                assert!(OsString::from("a") < b"a\0"[..] && b""[..] < OsString::from("a"));
+                assert!(OsString::from("a") < &b"b"[..] && &b"b"[..] > OsString::from("a"));
+                assert!(string > vec![0xFE] && vec![0xFF, 0] > string);
+            }
+            windows {}
         }
+    }
 
-        // this is wrong without the guarantee that `self` is non-empty
-        // On Windows this may be a slight overestimate, but that's OK.
-        let len = sep.len() * (self.len() - 1)
-            + self.iter().map(|s| s.borrow().len()).sum::<usize>();
-        let mut result = OsString::with_capacity(len);
-        let mut first = true;
+    #[test]
+    fn osstring_raw_parts() {
+        if_unix_windows! {
+            unix {
+                use unix::OsStringExt;
 
-        for s in self {
-            if first {
-                first = false;
-            } else {
-                result.push(sep);
+                let mut string = OsString::with_capacity(10);
+                string.push("ab");
+                string.push(non_unicode_osstring());
+                let (ptr, len, capacity) = string.clone().into_raw_parts();
+                assert_eq!(len, 3);
+                let rebuilt = unsafe { OsString::from_raw_parts(ptr, len, capacity) };
+                assert_eq!(rebuilt, string);
+
+                unsafe {
+                    let vec = string.as_mut_vec();
+                    vec.truncate(1);
+                    vec.push(0xFE);
+                }
+                assert_eq!(string, OsString::from_vec(vec![b'a', 0xFE]));
             }
-            result.push(s.borrow());
+            windows {}
         }
-        result
     }
 
-    fn connect(&self, sep: &OsStr) -> OsString {
-        self.join(sep)
-    }
-}
+    #[test]
+    fn osstring_from_wide_lossy() {
+        if_unix_windows! {
+            unix {}
+            windows {
+                use windows::OsStringExt;
 
+                assert_eq!(OsString::from_wide_lossy(&[]), OsString::new());
+                assert_eq!(OsString::from_wide_lossy(&[0x61, 0xD83D, 0xDCA9]),
+                           OsString::from("a\u{1F4A9}"));
+                let string = OsString::from_wide_lossy(&[0x61, 0xD800, 0x62, 0xDE3A]);
+                assert_eq!(string, OsString::from("a\u{FFFD}b\u{FFFD}"));
+                assert!(string.to_str().is_some());
+            }
+        }
+    }
 
-#[cfg(test)]
-mod tests {
-    use std::prelude::v1::*;
-    use std::borrow::Cow;
-    use super::*;
-    use slice_concat_ext::LocalSliceConcatExt;
+    #[test]
+    fn osstr_portable_bytes() {
+        use portable::{DecodeError, Encoding};
+
+        assert_eq!(OsStr::new("").to_portable_bytes(), b"\x00");
+        assert_eq!(OsStr::new("aé").to_portable_bytes(), b"\x00a\xC3\xA9");
+        let native = if_unix_windows! {
+            unix { Encoding::Bytes }
+            windows { Encoding::Wtf8 }
+        };
+        assert_eq!(non_unicode_osstring().to_portable_bytes()[0], native.tag());
+
+        for string in &[OsString::new(), unicode_osstring(), non_unicode_osstring()] {
+            assert_eq!(OsString::from_portable_bytes(&string.to_portable_bytes()).as_ref(),
+                       Ok(string));
+        }
 
-    fn unicode_str() -> &'static str { "aé 💩" }
-    fn unicode_osstring() -> OsString {
-        OsString::from(unicode_str())
+        assert_eq!(OsString::from_portable_bytes(b""), Err(DecodeError::MissingTag));
+        assert_eq!(OsString::from_portable_bytes(b"\x07abc"), Err(DecodeError::UnknownTag(7)));
+        assert_eq!(OsString::from_portable_bytes(b"\x00\xFF"),
+                   Err(DecodeError::Malformed(Encoding::Unicode)));
     }
 
-    fn non_unicode_osstring() -> OsString {
-        if_unix_windows! {
+    struct RecordingHasher(Vec<u8>);
+
+    impl Hasher for RecordingHasher {
+        fn finish(&self) -> u64 { 0 }
+        fn write(&mut self, bytes: &[u8]) { self.0.extend_from_slice(bytes) }
+    }
+
+    fn portable_hash_input<T: Hash>(value: T) -> Vec<u8> {
+        let mut hasher = RecordingHasher(Vec::new());
+        value.hash(&mut hasher);
+        hasher.0
+    }
+
+    #[test]
+    fn osstr_hash_portable() {
+        assert_eq!(portable_hash_input(PortableHash(OsStr::new("aé"))),
+                   b"\x03\x00\x00\x00\x00\x00\x00\x00a\xC3\xA9");
+        assert_eq!(portable_hash_input(PortableHash(OsString::new())),
+                   b"\x00\x00\x00\x00\x00\x00\x00\x00");
+        let long = OsString::from(vec!["a"; 0x0102].concat());
+        assert_eq!(&portable_hash_input(PortableHash(long))[..8],
+                   b"\x02\x01\x00\x00\x00\x00\x00\x00");
+        assert_eq!(PortableHash(unicode_osstring()), PortableHash(unicode_osstring()));
+        assert!(PortableHash(unicode_osstring()) != PortableHash(non_unicode_osstring()));
+
+        let (native, foreign) = if_unix_windows! {
             unix {
                 use unix::OsStringExt;
-                let string = OsString::from_vec(vec![0xFF]);
-                assert!(string.to_str().is_none());
-                string
+                use windows::OsStringExt as ForeignExt;
+                use windows::os_str::{OsString as Foreign, PortableHash as ForeignHash};
+                (portable_hash_input(PortableHash(OsString::from_vec(vec![0x61, 0xED, 0xA0, 0x80]))),
+                 portable_hash_input(ForeignHash(Foreign::from_wide(&[0x61, 0xD800]))))
             }
             windows {
                 use windows::OsStringExt;
-                let string = OsString::from_wide(&[0xD800]);
-                assert!(string.to_str().is_none());
-                string
+                use unix::OsStringExt as ForeignExt;
+                use unix::os_str::{OsString as Foreign, PortableHash as ForeignHash};
+                (portable_hash_input(PortableHash(OsString::from_wide(&[0x61, 0xD800]))),
+                 portable_hash_input(ForeignHash(Foreign::from_vec(vec![0x61, 0xED, 0xA0, 0x80]))))
+            }
+        };
+        assert_eq!(native, foreign);
+
+        if_unix_windows! {
+            unix {
+                use unix::OsStringExt;
+                let split1 = (PortableHash(OsString::from_vec(vec![0x61, 0xFF])),
+                              PortableHash(OsString::from("b")));
+                let split2 = (PortableHash(OsString::from("a")),
+                              PortableHash(OsString::from_vec(vec![0xFF, 0x62])));
+                assert!(portable_hash_input(split1) != portable_hash_input(split2));
             }
+            windows {}
         }
     }
 
-    fn split_char() -> (OsString, OsString) {
+    #[test]
+    fn osstr_portable_bytes_cross_platform() {
+        use portable::{DecodeError, Encoding};
+
         if_unix_windows! {
             unix {
-                use unix::OsStringExt;
-                (OsString::from_vec(vec![0xC2]), OsString::from_vec(vec![0xA2]))
+                use windows::{OsString as Foreign, OsStringExt};
+
+                let foreign = Foreign::from_wide(&[0x61, 0xD800]);
+                assert_eq!(OsString::from_portable_bytes(&foreign.to_portable_bytes()),
+                           Err(DecodeError::Incompatible { found: Encoding::Wtf8,
+                                                           native: Encoding::Bytes }));
+                let foreign = Foreign::from("aé");
+                assert_eq!(OsString::from_portable_bytes(&foreign.to_portable_bytes()),
+                           Ok(OsString::from("aé")));
+                assert_eq!(OsString::from_portable_bytes(b"\x02abc"), Ok(OsString::from("abc")));
             }
             windows {
-                use windows::OsStringExt;
-                (OsString::from_wide(&[0xD83D]), OsString::from_wide(&[0xDE3A]))
+                use unix::{OsString as Foreign, OsStringExt};
+
+                let foreign = Foreign::from_vec(vec![0x61, 0xFF]);
+                assert_eq!(OsString::from_portable_bytes(&foreign.to_portable_bytes()),
+                           Err(DecodeError::Incompatible { found: Encoding::Bytes,
+                                                           native: Encoding::Wtf8 }));
+                let foreign = Foreign::from("aé");
+                assert_eq!(OsString::from_portable_bytes(&foreign.to_portable_bytes()),
+                           Ok(OsString::from("aé")));
+                assert_eq!(OsString::from_portable_bytes(b"\x01abc"), Ok(OsString::from("abc")));
+                assert_eq!(OsString::from_portable_bytes(b"\x02\xED\xA0\xBD\xED\xB8\xBA"),
+                           Err(DecodeError::Malformed(Encoding::Wtf8)));
             }
         }
     }
 
-
+    #[cfg(feature = "serde")]
     #[test]
-    fn osstring_eq_smoke() {
-        assert_eq!(OsString::new(), OsString::new());
-        let string = OsString::from("abc");
-        assert_eq!(unicode_osstring(), unicode_osstring());
-        assert!(OsString::new() != unicode_osstring());
-        assert!(unicode_osstring() != string);
-        assert_eq!(non_unicode_osstring(), non_unicode_osstring());
-    }
+    fn osstring_serde() {
+        use serde_json;
 
-    #[test]
-    fn osstring_from_bytes() {
-        assert_eq!(OsString::from_bytes(unicode_str().as_bytes()),
-                   Some(OsString::from(unicode_str())));
+        assert_eq!(serde_json::to_string(&OsStr::new("aé")).unwrap(), r#"{"Unicode":"aé"}"#);
+        let encoded = serde_json::to_string(&non_unicode_osstring()).unwrap();
+        assert!(encoded.starts_with(r#"{"Encoded":["#));
+
+        for string in &[OsString::new(), unicode_osstring(), non_unicode_osstring()] {
+            let json = serde_json::to_string(string).unwrap();
+            assert_eq!(&serde_json::from_str::<OsString>(&json).unwrap(), string);
+        }
+
+        assert!(serde_json::from_str::<OsString>(r#"{"Encoded":[7]}"#).is_err());
+        assert!(serde_json::from_str::<OsString>(r#"{"Bytes":[]}"#).is_err());
     }
 
+    #[cfg(feature = "unicode-segmentation")]
     #[test]
-    fn osstring_capacity() {
-        assert!(OsString::with_capacity(10).capacity() >= 10);
-        assert!(OsString::from("Hello").capacity() >= 5);
+    fn osstr_graphemes() {
+        assert_eq!(OsStr::new("").graphemes().next(), None);
+        assert_eq!(OsStr::new("ae\u{301}\r\n").graphemes().collect::<Vec<_>>(),
+                   ["a", "e\u{301}", "\r\n"]);
+        assert_eq!(OsStr::new("ae\u{301}\r\n").graphemes().rev().collect::<Vec<_>>(),
+                   ["\r\n", "e\u{301}", "a"]);
+
+        let mut string = OsString::from("ab");
+        string.push(&non_unicode_osstring());
+        string.push(&non_unicode_osstring());
+        string.push("o\u{308}");
+        let mut run = non_unicode_osstring();
+        run.push(&non_unicode_osstring());
+        let expected = [OsStr::new("a"), OsStr::new("b"), &run, OsStr::new("o\u{308}")];
+        assert_eq!(string.graphemes().collect::<Vec<_>>(), expected);
+        let mut reversed = expected.to_vec();
+        reversed.reverse();
+        assert_eq!(string.graphemes().rev().collect::<Vec<_>>(), reversed);
+
+        let mut graphemes = string.graphemes();
+        assert_eq!(graphemes.next(), Some(OsStr::new("a")));
+        assert_eq!(graphemes.next_back(), Some(OsStr::new("o\u{308}")));
+        assert_eq!(graphemes.next_back(), Some(&run[..]));
+        assert_eq!(graphemes.next_back(), Some(OsStr::new("b")));
+        assert_eq!(graphemes.next(), None);
+        assert_eq!(graphemes.next_back(), None);
+
+        let mut graphemes = OsStr::new("ab").graphemes();
+        assert_eq!(graphemes.next_back(), Some(OsStr::new("b")));
+        assert_eq!(graphemes.next(), Some(OsStr::new("a")));
+        assert_eq!(graphemes.next(), None);
+    }
+
+    #[cfg(feature = "unicode-width")]
+    #[test]
+    fn osstr_display_width() {
+        assert_eq!(OsStr::new("").display_width(), 0);
+        assert_eq!(OsStr::new("abc").display_width(), 3);
+        assert_eq!(OsStr::new("日本語").display_width(), 6);
+        assert_eq!(OsStr::new("cafe\u{301}").display_width(), 4);
+        assert_eq!(unicode_osstring().display_width(), 5);
+
+        let escape_len = non_unicode_osstring().escape_debug().to_string().len();
+        assert_eq!(non_unicode_osstring().display_width(), escape_len);
+        let mut string = OsString::from("日");
+        string.push(&non_unicode_osstring());
+        string.push(&non_unicode_osstring());
+        string.push("a");
+        assert_eq!(string.display_width(), 3 + 2 * escape_len);
+        assert_eq!(string.display_width_with(1), 5);
+        assert_eq!(string.display_width_with(0), 3);
     }
 
+    #[cfg(feature = "regex")]
     #[test]
-    fn osstring_reserve() {
-        let mut string = OsString::from("Hello");
-        let len = string.len();
-        let cap = string.capacity();
-        assert!(cap >= len);
-        string.reserve_exact(cap - len);
-        assert_eq!(string.capacity(), cap);
-        string.reserve_exact(cap - len + 1);
-        assert!(string.capacity() > cap);
-        let cap = string.capacity();
-        string.reserve(cap - len + 1);
-        assert!(string.capacity() > cap);
+    fn osstr_regex() {
+        use regex::bytes::Regex;
+
+        let re = Regex::new(r"[0-9]+").unwrap();
+        let string = OsStr::new("img12_é3.png");
+        assert_eq!(string.regex_find(&re), Some((3, OsStr::new("12"))));
+        assert_eq!(string.regex_matches(&re).collect::<Vec<_>>(), ["12", "3"]);
+        assert_eq!(string.regex_split(&re).collect::<Vec<_>>(), ["img", "_é", ".png"]);
+        assert_eq!(OsStr::new("").regex_find(&re), None);
+        assert_eq!(OsStr::new("").regex_split(&re).collect::<Vec<_>>(), [""]);
+        assert_eq!(OsStr::new("12").regex_split(&re).collect::<Vec<_>>(), ["", ""]);
+
+        let mut string = non_unicode_osstring();
+        string.push("=1");
+        let any = Regex::new(r"(?s-u).").unwrap();
+        assert_eq!(string.regex_find(&Regex::new("=").unwrap()),
+                   Some((non_unicode_osstring().len(), OsStr::new("="))));
+        assert_eq!(string.regex_split(&Regex::new("=").unwrap()).collect::<Vec<_>>(),
+                   [&non_unicode_osstring()[..], OsStr::new("1")]);
+        // Unicode patterns never match non-Unicode data
+        assert_eq!(non_unicode_osstring().regex_find(&Regex::new("(?s).").unwrap()), None);
+        // Byte patterns only match whole code points on Windows
+        if_unix_windows! {
+            unix {
+                assert_eq!(non_unicode_osstring().regex_matches(&any).count(),
+                           non_unicode_osstring().len());
+            }
+            windows {
+                assert_eq!(non_unicode_osstring().regex_matches(&any).count(), 0);
+            }
+        }
     }
 
     #[test]
-    fn osstring_into_string() {
-        assert_eq!(unicode_osstring().into_string(), Ok(unicode_str().to_string()));
-        assert_eq!(non_unicode_osstring().into_string(), Err(non_unicode_osstring()));
+    fn osstr_percent_encode() {
+        use std::ascii::AsciiExt;
+
+        assert_eq!(OsStr::new("").percent_encode(), "");
+        assert_eq!(OsStr::new("a-b.c_d~e/F9").percent_encode(), "a-b.c_d~e/F9");
+        assert_eq!(OsStr::new("a b%c\n").percent_encode(), "a%20b%25c%0A");
+        assert_eq!(OsStr::new("é").percent_encode(), "%C3%A9");
+        assert_eq!(non_unicode_osstring().percent_encode(),
+                   if_unix_windows! {
+                       unix { "%FF" }
+                       windows { "%ED%A0%80" }
+                   });
+
+        for string in &[OsString::new(), unicode_osstring(), non_unicode_osstring()] {
+            let encoded = string.percent_encode();
+            assert!(encoded.is_ascii());
+            assert_eq!(OsString::from_percent_encoded(&encoded).as_ref(), Some(string));
+        }
     }
 
     #[test]
-    fn osstring_into_string_lossy() {
-        assert_eq!(unicode_osstring().into_string_lossy(), unicode_str());
-        assert_eq!(non_unicode_osstring().into_string_lossy(),
-                   String::from_utf8_lossy(b"\xFF"));
+    fn osstring_from_percent_encoded() {
+        assert_eq!(OsString::from_percent_encoded(""), Some(OsString::new()));
+        assert_eq!(OsString::from_percent_encoded("a b%2f%2Fé"), Some(OsString::from("a b//é")));
+        assert_eq!(OsString::from_percent_encoded("%"), None);
+        assert_eq!(OsString::from_percent_encoded("%4"), None);
+        assert_eq!(OsString::from_percent_encoded("%4g"), None);
+        if_unix_windows! {
+            unix {
+                use unix::OsStringExt;
+                assert_eq!(OsString::from_percent_encoded("%FF"),
+                           Some(OsString::from_vec(vec![0xFF])));
+            }
+            windows {
+                assert_eq!(OsString::from_percent_encoded("%FF"), None);
+            }
+        }
     }
 
     #[test]
-    fn osstring_push() {
-        let mut string = OsString::new();
-        string.push("foo");
-        string.push("x");
-        string.push(unicode_osstring());
-        assert_eq!(string, OsString::from(["foox", unicode_str()].concat()));
+    fn osstr_display() {
+        assert_eq!(format!("{}", OsStr::new("").display()), "");
+        assert_eq!(format!("{}", unicode_osstring().display()), unicode_str());
+        assert_eq!(format!("{:*<6}|{:>3.1}", OsStr::new("aé").display(), OsStr::new("xyz").display()),
+                   "aé****|  x");
+
+        let mut string = OsString::from("a");
         string.push(non_unicode_osstring());
-        assert!(string.into_string().is_err());
+        string.push("💩");
+        let lossy = string.to_string_lossy();
+        assert_eq!(format!("{}", string.display()), lossy);
+        assert_eq!(format!("{:6}", string.display()), format!("{:6}", lossy));
+        assert_eq!(format!("{:-^7}", string.display()), format!("{:-^7}", lossy));
+        assert_eq!(format!("{:>5.2}", string.display()), format!("{:>5.2}", lossy));
+        assert_eq!(format!("{:.0}", string.display()), "");
     }
 
     #[test]
-    fn osstring_clear() {
-        let mut string = non_unicode_osstring();
-        string.clear();
-        assert_eq!(&string, "");
+    fn osstr_escape_default() {
+        assert_eq!(format!("{}", OsStr::new("").escape_default()), "");
+        assert_eq!(format!("{}", OsStr::new("a\\b\"\n").escape_default()), "a\\\\b\\\"\\n");
+        assert_eq!(format!("{}", OsStr::new("aé").escape_default()), "a\\u{e9}");
+
+        let mut string = OsString::from("\\x");
+        string.push(&non_unicode_osstring());
+        string.push("é");
+        assert_eq!(format!("{}", string.escape_default()),
+                   if_unix_windows! {
+                       unix { "\\\\x\\xFF\\u{e9}" }
+                       windows { "\\\\x\\u{D800}\\u{e9}" }
+                   });
     }
 
     #[test]
-    fn osstr_is_empty() {
-        assert!(OsString::new().is_empty());
-        assert!(!unicode_osstring().is_empty());
-        assert!(!non_unicode_osstring().is_empty());
+    fn osstr_escape_debug() {
+        assert_eq!(format!("{}", OsStr::new("").escape_debug()), "");
+        assert_eq!(format!("{}", OsStr::new("aé 💩").escape_debug()), "aé 💩");
+        assert_eq!(format!("{}", OsStr::new("'\\\t\u{7}").escape_debug()),
+                   "\\'\\\\\\t\\u{7}");
+
+        let mut string = OsString::from("é");
+        string.push(&non_unicode_osstring());
+        assert_eq!(format!("{}", string.escape_debug()),
+                   if_unix_windows! {
+                       unix { "é\\xFF" }
+                       windows { "é\\u{D800}" }
+                   });
     }
 
     #[test]
-    fn osstr_len() {
-        assert_eq!(OsStr::new("").len(), 0);
-        assert_eq!(unicode_osstring().len(), unicode_str().len());
-        assert!(non_unicode_osstring().len() > 0);
+    fn osstr_split_search_paths() {
+        let split = |s: &str| OsStr::new(s).split_search_paths().collect::<Vec<_>>();
+        let o = |s: &str| OsString::from(s);
+
+        assert_eq!(split(""), [o("")]);
+        if_unix_windows! {
+            unix {
+                assert_eq!(split("/bin:/usr/bin:"), [o("/bin"), o("/usr/bin"), o("")]);
+                assert_eq!(split("a;b:\"c\""), [o("a;b"), o("\"c\"")]);
+            }
+            windows {
+                assert_eq!(split("C:\\bin;D:\\x;"), [o("C:\\bin"), o("D:\\x"), o("")]);
+                assert_eq!(split("a;\"b;c\";d\"e\""), [o("a"), o("b;c"), o("de")]);
+            }
+        }
+
+        let mut string = non_unicode_osstring();
+        string.push(if_unix_windows! { unix { ":" } windows { ";" } });
+        string.push("a");
+        assert_eq!(string.split_search_paths().collect::<Vec<_>>(),
+                   [non_unicode_osstring(), o("a")]);
     }
 
     #[test]
-    fn osstr_to_str() {
-        assert_eq!(unicode_osstring().to_str(), Some(unicode_str()));
-        assert_eq!(non_unicode_osstring().to_str(), None);
+    fn osstring_join_search_paths() {
+        let paths = [OsString::from("a"), OsString::new(), OsString::from("b")];
+        let joined = OsString::join_search_paths(&paths).unwrap();
+        assert_eq!(joined.split_search_paths().collect::<Vec<_>>(), paths);
+        assert_eq!(OsString::join_search_paths(Vec::<OsString>::new()), Ok(OsString::new()));
+
+        if_unix_windows! {
+            unix {
+                assert_eq!(joined, OsString::from("a::b"));
+                let err = OsString::join_search_paths(&["a", "b:c"]).unwrap_err();
+                assert_eq!(err.index(), 1);
+                assert!(OsString::join_search_paths(&["a;\"b"]).is_ok());
+            }
+            windows {
+                assert_eq!(joined, OsString::from("a;;b"));
+                let joined = OsString::join_search_paths(&["a", "b;c"]).unwrap();
+                assert_eq!(joined, OsString::from("a;\"b;c\""));
+                assert_eq!(joined.split_search_paths().collect::<Vec<_>>(),
+                           [OsString::from("a"), OsString::from("b;c")]);
+                let err = OsString::join_search_paths(&["a", "b\"c"]).unwrap_err();
+                assert_eq!(err.index(), 1);
+            }
+        }
+
+        let paths = [non_unicode_osstring(), OsString::from("a")];
+        let joined = OsString::join_search_paths(&paths).unwrap();
+        assert_eq!(joined.split_search_paths().collect::<Vec<_>>(), paths);
     }
 
     #[test]
-    fn osstr_to_string_lossy() {
-        assert_eq!(unicode_osstring().to_string_lossy(),
-                   Cow::Borrowed(unicode_str()));
-        assert_eq!(non_unicode_osstring().to_string_lossy(),
-                   String::from_utf8_lossy(b"\xFF"));
+    fn split_args() {
+        let split = |s: &str| super::split_args(OsStr::new(s)).unwrap();
+        let o = |s: &str| OsString::from(s);
+
+        assert_eq!(split(""), Vec::<OsString>::new());
+        assert_eq!(split(" a  b\t"), [o("a"), o("b")]);
+        if_unix_windows! {
+            unix {
+                assert_eq!(split("'a b' \"c\\\"d\\e\" f\\ g ''"),
+                           [o("a b"), o("c\"d\\e"), o("f g"), o("")]);
+                assert_eq!(split("a'b'\"c\"\\\nd\ne"), [o("abcd"), o("e")]);
+                assert_eq!(split("\"a\\\nb\""), [o("ab")]);
+                assert_eq!(super::split_args(OsStr::new("a 'b")).unwrap_err().position(), 2);
+                assert_eq!(super::split_args(OsStr::new("a \"b")).unwrap_err().position(), 2);
+                assert_eq!(super::split_args(OsStr::new("a\\")).unwrap_err().position(), 1);
+            }
+            windows {
+                assert_eq!(split("\"a b\" c\\d \"\" e\\\"f"),
+                           [o("a b"), o("c\\d"), o(""), o("e\"f")]);
+                assert_eq!(split("a\\\\\"b c\" d\\\\\\\"e"), [o("a\\b c"), o("d\\\"e")]);
+                assert_eq!(split("\"a\"\"b\" c\\"), [o("a\"b"), o("c\\")]);
+                assert_eq!(split("\"a"), [o("a")]);
+            }
+        }
     }
 
     #[test]
-    fn osstr_to_bytes() {
-        assert_eq!(unicode_osstring().to_bytes(), Some(unicode_str().as_bytes()));
+    fn join_args() {
+        let args = [OsString::from("plain"), OsString::new(), OsString::from("a b"),
+                    OsString::from("it's"), OsString::from("x\\\"y"),
+                    OsString::from("tail\\"), OsString::from("$HOME \\ \"\t"),
+                    unicode_osstring(), non_unicode_osstring()];
+        let joined = super::join_args(&args);
+        assert_eq!(super::split_args(&joined).unwrap(), args);
+        assert_eq!(super::join_args(Vec::<OsString>::new()), OsString::new());
+
         if_unix_windows! {
             unix {
-                assert_eq!(non_unicode_osstring().to_bytes(), Some(&b"\xFF"[..]));
+                assert_eq!(super::join_args(&["a", "", "b c", "it's", "-x=1,2"]),
+                           OsString::from("a '' 'b c' 'it'\\''s' -x=1,2"));
             }
             windows {
-                assert_eq!(non_unicode_osstring().to_bytes(), None);
+                assert_eq!(super::join_args(&["a", "", "b c", "d\"e", "f\\ g\\"]),
+                           OsString::from("a \"\" \"b c\" d\\\"e \"f\\ g\\\\\""));
             }
         }
     }
 
     #[test]
-    fn osstr_split_unicode() {
-        use super::OsStrSection::*;
-        assert!(OsStr::new("").split_unicode().next().is_none());
-        assert!(OsStr::new("").split_unicode().next_back().is_none());
-        assert_eq!(OsStr::new("a").split_unicode().collect::<Vec<_>>(),
-                   [Unicode("a")]);
-        assert_eq!(OsStr::new("a").split_unicode().rev().collect::<Vec<_>>(),
-                   [Unicode("a")]);
-        assert_eq!(non_unicode_osstring().split_unicode().collect::<Vec<_>>(),
-                   [NonUnicode(&non_unicode_osstring()[..])]);
-        assert_eq!(non_unicode_osstring().split_unicode().rev().collect::<Vec<_>>(),
-                   [NonUnicode(&non_unicode_osstring()[..])]);
+    fn cmp_natural() {
+        use std::cmp::Ordering;
+
+        let mut names = vec!["file10", "file2", "file1", "File3", "file02", "a", "file",
+                             "file1b", "file1a10", "file1a9", "x99999999999999999999",
+                             "x100000000000000000000", "2", "10", ""];
+        names.sort_by(|a, b| super::cmp_natural(a, b));
+        assert_eq!(names, ["", "2", "10", "File3", "a", "file", "file1", "file1a9", "file1a10",
+                           "file1b", "file02", "file2", "file10", "x99999999999999999999",
+                           "x100000000000000000000"]);
+
+        assert_eq!(super::cmp_natural("a1", "a1"), Ordering::Equal);
+        assert_eq!(super::cmp_natural("a01", "a1"), Ordering::Less);
+        assert_eq!(super::cmp_natural("a1", "a01"), Ordering::Greater);
+        assert_eq!(super::cmp_natural("a1", "a1b"), Ordering::Less);
+
+        let mut low = non_unicode_osstring();
+        low.push("9");
+        let mut high = non_unicode_osstring();
+        high.push("10");
+        assert_eq!(super::cmp_natural(&low, &high), Ordering::Less);
+        assert_eq!(super::cmp_natural(&high, &non_unicode_osstring()), Ordering::Greater);
+    }
 
-        let mut string = OsString::from("Γ");
-        string.push(&non_unicode_osstring());
-        string.push("abc");
-        assert_eq!(string.split_unicode().collect::<Vec<_>>(),
-                   [Unicode("Γ"), NonUnicode(&non_unicode_osstring()[..]), Unicode("abc")]);
-        assert_eq!(string.split_unicode().rev().collect::<Vec<_>>(),
-                   [Unicode("abc"), NonUnicode(&non_unicode_osstring()[..]), Unicode("Γ")]);
+    #[test]
+    fn natural_sort_key() {
+        let mut names = vec![OsString::from("v1.10"), OsString::from("v1.9"),
+                             OsString::from("v1.9.1")];
+        names.sort_by_key(|name| NaturalSortKey(name.clone()));
+        assert_eq!(names, ["v1.9", "v1.9.1", "v1.10"]);
+        assert!(NaturalSortKey("a2") < NaturalSortKey("a10"));
+        assert_eq!(NaturalSortKey("a2"), NaturalSortKey("a2"));
+        assert!(NaturalSortKey("a02") != NaturalSortKey("a2"));
+    }
 
-        let mut split = OsStr::new("a").split_unicode();
-        assert_eq!(split.next(), Some(Unicode("a")));
-        assert_eq!(split.next_back(), None);
-        let mut split = OsStr::new("a").split_unicode();
-        assert_eq!(split.next_back(), Some(Unicode("a")));
-        assert_eq!(split.next(), None);
+    #[test]
+    fn osstr_is_ascii() {
+        assert!(OsStr::new("").is_ascii());
+        assert!(OsStr::new("hello, world\n\x7f").is_ascii());
+        assert!(!OsStr::new("héllo").is_ascii());
+        assert!(!unicode_osstring().is_ascii());
+        assert!(!non_unicode_osstring().is_ascii());
+    }
 
-        let mut string = non_unicode_osstring();
-        string.push("a");
-        string.push(&non_unicode_osstring());
-        let mut split = string.split_unicode();
-        assert_eq!(split.next(), Some(NonUnicode(&non_unicode_osstring()[..])));
-        assert_eq!(split.next_back(), Some(NonUnicode(&non_unicode_osstring()[..])));
-        assert_eq!(split.next_back(), Some(Unicode("a")));
-        assert_eq!(split.next_back(), None);
-        let mut split = string.split_unicode();
-        assert_eq!(split.next(), Some(NonUnicode(&non_unicode_osstring()[..])));
-        assert_eq!(split.next(), Some(Unicode("a")));
-        assert_eq!(split.next_back(), Some(NonUnicode(&non_unicode_osstring()[..])));
-        assert_eq!(split.next_back(), None);
-        let mut split = string.split_unicode();
-        assert_eq!(split.next_back(), Some(NonUnicode(&non_unicode_osstring()[..])));
-        assert_eq!(split.next(), Some(NonUnicode(&non_unicode_osstring()[..])));
-        assert_eq!(split.next(), Some(Unicode("a")));
-        assert_eq!(split.next(), None);
-        let mut split = string.split_unicode();
-        assert_eq!(split.next_back(), Some(NonUnicode(&non_unicode_osstring()[..])));
-        assert_eq!(split.next_back(), Some(Unicode("a")));
-        assert_eq!(split.next(), Some(NonUnicode(&non_unicode_osstring()[..])));
-        assert_eq!(split.next(), None);
+    #[test]
+    fn osstr_eq_ignore_ascii_case() {
+        assert!(OsStr::new("").eq_ignore_ascii_case(""));
+        assert!(OsStr::new("Makefile").eq_ignore_ascii_case("MAKEFILE"));
+        assert!(OsStr::new("aé").eq_ignore_ascii_case("Aé"));
+        assert!(!OsStr::new("aé").eq_ignore_ascii_case("AÉ"));
+        assert!(!OsStr::new("abc").eq_ignore_ascii_case("ab"));
+
+        let mut lower = non_unicode_osstring();
+        lower.push("abc");
+        let mut upper = non_unicode_osstring();
+        upper.push("ABC");
+        assert!(lower.eq_ignore_ascii_case(&upper));
+        assert!(!lower.eq_ignore_ascii_case(unicode_osstring()));
     }
 
     #[test]
-    fn osstr_contains_os() {
-        assert!(OsStr::new("").contains_os(""));
-        assert!(OsStr::new("aé 💩").contains_os(""));
-        assert!(OsStr::new("aé 💩").contains_os("aé"));
-        assert!(OsStr::new("aé 💩").contains_os("é "));
-        assert!(OsStr::new("aé 💩").contains_os(" 💩"));
-        assert!(OsStr::new("aé 💩").contains_os("aé 💩"));
-        assert!(!OsStr::new("aé 💩").contains_os("b"));
-        assert!(!OsStr::new("").contains_os("a"));
+    fn osstr_find_ignore_ascii_case() {
+        assert_eq!(OsStr::new("").find_ignore_ascii_case(""), Some(0));
+        assert_eq!(OsStr::new("").find_ignore_ascii_case("a"), None);
+        assert_eq!(OsStr::new("Read.ME").find_ignore_ascii_case(".me"), Some(4));
+        assert_eq!(OsStr::new("aÉa").find_ignore_ascii_case("éA"), None);
+        assert!(OsStr::new("CONFIG=yes").contains_ignore_ascii_case("config="));
+        assert!(!OsStr::new("CONFIG=yes").contains_ignore_ascii_case("config:"));
+
+        let mut string = OsString::from("ab");
+        string.push(&non_unicode_osstring());
+        string.push("CD");
+        let mut needle = OsString::from("B");
+        needle.push(&non_unicode_osstring());
+        needle.push("c");
+        assert_eq!(string.find_ignore_ascii_case(&needle), Some(1));
+        assert!(string.contains_ignore_ascii_case(&needle));
 
         let (start, end) = split_char();
         let mut full = start.to_owned();
         full.push(&end);
-        // Sanity check
-        assert!(start.to_str().is_none() && end.to_str().is_none() &&
-                full.to_str().is_some());
+        assert_eq!(full.find_ignore_ascii_case(&end), full.find_os(&end));
+    }
 
-        assert!(!OsStr::new("").contains_os(&start));
-        assert!(!OsStr::new("").contains_os(&end));
+    #[test]
+    fn osstr_matches_glob() {
+        assert!(OsStr::new("").matches_glob(""));
+        assert!(OsStr::new("notes.TXT").matches_glob("*.[Tt][Xx][Tt]"));
+        assert!(!OsStr::new("notes.txt").matches_glob("*.md"));
+        assert!(unicode_osstring().matches_glob("*"));
+
+        let mut name = OsString::from("data");
+        name.push(&non_unicode_osstring());
+        name.push(".bin");
+        assert!(name.matches_glob("data?.bin"));
+        assert!(name.matches_glob("*.bin"));
+        assert!(!name.matches_glob("data.bin"));
+        assert!(!name.matches_glob("data[a-z].bin"));
+
+        let mut pattern = OsString::from("*");
+        pattern.push(&non_unicode_osstring());
+        pattern.push("*");
+        assert!(name.matches_glob(&pattern));
+        assert!(!OsStr::new("data.bin").matches_glob(&pattern));
+    }
 
-        assert!(start.contains_os(""));
-        assert!(start.contains_os(&start));
-        assert!(!start.contains_os(&end));
-        assert!(!start.contains_os(&full));
-        assert!(end.contains_os(""));
-        assert!(!end.contains_os(&start));
-        assert!(end.contains_os(&end));
-        assert!(!end.contains_os(&full));
-        assert!(full.contains_os(""));
-        assert!(full.contains_os(&start));
-        assert!(full.contains_os(&end));
-        assert!(full.contains_os(&full));
+    #[test]
+    fn osstr_starts_ends_with_ignore_ascii_case() {
+        assert!(OsStr::new("").starts_with_ignore_ascii_case(""));
+        assert!(OsStr::new("").ends_with_ignore_ascii_case(""));
+        assert!(OsStr::new("--Verbose").starts_with_ignore_ascii_case("--VERB"));
+        assert!(!OsStr::new("--Verbose").starts_with_ignore_ascii_case("-v"));
+        assert!(OsStr::new("photo.JPG").ends_with_ignore_ascii_case(".jpg"));
+        assert!(!OsStr::new("photo.JPG").ends_with_ignore_ascii_case(".jpeg"));
+        assert!(!OsStr::new("jpg").ends_with_ignore_ascii_case("a.jpg"));
+        assert!(!OsStr::new("É.txt").starts_with_ignore_ascii_case("é"));
+
+        let mut string = non_unicode_osstring();
+        string.push(".Txt");
+        assert!(string.ends_with_ignore_ascii_case(".TXT"));
+        let mut prefix = non_unicode_osstring();
+        prefix.push(".t");
+        assert!(string.starts_with_ignore_ascii_case(&prefix));
     }
 
     #[test]
-    fn osstr_starts_with_os() {
-        assert!(OsStr::new("").starts_with_os(""));
-        assert!(OsStr::new("aé 💩").starts_with_os(""));
-        assert!(OsStr::new("aé 💩").starts_with_os("aé"));
-        assert!(!OsStr::new("aé 💩").starts_with_os(" 💩"));
-        assert!(OsStr::new("aé 💩").starts_with_os("aé 💩"));
-        assert!(!OsStr::new("").starts_with_os("a"));
+    fn osstr_make_ascii_case() {
+        let mut string = OsString::from("Hello, Wörld");
+        string.make_ascii_lowercase();
+        assert_eq!(string, OsString::from("hello, wörld"));
+        string.make_ascii_uppercase();
+        assert_eq!(string, OsString::from("HELLO, WöRLD"));
+
+        let mut string = non_unicode_osstring();
+        string.push("aB");
+        string[..].make_ascii_uppercase();
+        let mut expected = non_unicode_osstring();
+        expected.push("AB");
+        assert_eq!(string, expected);
+    }
+
+    #[test]
+    fn osstr_eq_cmp_fold() {
+        use std::cmp::Ordering;
+
+        assert!(OsStr::new("").eq_fold(""));
+        assert!(OsStr::new("Hello").eq_fold("hELLO"));
+        assert!(OsStr::new("Straße").eq_fold("STRASSE"));
+        assert!(OsStr::new("\u{1E9E}").eq_fold("ss"));
+        assert!(OsStr::new("ΣΊΣΥΦΟΣ").eq_fold("σίσυφος"));
+        assert!(OsStr::new("\u{212A}elvin").eq_fold("kelvin"));
+        assert!(!OsStr::new("Hello").eq_fold("Hell"));
+        assert!(!OsStr::new("i").eq_fold("\u{131}"));
+
+        let mut string = OsString::from("Ab");
+        string.push(&non_unicode_osstring());
+        string.push("É");
+        let mut folded = OsString::from("aB");
+        folded.push(&non_unicode_osstring());
+        folded.push("é");
+        assert!(string.eq_fold(&folded));
+        assert_eq!(string.cmp_fold(&folded), Ordering::Equal);
+        assert!(!string.eq_fold("abé"));
+        assert!(!non_unicode_osstring().eq_fold(""));
+
+        assert_eq!(OsStr::new("apple").cmp_fold("Banana"), Ordering::Less);
+        assert_eq!(OsStr::new("Zebra").cmp_fold("apple"), Ordering::Greater);
+        assert_eq!(OsStr::new("STRASSE").cmp_fold("straße"), Ordering::Equal);
+        assert_eq!(OsStr::new("ab").cmp_fold("AbC"), Ordering::Less);
+    }
+
+    #[test]
+    fn osstr_cmp_with() {
+        use std::cmp::Ordering;
 
-        let (start, end) = split_char();
-        let mut full = start.to_owned();
-        full.push(&end);
-        // Sanity check
-        assert!(start.to_str().is_none() && end.to_str().is_none() &&
-                full.to_str().is_some());
+        let reverse = |a: &str, b: &str| b.cmp(a);
+        let caseless = |a: &str, b: &str| a.to_lowercase().cmp(&b.to_lowercase());
+        assert_eq!(OsStr::new("a").cmp_with("b", &reverse), Ordering::Greater);
+        assert_eq!(OsStr::new("").cmp_with("", &reverse), Ordering::Equal);
+        assert_eq!(OsStr::new("").cmp_with("a", &reverse), Ordering::Less);
+        assert_eq!(OsStr::new("Ab").cmp_with("aB", &caseless), Ordering::Equal);
 
-        assert!(!OsStr::new("").starts_with_os(&start));
-        assert!(!OsStr::new("").starts_with_os(&end));
+        let mut string = OsString::from("Ab");
+        string.push(&non_unicode_osstring());
+        string.push("c");
+        let mut other = OsString::from("aB");
+        other.push(&non_unicode_osstring());
+        assert_eq!(string.cmp_with(&other, &caseless), Ordering::Greater);
+        other.push("C");
+        assert_eq!(string.cmp_with(&other, &caseless), Ordering::Equal);
+        other.push("d");
+        assert_eq!(string.cmp_with(&other, &caseless), Ordering::Less);
+
+        // Unicode sorts before non-Unicode data.
+        assert_eq!(non_unicode_osstring().cmp_with("a", &caseless), Ordering::Greater);
+        assert_eq!(OsStr::new("a").cmp_with(&non_unicode_osstring(), &caseless), Ordering::Less);
+        assert_eq!(non_unicode_osstring().cmp_with(&non_unicode_osstring(), &caseless),
+                   Ordering::Equal);
+    }
 
-        assert!(start.starts_with_os(""));
-        assert!(start.starts_with_os(&start));
-        assert!(!start.starts_with_os(&end));
-        assert!(!start.starts_with_os(&full));
-        assert!(end.starts_with_os(""));
-        assert!(!end.starts_with_os(&start));
-        assert!(end.starts_with_os(&end));
-        assert!(!end.starts_with_os(&full));
-        assert!(full.starts_with_os(""));
-        assert!(full.starts_with_os(&start));
-        assert!(!full.starts_with_os(&end));
-        assert!(full.starts_with_os(&full));
+    #[test]
+    fn osstr_to_lowercase_uppercase() {
+        assert_eq!(OsStr::new("").to_lowercase(), OsString::new());
+        assert_eq!(OsStr::new("ΑΒΓ Straße").to_lowercase(), OsString::from("αβγ straße"));
+        assert_eq!(OsStr::new("ΑΒΓ Straße").to_uppercase(), OsString::from("ΑΒΓ STRASSE"));
+
+        let (start, end) = split_char();
+        let mut string = OsString::from("Ab");
+        string.push(&start);
+        string.push("Cd");
+        string.push(&end);
+        let mut expected = OsString::from("ab");
+        expected.push(&start);
+        expected.push("cd");
+        expected.push(&end);
+        assert_eq!(string.to_lowercase(), expected);
     }
 
     #[test]
-    fn osstr_ends_with_os() {
-        assert!(OsStr::new("").ends_with_os(""));
-        assert!(OsStr::new("aé 💩").ends_with_os(""));
-        assert!(!OsStr::new("aé 💩").ends_with_os("aé"));
-        assert!(OsStr::new("aé 💩").ends_with_os(" 💩"));
-        assert!(OsStr::new("aé 💩").ends_with_os("aé 💩"));
-        assert!(!OsStr::new("").ends_with_os("a"));
+    fn osstr_chars_lossy() {
+        assert_eq!(OsStr::new("").chars_lossy().collect::<String>(), "");
+        assert_eq!(OsStr::new("aé💩").chars_lossy().collect::<String>(), "aé💩");
+        assert_eq!(non_unicode_osstring().chars_lossy().collect::<String>(),
+                   non_unicode_osstring().to_string_lossy());
 
         let (start, end) = split_char();
-        let mut full = start.to_owned();
-        full.push(&end);
-        // Sanity check
-        assert!(start.to_str().is_none() && end.to_str().is_none() &&
-                full.to_str().is_some());
+        let mut string = OsString::from("a");
+        string.push(&start);
+        string.push("b");
+        string.push(&end);
+        assert_eq!(string.chars_lossy().collect::<String>(), "a\u{FFFD}b\u{FFFD}");
+    }
 
-        assert!(!OsStr::new("").ends_with_os(&start));
-        assert!(!OsStr::new("").ends_with_os(&end));
+    #[test]
+    fn osstr_to_str_chunks_lossy() {
+        assert!(OsStr::new("").to_str_chunks_lossy().next().is_none());
+        assert_eq!(OsStr::new("aé💩").to_str_chunks_lossy().collect::<Vec<_>>(),
+                   [Cow::Borrowed("aé💩")]);
+        assert_eq!(non_unicode_osstring().to_str_chunks_lossy().collect::<Vec<_>>(),
+                   ["\u{FFFD}"]);
 
-        assert!(start.ends_with_os(""));
-        assert!(start.ends_with_os(&start));
-        assert!(!start.ends_with_os(&end));
-        assert!(!start.ends_with_os(&full));
-        assert!(end.ends_with_os(""));
-        assert!(!end.ends_with_os(&start));
-        assert!(end.ends_with_os(&end));
-        assert!(!end.ends_with_os(&full));
-        assert!(full.ends_with_os(""));
-        assert!(!full.ends_with_os(&start));
-        assert!(full.ends_with_os(&end));
-        assert!(full.ends_with_os(&full));
+        let (start, end) = split_char();
+        let mut string = OsString::from("a");
+        string.push(&start);
+        string.push("b");
+        string.push(&end);
+        string.push(&non_unicode_osstring());
+        let chunks = string.to_str_chunks_lossy().collect::<Vec<_>>();
+        assert_eq!(&chunks[..3], ["a", "\u{FFFD}", "b"]);
+        assert_eq!(chunks.len(), 4);
+        assert_eq!(chunks.concat(), string.chars_lossy().collect::<String>());
+        assert_eq!(string.to_str_chunks_lossy().rev().collect::<Vec<_>>(),
+                   chunks.into_iter().rev().collect::<Vec<_>>());
     }
 
     #[test]
@@ -1165,6 +5703,84 @@ mod tests {
                    OsStr::new("ΓXYZ"));
     }
 
+    #[test]
+    fn osstr_replace_matches() {
+        assert_eq!(OsStr::new("").replace_matches('a', "b"), OsString::from(""));
+        assert_eq!(OsStr::new("caac").replace_matches('a', "bb"), OsString::from("cbbbbc"));
+        assert_eq!(OsStr::new("aaa").replace_matches("aa", "b"), OsString::from("ba"));
+        assert_eq!(OsStr::new("a1b22c").replace_matches(char::is_numeric, ""),
+                   OsString::from("abc"));
+        assert_eq!(OsStr::new("ab").replace_matches("", "-"), OsString::from("-a-b-"));
+
+        let (start, end) = split_char();
+        let mut string = OsString::from("a");
+        string.push(&start);
+        string.push("a");
+        string.push(&end);
+        string.push("a");
+        let mut expected = OsString::from("xy");
+        expected.push(&start);
+        expected.push("xy");
+        expected.push(&end);
+        expected.push("xy");
+        assert_eq!(string.replace_matches('a', "xy"), expected);
+    }
+
+    #[test]
+    fn osstr_replacen() {
+        assert_eq!(OsStr::new("").replacen('a', "b", 1), OsString::from(""));
+        assert_eq!(OsStr::new("aaa").replacen('a', "b", 0), OsString::from("aaa"));
+        assert_eq!(OsStr::new("aaa").replacen('a', "b", 2), OsString::from("bba"));
+        assert_eq!(OsStr::new("aaa").replacen('a', "b", 5), OsString::from("bbb"));
+        assert_eq!(OsStr::new("./a/./b").replacen("./", "", 1), OsString::from("a/./b"));
+
+        let (start, end) = split_char();
+        let mut string = OsString::from("a");
+        string.push(&start);
+        string.push("a");
+        string.push(&end);
+        string.push("a");
+        let mut expected = OsString::from("x");
+        expected.push(&start);
+        expected.push("x");
+        expected.push(&end);
+        expected.push("a");
+        assert_eq!(string.replacen('a', "x", 2), expected);
+    }
+
+    #[test]
+    fn osstr_replace_borrows_unchanged() {
+        fn is_borrowed(cow: Cow<OsStr>) -> bool {
+            match cow { Cow::Borrowed(_) => true, Cow::Owned(_) => false }
+        }
+
+        let string = non_unicode_osstring();
+        assert!(is_borrowed(string.replace("a", "b")));
+        assert!(!is_borrowed(string.replace("", "b")));
+        assert!(!is_borrowed(string.replace(&string, "b")));
+        assert!(is_borrowed(string.replace_matches('a', "b")));
+        assert!(!is_borrowed(string.replace_matches("", "b")));
+        assert!(is_borrowed(OsStr::new("a").replacen('a', "b", 0)));
+        assert!(!is_borrowed(OsStr::new("a").replacen('a', "b", 1)));
+        assert_eq!(OsStr::new("aaa").replacen('a', "b", 2), OsStr::new("bba"));
+
+        // Half of a surrogate pair is replaced on Windows.
+        let (start, _) = split_char();
+        assert_eq!(is_borrowed(OsStr::new("\u{1F63A}").replace(&start, "b")), !is_windows!());
+    }
+
+    #[test]
+    fn osstr_cow_conversions() {
+        let string = non_unicode_osstring();
+        assert_eq!(Cow::from(&string[..]), string);
+        assert_eq!(Cow::from(&string), string);
+        assert_eq!(Cow::from(string.clone()), string);
+        assert_eq!(string.to_cow(), string);
+        assert_eq!(string.clone().into_cow(), string);
+        match string.to_cow() { Cow::Borrowed(_) => {}, Cow::Owned(_) => panic!() }
+        match string.clone().into_cow() { Cow::Owned(_) => {}, Cow::Borrowed(_) => panic!() }
+    }
+
     #[test]
     fn osstr_split_whitespace() {
         assert!(OsStr::new("").split_whitespace().next().is_none());
@@ -1231,6 +5847,51 @@ mod tests {
         assert!(!string.contains(&non_unicode_osstring().into_string_lossy()[..]));
     }
 
+    #[test]
+    fn osstr_find() {
+        assert_eq!(OsStr::new("").find(""), Some(0));
+        assert_eq!(OsStr::new("").rfind(""), Some(0));
+        assert_eq!(OsStr::new("").find('a'), None);
+
+        let mut string = OsString::from("aé");
+        string.push(non_unicode_osstring());
+        string.push("é");
+        let len = non_unicode_osstring().len();
+        assert_eq!(string.find('é'), Some(1));
+        assert_eq!(string.rfind('é'), Some(3 + len));
+        assert_eq!(string.find(char::is_alphabetic), Some(0));
+        assert_eq!(string.rfind("aé"), Some(0));
+        assert_eq!(string.find(&non_unicode_osstring()), Some(3));
+        assert_eq!(string.rfind(&non_unicode_osstring()), Some(3));
+        assert_eq!(string.find('b'), None);
+    }
+
+    #[test]
+    fn osstr_os_pattern_split_char() {
+        let (start, end) = split_char();
+        let mut full = start.to_owned();
+        full.push(&end);
+
+        assert!(full.contains_os(&end));
+        assert!(full.starts_with_os(&start));
+        assert!(full.ends_with_os(&end));
+        assert!(full.replace(&end, "x") != full);
+        if_unix_windows! {
+            unix {
+                assert!(full.contains(&*end));
+                assert!(full.starts_with(&*start));
+                assert!(full.ends_with(&*end));
+            }
+            windows {
+                // The halves of a surrogate pair are not on code point
+                // boundaries.
+                assert!(!full.contains(&*end));
+                assert!(!full.starts_with(&*start));
+                assert!(!full.ends_with(&*end));
+            }
+        }
+    }
+
     #[test]
     fn osstr_contains_replacement() {
         assert!(!non_unicode_osstring().contains(&non_unicode_osstring()
@@ -1273,6 +5934,44 @@ mod tests {
         assert!(!string.ends_with('X'));
     }
 
+    #[test]
+    fn osstr_strip_prefix() {
+        assert_eq!(OsStr::new("").strip_prefix(""), Some(OsStr::new("")));
+        assert_eq!(OsStr::new("").strip_prefix('a'), None);
+        assert_eq!(OsStr::new("aé 💩").strip_prefix("aé"), Some(OsStr::new(" 💩")));
+        assert_eq!(OsStr::new("aé 💩").strip_prefix('a'), Some(OsStr::new("é 💩")));
+        assert_eq!(OsStr::new("aé 💩").strip_prefix(char::is_alphabetic),
+                   Some(OsStr::new("é 💩")));
+        assert_eq!(OsStr::new("aé 💩").strip_prefix('é'), None);
+
+        let mut string = OsString::from("x");
+        string.push(&non_unicode_osstring());
+        assert_eq!(string.strip_prefix('x'), Some(&non_unicode_osstring()[..]));
+        assert_eq!(non_unicode_osstring().strip_prefix(""), Some(&non_unicode_osstring()[..]));
+        assert_eq!(non_unicode_osstring().strip_prefix('x'), None);
+    }
+
+    #[test]
+    fn osstr_strip_suffix() {
+        assert_eq!(OsStr::new("").strip_suffix(""), Some(OsStr::new("")));
+        assert_eq!(OsStr::new("").strip_suffix('a'), None);
+        assert_eq!(OsStr::new("aé 💩").strip_suffix(" 💩"), Some(OsStr::new("aé")));
+        assert_eq!(OsStr::new("aé 💩").strip_suffix('💩'), Some(OsStr::new("aé ")));
+        assert_eq!(OsStr::new("aé 💩").strip_suffix(char::is_alphabetic), None);
+        assert_eq!(OsStr::new("aé 💩").strip_suffix('a'), None);
+
+        assert_eq!(OsStr::new("file.tar.gz").strip_suffix(".tar"), None);
+
+        let mut string = non_unicode_osstring();
+        string.push("x");
+        assert_eq!(string.strip_suffix('x'), Some(&non_unicode_osstring()[..]));
+        let mut file = string.clone();
+        file.push(".txt");
+        assert_eq!(file.strip_suffix(".txt"), Some(&string[..]));
+        assert_eq!(non_unicode_osstring().strip_suffix(""), Some(&non_unicode_osstring()[..]));
+        assert_eq!(non_unicode_osstring().strip_suffix('x'), None);
+    }
+
     #[test]
     fn osstr_split() {
         assert_eq!(OsStr::new("").split('a').collect::<Vec<_>>(), [OsStr::new("")]);
@@ -1294,6 +5993,155 @@ mod tests {
                    [OsStr::new(""), OsStr::new("a")]);
     }
 
+    #[test]
+    fn osstr_splitn_double_ended() {
+        let mut string = OsString::from("a,b");
+        string.push(non_unicode_osstring());
+        string.push(",,c,");
+        for count in 0..7 {
+            for pat in &[&[','][..], &[',', 'b'], &['x']] {
+                let forward = string.splitn(count, *pat).collect::<Vec<_>>();
+                let mut backward = string.splitn(count, *pat).rev().collect::<Vec<_>>();
+                backward.reverse();
+                assert_eq!(forward, backward);
+
+                let forward = string.rsplitn(count, *pat).collect::<Vec<_>>();
+                let mut backward = string.rsplitn(count, *pat).rev().collect::<Vec<_>>();
+                backward.reverse();
+                assert_eq!(forward, backward);
+            }
+
+            // Alternating ends
+            let expected = string.splitn(count, ',').collect::<Vec<_>>();
+            let mut split = string.splitn(count, ',');
+            let (mut front, mut back) = (Vec::new(), Vec::new());
+            loop {
+                match split.next_back() { Some(s) => back.push(s), None => break }
+                match split.next() { Some(s) => front.push(s), None => break }
+            }
+            assert!(split.next().is_none());
+            front.extend(back.into_iter().rev());
+            assert_eq!(front, expected);
+        }
+
+        let mut split = OsStr::new("a,b,c,d").splitn(3, ',');
+        assert_eq!(split.next_back(), Some(OsStr::new("c,d")));
+        assert_eq!(split.remainder(), Some(OsStr::new("a,b")));
+        assert_eq!(split.next_back(), Some(OsStr::new("b")));
+        assert_eq!(split.next(), Some(OsStr::new("a")));
+        assert_eq!(split.next(), None);
+        let mut split = OsStr::new("a,b,c,d").rsplitn(3, ',');
+        assert_eq!(split.next_back(), Some(OsStr::new("a,b")));
+        assert_eq!(split.next(), Some(OsStr::new("d")));
+        assert_eq!(split.clone().collect::<Vec<_>>(), ["c"]);
+        assert_eq!(split.next_back(), Some(OsStr::new("c")));
+        assert_eq!(split.next_back(), None);
+    }
+
+    #[test]
+    fn osstr_split_remainder() {
+        let mut string = OsString::from("a,b");
+        string.push(non_unicode_osstring());
+        string.push(",c");
+        let mut tail = non_unicode_osstring();
+        tail.push(",c");
+        let mut bc = OsString::from("b");
+        bc.push(&tail);
+
+        let mut split = string.split(',');
+        assert_eq!(split.remainder(), Some(&string[..]));
+        split.next();
+        assert_eq!(split.remainder(), Some(&bc[..]));
+        split.next_back();
+        assert_eq!(split.remainder(), Some(&bc[..bc.len() - 2]));
+        split.next();
+        assert_eq!(split.remainder(), None);
+
+        let mut split = string.rsplit(',');
+        split.next();
+        assert_eq!(split.remainder(), Some(&string[..string.len() - 2]));
+
+        let mut split = string.splitn(2, ',');
+        split.next();
+        assert_eq!(split.remainder(), Some(&bc[..]));
+        split.next();
+        assert_eq!(split.remainder(), None);
+        let mut split = string.rsplitn(3, ',');
+        split.next();
+        split.next();
+        assert_eq!(split.remainder(), Some(OsStr::new("a")));
+        assert_eq!(string.splitn(0, ',').remainder(), None);
+
+        let mut split = OsStr::new("a,,").split_terminator(',');
+        assert_eq!(split.remainder(), Some(OsStr::new("a,,")));
+        split.next();
+        assert_eq!(split.remainder(), Some(OsStr::new(",")));
+        split.next();
+        assert_eq!(split.remainder(), None);
+        assert_eq!(split.next(), None);
+        let mut split = OsStr::new("a,").rsplit_terminator(',');
+        split.next();
+        assert_eq!(split.remainder(), None);
+        assert_eq!(OsStr::new("").split(',').remainder(), Some(OsStr::new("")));
+    }
+
+    #[test]
+    fn osstr_split_code_point_boundaries() {
+        let (start, end) = split_char();
+        let mut string = OsString::from("a💩");
+        string.push(&start);
+        string.push("b💩");
+        string.push(&end);
+        string.push(non_unicode_osstring());
+        string.push("💩");
+
+        let check = |pieces: Vec<&OsStr>| for piece in pieces {
+            assert_eq!(OsStr::from_encoded_bytes(piece.as_encoded_bytes()), Some(piece));
+        };
+        let non_unicode = non_unicode_osstring();
+        for pat in &[&start[..], &end[..], &non_unicode[..], OsStr::new("")] {
+            check(string.split(*pat).collect());
+            check(string.rsplit(*pat).collect());
+            check(string.rsplit_terminator(*pat).collect());
+            check(string.splitn(3, *pat).collect());
+            check(string.matches(*pat).collect());
+        }
+        check(string.split("").collect());
+        check(string.split('b').collect());
+
+        // Half of a surrogate pair never matches.
+        assert_eq!(string.split(&start[..]).count(), 2);
+        assert_eq!(string.split(&end[..]).count(), 2);
+    }
+
+    #[test]
+    fn osstr_split_bad_searcher() {
+        struct Inside;
+        struct InsideSearcher<'a>(&'a [u8], bool);
+
+        impl<'a> OsPattern<'a> for Inside {
+            type Searcher = InsideSearcher<'a>;
+            fn into_os_searcher(self, haystack: &'a [u8]) -> InsideSearcher<'a> {
+                InsideSearcher(haystack, false)
+            }
+        }
+
+        impl<'a> OsSearcher<'a> for InsideSearcher<'a> {
+            fn haystack(&self) -> &'a [u8] { self.0 }
+            fn next_match(&mut self) -> Option<(usize, usize)> {
+                if self.1 { return None; }
+                self.1 = true;
+                Some((1, 1))
+            }
+        }
+
+        // Splitting inside a code point is only possible on Unix.
+        let result = ::std::panic::catch_unwind(|| {
+            OsStr::new("é").split(Inside).count()
+        });
+        assert_eq!(result.is_err(), is_windows!());
+    }
+
     #[test]
     fn osstr_split_terminator() {
         assert!(OsStr::new("").split_terminator('a').next().is_none());
@@ -1484,6 +6332,49 @@ mod tests {
                    ["a", "Γ", "a", "Γ", "Γ"]);
     }
 
+    #[test]
+    fn osstr_iterator_size_hints() {
+        fn check<I: Iterator + Clone>(mut iter: I) {
+            loop {
+                let (low, high) = iter.size_hint();
+                let count = iter.clone().count();
+                assert!(low <= count && count <= high.unwrap());
+                if iter.next().is_none() { break; }
+            }
+            assert!(iter.next().is_none());
+        }
+
+        let mut string = non_unicode_osstring();
+        string.push("a,Γ,,b\n");
+        string.push(non_unicode_osstring());
+        string.push("\r\n c ");
+        for string in &[OsString::new(), OsString::from(",\n"), string] {
+            check(string.split(','));
+            check(string.split(","));
+            check(string.split(""));
+            check(string.rsplit(',').rev());
+            check(string.split_terminator(','));
+            check(string.rsplit_terminator(','));
+            check(string.splitn(2, ','));
+            check(string.splitn(10, ','));
+            check(string.rsplitn(2, ','));
+            check(string.matches(','));
+            check(string.rmatches(""));
+            check(string.split_whitespace());
+            check(string.lines().rev());
+            check(string.split_unicode());
+            check(string.utf8_chunks());
+            check(string.invalid_sections().rev());
+            check(string.to_str_chunks_lossy());
+        }
+
+        // Adapters that need the iterator traits
+        let string = OsStr::new("a,b,c");
+        assert_eq!(string.split(',').fuse().rev().collect::<Vec<_>>(), ["c", "b", "a"]);
+        assert_eq!(string.split(',').size_hint(), (1, Some(7)));
+        assert_eq!(string.splitn(2, ',').size_hint(), (1, Some(2)));
+    }
+
     #[test]
     fn osstr_matches_double_ended() {
         assert!(OsStr::new("").matches('a').next_back().is_none());
@@ -1493,13 +6384,13 @@ mod tests {
         string.push(non_unicode_osstring());
         string.push("aΓ");
         let mut matches = string.matches('Γ');
-        assert_eq!(matches.next(), Some("Γ"));
-        assert_eq!(matches.next_back(), Some("Γ"));
+        assert_eq!(matches.next(), Some(OsStr::new("Γ")));
+        assert_eq!(matches.next_back(), Some(OsStr::new("Γ")));
         assert_eq!(matches.next(), None);
 
         let mut matches = string.matches('Γ');
-        assert_eq!(matches.next_back(), Some("Γ"));
-        assert_eq!(matches.next(), Some("Γ"));
+        assert_eq!(matches.next_back(), Some(OsStr::new("Γ")));
+        assert_eq!(matches.next(), Some(OsStr::new("Γ")));
         assert_eq!(matches.next_back(), None);
     }
 
@@ -1519,6 +6410,108 @@ mod tests {
                    ["Γ", "Γ", "a", "Γ", "a"]);
     }
 
+    #[test]
+    fn osstr_split_os() {
+        let separator = non_unicode_osstring();
+        let mut string = OsString::from("a");
+        string.push(&separator);
+        string.push("b");
+        string.push(&separator);
+        string.push("c");
+        let mut rest = OsString::from("b");
+        rest.push(&separator);
+        rest.push("c");
+        let mut front = OsString::from("a");
+        front.push(&separator);
+        front.push("b");
+
+        assert_eq!(string.split_os(&separator).collect::<Vec<_>>(), ["a", "b", "c"]);
+        assert_eq!(string.rsplit_os(&separator).collect::<Vec<_>>(), ["c", "b", "a"]);
+        assert_eq!(string.splitn_os(2, &separator).collect::<Vec<_>>(),
+                   [OsStr::new("a"), &*rest]);
+        assert_eq!(string.rsplitn_os(2, &separator).collect::<Vec<_>>(),
+                   [OsStr::new("c"), &*front]);
+        assert_eq!(string.split_os("b").collect::<Vec<_>>().len(), 2);
+        assert_eq!(OsStr::new("a::b").split_os("::").collect::<Vec<_>>(), ["a", "b"]);
+        assert_eq!(OsStr::new("").split_os(&separator).collect::<Vec<_>>(), [""]);
+        assert_eq!(OsStr::new("a").splitn_os(0, &separator).count(), 0);
+
+        let (start, end) = split_char();
+        let mut full = start.to_owned();
+        full.push(&end);
+        if_unix_windows! {
+            unix {
+                assert_eq!(full.split_os(&start).count(), 2);
+            }
+            windows {
+                assert_eq!(full.split_os(&start).count(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn osstr_matches_os() {
+        let marker = non_unicode_osstring();
+        let mut string = OsString::from("a");
+        string.push(&marker);
+        string.push("b");
+        string.push(&marker);
+        assert_eq!(string.matches_os(&marker).collect::<Vec<_>>(), [&*marker; 2]);
+        assert_eq!(string.rmatches_os(&marker).count(), 2);
+        assert_eq!(string.matches_os("b").collect::<Vec<_>>(), ["b"]);
+        assert_eq!(OsStr::new("aaa").matches_os("aa").collect::<Vec<_>>(), ["aa"]);
+        assert_eq!(OsStr::new("aaa").rmatches_os("aa").collect::<Vec<_>>(), ["aa"]);
+        assert_eq!(OsStr::new("").matches_os(&marker).next(), None);
+        assert_eq!(OsStr::new("ab").matches_os("").count(), 3);
+
+        let (start, end) = split_char();
+        let mut full = start.to_owned();
+        full.push(&end);
+        if_unix_windows! {
+            unix {
+                assert_eq!(full.matches_os(&end).collect::<Vec<_>>(), [&*end]);
+            }
+            windows {
+                assert_eq!(full.matches_os(&end).next(), None);
+            }
+        }
+    }
+
+    #[test]
+    fn osstr_os_pattern() {
+        let separator = non_unicode_osstring();
+        let mut string = OsString::from("a");
+        string.push(&separator);
+        string.push("b");
+        string.push(&separator);
+        string.push("c");
+        let mut rest = OsString::from("b");
+        rest.push(&separator);
+        rest.push("c");
+
+        assert!(string.contains(&*separator));
+        assert!(string.contains(&separator));
+        assert!(!string.contains(OsStr::new("ac")));
+        assert!(string.starts_with(OsStr::new("a")));
+        assert!(!string.starts_with(&*separator));
+        assert!(string.ends_with(OsStr::new("c")));
+        assert_eq!(separator.strip_prefix(&separator), Some(OsStr::new("")));
+        assert_eq!(string.strip_suffix(OsStr::new("c")), Some(&string[..string.len() - 1]));
+        assert_eq!(string.count_matches(&separator), 2);
+        assert_eq!(string.split_once(&separator), Some((OsStr::new("a"), &*rest)));
+        assert_eq!(string.rsplit_once(&separator).map(|x| x.1), Some(OsStr::new("c")));
+        assert_eq!(string.replace_matches(&separator, "-"), OsString::from("a-b-c"));
+
+        assert_eq!(string.split(&separator).collect::<Vec<_>>(), ["a", "b", "c"]);
+        assert_eq!(string.rsplit(&separator).collect::<Vec<_>>(), ["c", "b", "a"]);
+        assert_eq!(string.splitn(2, &separator).collect::<Vec<_>>(), [OsStr::new("a"), &*rest]);
+        assert_eq!(string.rsplitn(2, &separator).next(), Some(OsStr::new("c")));
+        assert_eq!(string.matches(&separator).collect::<Vec<_>>(), [&*separator; 2]);
+        assert_eq!(string.rmatches(&separator).count(), 2);
+        assert_eq!(OsStr::new("ab").split(OsStr::new("")).collect::<Vec<_>>(),
+                   ["", "a", "b", ""]);
+    }
+
     #[test]
     fn osstr_rmatches_double_ended() {
         assert!(OsStr::new("").rmatches('a').next_back().is_none());
@@ -1528,13 +6521,13 @@ mod tests {
         string.push(non_unicode_osstring());
         string.push("aΓ");
         let mut rmatches = string.rmatches('Γ');
-        assert_eq!(rmatches.next(), Some("Γ"));
-        assert_eq!(rmatches.next_back(), Some("Γ"));
+        assert_eq!(rmatches.next(), Some(OsStr::new("Γ")));
+        assert_eq!(rmatches.next_back(), Some(OsStr::new("Γ")));
         assert_eq!(rmatches.next(), None);
 
         let mut rmatches = string.rmatches('Γ');
-        assert_eq!(rmatches.next_back(), Some("Γ"));
-        assert_eq!(rmatches.next(), Some("Γ"));
+        assert_eq!(rmatches.next_back(), Some(OsStr::new("Γ")));
+        assert_eq!(rmatches.next(), Some(OsStr::new("Γ")));
         assert_eq!(rmatches.next_back(), None);
     }
 
@@ -1580,6 +6573,28 @@ mod tests {
         assert_eq!(string.trim_right(), &string[..]);
     }
 
+    #[test]
+    fn osstr_trim_start_end() {
+        assert_eq!(OsStr::new("").trim_start(), OsStr::new(""));
+        assert_eq!(OsStr::new("").trim_end(), OsStr::new(""));
+        assert_eq!(OsStr::new("\n\u{2009}Γ\t ").trim_start(), OsStr::new("Γ\t "));
+        assert_eq!(OsStr::new("\n\u{2009}Γ\t ").trim_end(), OsStr::new("\n\u{2009}Γ"));
+
+        let mut string = OsString::from(" \t");
+        string.push(&non_unicode_osstring());
+        string.push(" x ");
+        let mut trimmed = non_unicode_osstring();
+        trimmed.push(" x ");
+        assert_eq!(string.trim_start(), &trimmed[..]);
+
+        let mut string = OsString::from(" x ");
+        string.push(&non_unicode_osstring());
+        string.push("\n");
+        let mut trimmed = OsString::from(" x ");
+        trimmed.push(&non_unicode_osstring());
+        assert_eq!(string.trim_end(), &trimmed[..]);
+    }
+
     #[test]
     fn osstr_trim_matches() {
         assert_eq!(OsStr::new("").trim_matches('x'), OsStr::new(""));
@@ -1644,12 +6659,69 @@ mod tests {
         assert_eq!(string.trim_right_matches('x'), &string[..]);
     }
 
+    #[test]
+    fn osstr_trim_start_end_matches() {
+        assert_eq!(OsStr::new("").trim_start_matches('x'), OsStr::new(""));
+        assert_eq!(OsStr::new("").trim_end_matches('x'), OsStr::new(""));
+        assert_eq!(OsStr::new("xyzxyzx").trim_start_matches(&['x', 'y'] as &[_]),
+                   OsStr::new("zxyzx"));
+        assert_eq!(OsStr::new("xyzxyzx").trim_end_matches(&['x', 'y'] as &[_]),
+                   OsStr::new("xyzxyz"));
+        assert_eq!(OsStr::new("12ab34").trim_start_matches(|c: char| c.is_digit(10)),
+                   OsStr::new("ab34"));
+        assert_eq!(OsStr::new("12ab34").trim_end_matches(|c: char| c.is_digit(10)),
+                   OsStr::new("12ab"));
+
+        // Matches never extend across non-Unicode data.
+        let mut string = OsString::from("xx");
+        string.push(&non_unicode_osstring());
+        string.push("xx");
+        let mut tail = non_unicode_osstring();
+        tail.push("xx");
+        let mut head = OsString::from("xx");
+        head.push(&non_unicode_osstring());
+        assert_eq!(string.trim_start_matches('x'), &tail[..]);
+        assert_eq!(string.trim_end_matches('x'), &head[..]);
+        assert_eq!(string.trim_start_matches("xx"), &tail[..]);
+        assert_eq!(string.trim_end_matches("xx"), &head[..]);
+    }
+
     #[test]
     fn osstring_compare_str() {
         assert_eq!(&unicode_osstring(), unicode_str());
         assert!(non_unicode_osstring() != *"");
     }
 
+    #[test]
+    fn osstring_compare_mixed_types() {
+        let owned = unicode_osstring();
+        let borrowed: &OsStr = &owned;
+        let string = String::from(unicode_str());
+        let cow: Cow<OsStr> = Cow::Borrowed(borrowed);
+        let non_unicode = non_unicode_osstring();
+
+        assert!(owned == *borrowed && *borrowed == owned);
+        assert!(owned == borrowed && borrowed == owned);
+        assert!(cow == *borrowed && *borrowed == cow);
+        assert!(cow == borrowed && borrowed == cow);
+        assert!(cow == owned && owned == cow);
+        assert!(owned == string && string == owned);
+        assert!(owned == unicode_str() && unicode_str() == owned);
+        assert!(*borrowed == string && string == *borrowed);
+        assert!(non_unicode != string && string != *non_unicode);
+
+        let small = OsString::from("a");
+        assert!(small < *borrowed && *borrowed > small);
+        assert!(small < borrowed && borrowed > small);
+        assert!(cow > *small && *small < cow);
+        assert!(cow > small && small < cow);
+        assert!(small < string && string > small);
+        assert!(small < unicode_str() && unicode_str() > small);
+        assert!(*small < string && string > *small);
+        assert!(*"a" <= *small && *"" < *small && *"b" > *small);
+        assert!(*"a" <= small && *"b" > small);
+    }
+
     #[test]
     fn osstring_concat() {
         let mut string = non_unicode_osstring();
@@ -1674,3 +6746,192 @@ mod tests {
     }
 
 }
+
+/// Checks that `OsStr` methods agree with the `str` methods of the
+/// same names on valid UTF-8.
+#[cfg(all(test, feature = "arbitrary"))]
+mod differential_tests {
+    use std::prelude::v1::*;
+    use quickcheck::quickcheck;
+    use super::*;
+
+    fn strs<'a, I>(iter: I) -> Vec<&'a str> where I: Iterator<Item=&'a OsStr> {
+        iter.map(|s| s.to_str().unwrap()).collect()
+    }
+
+    // `s` with `sep` inserted around every code unit, as `OsStr::replace`
+    // does for an empty needle.
+    fn between_code_units(s: &str, sep: char) -> OsString {
+        if_unix_windows! {
+            unix {
+                use unix::OsStringExt;
+                let mut bytes = vec![sep as u8];
+                for byte in s.bytes() {
+                    bytes.push(byte);
+                    bytes.push(sep as u8);
+                }
+                OsString::from_vec(bytes)
+            }
+            windows {
+                use windows::OsStringExt;
+                let mut units = vec![sep as u16];
+                for unit in s.encode_utf16() {
+                    units.push(unit);
+                    units.push(sep as u16);
+                }
+                OsString::from_wide(&units)
+            }
+        }
+    }
+
+    // Returns up to `len % 3` characters of `s` starting at character
+    // `start`, so that patterns usually occur in `s`.
+    fn pattern(s: &str, start: usize, len: usize) -> String {
+        let count = s.chars().count();
+        s.chars().skip(start % (count + 1)).take(len % 3).collect()
+    }
+
+    #[test]
+    fn conversions() {
+        fn prop(s: String) -> bool {
+            let os = OsStr::new(&s);
+            os.to_str() == Some(&s[..]) &&
+                os.to_string_lossy() == s &&
+                os.to_os_string().into_string() == Ok(s.clone()) &&
+                os.len() == s.len() &&
+                os.is_empty() == s.is_empty() &&
+                os.chars_lossy().eq(s.chars()) &&
+                os.to_lowercase() == OsString::from(s.to_lowercase()) &&
+                os.to_uppercase() == OsString::from(s.to_uppercase())
+        }
+        quickcheck(prop as fn(String) -> bool);
+    }
+
+    #[test]
+    fn indexing() {
+        fn prop(s: String, index: usize) -> bool {
+            let os = OsStr::new(&s);
+            let index = index % (s.len() + 1);
+            if s.is_char_boundary(index) {
+                let (start, end) = s.split_at(index);
+                os.split_at(index) == (OsStr::new(start), OsStr::new(end)) &&
+                    os.get(0..index) == Some(OsStr::new(start))
+            } else {
+                // Every index is a code point boundary on Unix.
+                os.get(0..index).is_some() == !is_windows!()
+            }
+        }
+        quickcheck(prop as fn(String, usize) -> bool);
+    }
+
+    #[test]
+    fn whitespace() {
+        fn prop(parts: Vec<String>, separators: Vec<u8>) -> bool {
+            let mut s = String::new();
+            for (i, part) in parts.iter().enumerate() {
+                s.push_str(part);
+                let separator = separators.get(i).cloned().unwrap_or(0) % 4;
+                s.push_str([" ", "\n", "\r\n", "\t "][separator as usize]);
+            }
+            let os = OsStr::new(&s);
+            os.trim() == OsStr::new(s.trim()) &&
+                os.trim_left() == OsStr::new(s.trim_left()) &&
+                os.trim_right() == OsStr::new(s.trim_right()) &&
+                strs(os.lines()) == s.lines().collect::<Vec<_>>() &&
+                strs(os.split_whitespace()) == s.split_whitespace().collect::<Vec<_>>()
+        }
+        quickcheck(prop as fn(Vec<String>, Vec<u8>) -> bool);
+    }
+
+    #[test]
+    fn char_patterns() {
+        fn prop(s: String, start: usize, count: usize) -> bool {
+            let c = pattern(&s, start, 1).chars().next().unwrap_or('a');
+            let os = OsStr::new(&s);
+            os.contains(c) == s.contains(c) &&
+                os.starts_with(c) == s.starts_with(c) &&
+                os.ends_with(c) == s.ends_with(c) &&
+                os.find(c) == s.find(c) &&
+                os.rfind(c) == s.rfind(c) &&
+                os.count_matches(c) == s.matches(c).count() &&
+                strs(os.split(c)) == s.split(c).collect::<Vec<_>>() &&
+                strs(os.split(c).rev()) == s.split(c).rev().collect::<Vec<_>>() &&
+                strs(os.rsplit(c)) == s.rsplit(c).collect::<Vec<_>>() &&
+                strs(os.split_terminator(c)) == s.split_terminator(c).collect::<Vec<_>>() &&
+                strs(os.rsplit_terminator(c)) == s.rsplit_terminator(c).collect::<Vec<_>>() &&
+                strs(os.splitn(count % 4, c)) == s.splitn(count % 4, c).collect::<Vec<_>>() &&
+                strs(os.rsplitn(count % 4, c)) == s.rsplitn(count % 4, c).collect::<Vec<_>>() &&
+                strs(os.matches(c)) == s.matches(c).collect::<Vec<_>>() &&
+                strs(os.rmatches(c)) == s.rmatches(c).collect::<Vec<_>>() &&
+                os.trim_matches(c) == OsStr::new(s.trim_matches(c)) &&
+                os.trim_left_matches(c) == OsStr::new(s.trim_left_matches(c)) &&
+                os.trim_right_matches(c) == OsStr::new(s.trim_right_matches(c))
+        }
+        quickcheck(prop as fn(String, usize, usize) -> bool);
+    }
+
+    #[test]
+    fn str_patterns() {
+        fn prop(s: String, start: usize, len: usize, count: usize) -> bool {
+            let pat = pattern(&s, start, len);
+            let pat = &pat[..];
+            let os = OsStr::new(&s);
+            let stripped = if s.starts_with(pat) { Some(&s[pat.len()..]) } else { None };
+            os.contains(pat) == s.contains(pat) &&
+                os.starts_with(pat) == s.starts_with(pat) &&
+                os.ends_with(pat) == s.ends_with(pat) &&
+                os.strip_prefix(pat) == stripped.map(OsStr::new) &&
+                os.find(pat) == s.find(pat) &&
+                os.rfind(pat) == s.rfind(pat) &&
+                strs(os.split(pat)) == s.split(pat).collect::<Vec<_>>() &&
+                strs(os.rsplit(pat)) == s.rsplit(pat).collect::<Vec<_>>() &&
+                strs(os.split_terminator(pat)) == s.split_terminator(pat).collect::<Vec<_>>() &&
+                strs(os.splitn(count % 4, pat)) == s.splitn(count % 4, pat).collect::<Vec<_>>() &&
+                strs(os.rsplitn(count % 4, pat)) == s.rsplitn(count % 4, pat).collect::<Vec<_>>() &&
+                strs(os.matches(pat)) == s.matches(pat).collect::<Vec<_>>() &&
+                os.replace_matches(pat, "-") == OsString::from(s.replace(pat, "-"))
+        }
+        quickcheck(prop as fn(String, usize, usize, usize) -> bool);
+    }
+
+    #[test]
+    fn os_str_patterns() {
+        fn prop(s: String, start: usize, len: usize) -> bool {
+            let pat = pattern(&s, start, len);
+            let (os, os_pat) = (OsStr::new(&s), OsStr::new(&pat));
+            let pat = &pat[..];
+            let replaced = if pat.is_empty() {
+                between_code_units(&s, '-')
+            } else {
+                OsString::from(s.replace(pat, "-"))
+            };
+            let indices = s.match_indices(pat).map(|(i, m)| (i, OsStr::new(m))).collect::<Vec<_>>();
+            let rindices = s.rmatch_indices(pat).map(|(i, m)| (i, OsStr::new(m)))
+                .collect::<Vec<_>>();
+            os.contains_os(os_pat) == s.contains(pat) &&
+                os.starts_with_os(os_pat) == s.starts_with(pat) &&
+                os.ends_with_os(os_pat) == s.ends_with(pat) &&
+                os.find_os(os_pat) == s.find(pat) &&
+                os.rfind_os(os_pat) == s.rfind(pat) &&
+                os.match_indices_os(os_pat).collect::<Vec<_>>() == indices &&
+                os.rmatch_indices_os(os_pat).collect::<Vec<_>>() == rindices &&
+                os.count_matches_os(os_pat) == s.matches(pat).count() &&
+                strs(os.matches(os_pat)) == s.matches(pat).collect::<Vec<_>>() &&
+                strs(os.rmatches(os_pat)) == s.rmatches(pat).collect::<Vec<_>>() &&
+                strs(os.split(os_pat)) == s.split(pat).collect::<Vec<_>>() &&
+                strs(os.rsplit(os_pat)) == s.rsplit(pat).collect::<Vec<_>>() &&
+                os.replace(os_pat, "-") == replaced
+        }
+        quickcheck(prop as fn(String, usize, usize) -> bool);
+    }
+
+    #[test]
+    fn arbitrary_unicode() {
+        fn prop(os: OsString) -> bool {
+            let valid = os.to_str().is_some();
+            valid == (&*os.to_string_lossy() == &os[..]) &&
+                valid == os.clone().into_string().is_ok()
+        }
+        quickcheck(prop as fn(OsString) -> bool);
+    }
+}
@@ -0,0 +1,239 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Searching for many `OsStr` needles at once.
+//!
+//! An `OsStrSet` compiles its needles into an Aho-Corasick automaton,
+//! so a haystack is scanned once no matter how many needles there
+//! are.  Needles are matched exactly against the encoded data, as by
+//! `OsStr::find_os`.  As with any Aho-Corasick search, the haystack
+//! is scanned forward and the first match to end is reported, so a
+//! needle can hide a longer one that starts earlier.  An empty needle
+//! matches at every code point boundary and hides all the others.
+
+use std::borrow::ToOwned;
+use std::vec::Vec;
+
+use aho_corasick::{self, AcAutomaton, Automaton};
+
+use os_pattern::is_empty_match_boundary;
+use os_str::OsStr;
+
+/// A set of `OsStr` needles that can be searched for together.
+#[derive(Clone, Debug)]
+pub struct OsStrSet {
+    automaton: AcAutomaton<Vec<u8>>,
+    len: usize,
+    // The first empty needle, if any
+    empty: Option<usize>,
+}
+
+impl OsStrSet {
+    /// Builds a set from `needles`.  Needles are numbered in order,
+    /// starting from zero.
+    pub fn new<I, S>(needles: I) -> OsStrSet
+    where I: IntoIterator<Item = S>, S: AsRef<OsStr> {
+        let needles = needles.into_iter()
+            .map(|n| n.as_ref().as_encoded_bytes().to_owned())
+            .collect::<Vec<_>>();
+        let len = needles.len();
+        let empty = needles.iter().position(|n| n.is_empty());
+        // The automaton is never searched if there is an empty
+        // needle, and aho-corasick does not handle empty patterns.
+        let needles = if empty.is_some() { Vec::new() } else { needles };
+        OsStrSet {
+            automaton: AcAutomaton::new(needles),
+            len: len,
+            empty: empty,
+        }
+    }
+
+    /// Returns the number of needles in the set.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if any needle occurs in `haystack`.
+    pub fn is_match(&self, haystack: &OsStr) -> bool {
+        self.find_iter(haystack).next().is_some()
+    }
+
+    /// An iterator over the non-overlapping matches of the needles in
+    /// `haystack`.
+    pub fn find_iter<'s, 'h>(&'s self, haystack: &'h OsStr) -> SetMatches<'s, 'h> {
+        SetMatches {
+            haystack: haystack,
+            matches: self.automaton.find(haystack.as_encoded_bytes()),
+            empty: self.empty.map(|needle| (needle, 0)),
+        }
+    }
+
+    /// An iterator over the substrings of `haystack` separated by
+    /// matches of any of the needles.
+    pub fn split_by_any<'s, 'h>(&'s self, haystack: &'h OsStr) -> SetSplit<'s, 'h> {
+        SetSplit {
+            matches: self.find_iter(haystack),
+            position: Some(0),
+        }
+    }
+}
+
+/// A match found by an `OsStrSet`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SetMatch<'h> {
+    /// The index of the needle that matched.
+    pub needle: usize,
+    /// The byte index of the start of the match.
+    pub start: usize,
+    /// The matched substring.
+    pub matched: &'h OsStr,
+}
+
+pub struct SetMatches<'s, 'h> {
+    haystack: &'h OsStr,
+    matches: aho_corasick::Matches<'s, 'h, [u8], Vec<u8>, AcAutomaton<Vec<u8>>>,
+    // The empty needle and the next index to try it at
+    empty: Option<(usize, usize)>,
+}
+
+impl<'s, 'h> Iterator for SetMatches<'s, 'h> {
+    type Item = SetMatch<'h>;
+    fn next(&mut self) -> Option<SetMatch<'h>> {
+        if let Some((needle, ref mut position)) = self.empty {
+            let bytes = self.haystack.as_encoded_bytes();
+            while *position <= bytes.len() {
+                let start = *position;
+                *position += 1;
+                if is_empty_match_boundary(bytes, start) {
+                    return Some(SetMatch {
+                        needle: needle,
+                        start: start,
+                        matched: &self.haystack[start..start],
+                    });
+                }
+            }
+            return None;
+        }
+        // Needles start and end on code point boundaries, so every
+        // match does too.
+        self.matches.next().map(|m| SetMatch {
+            needle: m.pati,
+            start: m.start,
+            matched: &self.haystack[m.start..m.end],
+        })
+    }
+}
+
+pub struct SetSplit<'s, 'h> {
+    matches: SetMatches<'s, 'h>,
+    // The start of the next substring, or None when done
+    position: Option<usize>,
+}
+
+impl<'s, 'h> Iterator for SetSplit<'s, 'h> {
+    type Item = &'h OsStr;
+    fn next(&mut self) -> Option<&'h OsStr> {
+        let position = match self.position {
+            Some(position) => position,
+            None => return None,
+        };
+        let haystack = self.matches.haystack;
+        match self.matches.next() {
+            Some(m) => {
+                self.position = Some(m.start + m.matched.len());
+                Some(&haystack[position..m.start])
+            }
+            None => {
+                self.position = None;
+                Some(&haystack[position..])
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::prelude::v1::*;
+    use os_str::{OsStr, OsString};
+    use super::{OsStrSet, SetMatch};
+
+    #[test]
+    fn set_find_iter() {
+        let set = OsStrSet::new(&["foo", "bar", "foobar"]);
+        assert_eq!(set.len(), 3);
+        let haystack = OsStr::new("xfoobar-bar");
+        assert_eq!(set.find_iter(haystack).collect::<Vec<_>>(),
+                   [SetMatch { needle: 0, start: 1, matched: OsStr::new("foo") },
+                    SetMatch { needle: 1, start: 4, matched: OsStr::new("bar") },
+                    SetMatch { needle: 1, start: 8, matched: OsStr::new("bar") }]);
+        assert!(set.is_match(haystack));
+        assert!(!set.is_match(OsStr::new("fo ba")));
+        assert!(!set.is_match(OsStr::new("")));
+
+        let empty = OsStrSet::new(Vec::<&OsStr>::new());
+        assert_eq!(empty.len(), 0);
+        assert!(!empty.is_match(OsStr::new("abc")));
+    }
+
+    #[test]
+    fn set_split_by_any() {
+        let set = OsStrSet::new(&[",", ";;"]);
+        assert_eq!(set.split_by_any(OsStr::new("a,b;;c;d")).collect::<Vec<_>>(),
+                   ["a", "b", "c;d"]);
+        assert_eq!(set.split_by_any(OsStr::new("")).collect::<Vec<_>>(), [""]);
+        assert_eq!(set.split_by_any(OsStr::new(",")).collect::<Vec<_>>(), ["", ""]);
+    }
+
+    #[test]
+    fn set_empty_needle() {
+        let set = OsStrSet::new(&[""]);
+        assert_eq!(set.find_iter(OsStr::new("ab")).map(|m| m.start).collect::<Vec<_>>(),
+                   [0, 1, 2]);
+        // Never inside a character
+        let chars = OsStr::new("é");
+        assert!(set.find_iter(chars).all(|m| chars.get(m.start..m.start).is_some()));
+        // Hides the other needles
+        let set = OsStrSet::new(&["a", ""]);
+        assert_eq!(set.find_iter(OsStr::new("a")).map(|m| (m.needle, m.start))
+                       .collect::<Vec<_>>(),
+                   [(1, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn set_earliest_end() {
+        let set = OsStrSet::new(&["abc", "b"]);
+        assert_eq!(set.find_iter(OsStr::new("abcabc")).map(|m| (m.needle, m.start))
+                       .collect::<Vec<_>>(),
+                   [(1, 1), (1, 4)]);
+    }
+
+    #[test]
+    fn set_non_unicode() {
+        let mut marker = OsString::from("<");
+        marker.push(&non_unicode());
+        let mut haystack = OsString::from("a");
+        haystack.push(&marker);
+        haystack.push("b<c");
+        let set = OsStrSet::new(&[&marker[..], OsStr::new("c")]);
+        assert_eq!(set.find_iter(&haystack).map(|m| (m.needle, m.start)).collect::<Vec<_>>(),
+                   [(0, 1), (1, haystack.len() - 1)]);
+        assert_eq!(set.split_by_any(&haystack).collect::<Vec<_>>(), ["a", "b<", ""]);
+    }
+
+    fn non_unicode() -> OsString {
+        let bytes: &[u8] = if cfg!(any(windows, feature = "force-wtf8")) {
+            b"\xED\xA0\x80"
+        } else {
+            b"\xFF"
+        };
+        OsStr::from_encoded_bytes(bytes).unwrap().to_owned()
+    }
+}
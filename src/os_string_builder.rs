@@ -0,0 +1,164 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Building an `OsString` from many pieces with a single allocation.
+//!
+//! Pushing pieces onto an `OsString` one at a time reallocates
+//! whenever it runs out of room.  An `OsStringBuilder` instead borrows
+//! the pieces and adds up their lengths, so `finish` can allocate the
+//! result at its final size.
+//!
+//! ```
+//! use osstring_prototype::OsStr;
+//! use osstring_prototype::os_string_builder::{OsStringBuilder, join_with};
+//!
+//! let string = OsStringBuilder::with_separator("/").push("usr").push("lib").finish();
+//! assert_eq!(string, "usr/lib");
+//! assert_eq!(join_with(&["a", "b", "c"], OsStr::new(", ")), "a, b, c");
+//! ```
+
+use std::vec::Vec;
+
+use os_str::{OsStr, OsString};
+
+/// Collects borrowed pieces of an `OsString`, optionally separated,
+/// and concatenates them with one allocation.
+#[derive(Clone, Debug, Default)]
+pub struct OsStringBuilder<'a> {
+    pieces: Vec<&'a OsStr>,
+    separator: Option<&'a OsStr>,
+    len: usize,
+}
+
+impl<'a> OsStringBuilder<'a> {
+    /// Creates a builder that concatenates its pieces directly.
+    pub fn new() -> OsStringBuilder<'a> {
+        OsStringBuilder { pieces: Vec::new(), separator: None, len: 0 }
+    }
+
+    /// Creates a builder that places `separator` between each pair of
+    /// pieces.
+    pub fn with_separator<S: AsRef<OsStr> + ?Sized>(separator: &'a S) -> OsStringBuilder<'a> {
+        OsStringBuilder { pieces: Vec::new(), separator: Some(separator.as_ref()), len: 0 }
+    }
+
+    /// Appends a piece.
+    pub fn push<S: AsRef<OsStr> + ?Sized>(&mut self, piece: &'a S) -> &mut OsStringBuilder<'a> {
+        let piece = piece.as_ref();
+        if let Some(separator) = self.separator {
+            if !self.pieces.is_empty() {
+                self.len += separator.len();
+            }
+        }
+        self.len += piece.len();
+        self.pieces.push(piece);
+        self
+    }
+
+    /// Returns the number of pieces pushed so far.
+    pub fn pieces(&self) -> usize {
+        self.pieces.len()
+    }
+
+    /// Returns the length in bytes of the string `finish` will build.
+    ///
+    /// On Windows this may be a slight overestimate, since pieces
+    /// ending and starting with unpaired surrogates are joined.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if `finish` will build an empty string.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Builds the string, allocating it once at its final size.
+    pub fn finish(&self) -> OsString {
+        let mut result = OsString::with_capacity(self.len);
+        for (i, piece) in self.pieces.iter().enumerate() {
+            if let Some(separator) = self.separator {
+                if i > 0 {
+                    result.push(separator);
+                }
+            }
+            result.push(piece);
+        }
+        result
+    }
+}
+
+impl<'a, S: AsRef<OsStr> + ?Sized + 'a> Extend<&'a S> for OsStringBuilder<'a> {
+    fn extend<I: IntoIterator<Item = &'a S>>(&mut self, pieces: I) {
+        for piece in pieces {
+            self.push(piece);
+        }
+    }
+}
+
+/// Joins the pieces produced by an iterator, placing `separator`
+/// between each pair, with one allocation for the result.
+pub fn join_with<'a, I, S>(pieces: I, separator: &OsStr) -> OsString
+where I: IntoIterator<Item = &'a S>, S: AsRef<OsStr> + ?Sized + 'a {
+    let mut builder = OsStringBuilder::with_separator(separator);
+    for piece in pieces {
+        builder.push(piece);
+    }
+    builder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::prelude::v1::*;
+    use super::*;
+    use os_str::{OsStr, OsString};
+
+    #[test]
+    fn concatenate() {
+        let builder = OsStringBuilder::new();
+        assert!(builder.is_empty());
+        assert_eq!(builder.finish(), OsString::new());
+
+        let a = OsString::from("ab");
+        let string = OsStringBuilder::new().push(&a).push("").push(OsStr::new("cé")).finish();
+        assert_eq!(string, "abcé");
+    }
+
+    #[test]
+    fn separator() {
+        let mut builder = OsStringBuilder::with_separator(", ");
+        assert_eq!(builder.finish(), OsString::new());
+        builder.push("a");
+        assert_eq!(builder.len(), 1);
+        assert_eq!(builder.finish(), "a");
+        builder.push("b").push("");
+        assert_eq!((builder.pieces(), builder.len()), (3, 6));
+        assert_eq!(builder.finish(), "a, b, ");
+    }
+
+    #[test]
+    fn single_allocation() {
+        let mut builder = OsStringBuilder::with_separator("::");
+        builder.extend(&["std", "ffi", "OsString"]);
+        let string = builder.finish();
+        assert_eq!(string, "std::ffi::OsString");
+        assert_eq!(string.len(), builder.len());
+        assert_eq!(string.capacity(), builder.len());
+    }
+
+    #[test]
+    fn join_with() {
+        let pieces = vec![OsString::from("a"), OsString::from("b")];
+        assert_eq!(super::join_with(&pieces, OsStr::new("/")), "a/b");
+        assert_eq!(super::join_with(pieces.iter().rev(), OsStr::new("")), "ba");
+        let empty: [&str; 0] = [];
+        assert_eq!(super::join_with(&empty, OsStr::new("/")), "");
+    }
+}
@@ -0,0 +1,323 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! An analogue of `fmt::Write` for buffers that can hold non-Unicode
+//! data.
+
+use os_str::{OsStr, OsString};
+use wtf8::encode_utf8_raw;
+
+use std::cell::RefCell;
+use std::fmt;
+use std::str;
+
+/// Writes formatted data into an OS string buffer.
+///
+/// This is the same as `fmt::Write`, except that it can additionally
+/// accept `&OsStr` data.  Use the `os_write!` macro to write
+/// formatted output.
+pub trait OsWrite {
+    /// Writes an `OsStr` slice into this writer.
+    fn write_os_str(&mut self, s: &OsStr) -> fmt::Result;
+
+    /// Writes a string slice into this writer.
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.write_os_str(OsStr::new(s))
+    }
+
+    /// Writes a `char` into this writer.
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        let mut bytes = [0; 4];
+        let len = encode_utf8_raw(c as u32, &mut bytes).unwrap();
+        self.write_str(unsafe { str::from_utf8_unchecked(&bytes[..len]) })
+    }
+
+    /// Glue for usage of the `os_write!` macro with implementors of
+    /// this trait.
+    fn write_fmt(&mut self, args: fmt::Arguments) -> fmt::Result {
+        // Create a shim which translates a fmt::Write to an OsWrite
+        struct Adapter<'a, T: ?Sized + 'a>(&'a mut T);
+
+        impl<'a, T: OsWrite + ?Sized> fmt::Write for Adapter<'a, T> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.0.write_str(s)
+            }
+        }
+
+        fmt::write(&mut Adapter(self), args)
+    }
+}
+
+impl<'a, W: OsWrite + ?Sized> OsWrite for &'a mut W {
+    fn write_os_str(&mut self, s: &OsStr) -> fmt::Result {
+        (**self).write_os_str(s)
+    }
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        (**self).write_str(s)
+    }
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        (**self).write_char(c)
+    }
+    fn write_fmt(&mut self, args: fmt::Arguments) -> fmt::Result {
+        (**self).write_fmt(args)
+    }
+}
+
+impl OsWrite for OsString {
+    fn write_os_str(&mut self, s: &OsStr) -> fmt::Result {
+        self.push(s);
+        Ok(())
+    }
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.push_char(c);
+        Ok(())
+    }
+}
+
+/// An argument to `os_format!`.
+///
+/// This is implemented for the OS string types, which are written
+/// losslessly, and for all `Display` types.
+pub trait OsFormatArg {
+    /// Formats `self` into `f`, or appends it directly to `out`, which
+    /// holds everything formatted before it.
+    fn fmt_os(&self, f: &mut fmt::Formatter, out: &FormatBuffer) -> fmt::Result;
+}
+
+impl<'a> OsFormatArg for &'a OsStr {
+    fn fmt_os(&self, f: &mut fmt::Formatter, out: &FormatBuffer) -> fmt::Result {
+        match self.to_str() {
+            Some(s) => f.pad(s),
+            None => {
+                out.buf.borrow_mut().push(self);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl OsFormatArg for OsString {
+    fn fmt_os(&self, f: &mut fmt::Formatter, out: &FormatBuffer) -> fmt::Result {
+        (&self[..]).fmt_os(f, out)
+    }
+}
+
+impl<'a> OsFormatArg for &'a OsString {
+    fn fmt_os(&self, f: &mut fmt::Formatter, out: &FormatBuffer) -> fmt::Result {
+        (&self[..]).fmt_os(f, out)
+    }
+}
+
+impl<T: fmt::Display + ?Sized> OsFormatArg for T {
+    fn fmt_os(&self, f: &mut fmt::Formatter, _: &FormatBuffer) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// The buffer `os_format!` formats into.
+///
+/// The formatted text and the lossless OS string arguments are both
+/// appended to it, in order.
+#[doc(hidden)]
+pub struct FormatBuffer {
+    buf: RefCell<OsString>,
+}
+
+impl FormatBuffer {
+    pub fn new() -> FormatBuffer {
+        FormatBuffer { buf: RefCell::new(OsString::new()) }
+    }
+
+    pub fn write_fmt(&self, args: fmt::Arguments) {
+        struct Adapter<'a>(&'a RefCell<OsString>);
+
+        impl<'a> fmt::Write for Adapter<'a> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.0.borrow_mut().push_str(s);
+                Ok(())
+            }
+        }
+
+        fmt::write(&mut Adapter(&self.buf), args)
+            .expect("a formatting trait implementation returned an error")
+    }
+
+    pub fn into_os_string(self) -> OsString {
+        self.buf.into_inner()
+    }
+}
+
+/// An `os_format!` argument, which passes OS strings through to the
+/// `FormatBuffer` instead of the formatter.
+#[doc(hidden)]
+pub struct FormatArg<'a> {
+    arg: &'a OsFormatArg,
+    out: &'a FormatBuffer,
+}
+
+impl<'a> FormatArg<'a> {
+    pub fn new(arg: &'a OsFormatArg, out: &'a FormatBuffer) -> FormatArg<'a> {
+        FormatArg { arg: arg, out: out }
+    }
+}
+
+impl<'a> fmt::Display for FormatArg<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.arg.fmt_os(f, self.out)
+    }
+}
+
+/// Creates an `OsString` from a format string, like `format!`.
+///
+/// Arguments may be `OsStr`s or `OsString`s, which are inserted
+/// without loss, or any `Display` type.  The format string is checked
+/// by `format_args!`, so mismatched arguments fail to compile, and
+/// positional and named arguments, width and precision work as for
+/// `format!`.  Only `{}` (`Display`) formatting is supported, and
+/// non-Unicode OS strings are inserted as they are, ignoring width and
+/// precision.
+///
+/// # Example
+///
+/// ```
+/// #[macro_use] extern crate osstring_prototype;
+/// use osstring_prototype::{OsStr, OsString};
+///
+/// # fn main() {
+/// let dir = OsStr::new("/tmp");
+/// let string = os_format!("--out={}/{:03}.log", dir, 7);
+/// assert_eq!(string, OsString::from("--out=/tmp/007.log"));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! os_format {
+    // Wraps the arguments one at a time, so that `name = value` is not
+    // parsed as an assignment.
+    (@write $out:ident, $fmt:expr, [$($done:tt)*]) => (
+        $out.write_fmt(format_args!($fmt $($done)*))
+    );
+    (@write $out:ident, $fmt:expr, [$($done:tt)*] ,) => (
+        os_format!(@write $out, $fmt, [$($done)*])
+    );
+    (@write $out:ident, $fmt:expr, [$($done:tt)*] , $name:ident = $arg:expr , $($rest:tt)*) => (
+        os_format!(@write $out, $fmt,
+                   [$($done)* , $name = $crate::os_write::FormatArg::new(&$arg, &$out)]
+                   , $($rest)*)
+    );
+    (@write $out:ident, $fmt:expr, [$($done:tt)*] , $name:ident = $arg:expr) => (
+        os_format!(@write $out, $fmt, [$($done)*] , $name = $arg ,)
+    );
+    (@write $out:ident, $fmt:expr, [$($done:tt)*] , $arg:expr , $($rest:tt)*) => (
+        os_format!(@write $out, $fmt,
+                   [$($done)* , $crate::os_write::FormatArg::new(&$arg, &$out)]
+                   , $($rest)*)
+    );
+    (@write $out:ident, $fmt:expr, [$($done:tt)*] , $arg:expr) => (
+        os_format!(@write $out, $fmt, [$($done)*] , $arg ,)
+    );
+    ($fmt:expr) => (os_format!($fmt,));
+    ($fmt:expr, $($args:tt)*) => ({
+        let out = $crate::os_write::FormatBuffer::new();
+        os_format!(@write out, $fmt, [] , $($args)*);
+        out.into_os_string()
+    });
+}
+
+/// Writes formatted data into an `OsWrite`, like `write!`.
+///
+/// # Example
+///
+/// ```
+/// #[macro_use] extern crate osstring_prototype;
+/// use osstring_prototype::{OsStr, OsString};
+/// use osstring_prototype::os_write::OsWrite;
+///
+/// # fn main() {
+/// let mut string = OsString::new();
+/// string.write_os_str(OsStr::new("--jobs=")).unwrap();
+/// os_write!(string, "{}", 4).unwrap();
+/// assert_eq!(string, OsString::from("--jobs=4"));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! os_write {
+    ($dst:expr, $($arg:tt)*) => ($dst.write_fmt(format_args!($($arg)*)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::prelude::v1::*;
+    use os_str::{OsStr, OsString};
+    use super::OsWrite;
+
+    #[test]
+    fn os_write() {
+        let mut string = OsString::new();
+        string.write_str("a").unwrap();
+        string.write_char('é').unwrap();
+        string.write_os_str(OsStr::new("b")).unwrap();
+        os_write!(string, "{}-{:?}", 1, "x").unwrap();
+        assert_eq!(string, OsString::from("aéb1-\"x\""));
+
+        {
+            let writer = &mut string;
+            os_write!(writer, "{}", 'c').unwrap();
+        }
+        assert_eq!(string, OsString::from("aéb1-\"x\"c"));
+    }
+
+    #[test]
+    fn os_format() {
+        assert_eq!(os_format!(""), OsString::new());
+        assert_eq!(os_format!("{{a}}"), OsString::from("{a}"));
+        assert_eq!(os_format!("{}é{}", 1, "b"), OsString::from("1éb"));
+
+        let string = OsString::from("x");
+        let slice = OsStr::new("y");
+        assert_eq!(os_format!("{}{}{}", string, &string, slice), OsString::from("xxy"));
+
+        // An unpaired surrogate, which is non-Unicode on all platforms
+        let non_unicode = OsStr::from_encoded_bytes(b"\xED\xA0\x80").unwrap();
+        assert!(non_unicode.to_str().is_none());
+        let mut expected = OsString::from("a");
+        expected.push(&non_unicode);
+        expected.push("b");
+        assert_eq!(os_format!("a{}b", non_unicode), expected);
+    }
+
+    #[test]
+    fn os_format_arguments() {
+        let name = OsStr::new("ab");
+        assert_eq!(os_format!("x",), OsString::from("x"));
+        assert_eq!(os_format!("{}", name,), OsString::from("ab"));
+        assert_eq!(os_format!("{}-{}", 1, name,), OsString::from("1-ab"));
+        assert_eq!(os_format!("{n}", n = name), OsString::from("ab"));
+        assert_eq!(os_format!("{}{n}{n}", 1 + 1, n = name,), OsString::from("2abab"));
+        assert_eq!(os_format!("{a:>3}|{b}", a = 7, b = name), OsString::from("  7|ab"));
+    }
+
+    #[test]
+    fn os_format_specs() {
+        let name = OsStr::new("ab");
+        assert_eq!(os_format!("{1}-{0}", 1, name), OsString::from("ab-1"));
+        assert_eq!(os_format!("[{:>4}|{:<3}|{:.1}]", name, 7, "xyz"),
+                   OsString::from("[  ab|7  |x]"));
+
+        let non_unicode = OsStr::from_encoded_bytes(b"\xED\xA0\x80").unwrap();
+        let mut expected = OsString::from("[");
+        expected.push(&non_unicode);
+        expected.push("]");
+        assert_eq!(os_format!("[{:>4}]", non_unicode), expected);
+    }
+}
@@ -0,0 +1,107 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A lossless byte encoding for OS strings that can be stored or
+//! sent to another machine.
+//!
+//! An encoded string (see `OsStr::to_portable_bytes` and
+//! `OsString::from_portable_bytes`) is a single tag byte followed by
+//! a payload:
+//!
+//! | Tag    | Encoding            | Payload                         |
+//! |--------|---------------------|---------------------------------|
+//! | `0x00` | `Encoding::Unicode` | UTF-8                           |
+//! | `0x01` | `Encoding::Bytes`   | arbitrary bytes (Unix)          |
+//! | `0x02` | `Encoding::Wtf8`    | well-formed WTF-8 (Windows)     |
+//!
+//! Strings that are valid Unicode are always tagged `Unicode` and can
+//! be decoded on every platform.  Other strings are tagged with the
+//! native encoding of the platform that produced them, and can only
+//! be decoded on a platform with the same encoding.
+
+#[cfg(feature = "std")]
+use std::error;
+use std::fmt;
+
+/// The encoding of the payload of a portable OS string.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// UTF-8, valid on every platform.
+    Unicode,
+    /// Arbitrary bytes, as used on Unix.
+    Bytes,
+    /// WTF-8, as used on Windows.
+    Wtf8,
+}
+
+impl Encoding {
+    /// Returns the tag byte that marks this encoding.
+    pub fn tag(self) -> u8 {
+        match self {
+            Encoding::Unicode => 0,
+            Encoding::Bytes => 1,
+            Encoding::Wtf8 => 2,
+        }
+    }
+
+    /// Returns the encoding marked by a tag byte, if any.
+    pub fn from_tag(tag: u8) -> Option<Encoding> {
+        match tag {
+            0 => Some(Encoding::Unicode),
+            1 => Some(Encoding::Bytes),
+            2 => Some(Encoding::Wtf8),
+            _ => None,
+        }
+    }
+}
+
+/// An error returned when decoding a portable OS string fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input was empty, so it had no tag byte.
+    MissingTag,
+    /// The tag byte does not name a known encoding.
+    UnknownTag(u8),
+    /// The payload is not valid in the encoding it is tagged with.
+    Malformed(Encoding),
+    /// The payload holds non-Unicode data in an encoding that cannot
+    /// be represented on this platform.
+    Incompatible {
+        /// The encoding of the payload.
+        found: Encoding,
+        /// The native encoding of this platform.
+        native: Encoding,
+    },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DecodeError::MissingTag => f.write_str("missing encoding tag"),
+            DecodeError::UnknownTag(tag) => write!(f, "unknown encoding tag {:#04x}", tag),
+            DecodeError::Malformed(encoding) => write!(f, "malformed {:?} payload", encoding),
+            DecodeError::Incompatible { found, native } =>
+                write!(f, "non-Unicode {:?} payload cannot be represented \
+                           in the native {:?} encoding", found, native),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for DecodeError {
+    fn description(&self) -> &str {
+        match *self {
+            DecodeError::MissingTag => "missing encoding tag",
+            DecodeError::UnknownTag(..) => "unknown encoding tag",
+            DecodeError::Malformed(..) => "malformed payload",
+            DecodeError::Incompatible { .. } => "incompatible encoding",
+        }
+    }
+}
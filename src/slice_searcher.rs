@@ -1,6 +1,18 @@
-// FIXME: Use a better algorithm for this.  core::str::pattern has
-// some interesting stuff.
+//! Searching for byte strings in byte strings.
+//!
+//! Longer needles are found by checking each occurrence of their first
+//! (or last) byte.  With the `simd` feature, bytes are found with the
+//! vectorized `memchr` from the `memchr` crate.  Otherwise, they are
+//! found a word at a time.
 
+use std::ascii::AsciiExt;
+
+#[cfg(feature = "simd")]
+pub use memchr::{memchr, memrchr};
+#[cfg(not(feature = "simd"))]
+pub use self::fallback::{memchr, memrchr};
+
+#[derive(Clone)]
 pub struct SliceSearcher<'a, 'b> {
     haystack: &'a [u8],
     needle: &'b [u8],
@@ -23,16 +35,309 @@ impl<'a, 'b> Iterator for SliceSearcher<'a, 'b> {
     type Item = usize;
 
     fn next(&mut self) -> Option<usize> {
-        while self.position + self.needle.len() <= self.haystack.len() {
-            let check = self.position;
+        if self.needle.is_empty() {
+            if self.position > self.haystack.len() { return None; }
             self.position += 1;
-            if &self.haystack[check..check + self.needle.len()] == self.needle {
-                if !self.overlapping {
-                    self.position = check + self.needle.len();
-                }
+            return Some(self.position - 1);
+        }
+        match find(&self.haystack[self.position..], self.needle) {
+            Some(index) => {
+                let check = self.position + index;
+                self.position = if self.overlapping { check + 1 } else { check + self.needle.len() };
+                Some(check)
+            }
+            None => {
+                self.position = self.haystack.len();
+                None
+            }
+        }
+    }
+}
+
+/// Like `SliceSearcher`, but finds matches starting from the end of
+/// the haystack.
+#[derive(Clone)]
+pub struct ReverseSliceSearcher<'a, 'b> {
+    haystack: &'a [u8],
+    needle: &'b [u8],
+    // The end of the next possible match
+    end: Option<usize>,
+    overlapping: bool,
+}
+
+impl<'a, 'b> ReverseSliceSearcher<'a, 'b> {
+    pub fn new(haystack: &'a [u8], needle: &'b [u8], overlapping: bool)
+               -> ReverseSliceSearcher<'a, 'b> {
+        ReverseSliceSearcher {
+            haystack: haystack,
+            needle: needle,
+            end: Some(haystack.len()),
+            overlapping: overlapping,
+        }
+    }
+}
+
+impl<'a, 'b> Iterator for ReverseSliceSearcher<'a, 'b> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let end = match self.end {
+            Some(end) => end,
+            None => return None,
+        };
+        if self.needle.is_empty() {
+            self.end = end.checked_sub(1);
+            return Some(end);
+        }
+        let found = rfind(&self.haystack[..end], self.needle);
+        self.end = match found {
+            Some(check) if self.overlapping => Some(check + self.needle.len() - 1),
+            found => found,
+        };
+        found
+    }
+}
+
+/// Returns the index of the first occurrence of `needle` in
+/// `haystack`.  `needle` must not be empty.
+pub fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    // FIXME: Use a better algorithm for this.  core::str::pattern has
+    // some interesting stuff.
+    let mut position = 0;
+    while let Some(index) = memchr(needle[0], &haystack[position..]) {
+        let check = position + index;
+        if haystack.len() - check < needle.len() { return None; }
+        if &haystack[check..check + needle.len()] == needle { return Some(check); }
+        position = check + 1;
+    }
+    None
+}
+
+/// Returns the index of the last occurrence of `needle` in
+/// `haystack`.  `needle` must not be empty.
+pub fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    let mut end = haystack.len();
+    while let Some(index) = memrchr(needle[needle.len() - 1], &haystack[..end]) {
+        // The match would end just after index
+        if index + 1 < needle.len() { return None; }
+        let check = index + 1 - needle.len();
+        if &haystack[check..index + 1] == needle { return Some(check); }
+        end = index;
+    }
+    None
+}
+
+/// Returns the index of the first occurrence of `needle` in
+/// `haystack`, ignoring ASCII case.
+pub fn find_ignore_ascii_case(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    let first = match needle.first() {
+        Some(&first) => first,
+        None => return Some(0),
+    };
+    if first.to_ascii_lowercase() == first.to_ascii_uppercase() {
+        // The first byte has no case, so candidates can be found with
+        // `memchr`.
+        let mut position = 0;
+        while let Some(index) = memchr(first, &haystack[position..]) {
+            let check = position + index;
+            if haystack.len() - check < needle.len() { return None; }
+            if haystack[check..check + needle.len()].eq_ignore_ascii_case(needle) {
                 return Some(check);
             }
+            position = check + 1;
+        }
+        return None;
+    }
+    haystack.windows(needle.len()).position(|window| window.eq_ignore_ascii_case(needle))
+}
+
+/// A double-ended searcher for a single byte, for the patterns that
+/// only ever match one byte.
+#[derive(Clone)]
+pub struct ByteSearcher<'a> {
+    haystack: &'a [u8],
+    byte: u8,
+    // The part of the haystack that has not been searched
+    start: usize,
+    end: usize,
+}
+
+impl<'a> ByteSearcher<'a> {
+    pub fn new(haystack: &'a [u8], byte: u8) -> ByteSearcher<'a> {
+        ByteSearcher {
+            haystack: haystack,
+            byte: byte,
+            start: 0,
+            end: haystack.len(),
+        }
+    }
+
+    pub fn next_match(&mut self) -> Option<usize> {
+        let found = memchr(self.byte, &self.haystack[self.start..self.end])
+            .map(|index| self.start + index);
+        self.start = found.map_or(self.end, |found| found + 1);
+        found
+    }
+
+    pub fn next_match_back(&mut self) -> Option<usize> {
+        let found = memrchr(self.byte, &self.haystack[self.start..self.end])
+            .map(|index| self.start + index);
+        self.end = found.unwrap_or(self.start);
+        found
+    }
+}
+
+#[cfg(not(feature = "simd"))]
+mod fallback {
+    use std::cmp;
+    use std::mem;
+
+    const LO_USIZE: usize = !0 / 255;
+    const HI_USIZE: usize = LO_USIZE << 7;
+
+    // True if any byte of `x` is zero.  From "Matters Computational" by
+    // J. Arndt, as used by libstd's `memchr`.
+    fn contains_zero_byte(x: usize) -> bool {
+        x.wrapping_sub(LO_USIZE) & !x & HI_USIZE != 0
+    }
+
+    /// Returns the index of the first occurrence of `byte` in `haystack`.
+    ///
+    /// This checks a word at a time where the slice is aligned, which is
+    /// much faster than a plain loop on long haystacks.
+    pub fn memchr(byte: u8, haystack: &[u8]) -> Option<usize> {
+        let word_size = mem::size_of::<usize>();
+        let repeated = LO_USIZE * byte as usize;
+        let ptr = haystack.as_ptr();
+        let len = haystack.len();
+
+        // Bytes before the first aligned word
+        let mut offset = cmp::min((ptr as usize).wrapping_neg() % word_size, len);
+        if let Some(index) = haystack[..offset].iter().position(|&b| b == byte) {
+            return Some(index);
+        }
+
+        while offset + word_size <= len {
+            // ptr + offset is aligned and the whole word is in bounds.
+            let word = unsafe { *(ptr.offset(offset as isize) as *const usize) };
+            if contains_zero_byte(word ^ repeated) { break; }
+            offset += word_size;
+        }
+
+        haystack[offset..].iter().position(|&b| b == byte).map(|index| offset + index)
+    }
+
+    /// Returns the index of the last occurrence of `byte` in `haystack`.
+    /// See `memchr`.
+    pub fn memrchr(byte: u8, haystack: &[u8]) -> Option<usize> {
+        let word_size = mem::size_of::<usize>();
+        let repeated = LO_USIZE * byte as usize;
+        let ptr = haystack.as_ptr();
+        let len = haystack.len();
+
+        // Bytes after the last aligned word
+        let mut end = len - cmp::min((ptr as usize + len) % word_size, len);
+        if let Some(index) = haystack[end..].iter().rposition(|&b| b == byte) {
+            return Some(end + index);
+        }
+
+        while end >= word_size {
+            // ptr + end is aligned and the whole word is in bounds.
+            let word = unsafe { *(ptr.offset((end - word_size) as isize) as *const usize) };
+            if contains_zero_byte(word ^ repeated) { break; }
+            end -= word_size;
+        }
+
+        haystack[..end].iter().rposition(|&b| b == byte)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::prelude::v1::*;
+    use super::{ByteSearcher, ReverseSliceSearcher, SliceSearcher, find, find_ignore_ascii_case,
+                memchr, memrchr, rfind};
+
+    #[test]
+    fn memchr_unaligned() {
+        let haystack = b"0123456789abcdefghijklmnopqrstuvwxyz0123456789";
+        // Every start and end, so the word loop sees every alignment
+        for start in 0..haystack.len() {
+            for end in start..haystack.len() + 1 {
+                let slice = &haystack[start..end];
+                for &byte in b"0a9z!".iter() {
+                    assert_eq!(memchr(byte, slice), slice.iter().position(|&b| b == byte));
+                    assert_eq!(memrchr(byte, slice), slice.iter().rposition(|&b| b == byte));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn memchr_high_bytes() {
+        let haystack = b"\x80\xFF\x00\x7F\x80\xFF\x00\x7F\x80\xFF\x00\x7F\x80\xFF\x00\x7F\x01";
+        for &byte in b"\x00\x01\x7F\x80\xFF\xFE".iter() {
+            assert_eq!(memchr(byte, haystack), haystack.iter().position(|&b| b == byte));
+            assert_eq!(memrchr(byte, haystack), haystack.iter().rposition(|&b| b == byte));
         }
-        None
+    }
+
+    #[test]
+    fn slice_searcher_single_byte() {
+        let haystack = b"a/b//c";
+        assert_eq!(SliceSearcher::new(haystack, b"/", false).collect::<Vec<_>>(), [1, 3, 4]);
+        assert_eq!(SliceSearcher::new(haystack, b"/", true).collect::<Vec<_>>(), [1, 3, 4]);
+        assert_eq!(ReverseSliceSearcher::new(haystack, b"/", false).collect::<Vec<_>>(),
+                   [4, 3, 1]);
+        assert_eq!(SliceSearcher::new(b"", b"/", false).next(), None);
+        assert_eq!(ReverseSliceSearcher::new(b"", b"/", false).next(), None);
+    }
+
+    #[test]
+    fn find_rfind() {
+        let haystack = b"abcabcab";
+        assert_eq!(find(haystack, b"cab"), Some(2));
+        assert_eq!(rfind(haystack, b"cab"), Some(5));
+        assert_eq!(find(haystack, b"b"), Some(1));
+        assert_eq!(rfind(haystack, b"b"), Some(7));
+        assert_eq!(find(haystack, b"abd"), None);
+        assert_eq!(rfind(haystack, b"abd"), None);
+        assert_eq!(find(b"ab", b"abc"), None);
+        assert_eq!(rfind(b"", b"a"), None);
+    }
+
+    #[test]
+    fn find_ascii_case_insensitive() {
+        assert_eq!(find_ignore_ascii_case(b"abc", b""), Some(0));
+        assert_eq!(find_ignore_ascii_case(b"x.Tar.GZ", b".tar.gz"), Some(1));
+        assert_eq!(find_ignore_ascii_case(b"x.Tar.GZ", b"TAR"), Some(2));
+        assert_eq!(find_ignore_ascii_case(b"x.Tar.GZ", b".gzip"), None);
+        assert_eq!(find_ignore_ascii_case(b"\xC3\xA9A", b"\xC3\xA9a"), Some(0));
+        assert_eq!(find_ignore_ascii_case(b"\xC3\x89a", b"\xC3\xA9a"), None);
+        assert_eq!(find_ignore_ascii_case(b"ab", b"abc"), None);
+    }
+
+    #[test]
+    fn slice_searcher_overlapping() {
+        let haystack = b"aaaa";
+        assert_eq!(SliceSearcher::new(haystack, b"aa", false).collect::<Vec<_>>(), [0, 2]);
+        assert_eq!(SliceSearcher::new(haystack, b"aa", true).collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!(ReverseSliceSearcher::new(haystack, b"aa", false).collect::<Vec<_>>(),
+                   [2, 0]);
+        assert_eq!(ReverseSliceSearcher::new(haystack, b"aa", true).collect::<Vec<_>>(),
+                   [2, 1, 0]);
+        assert_eq!(SliceSearcher::new(b"ab", b"", false).collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!(ReverseSliceSearcher::new(b"ab", b"", false).collect::<Vec<_>>(), [2, 1, 0]);
+    }
+
+    #[test]
+    fn byte_searcher() {
+        let mut searcher = ByteSearcher::new(b"/a/b/c/", b'/');
+        assert_eq!(searcher.next_match(), Some(0));
+        assert_eq!(searcher.next_match_back(), Some(6));
+        assert_eq!(searcher.next_match_back(), Some(4));
+        assert_eq!(searcher.next_match(), Some(2));
+        assert_eq!(searcher.next_match(), None);
+        assert_eq!(searcher.next_match_back(), None);
     }
 }
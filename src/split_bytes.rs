@@ -1,44 +1,60 @@
+use core::cmp;
+use core::iter::FusedIterator;
 use core::str;
 use core::str::pattern::{DoubleEndedSearcher, Pattern, ReverseSearcher, Searcher};
 
+use os_pattern::{DoubleEndedOsSearcher, OsPattern, OsSearcher, ReverseOsSearcher};
 use utf8_sections::Utf8Sections;
 
 // make_iterator!{F and R wrap I yielding func => type}
-//    defines forward and reverse versions of struct with Clone and Iterator
-// make_iterator!{F and R wrap I with all impls yielding func => type}
-//    additionally adds new(slice: &'a [u8], pat: P) and DoubleEndedIterator
+//    defines forward and reverse versions of struct with Clone,
+//    Iterator and FusedIterator
+// make_iterator!{F and R wrap I yielding func => type; implement ...}
+//    additionally adds the listed impls: new(slice: &'a [u8], pat: P),
+//    remainder() and DoubleEndedIterator
 macro_rules! make_iterator {
     ($forward:ident and $reverse:ident wrap $inner:ident yielding $map:expr => $ret:ty) => {
-        pub struct $forward<'a, P>($inner<'a, P>) where P: Pattern<'a>;
+        pub struct $forward<'a, P>($inner<'a, P>) where P: OsPattern<'a>;
 
-        impl<'a, P> Clone for $forward<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: Clone {
+        impl<'a, P> Clone for $forward<'a, P> where P: OsPattern<'a>, P::Searcher: Clone {
             fn clone(&self) -> Self { $forward(self.0.clone()) }
         }
 
-        impl<'a, P> Iterator for $forward<'a, P> where P: Pattern<'a> + Clone {
+        impl<'a, P> Iterator for $forward<'a, P> where P: OsPattern<'a> {
             type Item = $ret;
 
             fn next(&mut self) -> Option<$ret> {
                 self.0.next().map($map)
             }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.0.size_hint()
+            }
         }
 
-        pub struct $reverse<'a, P>($inner<'a, P>) where P: Pattern<'a>;
+        impl<'a, P> FusedIterator for $forward<'a, P> where P: OsPattern<'a> {}
+
+        pub struct $reverse<'a, P>($inner<'a, P>) where P: OsPattern<'a>;
 
-        impl<'a, P> Clone for $reverse<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: Clone {
+        impl<'a, P> Clone for $reverse<'a, P> where P: OsPattern<'a>, P::Searcher: Clone {
             fn clone(&self) -> Self { $reverse(self.0.clone()) }
         }
 
         impl<'a, P> Iterator for $reverse<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
+        where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
             type Item = $ret;
 
             fn next(&mut self) -> Option<$ret> {
                 self.0.next_back().map($map)
             }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.0.size_hint()
+            }
         }
+
+        impl<'a, P> FusedIterator for $reverse<'a, P>
+        where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {}
     };
     ($forward:ident and $reverse:ident wrap $inner:ident yielding $map:expr => $ret:ty;
      implement $($implement:ident)*) => {
@@ -49,29 +65,41 @@ macro_rules! make_iterator {
 }
 macro_rules! implement {
     (new for $forward:ident and $reverse:ident wrap $inner:ident yielding $map:expr => $ret:ty) => {
-        impl<'a, P> $forward<'a, P> where P: Pattern<'a> + Clone {
+        impl<'a, P> $forward<'a, P> where P: OsPattern<'a> {
             pub fn new(slice: &'a [u8], pat: P) -> Self {
                 $forward($inner::new(slice, pat))
             }
         }
 
-        impl<'a, P> $reverse<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
+        impl<'a, P> $reverse<'a, P> where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
             pub fn new(slice: &'a [u8], pat: P) -> Self {
                 $reverse($inner::new(slice, pat))
             }
         }
     };
+    (remainder for $forward:ident and $reverse:ident wrap $inner:ident yielding $map:expr => $ret:ty) => {
+        impl<'a, P> $forward<'a, P> where P: OsPattern<'a> {
+            pub fn remainder(&self) -> Option<&'a [u8]> {
+                self.0.remainder()
+            }
+        }
+
+        impl<'a, P> $reverse<'a, P> where P: OsPattern<'a> {
+            pub fn remainder(&self) -> Option<&'a [u8]> {
+                self.0.remainder()
+            }
+        }
+    };
     (DoubleEndedIterator for $forward:ident and $reverse:ident wrap $inner:ident yielding $map:expr => $ret:ty) => {
         impl<'a, P> DoubleEndedIterator for $forward<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: DoubleEndedSearcher<'a> {
+        where P: OsPattern<'a>, P::Searcher: DoubleEndedOsSearcher<'a> {
             fn next_back(&mut self) -> Option<$ret> {
                 self.0.next_back().map($map)
             }
         }
 
         impl<'a, P> DoubleEndedIterator for $reverse<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: DoubleEndedSearcher<'a> {
+        where P: OsPattern<'a>, P::Searcher: DoubleEndedOsSearcher<'a> {
             fn next_back(&mut self) -> Option<$ret> {
                 self.0.next().map($map)
             }
@@ -79,34 +107,51 @@ macro_rules! implement {
     };
 }
 
-make_iterator!{Matches and RMatches wrap MatchImpl yielding |x| x.1 => &'a str;
+make_iterator!{Matches and RMatches wrap MatchImpl yielding |x| x.1 => &'a [u8];
                implement new DoubleEndedIterator}
 make_iterator!{Split and RSplit wrap SplitImpl yielding |x| x => &'a [u8];
-               implement new DoubleEndedIterator}
-make_iterator!{SplitN and RSplitN wrap SplitNImpl yielding |x| x => &'a [u8]}
+               implement new remainder DoubleEndedIterator}
+make_iterator!{SplitN and RSplitN wrap SplitNImpl yielding |x| x => &'a [u8];
+               implement remainder}
 make_iterator!{SplitTerminator and RSplitTerminator wrap SplitImpl
                yielding |x| x => &'a [u8];
-               implement DoubleEndedIterator}
+               implement remainder DoubleEndedIterator}
 
-impl<'a, P> SplitN<'a, P> where P: Pattern<'a> {
+impl<'a, P> SplitN<'a, P> where P: OsPattern<'a> {
     pub fn new(slice: &'a [u8], count: usize, pat: P) -> Self {
         SplitN(SplitNImpl::new(slice, count, pat))
     }
 }
 
-impl<'a, P> RSplitN<'a, P> where P: Pattern<'a> {
+impl<'a, P> RSplitN<'a, P> where P: OsPattern<'a> {
     pub fn new(slice: &'a [u8], count: usize, pat: P) -> Self {
         RSplitN(SplitNImpl::new(slice, count, pat))
     }
 }
 
-impl<'a, P> SplitTerminator<'a, P> where P: Pattern<'a> {
+// Iterating from the uncounted end rescans the counted pieces, so the
+// searcher must be cloneable.
+impl<'a, P> DoubleEndedIterator for SplitN<'a, P>
+where P: OsPattern<'a>, P::Searcher: DoubleEndedOsSearcher<'a> + Clone {
+    fn next_back(&mut self) -> Option<&'a [u8]> {
+        self.0.next_back_counted_from_front()
+    }
+}
+
+impl<'a, P> DoubleEndedIterator for RSplitN<'a, P>
+where P: OsPattern<'a>, P::Searcher: DoubleEndedOsSearcher<'a> + Clone {
+    fn next_back(&mut self) -> Option<&'a [u8]> {
+        self.0.next_counted_from_back()
+    }
+}
+
+impl<'a, P> SplitTerminator<'a, P> where P: OsPattern<'a> {
     pub fn new(slice: &'a [u8], pat: P) -> Self {
         SplitTerminator(SplitImpl::skipping_terminator(slice, pat, true))
     }
 }
 
-impl<'a, P> RSplitTerminator<'a, P> where P: Pattern<'a> {
+impl<'a, P> RSplitTerminator<'a, P> where P: OsPattern<'a> {
     pub fn new(slice: &'a [u8], pat: P) -> Self {
         RSplitTerminator(SplitImpl::skipping_terminator(slice, pat, true))
     }
@@ -114,126 +159,67 @@ impl<'a, P> RSplitTerminator<'a, P> where P: Pattern<'a> {
 
 
 
-struct MatchImpl<'a, P> where P: Pattern<'a> {
-    sections: Utf8Sections<'a>,
-    pat: P,
-    front_searcher: Option<P::Searcher>,
-    back_searcher: Option<P::Searcher>,
-    front_section: (usize, &'a str),
-    back_section: (usize, &'a str),
+struct MatchImpl<'a, P> where P: OsPattern<'a> {
+    slice: &'a [u8],
+    searcher: <P as OsPattern<'a>>::Searcher,
+    // Set once the searcher runs out of matches, so a searcher that
+    // does not keep returning None cannot restart iteration
+    finished: bool,
 }
 
-impl<'a, P> Clone for MatchImpl<'a, P>
-where P: Pattern<'a> + Clone, P::Searcher: Clone {
+impl<'a, P> Clone for MatchImpl<'a, P> where P: OsPattern<'a>, P::Searcher: Clone {
     fn clone(&self) -> Self {
         MatchImpl {
-            sections: self.sections.clone(),
-            pat: self.pat.clone(),
-            front_searcher: self.front_searcher.clone(),
-            back_searcher: self.back_searcher.clone(),
-            front_section: self.front_section.clone(),
-            back_section: self.back_section.clone(),
+            slice: self.slice,
+            searcher: self.searcher.clone(),
+            finished: self.finished,
         }
     }
 }
 
-impl<'a, P> MatchImpl<'a, P> where P: Pattern<'a> {
+impl<'a, P> MatchImpl<'a, P> where P: OsPattern<'a> {
     fn new(slice: &'a [u8], pat: P) -> Self {
         MatchImpl {
-            pat: pat,
-            sections: Utf8Sections::new(slice),
-            front_searcher: None,
-            back_searcher: None,
-            front_section: (0, ""),
-            back_section: (slice.len(), ""),
+            slice: slice,
+            searcher: pat.into_os_searcher(slice),
+            finished: false,
         }
     }
 
-    fn next(&mut self) -> Option<(usize, &'a str)> where P: Clone {
-        loop {
-            if self.front_section.0 == self.back_section.0 {
-                // Last section.  Both directions use the same iterator.
-                let (searcher, section) =
-                    match (self.front_searcher.as_mut(), self.back_searcher.as_mut()) {
-                        (Some(front), None) => (front, self.front_section),
-                        (None, Some(back)) => (back, self.back_section),
-                        (None, None) => return None,
-                        (Some(_), Some(_)) => unreachable!(),
-                    };
-                return searcher.next_match()
-                    .map(|(start, end)| (section.0 + start, &section.1[start..end]));
-            }
-            if self.front_searcher.is_none() {
-                if let Some(section) = self.sections.next() {
-                    self.front_section = section;
-                    // New segment
-                    self.front_searcher =
-                        Some(self.pat.clone().into_searcher(self.front_section.1));
-                } else {
-                    // Starting the last segment.  Go back to the top.
-                    self.front_section = self.back_section;
-                    continue;
-                }
-            }
-
-            if let Some((start, end)) = self.front_searcher.as_mut().unwrap().next_match() {
-                let section = self.front_section;
-                return Some((section.0 + start, &section.1[start..end]));
-            }
-
-            // Finished this segment
-            self.front_searcher = None;
-        }
+    fn next(&mut self) -> Option<(usize, &'a [u8])> {
+        if self.finished { return None; }
+        let slice = self.slice;
+        let result = self.searcher.next_match().map(|(start, end)| (start, &slice[start..end]));
+        self.finished = result.is_none();
+        result
     }
 
-    fn next_back(&mut self) -> Option<(usize, &'a str)>
-    where P: Clone, P::Searcher: ReverseSearcher<'a> {
-        loop {
-            if self.front_section.0 == self.back_section.0 {
-                // Last section.  Both directions use the same iterator.
-                let (searcher, section) =
-                    match (self.front_searcher.as_mut(), self.back_searcher.as_mut()) {
-                        (Some(front), None) => (front, self.front_section),
-                        (None, Some(back)) => (back, self.back_section),
-                        (None, None) => return None,
-                        (Some(_), Some(_)) => unreachable!(),
-                    };
-                return searcher.next_match_back()
-                    .map(|(start, end)| (section.0 + start, &section.1[start..end]));
-            }
-            if self.back_searcher.is_none() {
-                if let Some(section) = self.sections.next_back() {
-                    // New segment
-                    self.back_section = section;
-                    self.back_searcher =
-                        Some(self.pat.clone().into_searcher(self.back_section.1));
-                } else {
-                    // Starting the last segment.  Go back to the top.
-                    self.back_section = self.front_section;
-                    continue;
-                }
-            }
-
-            if let Some((start, end)) = self.back_searcher.as_mut().unwrap().next_match_back() {
-                let section = self.back_section;
-                return Some((section.0 + start, &section.1[start..end]));
-            }
+    fn next_back(&mut self) -> Option<(usize, &'a [u8])>
+    where P::Searcher: ReverseOsSearcher<'a> {
+        if self.finished { return None; }
+        let slice = self.slice;
+        let result = self.searcher.next_match_back().map(|(start, end)| (start, &slice[start..end]));
+        self.finished = result.is_none();
+        result
+    }
 
-            // Finished this segment
-            self.back_searcher = None;
-        }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.finished { return (0, Some(0)); }
+        // An empty pattern can match at every position.
+        (0, Some(self.slice.len() + 1))
     }
 }
 
-struct SplitImpl<'a, P> where P: Pattern<'a> {
+struct SplitImpl<'a, P> where P: OsPattern<'a> {
     slice: &'a [u8],
     matches: MatchImpl<'a, P>,
-    remainder: (usize, usize),
+    // The bounds of the part of the slice that has not been split,
+    // or None once the last piece has been returned
+    remainder: Option<(usize, usize)>,
     skip_terminator: bool,
 }
 
-impl<'a, P> Clone for SplitImpl<'a, P>
-where P: Pattern<'a> + Clone, P::Searcher: Clone {
+impl<'a, P> Clone for SplitImpl<'a, P> where P: OsPattern<'a>, P::Searcher: Clone {
     fn clone(&self) -> Self {
         SplitImpl {
             slice: self.slice.clone(),
@@ -244,7 +230,7 @@ where P: Pattern<'a> + Clone, P::Searcher: Clone {
     }
 }
 
-impl<'a, P> SplitImpl<'a, P> where P: Pattern<'a> {
+impl<'a, P> SplitImpl<'a, P> where P: OsPattern<'a> {
     fn new(slice: &'a [u8], pat: P) -> Self {
         Self::skipping_terminator(slice, pat, false)
     }
@@ -253,95 +239,116 @@ impl<'a, P> SplitImpl<'a, P> where P: Pattern<'a> {
         SplitImpl {
             slice: slice,
             matches: MatchImpl::new(slice, pat),
-            remainder: (0, slice.len()),
+            remainder: Some((0, slice.len())),
             skip_terminator: skip_terminator,
         }
     }
 
-    fn next(&mut self) -> Option<&'a [u8]> where P: Clone {
-        if self.remainder.1 < self.remainder.0 { return None; }
-        if let Some((start, mat)) = self.matches.next() {
-            let result = &self.slice[self.remainder.0..start];
-            self.remainder.0 = start + mat.len();
-            Some(result)
+    fn next(&mut self) -> Option<&'a [u8]> {
+        let (start, end) = match self.remainder {
+            Some(remainder) => remainder,
+            None => return None,
+        };
+        if let Some((match_start, mat)) = self.matches.next() {
+            self.remainder = Some((match_start + mat.len(), end));
+            Some(&self.slice[start..match_start])
         } else {
-            // We've exhausted all the matches, but we still
-            // have the section between the last matches from
-            // either side to return.
-            let result = &self.slice[self.remainder.0..self.remainder.1];
-            // Skip the last entry if we've been asked to and it
-            // starts at the end of the slice.
-            let result = if self.skip_terminator && self.remainder.0 == self.slice.len() {
-                None
-            } else {
-                Some(result)
-            };
-            self.remainder.0 = self.remainder.1 + 1;
-            return result;
+            self.last_piece(start, end)
         }
     }
 
-    fn next_back(&mut self) -> Option<&'a [u8]>
-    where P: Clone, P::Searcher: ReverseSearcher<'a> {
-        if self.remainder.1 < self.remainder.0 { return None; }
-        if let Some((start, mat)) = self.matches.next_back() {
-            let result = &self.slice[start + mat.len()..self.remainder.1];
-            self.remainder.1 = start;
+    fn next_back(&mut self) -> Option<&'a [u8]> where P::Searcher: ReverseOsSearcher<'a> {
+        let (start, end) = match self.remainder {
+            Some(remainder) => remainder,
+            None => return None,
+        };
+        if let Some((match_start, mat)) = self.matches.next_back() {
+            let piece_start = match_start + mat.len();
+            self.remainder = Some((start, match_start));
             // Skip the value if we've been asked to and it starts at
             // the end of the slice.
-            if self.skip_terminator && start + mat.len() == self.slice.len() {
+            if self.skip_terminator && piece_start == self.slice.len() {
                 self.next_back()
             } else {
-                Some(result)
+                Some(&self.slice[piece_start..end])
             }
         } else {
-            // We've exhausted all the matches, but we still
-            // have the section between the last matches from
-            // either side to return.
-            let result = &self.slice[self.remainder.0..self.remainder.1];
-            // Skip the last entry if we've been asked to and it
-            // starts at the end of the slice.
-            let result = if self.skip_terminator && self.remainder.0 == self.slice.len() {
-                None
-            } else {
-                Some(result)
-            };
-            self.remainder.0 = self.remainder.1 + 1;
-            return result;
+            self.last_piece(start, end)
+        }
+    }
+
+    // We've exhausted all the matches, but we still have the section
+    // between the last matches from either side to return.
+    fn last_piece(&mut self, start: usize, end: usize) -> Option<&'a [u8]> {
+        self.remainder = None;
+        // Skip the last entry if we've been asked to and it starts at
+        // the end of the slice.
+        if self.skip_terminator && start == self.slice.len() {
+            None
+        } else {
+            Some(&self.slice[start..end])
         }
     }
 
     fn rest(&self) -> Option<&'a [u8]> {
-        if self.remainder.1 < self.remainder.0 { return None; }
-        Some(&self.slice[self.remainder.0..self.remainder.1])
+        self.remainder.map(|(start, end)| &self.slice[start..end])
+    }
+
+    // The position of `piece`, which must be part of the slice
+    fn offset(&self, piece: &'a [u8]) -> usize {
+        piece.as_ptr() as usize - self.slice.as_ptr() as usize
+    }
+
+    // Like rest, but None if only a skipped terminator is left
+    fn remainder(&self) -> Option<&'a [u8]> {
+        match self.remainder {
+            Some((start, _)) if self.skip_terminator && start == self.slice.len() => None,
+            _ => self.rest(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.remainder {
+            None => (0, Some(0)),
+            Some((start, end)) => {
+                // There is one more piece than there are matches, and
+                // an empty pattern can match at every position.
+                let low = if self.skip_terminator { 0 } else { 1 };
+                (low, Some(end - start + 2))
+            }
+        }
     }
 }
 
 
-struct SplitNImpl<'a, P> where P: Pattern<'a> {
+struct SplitNImpl<'a, P> where P: OsPattern<'a> {
     split: SplitImpl<'a, P>,
     count: usize,
+    // Set once the remainder holds no more than `count` pieces, so
+    // they can be split off the uncounted end directly
+    cut: bool,
 }
 
-impl<'a, P> Clone for SplitNImpl<'a, P>
-where P: Pattern<'a> + Clone, P::Searcher: Clone {
+impl<'a, P> Clone for SplitNImpl<'a, P> where P: OsPattern<'a>, P::Searcher: Clone {
     fn clone(&self) -> Self {
         SplitNImpl {
             split: self.split.clone(),
             count: self.count.clone(),
+            cut: self.cut,
         }
     }
 }
 
-impl<'a, P> SplitNImpl<'a, P> where P: Pattern<'a> {
+impl<'a, P> SplitNImpl<'a, P> where P: OsPattern<'a> {
     fn new(slice: &'a [u8], count: usize, pat: P) -> Self {
         SplitNImpl {
             split: SplitImpl::new(slice, pat),
             count: count,
+            cut: false,
         }
     }
 
-    fn next(&mut self) -> Option<&'a [u8]> where P: Clone {
+    fn next(&mut self) -> Option<&'a [u8]> {
         match self.count {
             0 => None,
             1 => { self.count = 0; self.split.rest() },
@@ -349,14 +356,101 @@ impl<'a, P> SplitNImpl<'a, P> where P: Pattern<'a> {
         }
     }
 
-    fn next_back(&mut self) -> Option<&'a [u8]>
-    where P: Clone, P::Searcher: ReverseSearcher<'a> {
+    fn next_back(&mut self) -> Option<&'a [u8]> where P::Searcher: ReverseOsSearcher<'a> {
         match self.count {
             0 => None,
             1 => { self.count = 0; self.split.rest() },
             _ => { self.count -= 1; self.split.next_back() },
         }
     }
+
+    // The last piece of the pieces counted from the front.  If there
+    // are at least `count` pieces, it is the unsplit rest after the
+    // first `count - 1`, which needs a scan from the front to find.
+    // The scan cuts the remainder down to the counted pieces, so it is
+    // only needed on the first call.
+    fn next_back_counted_from_front(&mut self) -> Option<&'a [u8]>
+    where P::Searcher: DoubleEndedOsSearcher<'a> + Clone {
+        if self.count == 0 { return None; }
+        if self.cut {
+            self.count -= 1;
+            return self.split.next_back();
+        }
+        self.cut = true;
+        let mut probe = self.split.clone();
+        let mut separator_start = None;
+        for _ in 1..self.count {
+            match probe.next() {
+                Some(piece) => separator_start = Some(self.split.offset(piece) + piece.len()),
+                None => break,
+            }
+        }
+        self.count -= 1;
+        match probe.rest() {
+            Some(last) => {
+                // Stop splitting before the last piece, leaving
+                // exactly `count` pieces, and drop the matches from
+                // the separator on so later calls continue from there.
+                if let Some(end) = separator_start {
+                    self.split.remainder = self.split.remainder.map(|(start, _)| (start, end));
+                    while let Some((match_start, _)) = self.split.matches.next_back() {
+                        if match_start == end { break; }
+                    }
+                }
+                Some(last)
+            }
+            // There are fewer than `count` pieces, so the count
+            // doesn't matter.
+            None => self.split.next_back(),
+        }
+    }
+
+    // The mirror image of next_back_counted_from_front
+    fn next_counted_from_back(&mut self) -> Option<&'a [u8]>
+    where P::Searcher: DoubleEndedOsSearcher<'a> + Clone {
+        if self.count == 0 { return None; }
+        if self.cut {
+            self.count -= 1;
+            return self.split.next();
+        }
+        self.cut = true;
+        let mut probe = self.split.clone();
+        let mut separator_end = None;
+        for _ in 1..self.count {
+            match probe.next_back() {
+                Some(piece) => separator_end = Some(self.split.offset(piece)),
+                None => break,
+            }
+        }
+        self.count -= 1;
+        match probe.rest() {
+            Some(first) => {
+                if let Some(start) = separator_end {
+                    self.split.remainder = self.split.remainder.map(|(_, end)| (start, end));
+                    while let Some((match_start, mat)) = self.split.matches.next() {
+                        if match_start + mat.len() == start { break; }
+                    }
+                }
+                Some(first)
+            }
+            None => self.split.next(),
+        }
+    }
+
+    fn remainder(&self) -> Option<&'a [u8]> {
+        if self.count == 0 { None } else { self.split.rest() }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.count {
+            0 => (0, Some(0)),
+            1 => if self.split.rest().is_some() { (1, Some(1)) } else { (0, Some(0)) },
+            _ => {
+                let (low, high) = self.split.size_hint();
+                (low, high.map(|high| cmp::min(high, self.count)))
+            }
+        }
+    }
 }
 
 
@@ -1,13 +1,73 @@
 use std::prelude::v1::*;
-use std::borrow::Borrow;
-use std::ffi;
+use std::borrow::{Borrow, Cow};
+use std::cmp;
+use std::ffi::{self, CStr, CString};
+use std::hash::Hasher;
+use std::iter::FusedIterator;
 use std::mem;
+use std::ops;
+use std::path::Path;
 use std::str::pattern::{DoubleEndedSearcher, Pattern, ReverseSearcher};
 
+#[cfg(feature = "regex")]
+use regex::bytes::Regex;
+
+use collation::Collator;
+use os_pattern::{DoubleEndedOsSearcher, OsPattern, ReverseOsSearcher};
 use os_str;
+use portable::DecodeError;
 use slice_concat_ext::LocalSliceConcatExt;
 
+// On Unix, owned strings and shared borrows convert through the
+// platform extension traits, which move or borrow the bytes without
+// copying.
+#[cfg(unix)]
+mod platform {
+    use std::ffi;
+    use std::os::unix::ffi::{OsStrExt as StdOsStrExt, OsStringExt as StdOsStringExt};
+    use os_str;
+    use unix::{OsStrExt, OsStringExt};
+
+    pub fn string_from_std(x: ffi::OsString) -> os_str::OsString {
+        os_str::OsString::from_vec(x.into_vec())
+    }
+
+    pub fn string_to_std(x: os_str::OsString) -> ffi::OsString {
+        ffi::OsString::from_vec(x.into_vec())
+    }
+
+    pub fn str_from_std(x: &ffi::OsStr) -> &os_str::OsStr {
+        <os_str::OsStr as OsStrExt>::from_bytes(x.as_bytes())
+    }
+
+    pub fn str_to_std(x: &os_str::OsStr) -> &ffi::OsStr {
+        <ffi::OsStr as StdOsStrExt>::from_bytes(x.as_bytes())
+    }
+}
+
 macro_rules! make_conversions {
+    ($a:ty : $b:ty, $a_to_b:path, $b_to_a:path) => {
+        impl<'a> From<$a> for $b {
+            fn from(x: $a) -> $b {
+                $a_to_b(x)
+            }
+        }
+
+        impl<'a> From<$b> for $a {
+            fn from(x: $b) -> $a {
+                $b_to_a(x)
+            }
+        }
+    }
+}
+
+// libstd offers no way to reach the data behind a `&mut OsStr` or a
+// `Box<OsStr>`, nor, on Windows, the WTF-8 behind any of its strings
+// without re-encoding.  These conversions instead rely on libstd using
+// the same representation as this crate, a byte vector on Unix and a
+// `Wtf8Buf` on Windows, which is why this module is only built where
+// that holds.  This is the only place the assumption is made.
+macro_rules! reinterpret_conversions {
     ($a:ty : $b:ty) => {
         impl<'a> From<$a> for $b {
             fn from(x: $a) -> $b {
@@ -23,17 +83,61 @@ macro_rules! make_conversions {
     }
 }
 
-make_conversions!{os_str::OsString : ffi::OsString}
-make_conversions!{&'a os_str::OsString : &'a ffi::OsString}
-make_conversions!{&'a mut os_str::OsString : &'a mut ffi::OsString}
-make_conversions!{&'a os_str::OsStr : &'a ffi::OsStr}
-make_conversions!{&'a mut os_str::OsStr : &'a mut ffi::OsStr}
+#[cfg(unix)]
+make_conversions!{os_str::OsString : ffi::OsString,
+                  platform::string_to_std, platform::string_from_std}
+#[cfg(unix)]
+make_conversions!{&'a os_str::OsStr : &'a ffi::OsStr,
+                  platform::str_to_std, platform::str_from_std}
+#[cfg(windows)]
+reinterpret_conversions!{os_str::OsString : ffi::OsString}
+#[cfg(windows)]
+reinterpret_conversions!{&'a os_str::OsStr : &'a ffi::OsStr}
+reinterpret_conversions!{&'a mut os_str::OsStr : &'a mut ffi::OsStr}
+reinterpret_conversions!{Box<os_str::OsStr> : Box<ffi::OsStr>}
+
+/// Runs `f` on the string, moving its buffer in and out of an
+/// `os_str::OsString`.
+fn with_string_mut<F, R>(s: &mut ffi::OsString, f: F) -> R
+where F: FnOnce(&mut os_str::OsString) -> R {
+    let mut string = os_str::OsString::from(mem::replace(s, ffi::OsString::new()));
+    let result = f(&mut string);
+    *s = string.into();
+    result
+}
+
+fn cow_into_std(x: Cow<os_str::OsStr>) -> Cow<ffi::OsStr> {
+    match x {
+        Cow::Borrowed(s) => Cow::Borrowed(s.into()),
+        Cow::Owned(s) => Cow::Owned(s.into()),
+    }
+}
 
 pub trait OsStringPrototyping {
     fn with_capacity(capacity: usize) -> Self;
     fn capacity(&self) -> usize;
+    fn reserve(&mut self, additional: usize);
+    fn reserve_exact(&mut self, additional: usize);
     fn into_string_lossy(self) -> String;
+    fn into_string_with_replacement(self, rep: &str) -> String;
     fn clear(&mut self);
+    fn from_bytes<B>(bytes: B) -> Option<Self> where B: Into<Vec<u8>>, Self: Sized;
+    fn from_portable_bytes(bytes: &[u8]) -> Result<Self, DecodeError> where Self: Sized;
+    fn from_percent_encoded(s: &str) -> Option<Self> where Self: Sized;
+    fn from_cstring(s: CString) -> Option<Self> where Self: Sized;
+    fn join_search_paths<I, T>(paths: I) -> Result<Self, JoinSearchPathsError>
+        where I: IntoIterator<Item = T>, T: AsRef<ffi::OsStr>, Self: Sized;
+    fn shrink_to_fit(&mut self);
+    fn shrink_to(&mut self, min_capacity: usize);
+    fn try_into_string(self) -> Result<String, IntoStringError>;
+    fn push_str(&mut self, s: &str);
+    fn push_char(&mut self, c: char);
+    fn truncate(&mut self, new_len: usize);
+    fn retain<F, G>(&mut self, keep_char: F, keep_non_unicode: G)
+        where F: FnMut(char) -> bool, G: FnMut(&ffi::OsStr) -> bool;
+    fn pop(&mut self) -> Option<Popped>;
+    fn into_boxed_os_str(self) -> Box<ffi::OsStr>;
+    fn into_cow<'a>(self) -> Cow<'a, ffi::OsStr>;
 }
 
 impl OsStringPrototyping for ffi::OsString {
@@ -41,13 +145,73 @@ impl OsStringPrototyping for ffi::OsString {
         os_str::OsString::with_capacity(capacity).into()
     }
     fn capacity(&self) -> usize {
-        <&os_str::OsString>::from(self).capacity()
+        ffi::OsString::capacity(self)
+    }
+    fn reserve(&mut self, additional: usize) {
+        with_string_mut(self, |string| string.reserve(additional))
+    }
+    fn reserve_exact(&mut self, additional: usize) {
+        with_string_mut(self, |string| string.reserve_exact(additional))
     }
     fn into_string_lossy(self) -> String {
         <os_str::OsString>::from(self).into_string_lossy()
     }
+    fn into_string_with_replacement(self, rep: &str) -> String {
+        <os_str::OsString>::from(self).into_string_with_replacement(rep)
+    }
     fn clear(&mut self) {
-        <&mut os_str::OsString>::from(self).clear()
+        with_string_mut(self, |string| string.clear())
+    }
+    fn from_bytes<B>(bytes: B) -> Option<Self> where B: Into<Vec<u8>> {
+        os_str::OsString::from_bytes(bytes).map(|s| s.into())
+    }
+    fn from_portable_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        os_str::OsString::from_portable_bytes(bytes).map(|s| s.into())
+    }
+    fn from_percent_encoded(s: &str) -> Option<Self> {
+        os_str::OsString::from_percent_encoded(s).map(|s| s.into())
+    }
+    fn from_cstring(s: CString) -> Option<Self> {
+        os_str::OsString::from_cstring(s).map(|s| s.into())
+    }
+    fn join_search_paths<I, T>(paths: I) -> Result<Self, JoinSearchPathsError>
+    where I: IntoIterator<Item = T>, T: AsRef<ffi::OsStr> {
+        let paths = paths.into_iter().map(|p| p.as_ref().to_owned().into())
+            .collect::<Vec<os_str::OsString>>();
+        os_str::OsString::join_search_paths(paths).map(|s| s.into())
+    }
+    fn shrink_to_fit(&mut self) {
+        with_string_mut(self, |string| string.shrink_to_fit())
+    }
+    fn shrink_to(&mut self, min_capacity: usize) {
+        with_string_mut(self, |string| string.shrink_to(min_capacity))
+    }
+    fn try_into_string(self) -> Result<String, IntoStringError> {
+        <os_str::OsString>::from(self).try_into_string()
+    }
+    fn push_str(&mut self, s: &str) {
+        with_string_mut(self, |string| string.push_str(s))
+    }
+    fn push_char(&mut self, c: char) {
+        with_string_mut(self, |string| string.push_char(c))
+    }
+    fn truncate(&mut self, new_len: usize) {
+        with_string_mut(self, |string| string.truncate(new_len))
+    }
+    fn retain<F, G>(&mut self, keep_char: F, mut keep_non_unicode: G)
+    where F: FnMut(char) -> bool, G: FnMut(&ffi::OsStr) -> bool {
+        with_string_mut(self, |string| {
+            string.retain(keep_char, |s| keep_non_unicode(s.into()))
+        })
+    }
+    fn pop(&mut self) -> Option<Popped> {
+        with_string_mut(self, |string| string.pop().map(|x| x.into()))
+    }
+    fn into_boxed_os_str(self) -> Box<ffi::OsStr> {
+        <os_str::OsString>::from(self).into_boxed_os_str().into()
+    }
+    fn into_cow<'a>(self) -> Cow<'a, ffi::OsStr> {
+        Cow::Owned(self)
     }
 }
 
@@ -55,40 +219,137 @@ pub trait OsStrPrototyping {
     fn is_empty(&self) -> bool;
     fn len(&self) -> usize;
     fn split_unicode<'a>(&'a self) -> SplitUnicode<'a>;
+    fn chars_lossy<'a>(&'a self) -> CharsLossy<'a>;
+    fn to_string_with_replacement(&self, rep: &str) -> Cow<str>;
+    fn to_str_chunks_lossy<'a>(&'a self) -> StrChunksLossy<'a>;
+    fn utf8_chunks<'a>(&'a self) -> Utf8Chunks<'a>;
+    fn invalid_sections<'a>(&'a self) -> InvalidSections<'a>;
     fn split_whitespace<'a>(&'a self) -> SplitWhitespace<'a>;
     fn lines<'a>(&'a self) -> Lines<'a>;
     fn contains_os<S: AsRef<ffi::OsStr>>(&self, needle: S) -> bool;
     fn starts_with_os<S: AsRef<ffi::OsStr>>(&self, needle: S) -> bool;
     fn ends_with_os<S: AsRef<ffi::OsStr>>(&self, needle: S) -> bool;
-    fn replace<T: AsRef<ffi::OsStr>, U: AsRef<ffi::OsStr>>(&self, from: T, to: U) -> ffi::OsString;
-    fn contains<'a, P>(&'a self, pat: P) -> bool where P: Pattern<'a> + Clone;
-    fn starts_with<'a, P>(&'a self, pat: P) -> bool where P: Pattern<'a>;
+    fn replace<T: AsRef<ffi::OsStr>, U: AsRef<ffi::OsStr>>(&self, from: T, to: U) -> Cow<ffi::OsStr>;
+    fn contains<'a, P>(&'a self, pat: P) -> bool where P: OsPattern<'a>;
+    fn starts_with<'a, P>(&'a self, pat: P) -> bool where P: OsPattern<'a>;
     fn ends_with<'a, P>(&'a self, pat: P) -> bool
-        where P: Pattern<'a>, P::Searcher: ReverseSearcher<'a>;
+        where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a>;
+    fn find<'a, P>(&'a self, pat: P) -> Option<usize> where P: OsPattern<'a>;
+    fn rfind<'a, P>(&'a self, pat: P) -> Option<usize>
+        where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a>;
     fn split<'a, P>(&'a self, pat: P) -> Split<'a, P>
-        where P: Pattern<'a> + Clone;
+        where P: OsPattern<'a>;
     fn rsplit<'a, P>(&'a self, pat: P) -> RSplit<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a>;
+        where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a>;
     fn split_terminator<'a, P>(&'a self, pat: P) -> SplitTerminator<'a, P>
-        where P: Pattern<'a> + Clone;
+        where P: OsPattern<'a>;
     fn rsplit_terminator<'a, P>(&'a self, pat: P) -> RSplitTerminator<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a>;
+        where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a>;
     fn splitn<'a, P>(&'a self, count: usize, pat: P) -> SplitN<'a, P>
-        where P: Pattern<'a> + Clone;
+        where P: OsPattern<'a>;
     fn rsplitn<'a, P>(&'a self, count: usize, pat: P) -> RSplitN<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a>;
+        where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a>;
+    fn split_os<'a, 'b, S>(&'a self, sep: &'b S) -> Split<'a, &'b os_str::OsStr>
+        where S: AsRef<ffi::OsStr> + ?Sized;
+    fn rsplit_os<'a, 'b, S>(&'a self, sep: &'b S) -> RSplit<'a, &'b os_str::OsStr>
+        where S: AsRef<ffi::OsStr> + ?Sized;
+    fn splitn_os<'a, 'b, S>(&'a self, count: usize, sep: &'b S) -> SplitN<'a, &'b os_str::OsStr>
+        where S: AsRef<ffi::OsStr> + ?Sized;
+    fn rsplitn_os<'a, 'b, S>(&'a self, count: usize, sep: &'b S)
+                             -> RSplitN<'a, &'b os_str::OsStr>
+        where S: AsRef<ffi::OsStr> + ?Sized;
+    fn split_once<'a, P>(&'a self, pat: P) -> Option<(&'a Self, &'a Self)>
+        where P: OsPattern<'a>;
+    fn rsplit_once<'a, P>(&'a self, pat: P) -> Option<(&'a Self, &'a Self)>
+        where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a>;
     fn matches<'a, P>(&'a self, pat: P) -> Matches<'a, P>
-        where P: Pattern<'a> + Clone;
+        where P: OsPattern<'a>;
     fn rmatches<'a, P>(&'a self, pat: P) -> RMatches<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a>;
+        where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a>;
+    fn matches_os<'a, 'b, S>(&'a self, needle: &'b S) -> Matches<'a, &'b os_str::OsStr>
+        where S: AsRef<ffi::OsStr> + ?Sized;
+    fn rmatches_os<'a, 'b, S>(&'a self, needle: &'b S) -> RMatches<'a, &'b os_str::OsStr>
+        where S: AsRef<ffi::OsStr> + ?Sized;
     fn trim(&self) -> &Self;
+    fn trim_start(&self) -> &Self;
+    fn trim_end(&self) -> &Self;
     fn trim_left(&self) -> &Self;
     fn trim_right(&self) -> &Self;
     fn trim_matches<'a, P>(&'a self, pat: P) -> &'a Self
     where P: Pattern<'a> + Clone, P::Searcher: DoubleEndedSearcher<'a>;
+    fn trim_start_matches<'a, P>(&'a self, pat: P) -> &Self where P: Pattern<'a>;
+    fn trim_end_matches<'a, P>(&'a self, pat: P) -> &Self
+    where P: Pattern<'a>, P::Searcher: ReverseSearcher<'a>;
     fn trim_left_matches<'a, P>(&'a self, pat: P) -> &Self where P: Pattern<'a>;
     fn trim_right_matches<'a, P>(&'a self, pat: P) -> &Self
     where P: Pattern<'a>, P::Searcher: ReverseSearcher<'a>;
+    fn from_bytes(bytes: &[u8]) -> Option<&Self>;
+    fn to_bytes(&self) -> Option<&[u8]>;
+    fn from_encoded_bytes(bytes: &[u8]) -> Option<&Self>;
+    fn as_encoded_bytes(&self) -> &[u8];
+    fn to_portable_bytes(&self) -> Vec<u8>;
+    fn hash_portable<H: Hasher>(&self, state: &mut H);
+    fn percent_encode(&self) -> String;
+    fn from_cstr(s: &CStr) -> Option<&Self>;
+    fn to_cstring(&self) -> Option<CString>;
+    fn try_to_cstring(&self) -> Result<CString, ToCStringError>;
+    fn as_cstr_with_nul<'a>(&self, buf: &'a mut Vec<u8>) -> Result<&'a CStr, ToCStringError>;
+    fn try_to_str(&self) -> Result<&str, OsStrUtf8Error>;
+    fn to_cow(&self) -> Cow<ffi::OsStr>;
+    fn display(&self) -> Display;
+    fn escape_default(&self) -> EscapeDefault;
+    fn escape_debug(&self) -> EscapeDebug;
+    fn split_at(&self, mid: usize) -> (&Self, &Self);
+    fn try_split_at(&self, mid: usize) -> Option<(&Self, &Self)>;
+    fn get(&self, range: ops::Range<usize>) -> Option<&Self>;
+    fn slice_pop_char(&self) -> Option<(&Self, char)>;
+    fn is_ascii(&self) -> bool;
+    fn eq_ignore_ascii_case<S: AsRef<ffi::OsStr>>(&self, other: S) -> bool;
+    fn eq_fold<S: AsRef<ffi::OsStr>>(&self, other: S) -> bool;
+    fn cmp_fold<S: AsRef<ffi::OsStr>>(&self, other: S) -> cmp::Ordering;
+    fn cmp_with<S, C>(&self, other: S, collator: &C) -> cmp::Ordering
+        where S: AsRef<ffi::OsStr>, C: Collator + ?Sized;
+    fn contains_ignore_ascii_case<S: AsRef<ffi::OsStr>>(&self, needle: S) -> bool;
+    fn find_ignore_ascii_case<S: AsRef<ffi::OsStr>>(&self, needle: S) -> Option<usize>;
+    fn starts_with_ignore_ascii_case<S: AsRef<ffi::OsStr>>(&self, needle: S) -> bool;
+    fn ends_with_ignore_ascii_case<S: AsRef<ffi::OsStr>>(&self, needle: S) -> bool;
+    fn make_ascii_lowercase(&mut self);
+    fn make_ascii_uppercase(&mut self);
+    fn to_lowercase(&self) -> ffi::OsString;
+    fn to_uppercase(&self) -> ffi::OsString;
+    fn matches_glob<S: AsRef<ffi::OsStr>>(&self, pattern: S) -> bool;
+    fn contains_byte(&self, byte: u8) -> bool;
+    fn find_byte(&self, byte: u8) -> Option<usize>;
+    fn find_os<S: AsRef<ffi::OsStr>>(&self, needle: S) -> Option<usize>;
+    fn rfind_os<S: AsRef<ffi::OsStr>>(&self, needle: S) -> Option<usize>;
+    fn match_indices_os<'a, 'b, S>(&'a self, needle: &'b S) -> MatchIndicesOs<'a, 'b>
+        where S: AsRef<ffi::OsStr> + ?Sized;
+    fn rmatch_indices_os<'a, 'b, S>(&'a self, needle: &'b S) -> RMatchIndicesOs<'a, 'b>
+        where S: AsRef<ffi::OsStr> + ?Sized;
+    fn count_matches_os<S: AsRef<ffi::OsStr>>(&self, needle: S) -> usize;
+    fn split_once_os<S: AsRef<ffi::OsStr>>(&self, needle: S) -> Option<(&Self, &Self)>;
+    fn rsplit_once_os<S: AsRef<ffi::OsStr>>(&self, needle: S) -> Option<(&Self, &Self)>;
+    fn strip_prefix<'a, P>(&'a self, pat: P) -> Option<&'a Self> where P: OsPattern<'a>;
+    fn strip_suffix<'a, P>(&'a self, pat: P) -> Option<&'a Self>
+        where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a>;
+    fn count_matches<'a, P>(&'a self, pat: P) -> usize where P: OsPattern<'a>;
+    fn replace_matches<'a, P>(&'a self, pat: P, to: &str) -> Cow<'a, ffi::OsStr>
+        where P: OsPattern<'a>;
+    fn replacen<'a, P>(&'a self, pat: P, to: &str, count: usize) -> Cow<'a, ffi::OsStr>
+        where P: OsPattern<'a>;
+    fn split_search_paths<'a>(&'a self) -> SplitSearchPaths<'a>;
+    #[cfg(feature = "regex")]
+    fn regex_find<'a>(&'a self, re: &Regex) -> Option<(usize, &'a Self)>;
+    #[cfg(feature = "regex")]
+    fn regex_matches<'a, 'r>(&'a self, re: &'r Regex) -> RegexMatches<'a, 'r>;
+    #[cfg(feature = "regex")]
+    fn regex_split<'a, 'r>(&'a self, re: &'r Regex) -> RegexSplit<'a, 'r>;
+    #[cfg(feature = "unicode-segmentation")]
+    fn graphemes<'a>(&'a self) -> Graphemes<'a>;
+    #[cfg(feature = "unicode-width")]
+    fn display_width(&self) -> usize;
+    #[cfg(feature = "unicode-width")]
+    fn display_width_with(&self, non_unicode_width: usize) -> usize;
 }
 
 impl OsStrPrototyping for ffi::OsStr {
@@ -101,6 +362,25 @@ impl OsStrPrototyping for ffi::OsStr {
     fn split_unicode<'a>(&'a self) -> SplitUnicode<'a> {
         <&os_str::OsStr>::from(self).split_unicode().into()
     }
+    fn chars_lossy<'a>(&'a self) -> CharsLossy<'a> {
+        <&os_str::OsStr>::from(self).chars_lossy()
+    }
+
+    fn to_string_with_replacement(&self, rep: &str) -> Cow<str> {
+        <&os_str::OsStr>::from(self).to_string_with_replacement(rep)
+    }
+
+    fn to_str_chunks_lossy<'a>(&'a self) -> StrChunksLossy<'a> {
+        <&os_str::OsStr>::from(self).to_str_chunks_lossy()
+    }
+
+    fn utf8_chunks<'a>(&'a self) -> Utf8Chunks<'a> {
+        <&os_str::OsStr>::from(self).utf8_chunks()
+    }
+
+    fn invalid_sections<'a>(&'a self) -> InvalidSections<'a> {
+        <&os_str::OsStr>::from(self).invalid_sections()
+    }
     fn split_whitespace<'a>(&'a self) -> SplitWhitespace<'a> {
         <&os_str::OsStr>::from(self).split_whitespace().into()
     }
@@ -116,56 +396,108 @@ impl OsStrPrototyping for ffi::OsStr {
     fn ends_with_os<S: AsRef<ffi::OsStr>>(&self, needle: S) -> bool {
         <&os_str::OsStr>::from(self).ends_with_os(<&os_str::OsStr>::from(needle.as_ref()))
     }
-    fn replace<T: AsRef<ffi::OsStr>, U: AsRef<ffi::OsStr>>(&self, from: T, to: U) -> ffi::OsString {
+    fn replace<T: AsRef<ffi::OsStr>, U: AsRef<ffi::OsStr>>(&self, from: T, to: U) -> Cow<ffi::OsStr> {
         let from: &os_str::OsStr = from.as_ref().into();
         let to: &os_str::OsStr = to.as_ref().into();
-        <&os_str::OsStr>::from(self).replace(from, to).into()
+        cow_into_std(<&os_str::OsStr>::from(self).replace(from, to))
     }
-    fn contains<'a, P>(&'a self, pat: P) -> bool where P: Pattern<'a> + Clone {
+    fn contains<'a, P>(&'a self, pat: P) -> bool where P: OsPattern<'a> {
         <&os_str::OsStr>::from(self).contains(pat)
     }
-    fn starts_with<'a, P>(&'a self, pat: P) -> bool where P: Pattern<'a> {
+    fn starts_with<'a, P>(&'a self, pat: P) -> bool where P: OsPattern<'a> {
         <&os_str::OsStr>::from(self).starts_with(pat)
     }
     fn ends_with<'a, P>(&'a self, pat: P) -> bool
-        where P: Pattern<'a>, P::Searcher: ReverseSearcher<'a> {
+        where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
         <&os_str::OsStr>::from(self).ends_with(pat)
     }
+    fn find<'a, P>(&'a self, pat: P) -> Option<usize> where P: OsPattern<'a> {
+        <&os_str::OsStr>::from(self).find(pat)
+    }
+    fn rfind<'a, P>(&'a self, pat: P) -> Option<usize>
+        where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
+        <&os_str::OsStr>::from(self).rfind(pat)
+    }
     fn split<'a, P>(&'a self, pat: P) -> Split<'a, P>
-    where P: Pattern<'a> + Clone {
+    where P: OsPattern<'a> {
         <&os_str::OsStr>::from(self).split(pat).into()
     }
     fn rsplit<'a, P>(&'a self, pat: P) -> RSplit<'a, P>
-    where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
         <&os_str::OsStr>::from(self).rsplit(pat).into()
     }
     fn split_terminator<'a, P>(&'a self, pat: P) -> SplitTerminator<'a, P>
-    where P: Pattern<'a> + Clone {
+    where P: OsPattern<'a> {
         <&os_str::OsStr>::from(self).split_terminator(pat).into()
     }
     fn rsplit_terminator<'a, P>(&'a self, pat: P) -> RSplitTerminator<'a, P>
-    where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
         <&os_str::OsStr>::from(self).rsplit_terminator(pat).into()
     }
     fn splitn<'a, P>(&'a self, count: usize, pat: P) -> SplitN<'a, P>
-    where P: Pattern<'a> + Clone {
+    where P: OsPattern<'a> {
         <&os_str::OsStr>::from(self).splitn(count, pat).into()
     }
     fn rsplitn<'a, P>(&'a self, count: usize, pat: P) -> RSplitN<'a, P>
-    where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
         <&os_str::OsStr>::from(self).rsplitn(count, pat).into()
     }
+    fn split_os<'a, 'b, S>(&'a self, sep: &'b S) -> Split<'a, &'b os_str::OsStr>
+    where S: AsRef<ffi::OsStr> + ?Sized {
+        let sep: &os_str::OsStr = sep.as_ref().into();
+        <&os_str::OsStr>::from(self).split_os(sep).into()
+    }
+    fn rsplit_os<'a, 'b, S>(&'a self, sep: &'b S) -> RSplit<'a, &'b os_str::OsStr>
+    where S: AsRef<ffi::OsStr> + ?Sized {
+        let sep: &os_str::OsStr = sep.as_ref().into();
+        <&os_str::OsStr>::from(self).rsplit_os(sep).into()
+    }
+    fn splitn_os<'a, 'b, S>(&'a self, count: usize, sep: &'b S) -> SplitN<'a, &'b os_str::OsStr>
+    where S: AsRef<ffi::OsStr> + ?Sized {
+        let sep: &os_str::OsStr = sep.as_ref().into();
+        <&os_str::OsStr>::from(self).splitn_os(count, sep).into()
+    }
+    fn rsplitn_os<'a, 'b, S>(&'a self, count: usize, sep: &'b S)
+                             -> RSplitN<'a, &'b os_str::OsStr>
+    where S: AsRef<ffi::OsStr> + ?Sized {
+        let sep: &os_str::OsStr = sep.as_ref().into();
+        <&os_str::OsStr>::from(self).rsplitn_os(count, sep).into()
+    }
+    fn split_once<'a, P>(&'a self, pat: P) -> Option<(&'a Self, &'a Self)>
+    where P: OsPattern<'a> {
+        <&os_str::OsStr>::from(self).split_once(pat).map(|(a, b)| (a.into(), b.into()))
+    }
+    fn rsplit_once<'a, P>(&'a self, pat: P) -> Option<(&'a Self, &'a Self)>
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
+        <&os_str::OsStr>::from(self).rsplit_once(pat).map(|(a, b)| (a.into(), b.into()))
+    }
     fn matches<'a, P>(&'a self, pat: P) -> Matches<'a, P>
-    where P: Pattern<'a> + Clone {
+    where P: OsPattern<'a> {
         <&os_str::OsStr>::from(self).matches(pat).into()
     }
     fn rmatches<'a, P>(&'a self, pat: P) -> RMatches<'a, P>
-    where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
         <&os_str::OsStr>::from(self).rmatches(pat).into()
     }
+    fn matches_os<'a, 'b, S>(&'a self, needle: &'b S) -> Matches<'a, &'b os_str::OsStr>
+    where S: AsRef<ffi::OsStr> + ?Sized {
+        let needle: &os_str::OsStr = needle.as_ref().into();
+        <&os_str::OsStr>::from(self).matches_os(needle).into()
+    }
+    fn rmatches_os<'a, 'b, S>(&'a self, needle: &'b S) -> RMatches<'a, &'b os_str::OsStr>
+    where S: AsRef<ffi::OsStr> + ?Sized {
+        let needle: &os_str::OsStr = needle.as_ref().into();
+        <&os_str::OsStr>::from(self).rmatches_os(needle).into()
+    }
     fn trim(&self) -> &Self {
         <&os_str::OsStr>::from(self).trim().into()
     }
+    fn trim_start(&self) -> &Self {
+        <&os_str::OsStr>::from(self).trim_start().into()
+    }
+    fn trim_end(&self) -> &Self {
+        <&os_str::OsStr>::from(self).trim_end().into()
+    }
     fn trim_left(&self) -> &Self {
         <&os_str::OsStr>::from(self).trim_left().into()
     }
@@ -176,6 +508,13 @@ impl OsStrPrototyping for ffi::OsStr {
     where P: Pattern<'a> + Clone, P::Searcher: DoubleEndedSearcher<'a> {
         <&os_str::OsStr>::from(self).trim_matches(pat).into()
     }
+    fn trim_start_matches<'a, P>(&'a self, pat: P) -> &Self where P: Pattern<'a> {
+        <&os_str::OsStr>::from(self).trim_start_matches(pat).into()
+    }
+    fn trim_end_matches<'a, P>(&'a self, pat: P) -> &Self
+    where P: Pattern<'a>, P::Searcher: ReverseSearcher<'a> {
+        <&os_str::OsStr>::from(self).trim_end_matches(pat).into()
+    }
     fn trim_left_matches<'a, P>(&'a self, pat: P) -> &Self where P: Pattern<'a> {
         <&os_str::OsStr>::from(self).trim_left_matches(pat).into()
     }
@@ -183,6 +522,246 @@ impl OsStrPrototyping for ffi::OsStr {
     where P: Pattern<'a>, P::Searcher: ReverseSearcher<'a> {
         <&os_str::OsStr>::from(self).trim_right_matches(pat).into()
     }
+    fn from_bytes(bytes: &[u8]) -> Option<&Self> {
+        os_str::OsStr::from_bytes(bytes).map(|s| s.into())
+    }
+    fn to_bytes(&self) -> Option<&[u8]> {
+        <&os_str::OsStr>::from(self).to_bytes()
+    }
+    fn from_encoded_bytes(bytes: &[u8]) -> Option<&Self> {
+        os_str::OsStr::from_encoded_bytes(bytes).map(|s| s.into())
+    }
+    fn as_encoded_bytes(&self) -> &[u8] {
+        <&os_str::OsStr>::from(self).as_encoded_bytes()
+    }
+    fn to_portable_bytes(&self) -> Vec<u8> {
+        <&os_str::OsStr>::from(self).to_portable_bytes()
+    }
+    fn hash_portable<H: Hasher>(&self, state: &mut H) {
+        <&os_str::OsStr>::from(self).hash_portable(state)
+    }
+    fn percent_encode(&self) -> String {
+        <&os_str::OsStr>::from(self).percent_encode()
+    }
+    fn from_cstr(s: &CStr) -> Option<&Self> {
+        os_str::OsStr::from_cstr(s).map(|s| s.into())
+    }
+    fn to_cstring(&self) -> Option<CString> {
+        <&os_str::OsStr>::from(self).to_cstring()
+    }
+    fn try_to_cstring(&self) -> Result<CString, ToCStringError> {
+        <&os_str::OsStr>::from(self).try_to_cstring()
+    }
+    fn as_cstr_with_nul<'a>(&self, buf: &'a mut Vec<u8>) -> Result<&'a CStr, ToCStringError> {
+        <&os_str::OsStr>::from(self).as_cstr_with_nul(buf)
+    }
+    fn try_to_str(&self) -> Result<&str, OsStrUtf8Error> {
+        <&os_str::OsStr>::from(self).try_to_str()
+    }
+    fn to_cow(&self) -> Cow<ffi::OsStr> {
+        Cow::Borrowed(self)
+    }
+    fn display(&self) -> Display {
+        <&os_str::OsStr>::from(self).display()
+    }
+    fn escape_default(&self) -> EscapeDefault {
+        <&os_str::OsStr>::from(self).escape_default()
+    }
+    fn escape_debug(&self) -> EscapeDebug {
+        <&os_str::OsStr>::from(self).escape_debug()
+    }
+    fn split_at(&self, mid: usize) -> (&Self, &Self) {
+        let (a, b) = <&os_str::OsStr>::from(self).split_at(mid);
+        (a.into(), b.into())
+    }
+    fn try_split_at(&self, mid: usize) -> Option<(&Self, &Self)> {
+        <&os_str::OsStr>::from(self).try_split_at(mid).map(|(a, b)| (a.into(), b.into()))
+    }
+    fn get(&self, range: ops::Range<usize>) -> Option<&Self> {
+        <&os_str::OsStr>::from(self).get(range).map(|s| s.into())
+    }
+    fn slice_pop_char(&self) -> Option<(&Self, char)> {
+        <&os_str::OsStr>::from(self).slice_pop_char().map(|(s, c)| (s.into(), c))
+    }
+    fn is_ascii(&self) -> bool {
+        <&os_str::OsStr>::from(self).is_ascii()
+    }
+    fn eq_ignore_ascii_case<S: AsRef<ffi::OsStr>>(&self, other: S) -> bool {
+        <&os_str::OsStr>::from(self).eq_ignore_ascii_case(<&os_str::OsStr>::from(other.as_ref()))
+    }
+    fn eq_fold<S: AsRef<ffi::OsStr>>(&self, other: S) -> bool {
+        <&os_str::OsStr>::from(self).eq_fold(<&os_str::OsStr>::from(other.as_ref()))
+    }
+    fn cmp_fold<S: AsRef<ffi::OsStr>>(&self, other: S) -> cmp::Ordering {
+        <&os_str::OsStr>::from(self).cmp_fold(<&os_str::OsStr>::from(other.as_ref()))
+    }
+    fn cmp_with<S, C>(&self, other: S, collator: &C) -> cmp::Ordering
+    where S: AsRef<ffi::OsStr>, C: Collator + ?Sized {
+        <&os_str::OsStr>::from(self).cmp_with(<&os_str::OsStr>::from(other.as_ref()), collator)
+    }
+    fn contains_ignore_ascii_case<S: AsRef<ffi::OsStr>>(&self, needle: S) -> bool {
+        <&os_str::OsStr>::from(self)
+            .contains_ignore_ascii_case(<&os_str::OsStr>::from(needle.as_ref()))
+    }
+    fn find_ignore_ascii_case<S: AsRef<ffi::OsStr>>(&self, needle: S) -> Option<usize> {
+        <&os_str::OsStr>::from(self)
+            .find_ignore_ascii_case(<&os_str::OsStr>::from(needle.as_ref()))
+    }
+    fn starts_with_ignore_ascii_case<S: AsRef<ffi::OsStr>>(&self, needle: S) -> bool {
+        <&os_str::OsStr>::from(self)
+            .starts_with_ignore_ascii_case(<&os_str::OsStr>::from(needle.as_ref()))
+    }
+    fn ends_with_ignore_ascii_case<S: AsRef<ffi::OsStr>>(&self, needle: S) -> bool {
+        <&os_str::OsStr>::from(self)
+            .ends_with_ignore_ascii_case(<&os_str::OsStr>::from(needle.as_ref()))
+    }
+    fn make_ascii_lowercase(&mut self) {
+        <&mut os_str::OsStr>::from(self).make_ascii_lowercase()
+    }
+    fn make_ascii_uppercase(&mut self) {
+        <&mut os_str::OsStr>::from(self).make_ascii_uppercase()
+    }
+    fn to_lowercase(&self) -> ffi::OsString {
+        <&os_str::OsStr>::from(self).to_lowercase().into()
+    }
+    fn to_uppercase(&self) -> ffi::OsString {
+        <&os_str::OsStr>::from(self).to_uppercase().into()
+    }
+    fn matches_glob<S: AsRef<ffi::OsStr>>(&self, pattern: S) -> bool {
+        <&os_str::OsStr>::from(self).matches_glob(<&os_str::OsStr>::from(pattern.as_ref()))
+    }
+    fn contains_byte(&self, byte: u8) -> bool {
+        <&os_str::OsStr>::from(self).contains_byte(byte)
+    }
+    fn find_byte(&self, byte: u8) -> Option<usize> {
+        <&os_str::OsStr>::from(self).find_byte(byte)
+    }
+    fn find_os<S: AsRef<ffi::OsStr>>(&self, needle: S) -> Option<usize> {
+        <&os_str::OsStr>::from(self).find_os(<&os_str::OsStr>::from(needle.as_ref()))
+    }
+    fn rfind_os<S: AsRef<ffi::OsStr>>(&self, needle: S) -> Option<usize> {
+        <&os_str::OsStr>::from(self).rfind_os(<&os_str::OsStr>::from(needle.as_ref()))
+    }
+    fn match_indices_os<'a, 'b, S>(&'a self, needle: &'b S) -> MatchIndicesOs<'a, 'b>
+    where S: AsRef<ffi::OsStr> + ?Sized {
+        let needle: &os_str::OsStr = needle.as_ref().into();
+        <&os_str::OsStr>::from(self).match_indices_os(needle).into()
+    }
+    fn rmatch_indices_os<'a, 'b, S>(&'a self, needle: &'b S) -> RMatchIndicesOs<'a, 'b>
+    where S: AsRef<ffi::OsStr> + ?Sized {
+        let needle: &os_str::OsStr = needle.as_ref().into();
+        <&os_str::OsStr>::from(self).rmatch_indices_os(needle).into()
+    }
+    fn count_matches_os<S: AsRef<ffi::OsStr>>(&self, needle: S) -> usize {
+        <&os_str::OsStr>::from(self).count_matches_os(<&os_str::OsStr>::from(needle.as_ref()))
+    }
+    fn split_once_os<S: AsRef<ffi::OsStr>>(&self, needle: S) -> Option<(&Self, &Self)> {
+        <&os_str::OsStr>::from(self).split_once_os(<&os_str::OsStr>::from(needle.as_ref()))
+            .map(|(a, b)| (a.into(), b.into()))
+    }
+    fn rsplit_once_os<S: AsRef<ffi::OsStr>>(&self, needle: S) -> Option<(&Self, &Self)> {
+        <&os_str::OsStr>::from(self).rsplit_once_os(<&os_str::OsStr>::from(needle.as_ref()))
+            .map(|(a, b)| (a.into(), b.into()))
+    }
+    fn strip_prefix<'a, P>(&'a self, pat: P) -> Option<&'a Self> where P: OsPattern<'a> {
+        <&os_str::OsStr>::from(self).strip_prefix(pat).map(|s| s.into())
+    }
+    fn strip_suffix<'a, P>(&'a self, pat: P) -> Option<&'a Self>
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
+        <&os_str::OsStr>::from(self).strip_suffix(pat).map(|s| s.into())
+    }
+    fn count_matches<'a, P>(&'a self, pat: P) -> usize where P: OsPattern<'a> {
+        <&os_str::OsStr>::from(self).count_matches(pat)
+    }
+    fn replace_matches<'a, P>(&'a self, pat: P, to: &str) -> Cow<'a, ffi::OsStr>
+    where P: OsPattern<'a> {
+        cow_into_std(<&os_str::OsStr>::from(self).replace_matches(pat, to))
+    }
+    fn replacen<'a, P>(&'a self, pat: P, to: &str, count: usize) -> Cow<'a, ffi::OsStr>
+    where P: OsPattern<'a> {
+        cow_into_std(<&os_str::OsStr>::from(self).replacen(pat, to, count))
+    }
+    fn split_search_paths<'a>(&'a self) -> SplitSearchPaths<'a> {
+        SplitSearchPaths(<&os_str::OsStr>::from(self).split_search_paths())
+    }
+    #[cfg(feature = "regex")]
+    fn regex_find<'a>(&'a self, re: &Regex) -> Option<(usize, &'a Self)> {
+        <&os_str::OsStr>::from(self).regex_find(re).map(|(i, s)| (i, s.into()))
+    }
+    #[cfg(feature = "regex")]
+    fn regex_matches<'a, 'r>(&'a self, re: &'r Regex) -> RegexMatches<'a, 'r> {
+        RegexMatches(<&os_str::OsStr>::from(self).regex_matches(re))
+    }
+    #[cfg(feature = "regex")]
+    fn regex_split<'a, 'r>(&'a self, re: &'r Regex) -> RegexSplit<'a, 'r> {
+        RegexSplit(<&os_str::OsStr>::from(self).regex_split(re))
+    }
+    #[cfg(feature = "unicode-segmentation")]
+    fn graphemes<'a>(&'a self) -> Graphemes<'a> {
+        <&os_str::OsStr>::from(self).graphemes().into()
+    }
+    #[cfg(feature = "unicode-width")]
+    fn display_width(&self) -> usize {
+        <&os_str::OsStr>::from(self).display_width()
+    }
+    #[cfg(feature = "unicode-width")]
+    fn display_width_with(&self, non_unicode_width: usize) -> usize {
+        <&os_str::OsStr>::from(self).display_width_with(non_unicode_width)
+    }
+}
+
+/// String operations on the `OsStr` data of a path.  These act on the
+/// path as a whole, not on its components.  Methods whose names would
+/// collide with inherent `Path` methods (like `starts_with`) are
+/// omitted; use the `_os` variants or `as_os_str()` instead.
+pub trait PathPrototyping {
+    fn split_unicode<'a>(&'a self) -> SplitUnicode<'a>;
+    fn contains_os<S: AsRef<ffi::OsStr>>(&self, needle: S) -> bool;
+    fn starts_with_os<S: AsRef<ffi::OsStr>>(&self, needle: S) -> bool;
+    fn ends_with_os<S: AsRef<ffi::OsStr>>(&self, needle: S) -> bool;
+    fn contains<'a, P>(&'a self, pat: P) -> bool where P: OsPattern<'a>;
+    fn split<'a, P>(&'a self, pat: P) -> Split<'a, P>
+        where P: OsPattern<'a>;
+    fn rsplit<'a, P>(&'a self, pat: P) -> RSplit<'a, P>
+        where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a>;
+    fn matches<'a, P>(&'a self, pat: P) -> Matches<'a, P>
+        where P: OsPattern<'a>;
+    fn rmatches<'a, P>(&'a self, pat: P) -> RMatches<'a, P>
+        where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a>;
+}
+
+impl PathPrototyping for Path {
+    fn split_unicode<'a>(&'a self) -> SplitUnicode<'a> {
+        self.as_os_str().split_unicode()
+    }
+    fn contains_os<S: AsRef<ffi::OsStr>>(&self, needle: S) -> bool {
+        self.as_os_str().contains_os(needle)
+    }
+    fn starts_with_os<S: AsRef<ffi::OsStr>>(&self, needle: S) -> bool {
+        self.as_os_str().starts_with_os(needle)
+    }
+    fn ends_with_os<S: AsRef<ffi::OsStr>>(&self, needle: S) -> bool {
+        self.as_os_str().ends_with_os(needle)
+    }
+    fn contains<'a, P>(&'a self, pat: P) -> bool where P: OsPattern<'a> {
+        self.as_os_str().contains(pat)
+    }
+    fn split<'a, P>(&'a self, pat: P) -> Split<'a, P>
+    where P: OsPattern<'a> {
+        self.as_os_str().split(pat)
+    }
+    fn rsplit<'a, P>(&'a self, pat: P) -> RSplit<'a, P>
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
+        self.as_os_str().rsplit(pat)
+    }
+    fn matches<'a, P>(&'a self, pat: P) -> Matches<'a, P>
+    where P: OsPattern<'a> {
+        self.as_os_str().matches(pat)
+    }
+    fn rmatches<'a, P>(&'a self, pat: P) -> RMatches<'a, P>
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
+        self.as_os_str().rmatches(pat)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -200,6 +779,21 @@ impl<'a> From<os_str::OsStrSection<'a>> for OsStrSection<'a> {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Popped {
+    Char(char),
+    NonUnicode(ffi::OsString),
+}
+
+impl From<os_str::Popped> for Popped {
+    fn from(x: os_str::Popped) -> Popped {
+        match x {
+            os_str::Popped::Char(c) => Popped::Char(c),
+            os_str::Popped::NonUnicode(s) => Popped::NonUnicode(s.into()),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct SplitUnicode<'a>(os_str::SplitUnicode<'a>);
 
@@ -215,6 +809,10 @@ impl<'a> Iterator for SplitUnicode<'a> {
     fn next(&mut self) -> Option<OsStrSection<'a>> {
         self.0.next().map(|x| x.into())
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
 }
 
 impl<'a> DoubleEndedIterator for SplitUnicode<'a> {
@@ -223,6 +821,67 @@ impl<'a> DoubleEndedIterator for SplitUnicode<'a> {
     }
 }
 
+impl<'a> FusedIterator for SplitUnicode<'a> {}
+
+#[derive(Clone)]
+pub struct SplitSearchPaths<'a>(os_str::SplitSearchPaths<'a>);
+
+impl<'a> Iterator for SplitSearchPaths<'a> {
+    type Item = ffi::OsString;
+
+    fn next(&mut self) -> Option<ffi::OsString> {
+        self.0.next().map(|x| x.into())
+    }
+}
+
+#[cfg(feature = "regex")]
+macro_rules! forward_regex_iterator {
+    ($name:ident) => {
+        pub struct $name<'a, 'r>(os_str::$name<'a, 'r>);
+
+        impl<'a, 'r> Iterator for $name<'a, 'r> {
+            type Item = &'a ffi::OsStr;
+
+            fn next(&mut self) -> Option<&'a ffi::OsStr> {
+                self.0.next().map(|x| x.into())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "regex")]
+forward_regex_iterator!{RegexMatches}
+#[cfg(feature = "regex")]
+forward_regex_iterator!{RegexSplit}
+
+macro_rules! forward_match_indices {
+    ($name:ident) => {
+        #[derive(Clone)]
+        pub struct $name<'a, 'b> {
+            inner: os_str::$name<'a, 'b>
+        }
+
+        impl<'a, 'b> From<os_str::$name<'a, 'b>> for $name<'a, 'b> {
+            fn from(x: os_str::$name<'a, 'b>) -> $name<'a, 'b> {
+                $name { inner: x }
+            }
+        }
+
+        impl<'a, 'b> Iterator for $name<'a, 'b> {
+            type Item = (usize, &'a ffi::OsStr);
+
+            fn next(&mut self) -> Option<(usize, &'a ffi::OsStr)> {
+                self.inner.next().map(|(i, x)| (i, x.into()))
+            }
+        }
+
+        impl<'a, 'b> FusedIterator for $name<'a, 'b> {}
+    }
+}
+
+forward_match_indices!{MatchIndicesOs}
+forward_match_indices!{RMatchIndicesOs}
+
 
 macro_rules! forward_iterator_simple {
     ($name:ident) => {
@@ -243,6 +902,10 @@ macro_rules! forward_iterator_simple {
             fn next(&mut self) -> Option<&'a ffi::OsStr> {
                 self.inner.next().map(|x| x.into())
             }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.inner.size_hint()
+            }
         }
 
         impl<'a> DoubleEndedIterator for $name<'a> {
@@ -250,54 +913,69 @@ macro_rules! forward_iterator_simple {
                 self.inner.next_back().map(|x| x.into())
             }
         }
+
+        impl<'a> FusedIterator for $name<'a> {}
     }
 }
 macro_rules! forward_iterator {
     ($forward:ident and $reverse:ident) => {
-        pub struct $forward<'a, P> where P: Pattern<'a> {
+        pub struct $forward<'a, P> where P: OsPattern<'a> {
             inner: os_str::$forward<'a, P>
         }
 
-        impl<'a, P> Clone for $forward<'a, P> where P: Pattern<'a> + Clone, P::Searcher: Clone {
+        impl<'a, P> Clone for $forward<'a, P> where P: OsPattern<'a>, P::Searcher: Clone {
             fn clone(&self) -> Self { $forward { inner: self.inner.clone() } }
         }
 
-        impl<'a, P> From<os_str::$forward<'a, P>> for $forward<'a, P> where P: Pattern<'a> {
+        impl<'a, P> From<os_str::$forward<'a, P>> for $forward<'a, P> where P: OsPattern<'a> {
             fn from(x: os_str::$forward<'a, P>) -> $forward<'a, P> {
                 $forward { inner: x }
             }
         }
 
-        impl<'a, P> Iterator for $forward<'a, P> where P: Pattern<'a> + Clone {
+        impl<'a, P> Iterator for $forward<'a, P> where P: OsPattern<'a> {
             type Item = &'a ffi::OsStr;
 
             fn next(&mut self) -> Option<&'a ffi::OsStr> {
                 self.inner.next().map(|x| x.into())
             }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.inner.size_hint()
+            }
         }
 
-        pub struct $reverse<'a, P> where P: Pattern<'a> {
+        impl<'a, P> FusedIterator for $forward<'a, P> where P: OsPattern<'a> {}
+
+        pub struct $reverse<'a, P> where P: OsPattern<'a> {
             inner: os_str::$reverse<'a, P>
         }
 
-        impl<'a, P> Clone for $reverse<'a, P> where P: Pattern<'a> + Clone, P::Searcher: Clone {
+        impl<'a, P> Clone for $reverse<'a, P> where P: OsPattern<'a>, P::Searcher: Clone {
             fn clone(&self) -> Self { $reverse { inner: self.inner.clone() } }
         }
 
-        impl<'a, P> From<os_str::$reverse<'a, P>> for $reverse<'a, P> where P: Pattern<'a> {
+        impl<'a, P> From<os_str::$reverse<'a, P>> for $reverse<'a, P> where P: OsPattern<'a> {
             fn from(x: os_str::$reverse<'a, P>) -> $reverse<'a, P> {
                 $reverse { inner: x }
             }
         }
 
         impl<'a, P> Iterator for $reverse<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
+        where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
             type Item = &'a ffi::OsStr;
 
             fn next(&mut self) -> Option<&'a ffi::OsStr> {
                 self.inner.next().map(|x| x.into())
             }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.inner.size_hint()
+            }
         }
+
+        impl<'a, P> FusedIterator for $reverse<'a, P>
+        where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {}
     }
 }
 macro_rules! forward_double_ended {
@@ -305,14 +983,14 @@ macro_rules! forward_double_ended {
         forward_iterator!{$forward and $reverse}
 
         impl<'a, P> DoubleEndedIterator for $forward<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: DoubleEndedSearcher<'a> {
+        where P: OsPattern<'a>, P::Searcher: DoubleEndedOsSearcher<'a> {
             fn next_back(&mut self) -> Option<&'a ffi::OsStr> {
                 self.inner.next_back().map(|x| x.into())
             }
         }
 
         impl<'a, P> DoubleEndedIterator for $reverse<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: DoubleEndedSearcher<'a> {
+        where P: OsPattern<'a>, P::Searcher: DoubleEndedOsSearcher<'a> {
             fn next_back(&mut self) -> Option<&'a ffi::OsStr> {
                 self.inner.next_back().map(|x| x.into())
             }
@@ -322,41 +1000,183 @@ macro_rules! forward_double_ended {
 
 forward_iterator_simple!{SplitWhitespace}
 forward_iterator_simple!{Lines}
+#[cfg(feature = "unicode-segmentation")]
+forward_iterator_simple!{Graphemes}
 forward_double_ended!{Split and RSplit}
 forward_double_ended!{SplitTerminator and RSplitTerminator}
 forward_iterator!{SplitN and RSplitN}
-pub use os_str::{Matches, RMatches};
+forward_double_ended!{Matches and RMatches}
+
+macro_rules! forward_remainder {
+    ($($name:ident)*) => { $(
+        impl<'a, P> $name<'a, P> where P: OsPattern<'a> {
+            pub fn remainder(&self) -> Option<&'a ffi::OsStr> {
+                self.inner.remainder().map(|x| x.into())
+            }
+        }
+    )* }
+}
+
+forward_remainder!{Split RSplit SplitTerminator RSplitTerminator SplitN RSplitN}
+
+impl<'a, P> DoubleEndedIterator for SplitN<'a, P>
+where P: OsPattern<'a>, P::Searcher: DoubleEndedOsSearcher<'a> + Clone {
+    fn next_back(&mut self) -> Option<&'a ffi::OsStr> {
+        self.inner.next_back().map(|x| x.into())
+    }
+}
+
+impl<'a, P> DoubleEndedIterator for RSplitN<'a, P>
+where P: OsPattern<'a>, P::Searcher: DoubleEndedOsSearcher<'a> + Clone {
+    fn next_back(&mut self) -> Option<&'a ffi::OsStr> {
+        self.inner.next_back().map(|x| x.into())
+    }
+}
+pub use os_str::{CharsLossy, InvalidSections, StrChunksLossy, Utf8Chunks};
+pub use os_str::{Display, EscapeDebug, EscapeDefault};
+pub use os_str::{IntoStringError, JoinSearchPathsError, OsStrUtf8Error, ToCStringError};
 
 
 impl<S: Borrow<ffi::OsStr>> LocalSliceConcatExt<ffi::OsStr> for [S] {
     type Output = ffi::OsString;
 
     fn concat(&self) -> Self::Output {
-        self.iter().map(|s| <&os_str::OsStr>::from(s.borrow())).collect::<Vec<_>>().concat().into()
+        let len = self.iter().map(|s| s.borrow().len()).sum();
+        let mut result = ffi::OsString::with_capacity(len);
+        for s in self {
+            result.push(s.borrow());
+        }
+        result
     }
     fn join(&self, sep: &ffi::OsStr) -> Self::Output {
-        self.iter().map(|s| <&os_str::OsStr>::from(s.borrow())).collect::<Vec<_>>().join(<&os_str::OsStr>::from(sep.borrow())).into()
+        if self.is_empty() {
+            return ffi::OsString::new();
+        }
+        // On Windows this may be a slight overestimate, but that's OK.
+        let len = sep.len() * (self.len() - 1)
+            + self.iter().map(|s| s.borrow().len()).sum::<usize>();
+        let mut result = ffi::OsString::with_capacity(len);
+        push_joined(&mut result, self.iter().map(|s| s.borrow()), sep);
+        result
     }
     fn connect(&self, sep: &ffi::OsStr) -> Self::Output {
         self.join(sep)
     }
 }
 
+/// Concatenates the strings produced by an iterator.
+///
+/// This is `LocalSliceConcatExt::concat` for any `IntoIterator`.
+/// Because the strings can only be visited once, the result grows as
+/// they are pushed instead of being allocated up front.
+pub fn concat<I>(strings: I) -> ffi::OsString
+where I: IntoIterator, I::Item: AsRef<ffi::OsStr> {
+    let mut result = ffi::OsString::new();
+    for s in strings {
+        result.push(s.as_ref());
+    }
+    result
+}
+
+/// Joins the strings produced by an iterator, placing `sep` between
+/// each pair.
+///
+/// This is `LocalSliceConcatExt::join` for any `IntoIterator`.
+pub fn join<I>(strings: I, sep: &ffi::OsStr) -> ffi::OsString
+where I: IntoIterator, I::Item: AsRef<ffi::OsStr> {
+    let mut result = ffi::OsString::new();
+    push_joined(&mut result, strings, sep);
+    result
+}
+
+fn push_joined<I>(result: &mut ffi::OsString, strings: I, sep: &ffi::OsStr)
+where I: IntoIterator, I::Item: AsRef<ffi::OsStr> {
+    let mut first = true;
+    for s in strings {
+        if first {
+            first = false;
+        } else {
+            result.push(sep);
+        }
+        result.push(s.as_ref());
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
     use std::prelude::v1::*;
     use prelude::*;
-    use std::ffi::{OsStr, OsString};
+    use std::borrow::Cow;
+    use std::ffi::{CString, OsStr, OsString};
+    use os_str::ToCStringError;
+    #[cfg(unix)]
+    use std::os::unix::ffi::{OsStrExt, OsStringExt};
+
+    #[test]
+    #[cfg(unix)]
+    fn conversions() {
+        use os_str;
+
+        let mut string = OsString::with_capacity(100);
+        string.push("hello");
+        let ptr = string.as_bytes().as_ptr();
+        let proto = os_str::OsString::from(string);
+        assert_eq!(proto, "hello");
+        let string = OsString::from(proto);
+        assert_eq!(string, OsStr::new("hello"));
+        assert_eq!(string.as_bytes().as_ptr(), ptr);
+        assert!(string.capacity() >= 100);
+
+        let bytes = &b"a\xFFb"[..];
+        let proto = <&os_str::OsStr>::from(<OsStr as OsStrExt>::from_bytes(bytes));
+        assert_eq!(proto.to_bytes(), Some(bytes));
+        assert_eq!(<&OsStr>::from(proto).as_bytes(), bytes);
+        assert_eq!(OsString::from(proto.to_os_string()).as_bytes(), bytes);
+
+        let mut string = OsString::from_vec(bytes.to_vec());
+        string.push_str("c");
+        assert_eq!(string.as_bytes(), b"a\xFFbc");
+    }
 
     #[test]
     fn osstring() {
         assert!(OsString::with_capacity(10).capacity() >= 10);
+        let mut string = OsString::new();
+        OsStringPrototyping::reserve(&mut string, 10);
+        assert!(string.capacity() >= 10);
+        OsStringPrototyping::reserve_exact(&mut string, 20);
+        assert!(string.capacity() >= 20);
         let string = OsString::from("hello");
         assert_eq!(string.into_string_lossy(), "hello");
+        let string = OsString::from("hello");
+        assert_eq!(string.into_string_with_replacement("?"), "hello");
         let mut string = OsString::from("hello");
         string.clear();
         assert_eq!(string, OsString::from(""));
+
+        assert_eq!(<OsString as OsStringPrototyping>::from_bytes(&b"ab"[..]),
+                   Some(OsString::from("ab")));
+        assert_eq!(OsString::from_portable_bytes(&OsStr::new("ab").to_portable_bytes()),
+                   Ok(OsString::from("ab")));
+        assert_eq!(OsString::from_percent_encoded("a%20b"), Some(OsString::from("a b")));
+        assert_eq!(OsString::from_cstring(CString::new("ab").unwrap()),
+                   Some(OsString::from("ab")));
+        let sep = if cfg!(windows) { "a;b" } else { "a:b" };
+        assert_eq!(OsString::join_search_paths(&["a", "b"]), Ok(OsString::from(sep)));
+        let mut string = OsString::with_capacity(100);
+        OsStringPrototyping::push_str(&mut string, "hello");
+        string.push_char('!');
+        OsStringPrototyping::shrink_to(&mut string, 10);
+        assert!(string.capacity() >= 10);
+        OsStringPrototyping::shrink_to_fit(&mut string);
+        assert_eq!(string.pop(), Some(Popped::Char('!')));
+        string.truncate(4);
+        string.retain(|c| c != 'e', |_| true);
+        assert_eq!(string, OsString::from("hll"));
+        assert_eq!(string.clone().try_into_string(), Ok("hll".to_string()));
+        assert_eq!(&*string.clone().into_cow(), OsStr::new("hll"));
+        assert_eq!(&*OsStringPrototyping::into_boxed_os_str(string), OsStr::new("hll"));
     }
 
     #[test]
@@ -365,6 +1185,11 @@ mod tests {
         assert!(!string.is_empty());
         assert_eq!(string.len(), 5);
         assert_eq!(string.split_unicode().next(), Some(OsStrSection::Unicode("hello")));
+        assert_eq!(string.chars_lossy().collect::<String>(), "hello");
+        assert_eq!(string.to_string_with_replacement("?"), "hello");
+        assert_eq!(string.to_str_chunks_lossy().collect::<Vec<_>>(), ["hello"]);
+        assert_eq!(string.utf8_chunks().collect::<Vec<_>>(), [("hello", &b""[..])]);
+        assert!(string.invalid_sections().next().is_none());
         assert_eq!(OsStr::new("\nHello  World").split_whitespace().collect::<Vec<_>>(),
                    [OsStr::new("Hello"), OsStr::new("World")]);
         assert_eq!(OsStr::new("\nHello\n  World").lines().collect::<Vec<_>>(),
@@ -376,10 +1201,28 @@ mod tests {
         assert!(string.contains("ll"));
         assert!(string.starts_with("he"));
         assert!(string.ends_with("lo"));
+        assert_eq!(string.find('l'), Some(2));
+        assert_eq!(string.split_os(OsStr::new("l")).collect::<Vec<_>>(),
+                   [OsStr::new("he"), OsStr::new(""), OsStr::new("o")]);
+        assert_eq!(string.rsplitn_os(2, "l").collect::<Vec<_>>(),
+                   [OsStr::new("o"), OsStr::new("hel")]);
+        assert_eq!(string.matches_os("l").count(), 2);
+        assert_eq!(string.rmatches_os(OsStr::new("lo")).collect::<Vec<_>>(), [OsStr::new("lo")]);
+        assert_eq!(string.rfind('l'), Some(3));
         assert_eq!(string.split('l').collect::<Vec<_>>(),
                    [OsStr::new("he"), OsStr::new(""), OsStr::new("o")]);
         assert_eq!(string.rsplit('l').collect::<Vec<_>>(),
                    [OsStr::new("o"), OsStr::new(""), OsStr::new("he")]);
+        assert_eq!(string.rsplit('l').fuse().rev().collect::<Vec<_>>(),
+                   [OsStr::new("he"), OsStr::new(""), OsStr::new("o")]);
+        assert_eq!(string.split('l').size_hint(), (1, Some(7)));
+        let mut split = string.split('l');
+        split.next();
+        assert_eq!(split.remainder(), Some(OsStr::new("lo")));
+        assert_eq!(string.splitn(2, 'l').rev().collect::<Vec<_>>(),
+                   [OsStr::new("lo"), OsStr::new("he")]);
+        assert_eq!(string.rsplitn(2, 'l').clone().rev().collect::<Vec<_>>(),
+                   [OsStr::new("hel"), OsStr::new("o")]);
         assert_eq!(string.split_terminator('o').collect::<Vec<_>>(),
                    [OsStr::new("hell")]);
         assert_eq!(string.rsplit_terminator('o').collect::<Vec<_>>(),
@@ -388,14 +1231,140 @@ mod tests {
                    [OsStr::new("he"), OsStr::new("lo")]);
         assert_eq!(string.rsplitn(2, 'l').collect::<Vec<_>>(),
                    [OsStr::new("o"), OsStr::new("hel")]);
+        assert_eq!(string.split_once('l'), Some((OsStr::new("he"), OsStr::new("lo"))));
+        assert_eq!(string.rsplit_once('l'), Some((OsStr::new("hel"), OsStr::new("o"))));
         assert_eq!(string.matches('l').collect::<Vec<_>>(), ["l"; 2]);
         assert_eq!(string.rmatches('l').collect::<Vec<_>>(), ["l"; 2]);
         assert_eq!(OsStr::new(" \nHello World ").trim(), OsStr::new("Hello World"));
+        assert_eq!(OsStr::new(" \nHello World ").trim_start(), OsStr::new("Hello World "));
+        assert_eq!(OsStr::new(" \nHello World ").trim_end(), OsStr::new(" \nHello World"));
         assert_eq!(OsStr::new(" \nHello World ").trim_left(), OsStr::new("Hello World "));
         assert_eq!(OsStr::new(" \nHello World ").trim_right(), OsStr::new(" \nHello World"));
         assert_eq!(OsStr::new("aabcaa").trim_matches('a'), OsStr::new("bc"));
+        assert_eq!(OsStr::new("aabcaa").trim_start_matches('a'), OsStr::new("bcaa"));
+        assert_eq!(OsStr::new("aabcaa").trim_end_matches('a'), OsStr::new("aabc"));
         assert_eq!(OsStr::new("aabcaa").trim_left_matches('a'), OsStr::new("bcaa"));
         assert_eq!(OsStr::new("aabcaa").trim_right_matches('a'), OsStr::new("aabc"));
+
+        assert_eq!(<OsStr as OsStrPrototyping>::from_bytes(b"ab"), Some(OsStr::new("ab")));
+        assert_eq!(string.to_bytes(), Some(&b"hello"[..]));
+        assert_eq!(OsStrPrototyping::as_encoded_bytes(&*string), b"hello");
+        assert_eq!(<OsStr as OsStrPrototyping>::from_encoded_bytes(b"ab"),
+                   Some(OsStr::new("ab")));
+        assert_eq!(OsStr::new("a b").percent_encode(), "a%20b");
+        {
+            use std::collections::hash_map::DefaultHasher;
+            use std::hash::{Hash, Hasher};
+            use os_str;
+            let (mut std_hasher, mut proto_hasher) = (DefaultHasher::new(), DefaultHasher::new());
+            OsStr::new("ab").hash_portable(&mut std_hasher);
+            os_str::PortableHash(os_str::OsStr::new("ab")).hash(&mut proto_hasher);
+            assert_eq!(std_hasher.finish(), proto_hasher.finish());
+        }
+        let cstr = CString::new("ab").unwrap();
+        assert_eq!(OsStr::from_cstr(&cstr), Some(OsStr::new("ab")));
+        assert_eq!(string.to_cstring(), Some(CString::new("hello").unwrap()));
+        assert_eq!(OsStr::new("a\0b").try_to_cstring(), Err(ToCStringError::InteriorNul(1)));
+        let mut buf = Vec::new();
+        assert_eq!(string.as_cstr_with_nul(&mut buf).unwrap().to_bytes(), b"hello");
+        assert_eq!(string.try_to_str(), Ok("hello"));
+        assert_eq!(string.to_cow(), Cow::Borrowed(OsStr::new("hello")));
+        assert_eq!(OsStrPrototyping::display(&*string).to_string(), "hello");
+        assert_eq!(OsStr::new("a\tb").escape_default().to_string(), "a\\tb");
+        assert_eq!(OsStr::new("a\tb").escape_debug().to_string(), "a\\tb");
+        assert_eq!(string.split_at(2), (OsStr::new("he"), OsStr::new("llo")));
+        assert_eq!(string.try_split_at(6), None);
+        assert_eq!(string.get(1..3), Some(OsStr::new("el")));
+        assert_eq!(string.slice_pop_char(), Some((OsStr::new("hell"), 'o')));
+        assert!(OsStrPrototyping::is_ascii(&*string));
+        assert!(OsStrPrototyping::eq_ignore_ascii_case(&*string, "HeLLo"));
+        assert!(OsStr::new("Straße").eq_fold("STRASSE"));
+        assert_eq!(OsStr::new("a").cmp_fold("B"), ::std::cmp::Ordering::Less);
+        assert_eq!(OsStr::new("a").cmp_with("b", &|a: &str, b: &str| b.cmp(a)),
+                   ::std::cmp::Ordering::Greater);
+        assert!(string.contains_ignore_ascii_case("LL"));
+        assert_eq!(string.find_ignore_ascii_case("LO"), Some(3));
+        assert!(string.starts_with_ignore_ascii_case("HE"));
+        assert!(string.ends_with_ignore_ascii_case("LO"));
+        let mut upper = string.clone();
+        OsStrPrototyping::make_ascii_uppercase(&mut *upper);
+        assert_eq!(upper, OsString::from("HELLO"));
+        OsStrPrototyping::make_ascii_lowercase(&mut *upper);
+        assert_eq!(upper, string);
+        assert_eq!(string.to_uppercase(), OsString::from("HELLO"));
+        assert_eq!(upper.to_uppercase().to_lowercase(), string);
+        assert!(string.matches_glob("h*o"));
+        assert!(string.contains_byte(b'e'));
+        assert_eq!(string.find_byte(b'l'), Some(2));
+        assert_eq!(string.find_os("l"), Some(2));
+        assert_eq!(string.rfind_os("l"), Some(3));
+        assert_eq!(string.match_indices_os("l").collect::<Vec<_>>(),
+                   [(2, OsStr::new("l")), (3, OsStr::new("l"))]);
+        assert_eq!(string.rmatch_indices_os("l").collect::<Vec<_>>(),
+                   [(3, OsStr::new("l")), (2, OsStr::new("l"))]);
+        assert_eq!(string.count_matches_os("l"), 2);
+        assert_eq!(string.split_once_os("l"), Some((OsStr::new("he"), OsStr::new("lo"))));
+        assert_eq!(string.rsplit_once_os("l"), Some((OsStr::new("hel"), OsStr::new("o"))));
+        assert_eq!(string.strip_prefix("he"), Some(OsStr::new("llo")));
+        assert_eq!(string.strip_suffix('o'), Some(OsStr::new("hell")));
+        assert_eq!(string.count_matches('l'), 2);
+        assert_eq!(string.replace_matches('l', "L"), OsStr::new("heLLo"));
+        assert_eq!(string.replacen('l', "L", 1), OsStr::new("heLlo"));
+        assert_eq!(string.replacen('x', "L", 1), Cow::Borrowed(OsStr::new("hello")));
+        let paths = if cfg!(windows) { "a;b" } else { "a:b" };
+        assert_eq!(OsStr::new(paths).split_search_paths().collect::<Vec<_>>(),
+                   [OsString::from("a"), OsString::from("b")]);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn osstr_regex() {
+        use regex::bytes::Regex;
+
+        let re = Regex::new(r"[0-9]+").unwrap();
+        let string = OsStr::new("img12_3.png");
+        assert_eq!(string.regex_find(&re), Some((3, OsStr::new("12"))));
+        assert_eq!(string.regex_matches(&re).collect::<Vec<_>>(), ["12", "3"]);
+        assert_eq!(string.regex_split(&re).collect::<Vec<_>>(), ["img", "_", ".png"]);
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    #[test]
+    fn osstr_graphemes() {
+        let string = OsStr::new("cafe\u{301}!");
+        assert_eq!(string.graphemes().collect::<Vec<_>>(), ["c", "a", "f", "e\u{301}", "!"]);
+        assert_eq!(string.graphemes().next_back(), Some(OsStr::new("!")));
+    }
+
+    #[cfg(feature = "unicode-width")]
+    #[test]
+    fn osstr_display_width() {
+        assert_eq!(OsStr::new("a\u{301}b").display_width(), 2);
+        assert_eq!(OsStr::new("ab").display_width_with(3), 2);
+    }
+
+    #[test]
+    fn path() {
+        use std::path::{Path, PathBuf};
+
+        let path = Path::new("dir/file.tar.gz");
+        assert_eq!(path.split_unicode().next(),
+                   Some(OsStrSection::Unicode("dir/file.tar.gz")));
+        assert!(path.contains_os(OsStr::new("r/f")));
+        assert!(path.starts_with_os(OsStr::new("di")));
+        assert!(!path.starts_with(Path::new("di")));
+        assert!(path.ends_with_os(OsStr::new(".gz")));
+        assert_eq!(path.as_os_str().strip_suffix(".gz"), Some(OsStr::new("dir/file.tar")));
+        assert!(path.contains("file"));
+        assert_eq!(path.split('.').collect::<Vec<_>>(),
+                   [OsStr::new("dir/file"), OsStr::new("tar"), OsStr::new("gz")]);
+        assert_eq!(path.rsplit('/').collect::<Vec<_>>(),
+                   [OsStr::new("file.tar.gz"), OsStr::new("dir")]);
+        assert_eq!(path.matches('.').count(), 2);
+        assert_eq!(path.rmatches('r').collect::<Vec<_>>(), ["r"; 2]);
+
+        let path = PathBuf::from("dir/file");
+        assert!(path.contains_os(OsStr::new("r/f")));
     }
 
     #[test]
@@ -404,5 +1373,21 @@ mod tests {
                    OsString::from("Helloworld"));
         assert_eq!([OsStr::new("Hello"), OsStr::new("world")].join(OsStr::new(" ")),
                    OsString::from("Hello world"));
+        let empty: [&OsStr; 0] = [];
+        assert_eq!(empty.join(OsStr::new(" ")), OsString::new());
+        assert_eq!([OsStr::new("Hello")].join(OsStr::new(" ")), OsString::from("Hello"));
+    }
+
+    #[test]
+    fn concat_join_iter() {
+        use std::path::Path;
+        use super::{concat, join};
+
+        let words = "a b c".split(' ');
+        assert_eq!(concat(words.clone()), OsString::from("abc"));
+        assert_eq!(join(words, OsStr::new(", ")), OsString::from("a, b, c"));
+        assert_eq!(join(Vec::<String>::new(), OsStr::new(", ")), OsString::new());
+        assert_eq!(join(Path::new("usr/local/bin").iter(), OsStr::new(":")),
+                   OsString::from("usr:local:bin"));
     }
 }
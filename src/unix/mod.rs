@@ -11,18 +11,26 @@
 /// The underlying OsString/OsStr implementation on Unix systems: just
 /// a `Vec<u8>`/`[u8]`.
 
-use slice_searcher::SliceSearcher;
+use os_pattern::{DoubleEndedOsSearcher, OsPattern, OsSearcher, ReverseOsSearcher};
+use slice_searcher::{ReverseSliceSearcher, SliceSearcher};
 use split_bytes;
 use utf8_sections::{self, Utf8Sections};
+use wtf8::encode_utf8_raw;
 
+use std::ascii::AsciiExt;
 use std::borrow::Cow;
+use std::boxed::Box;
+use std::cmp;
 use std::fmt::{self, Debug};
+use std::iter::FusedIterator;
 use std::vec::Vec;
 use std::str;
-use core::str::pattern::{DoubleEndedSearcher, Pattern, ReverseSearcher, Searcher};
+use core::str::pattern::{DoubleEndedSearcher, Pattern, ReverseSearcher};
 use std::string::String;
 use std::mem;
 
+const UTF8_REPLACEMENT_CHARACTER: &'static [u8] = b"\xEF\xBF\xBD";
+
 #[derive(Clone, Hash)]
 pub struct Buf {
     pub inner: Vec<u8>
@@ -53,6 +61,10 @@ impl Buf {
         unsafe { mem::transmute(&*self.inner) }
     }
 
+    pub fn as_mut_slice(&mut self) -> &mut Slice {
+        unsafe { mem::transmute(&mut *self.inner) }
+    }
+
     pub fn with_capacity(capacity: usize) -> Self {
         Buf { inner: Vec::with_capacity(capacity) }
     }
@@ -65,27 +77,163 @@ impl Buf {
         self.inner.reserve(additional)
     }
 
-    fn reserve_exact(&mut self, additional: usize) {
+    pub fn reserve_exact(&mut self, additional: usize) {
         self.inner.reserve_exact(additional)
     }
 
+    pub fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit()
+    }
+
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        let capacity = cmp::max(self.inner.len(), min_capacity);
+        if self.inner.capacity() > capacity {
+            let mut inner = Vec::with_capacity(capacity);
+            inner.extend_from_slice(&self.inner);
+            self.inner = inner;
+        }
+    }
+
     pub fn into_string(self) -> Result<String, Buf> {
         String::from_utf8(self.inner).map_err(|p| Buf { inner: p.into_bytes() } )
     }
 
+    /// Replaces each invalid sequence with U+FFFD in the existing
+    /// buffer, which only reallocates if the result outgrows its
+    /// capacity.
     pub fn into_string_lossy(self) -> String {
-        self.into_string().unwrap_or_else(|buf| buf.as_slice().to_string_lossy().into_owned())
+        let mut inner = match self.into_string() {
+            Ok(string) => return string,
+            Err(buf) => buf.inner,
+        };
+
+        // A replacement character is at least as long as the sequence
+        // it replaces, so work out how much the string grows first.
+        let len = inner.len();
+        let mut extra = 0;
+        let mut pos = 0;
+        while let Err(error) = str::from_utf8(&inner[pos..]) {
+            pos += error.valid_up_to();
+            let invalid = utf8_sections::invalid_len(&inner[pos..]);
+            extra += UTF8_REPLACEMENT_CHARACTER.len() - invalid;
+            pos += invalid;
+        }
+
+        // Move the data to the end of the buffer and rebuild it from
+        // the front.  The output never overtakes the input, because
+        // the remaining growth is never more than the gap between them.
+        if extra > 0 {
+            inner.resize(len + extra, 0);
+            for i in (0..len).rev() {
+                inner[i + extra] = inner[i];
+            }
+        }
+        let mut read = extra;
+        let mut write = 0;
+        loop {
+            let (valid, done) = match str::from_utf8(&inner[read..]) {
+                Ok(s) => (s.len(), true),
+                Err(error) => (error.valid_up_to(), false),
+            };
+            move_bytes(&mut inner, read, write, valid);
+            read += valid;
+            write += valid;
+            if done { break; }
+
+            let invalid = utf8_sections::invalid_len(&inner[read..]);
+            read += invalid;
+            inner[write..write + UTF8_REPLACEMENT_CHARACTER.len()]
+                .copy_from_slice(UTF8_REPLACEMENT_CHARACTER);
+            write += UTF8_REPLACEMENT_CHARACTER.len();
+        }
+        debug_assert_eq!(write, inner.len());
+        unsafe { String::from_utf8_unchecked(inner) }
     }
 
     pub fn push_slice(&mut self, s: &Slice) {
         self.inner.extend_from_slice(&s.inner)
     }
 
+    pub fn into_box(self) -> Box<Slice> {
+        unsafe { mem::transmute(self.inner.into_boxed_slice()) }
+    }
+
+    pub fn from_box(boxed: Box<Slice>) -> Buf {
+        let inner: Box<[u8]> = unsafe { mem::transmute(boxed) };
+        Buf { inner: inner.into_vec() }
+    }
+
+    pub fn push_str(&mut self, s: &str) {
+        self.inner.extend_from_slice(s.as_bytes())
+    }
+
+    pub fn push_char(&mut self, c: char) {
+        let mut bytes = [0; 4];
+        let len = encode_utf8_raw(c as u32, &mut bytes).unwrap();
+        self.inner.extend_from_slice(&bytes[..len])
+    }
+
+    pub fn retain<F, G>(&mut self, mut keep_char: F, mut keep_non_unicode: G)
+    where F: FnMut(char) -> bool, G: FnMut(&Slice) -> bool {
+        let len = self.inner.len();
+        let mut read = 0;
+        let mut write = 0;
+        while read < len {
+            let section_end = read + match str::from_utf8(&self.inner[read..]) {
+                Ok(s) => s.len(),
+                Err(error) => error.valid_up_to(),
+            };
+            while read < section_end {
+                let (char_len, keep) = {
+                    let rest = unsafe { str::from_utf8_unchecked(&self.inner[read..section_end]) };
+                    let c = rest.chars().next().unwrap();
+                    (c.len_utf8(), keep_char(c))
+                };
+                if keep {
+                    move_bytes(&mut self.inner, read, write, char_len);
+                    write += char_len;
+                }
+                read += char_len;
+            }
+            if read == len { break; }
+
+            // Find the start of the next Unicode section, using the
+            // same rules as Utf8Sections.
+            let mut run_end = read + 1;
+            while run_end < len {
+                match str::from_utf8(&self.inner[run_end..]) {
+                    Ok(..) => break,
+                    Err(error) => if error.valid_up_to() > 0 { break; },
+                }
+                run_end += 1;
+            }
+            if keep_non_unicode(Slice::from_u8_slice(&self.inner[read..run_end])) {
+                move_bytes(&mut self.inner, read, write, run_end - read);
+                write += run_end - read;
+            }
+            read = run_end;
+        }
+        self.inner.truncate(write);
+    }
+
+    pub fn truncate(&mut self, new_len: usize) {
+        assert!(new_len <= self.inner.len());
+        self.inner.truncate(new_len)
+    }
+
     pub fn clear(&mut self) {
         self.inner.clear()
     }
 }
 
+/// Moves `len` bytes from `from` to `to` within `bytes`, where `to <= from`.
+fn move_bytes(bytes: &mut [u8], from: usize, to: usize, len: usize) {
+    if from == to { return; }
+    for i in 0..len {
+        bytes[to + i] = bytes[from + i];
+    }
+}
+
 impl Slice {
     fn from_u8_slice(s: &[u8]) -> &Slice {
         unsafe { mem::transmute(s) }
@@ -95,6 +243,14 @@ impl Slice {
         Slice::from_u8_slice(s.as_bytes())
     }
 
+    pub fn from_encoded_bytes(bytes: &[u8]) -> Option<&Slice> {
+        Some(Slice::from_u8_slice(bytes))
+    }
+
+    pub unsafe fn from_encoded_bytes_unchecked(bytes: &[u8]) -> &Slice {
+        Slice::from_u8_slice(bytes)
+    }
+
     pub fn to_str(&self) -> Option<&str> {
         str::from_utf8(&self.inner).ok()
     }
@@ -115,6 +271,12 @@ impl Slice {
         self.inner.len()
     }
 
+    pub fn split_at(&self, mid: usize) -> Option<(&Slice, &Slice)> {
+        if mid > self.len() { return None; }
+        let (a, b) = self.inner.split_at(mid);
+        Some((Slice::from_u8_slice(a), Slice::from_u8_slice(b)))
+    }
+
     pub fn split_unicode<'a>(&'a self) -> SplitUnicode<'a> {
         SplitUnicode(utf8_sections::SplitUnicode::new(&self.inner))
     }
@@ -131,59 +293,96 @@ impl Slice {
         self.inner.ends_with(&needle.inner)
     }
 
-    pub fn replace(&self, from: &Slice, to: &Slice) -> Buf {
+    pub fn find_os(&self, needle: &Slice) -> Option<usize> {
+        SliceSearcher::new(&self.inner, &needle.inner, false).next()
+    }
+
+    pub fn rfind_os(&self, needle: &Slice) -> Option<usize> {
+        ReverseSliceSearcher::new(&self.inner, &needle.inner, false).next()
+    }
+
+    pub fn split_once_os(&self, needle: &Slice) -> Option<(&Slice, &Slice)> {
+        self.find_os(needle).map(|start| {
+            (Slice::from_u8_slice(&self.inner[..start]),
+             Slice::from_u8_slice(&self.inner[start + needle.len()..]))
+        })
+    }
+
+    pub fn rsplit_once_os(&self, needle: &Slice) -> Option<(&Slice, &Slice)> {
+        self.rfind_os(needle).map(|start| {
+            (Slice::from_u8_slice(&self.inner[..start]),
+             Slice::from_u8_slice(&self.inner[start + needle.len()..]))
+        })
+    }
+
+    pub fn make_ascii_lowercase(&mut self) {
+        self.inner.make_ascii_lowercase()
+    }
+
+    pub fn make_ascii_uppercase(&mut self) {
+        self.inner.make_ascii_uppercase()
+    }
+
+    pub fn replace(&self, from: &Slice, to: &Slice) -> Option<Buf> {
+        let mut matches = SliceSearcher::new(&self.inner, &from.inner, false).peekable();
+        if matches.peek().is_none() { return None; }
         let mut result = Vec::new();
         let mut position = 0;
-        for offset in SliceSearcher::new(&self.inner, &from.inner, false) {
+        for offset in matches {
             result.extend_from_slice(&self.inner[position..offset]);
             result.extend_from_slice(&to.inner);
             position = offset + from.len();
         }
         result.extend_from_slice(&self.inner[position..]);
-        Buf { inner: result }
+        Some(Buf { inner: result })
+    }
+
+    pub fn chars_lossy<'a>(&'a self) -> CharsLossy<'a> {
+        CharsLossy {
+            sections: utf8_sections::SplitUnicode::new(&self.inner),
+            chars: "".chars(),
+        }
     }
 
     pub fn utf8_sections<'a>(&'a self) -> Utf8Sections<'a> {
         Utf8Sections::new(&self.inner)
     }
 
-    pub fn split<'a, P>(&'a self, pat: P) -> Split<'a, P>
-    where P: Pattern<'a> + Clone {
+    pub fn split<'a, P>(&'a self, pat: P) -> Split<'a, P> where P: OsPattern<'a> {
         Split { inner: split_bytes::Split::new(&self.inner, pat) }
     }
 
     pub fn rsplit<'a, P>(&'a self, pat: P) -> RSplit<'a, P>
-    where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
         RSplit { inner: split_bytes::RSplit::new(&self.inner, pat) }
     }
 
     pub fn split_terminator<'a, P>(&'a self, pat: P) -> SplitTerminator<'a, P>
-    where P: Pattern<'a> + Clone {
+    where P: OsPattern<'a> {
         SplitTerminator { inner: split_bytes::SplitTerminator::new(&self.inner, pat) }
     }
 
     pub fn rsplit_terminator<'a, P>(&'a self, pat: P) -> RSplitTerminator<'a, P>
-    where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
         RSplitTerminator { inner: split_bytes::RSplitTerminator::new(&self.inner, pat) }
     }
 
     pub fn splitn<'a, P>(&'a self, count: usize, pat: P) -> SplitN<'a, P>
-    where P: Pattern<'a> + Clone {
+    where P: OsPattern<'a> {
         SplitN { inner: split_bytes::SplitN::new(&self.inner, count, pat) }
     }
 
     pub fn rsplitn<'a, P>(&'a self, count: usize, pat: P) -> RSplitN<'a, P>
-    where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
         RSplitN { inner: split_bytes::RSplitN::new(&self.inner, count, pat) }
     }
 
-    pub fn matches<'a, P>(&'a self, pat: P) -> Matches<'a, P>
-    where P: Pattern<'a> + Clone {
+    pub fn matches<'a, P>(&'a self, pat: P) -> Matches<'a, P> where P: OsPattern<'a> {
         Matches { inner: split_bytes::Matches::new(&self.inner, pat) }
     }
 
     pub fn rmatches<'a, P>(&'a self, pat: P) -> RMatches<'a, P>
-    where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
         RMatches { inner: split_bytes::RMatches::new(&self.inner, pat) }
     }
 
@@ -228,6 +427,10 @@ impl<'a> Iterator for SplitUnicode<'a> {
     fn next(&mut self) -> Option<Section<'a>> {
         self.0.next().map(|x| x.into())
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
 }
 
 impl<'a> DoubleEndedIterator for SplitUnicode<'a> {
@@ -236,31 +439,59 @@ impl<'a> DoubleEndedIterator for SplitUnicode<'a> {
     }
 }
 
+impl<'a> FusedIterator for SplitUnicode<'a> {}
+
+#[derive(Clone)]
+pub struct CharsLossy<'a> {
+    sections: utf8_sections::SplitUnicode<'a>,
+    chars: str::Chars<'a>,
+}
+
+impl<'a> Iterator for CharsLossy<'a> {
+    type Item = char;
+    fn next(&mut self) -> Option<char> {
+        loop {
+            if let Some(c) = self.chars.next() { return Some(c); }
+            match self.sections.next() {
+                Some(utf8_sections::Section::Unicode(s)) => self.chars = s.chars(),
+                // A whole invalid run is replaced by a single character
+                Some(utf8_sections::Section::NonUnicode(_)) => return Some('\u{FFFD}'),
+                None => return None,
+            }
+        }
+    }
+}
+
 macro_rules! make_iterator {
     ($name:ident requires $bound:ident yielding $map:expr => $ret:ty) => {
-        pub struct $name<'a, P> where P: Pattern<'a> {
+        pub struct $name<'a, P> where P: OsPattern<'a> {
             inner: split_bytes::$name<'a, P>,
         }
 
-        impl<'a, P> Clone for $name<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: Clone {
+        impl<'a, P> Clone for $name<'a, P> where P: OsPattern<'a>, P::Searcher: Clone {
             fn clone(&self) -> Self { $name { inner: self.inner.clone() } }
         }
 
-        impl<'a, P> Iterator for $name<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: $bound<'a> {
+        impl<'a, P> Iterator for $name<'a, P> where P: OsPattern<'a>, P::Searcher: $bound<'a> {
             type Item = $ret;
 
             fn next(&mut self) -> Option<$ret> {
                 self.inner.next().map($map)
             }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.inner.size_hint()
+            }
         }
+
+        impl<'a, P> FusedIterator for $name<'a, P>
+        where P: OsPattern<'a>, P::Searcher: $bound<'a> {}
     };
     ($name:ident requires $bound:ident is double ended yielding $map:expr => $ret:ty) => {
         make_iterator!{$name requires $bound yielding $map => $ret}
 
         impl<'a, P> DoubleEndedIterator for $name<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: DoubleEndedSearcher<'a> {
+        where P: OsPattern<'a>, P::Searcher: DoubleEndedOsSearcher<'a> {
             fn next_back(&mut self) -> Option<$ret> {
                 self.inner.next_back().map($map)
             }
@@ -268,18 +499,45 @@ macro_rules! make_iterator {
     }
 }
 
-make_iterator!{Split requires Searcher is double ended
+make_iterator!{Split requires OsSearcher is double ended
+               yielding Slice::from_u8_slice => &'a Slice}
+make_iterator!{RSplit requires ReverseOsSearcher is double ended
                yielding Slice::from_u8_slice => &'a Slice}
-make_iterator!{RSplit requires ReverseSearcher is double ended
+make_iterator!{SplitTerminator requires OsSearcher is double ended
                yielding Slice::from_u8_slice => &'a Slice}
-make_iterator!{SplitTerminator requires Searcher is double ended
+make_iterator!{RSplitTerminator requires ReverseOsSearcher is double ended
                yielding Slice::from_u8_slice => &'a Slice}
-make_iterator!{RSplitTerminator requires ReverseSearcher is double ended
+make_iterator!{SplitN requires OsSearcher yielding Slice::from_u8_slice => &'a Slice}
+make_iterator!{RSplitN requires ReverseOsSearcher yielding Slice::from_u8_slice => &'a Slice}
+make_iterator!{Matches requires OsSearcher is double ended yielding Slice::from_u8_slice => &'a Slice}
+make_iterator!{RMatches requires ReverseOsSearcher is double ended
                yielding Slice::from_u8_slice => &'a Slice}
-make_iterator!{SplitN requires Searcher yielding Slice::from_u8_slice => &'a Slice}
-make_iterator!{RSplitN requires ReverseSearcher yielding Slice::from_u8_slice => &'a Slice}
-make_iterator!{Matches requires Searcher is double ended yielding |x| x => &'a str}
-make_iterator!{RMatches requires ReverseSearcher is double ended yielding |x| x => &'a str}
+
+macro_rules! make_remainder {
+    ($($name:ident)*) => { $(
+        impl<'a, P> $name<'a, P> where P: OsPattern<'a> {
+            pub fn remainder(&self) -> Option<&'a Slice> {
+                self.inner.remainder().map(Slice::from_u8_slice)
+            }
+        }
+    )* }
+}
+
+make_remainder!{Split RSplit SplitTerminator RSplitTerminator SplitN RSplitN}
+
+impl<'a, P> DoubleEndedIterator for SplitN<'a, P>
+where P: OsPattern<'a>, P::Searcher: DoubleEndedOsSearcher<'a> + Clone {
+    fn next_back(&mut self) -> Option<&'a Slice> {
+        self.inner.next_back().map(Slice::from_u8_slice)
+    }
+}
+
+impl<'a, P> DoubleEndedIterator for RSplitN<'a, P>
+where P: OsPattern<'a>, P::Searcher: DoubleEndedOsSearcher<'a> + Clone {
+    fn next_back(&mut self) -> Option<&'a Slice> {
+        self.inner.next_back().map(Slice::from_u8_slice)
+    }
+}
 
 pub mod os_str {
     use super::{Buf, Slice};
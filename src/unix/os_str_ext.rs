@@ -12,10 +12,11 @@
 
 
 use super::{OsStr, OsString};
+use std::cmp;
 use std::mem;
 use std::prelude::v1::*;
 use super::Buf;
-use sys_common::{FromInner, IntoInner, AsInner};
+use sys_common::{FromInner, IntoInner, AsInner, AsInnerMut};
 
 /// Unix-specific extensions to `OsString`.
 pub trait OsStringExt {
@@ -24,6 +25,26 @@ pub trait OsStringExt {
 
     /// Yields the underlying byte vector of this `OsString`.
     fn into_vec(self) -> Vec<u8>;
+
+    /// Decomposes an `OsString` into its raw components: a pointer to
+    /// the bytes, the length in bytes, and the allocated capacity.
+    ///
+    /// The caller becomes responsible for the memory, which can be
+    /// released by converting back with `from_raw_parts`.
+    fn into_raw_parts(self) -> (*mut u8, usize, usize);
+
+    /// Creates an `OsString` from a pointer, a length and a capacity.
+    ///
+    /// The requirements are those of `Vec::from_raw_parts`.  Unlike
+    /// `String::from_raw_parts`, there are no requirements on the
+    /// contents, since any byte sequence is a valid `OsString` on Unix.
+    unsafe fn from_raw_parts(ptr: *mut u8, length: usize, capacity: usize) -> Self;
+
+    /// Returns a mutable reference to the contents of this `OsString`.
+    ///
+    /// This is `unsafe` to match `String::as_mut_vec`, but any
+    /// modification of the vector leaves a valid `OsString` on Unix.
+    unsafe fn as_mut_vec(&mut self) -> &mut Vec<u8>;
 }
 
 impl OsStringExt for OsString {
@@ -33,21 +54,109 @@ impl OsStringExt for OsString {
     fn into_vec(self) -> Vec<u8> {
         self.into_inner().inner
     }
+    fn into_raw_parts(self) -> (*mut u8, usize, usize) {
+        let mut vec = self.into_vec();
+        let parts = (vec.as_mut_ptr(), vec.len(), vec.capacity());
+        mem::forget(vec);
+        parts
+    }
+    unsafe fn from_raw_parts(ptr: *mut u8, length: usize, capacity: usize) -> OsString {
+        OsString::from_vec(Vec::from_raw_parts(ptr, length, capacity))
+    }
+    unsafe fn as_mut_vec(&mut self) -> &mut Vec<u8> {
+        &mut self.as_inner_mut().inner
+    }
+}
+
+/// Yields the underlying byte vector, as `OsStringExt::into_vec`
+/// does.  The allocation is reused, so the capacity is preserved.
+impl From<OsString> for Vec<u8> {
+    fn from(s: OsString) -> Vec<u8> {
+        s.into_vec()
+    }
 }
 
 /// Unix-specific extensions to `OsStr`.
+///
+/// Since an `OsStr` is a byte string on Unix, `OsStr` and `OsString`
+/// can also be compared directly with `[u8]` and `Vec<u8>`.
 pub trait OsStrExt {
     fn from_bytes(slice: &[u8]) -> &Self;
 
+    /// Creates an `OsStr` from the bytes of `slice` up to the first
+    /// nul, or from all of `slice` if it contains no nul.
+    ///
+    /// This is useful for buffers filled in by C functions.
+    fn from_bytes_until_nul(slice: &[u8]) -> &Self;
+
     /// Gets the underlying byte view of the `OsStr` slice.
     fn as_bytes(&self) -> &[u8];
+
+    /// Encodes the string as UTF-16, or returns `None` if it is not
+    /// valid Unicode.
+    fn to_wide(&self) -> Option<Vec<u16>>;
+
+    /// Encodes the string as UTF-16, replacing each invalid sequence
+    /// with U+FFFD REPLACEMENT CHARACTER as `to_string_lossy` does.
+    fn to_wide_lossy(&self) -> Vec<u16>;
 }
 
 impl OsStrExt for OsStr {
     fn from_bytes(slice: &[u8]) -> &OsStr {
         unsafe { mem::transmute(slice) }
     }
+    fn from_bytes_until_nul(slice: &[u8]) -> &OsStr {
+        let len = slice.iter().position(|&b| b == 0).unwrap_or(slice.len());
+        <OsStr as OsStrExt>::from_bytes(&slice[..len])
+    }
     fn as_bytes(&self) -> &[u8] {
         &self.as_inner().inner
     }
+    fn to_wide(&self) -> Option<Vec<u16>> {
+        self.to_str().map(|s| s.encode_utf16().collect())
+    }
+    fn to_wide_lossy(&self) -> Vec<u16> {
+        self.to_string_lossy().encode_utf16().collect()
+    }
 }
+
+// Comparisons in both directions between an OS string type and a byte
+// string type, comparing the underlying bytes.
+macro_rules! impl_bytes_cmp {
+    ($os:ty, $bytes:ty) => {
+        impl<'a> PartialEq<$bytes> for $os {
+            #[inline]
+            fn eq(&self, other: &$bytes) -> bool {
+                self.as_bytes() == &other[..]
+            }
+        }
+
+        impl<'a> PartialEq<$os> for $bytes {
+            #[inline]
+            fn eq(&self, other: &$os) -> bool {
+                &self[..] == other.as_bytes()
+            }
+        }
+
+        impl<'a> PartialOrd<$bytes> for $os {
+            #[inline]
+            fn partial_cmp(&self, other: &$bytes) -> Option<cmp::Ordering> {
+                self.as_bytes().partial_cmp(&other[..])
+            }
+        }
+
+        impl<'a> PartialOrd<$os> for $bytes {
+            #[inline]
+            fn partial_cmp(&self, other: &$os) -> Option<cmp::Ordering> {
+                self[..].partial_cmp(other.as_bytes())
+            }
+        }
+    }
+}
+
+impl_bytes_cmp!(OsStr, [u8]);
+impl_bytes_cmp!(OsStr, Vec<u8>);
+impl_bytes_cmp!(&'a OsStr, Vec<u8>);
+impl_bytes_cmp!(OsString, [u8]);
+impl_bytes_cmp!(OsString, &'a [u8]);
+impl_bytes_cmp!(OsString, Vec<u8>);
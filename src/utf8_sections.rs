@@ -1,3 +1,5 @@
+use core::cmp;
+use core::iter::FusedIterator;
 use core::str;
 use core::ops::Range;
 
@@ -65,6 +67,12 @@ impl<'a> Iterator for Utf8Sections<'a> {
             if str_start == 0 { return Some((0, "")); }
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.start > self.end { return (0, Some(0)); }
+        // Each section consumes at least one position.
+        (0, Some(self.end - self.start + 1))
+    }
 }
 
 impl<'a> DoubleEndedIterator for Utf8Sections<'a> {
@@ -115,6 +123,8 @@ impl<'a> DoubleEndedIterator for Utf8Sections<'a> {
     }
 }
 
+impl<'a> FusedIterator for Utf8Sections<'a> {}
+
 
 #[derive(Clone, PartialEq, Eq)]
 pub enum Section<'a> {
@@ -201,6 +211,12 @@ impl<'a> Iterator for SplitUnicode<'a> {
             }
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Sections are never empty.
+        let len = self.remaining.end - self.remaining.start;
+        (if len == 0 { 0 } else { 1 }, Some(len))
+    }
 }
 
 impl<'a> DoubleEndedIterator for SplitUnicode<'a> {
@@ -265,6 +281,158 @@ impl<'a> DoubleEndedIterator for SplitUnicode<'a> {
     }
 }
 
+impl<'a> FusedIterator for SplitUnicode<'a> {}
+
+
+/// A piece of a byte stream reported by `Utf8Scanner`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Utf8Event<'a> {
+    Valid(&'a str),
+    Invalid(&'a [u8]),
+}
+
+/// Splits a byte stream into valid UTF-8 and invalid runs as it
+/// arrives, without buffering more than one code point.
+///
+/// A code point split between two chunks is held back until the rest
+/// of it is fed.  Events are never empty, but a run in the stream may
+/// be reported as several events.
+///
+/// # Example
+///
+/// ```
+/// use osstring_prototype::{Utf8Event, Utf8Scanner};
+/// let mut scanner = Utf8Scanner::new();
+/// let mut events = Vec::new();
+/// for chunk in &[&b"a\xCE"[..], b"\x93\xFF"] {
+///     scanner.feed(chunk, |e| events.push(format!("{:?}", e)));
+/// }
+/// scanner.finish(|e| events.push(format!("{:?}", e)));
+/// assert_eq!(events, ["Valid(\"a\")", "Valid(\"Γ\")", "Invalid([255])"]);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Utf8Scanner {
+    // The start of a code point from the end of the last chunk
+    partial: [u8; 4],
+    partial_len: usize,
+}
+
+impl Utf8Scanner {
+    pub fn new() -> Self {
+        Utf8Scanner::default()
+    }
+
+    /// Scans the next chunk of the stream, calling `f` with each
+    /// event.
+    pub fn feed<F>(&mut self, chunk: &[u8], mut f: F) where F: FnMut(Utf8Event) {
+        let mut rest = chunk;
+        if self.partial_len > 0 {
+            let held = self.partial_len;
+            let width = char_width(self.partial[0]);
+            let mut buf = self.partial;
+            let take = cmp::min(width - held, chunk.len());
+            buf[held..held + take].copy_from_slice(&chunk[..take]);
+            let len = held + take;
+            if len < width && is_char_prefix(&buf[..len]) {
+                // Still incomplete, and the chunk is used up
+                self.partial = buf;
+                self.partial_len = len;
+                return;
+            }
+            self.partial_len = 0;
+            if let Ok(s) = str::from_utf8(&buf[..len]) {
+                f(Utf8Event::Valid(s));
+                rest = &chunk[take..];
+            } else {
+                let bad = invalid_len(&buf[..len]);
+                f(Utf8Event::Invalid(&buf[..bad]));
+                rest = &chunk[bad - held..];
+            }
+        }
+
+        while !rest.is_empty() {
+            let valid = match str::from_utf8(rest) {
+                Ok(s) => s.len(),
+                Err(e) => e.valid_up_to(),
+            };
+            if valid > 0 {
+                f(Utf8Event::Valid(unsafe { str::from_utf8_unchecked(&rest[..valid]) }));
+                rest = &rest[valid..];
+            }
+
+            // Collect the invalid run, stopping at the next character
+            // or at an incomplete one at the end of the chunk.
+            let mut bad = 0;
+            while bad < rest.len() && !starts_with_char(&rest[bad..]) {
+                if is_char_prefix(&rest[bad..]) { break; }
+                bad += invalid_len(&rest[bad..]);
+            }
+            if bad > 0 {
+                f(Utf8Event::Invalid(&rest[..bad]));
+                rest = &rest[bad..];
+            }
+
+            if !rest.is_empty() && is_char_prefix(rest) {
+                self.partial[..rest.len()].copy_from_slice(rest);
+                self.partial_len = rest.len();
+                return;
+            }
+        }
+    }
+
+    /// Ends the stream, reporting an incomplete code point at the
+    /// end as invalid.
+    pub fn finish<F>(&mut self, mut f: F) where F: FnMut(Utf8Event) {
+        if self.partial_len > 0 {
+            f(Utf8Event::Invalid(&self.partial[..self.partial_len]));
+            self.partial_len = 0;
+        }
+    }
+}
+
+/// The length of the character started by `first`, or 1 if it
+/// cannot start a multibyte character.
+fn char_width(first: u8) -> usize {
+    match first {
+        0xC2...0xDF => 2,
+        0xE0...0xEF => 3,
+        0xF0...0xF4 => 4,
+        _ => 1,
+    }
+}
+
+fn starts_with_char(bytes: &[u8]) -> bool {
+    let width = char_width(bytes[0]);
+    bytes.len() >= width && str::from_utf8(&bytes[..width]).is_ok()
+}
+
+/// Returns true if `bytes` is the start of a character, but too
+/// short to be a whole one.
+fn is_char_prefix(bytes: &[u8]) -> bool {
+    let width = char_width(bytes[0]);
+    if width == 1 || bytes.len() >= width { return false; }
+    if bytes.len() >= 2 {
+        let second = match (bytes[0], bytes[1]) {
+            (0xE0, 0xA0...0xBF) | (0xE1...0xEC, 0x80...0xBF) | (0xED, 0x80...0x9F) |
+            (0xEE...0xEF, 0x80...0xBF) | (0xF0, 0x90...0xBF) | (0xF1...0xF3, 0x80...0xBF) |
+            (0xF4, 0x80...0x8F) => true,
+            _ => false,
+        };
+        if !second { return false; }
+    }
+    bytes[1..].iter().all(|&b| b & 0xC0 == 0x80)
+}
+
+/// The length of the invalid sequence at the start of `bytes`: the
+/// longest prefix of a character, or a single byte.
+pub fn invalid_len(bytes: &[u8]) -> usize {
+    let mut len = 1;
+    while len < bytes.len() && is_char_prefix(&bytes[..len + 1]) {
+        len += 1;
+    }
+    len
+}
+
 #[cfg(test)]
 mod tests {
     use std::prelude::v1::*;
@@ -412,4 +580,107 @@ mod tests {
         assert_eq!(sections.next(), Some((0, "😺 ")));
         assert_eq!(sections.next(), None);
     }
+
+    #[test]
+    fn size_hints() {
+        fn check<I: Iterator + Clone>(mut iter: I) {
+            loop {
+                let (low, high) = iter.size_hint();
+                let count = iter.clone().count();
+                assert!(low <= count && count <= high.unwrap());
+                if iter.next().is_none() { break; }
+            }
+            assert!(iter.next().is_none());
+        }
+
+        let bufs: [&[u8]; 5] = [b"", b"abc", b"\xFF", b"\xFF\xFF\xFF", b"a\xFFb\xCE\x93\xFF"];
+        for buf in &bufs {
+            check(Utf8Sections::new(buf));
+            check(Utf8Sections::new(buf).rev());
+            check(SplitUnicode::new(buf));
+            check(SplitUnicode::new(buf).rev());
+        }
+    }
+
+    // Runs the chunks through a scanner, merging adjacent events of
+    // the same kind.
+    fn scan(chunks: &[&[u8]]) -> Vec<(bool, Vec<u8>)> {
+        let mut result: Vec<(bool, Vec<u8>)> = Vec::new();
+        {
+            let mut push = |event: Utf8Event| {
+                let (valid, bytes) = match event {
+                    Utf8Event::Valid(s) => (true, s.as_bytes()),
+                    Utf8Event::Invalid(b) => (false, b),
+                };
+                assert!(!bytes.is_empty());
+                if let Some(last) = result.last_mut() {
+                    if last.0 == valid {
+                        last.1.extend_from_slice(bytes);
+                        return;
+                    }
+                }
+                result.push((valid, bytes.to_vec()));
+            };
+            let mut scanner = Utf8Scanner::new();
+            for chunk in chunks {
+                scanner.feed(chunk, &mut push);
+            }
+            scanner.finish(&mut push);
+        }
+        result
+    }
+
+    #[test]
+    fn scanner_events() {
+        let mut scanner = Utf8Scanner::new();
+        let mut events = Vec::new();
+        scanner.feed(b"ab\xFF\xFEc\xCE", |e| events.push(format!("{:?}", e)));
+        assert_eq!(events, ["Valid(\"ab\")", "Invalid([255, 254])", "Valid(\"c\")"]);
+        events.clear();
+        scanner.feed(b"", |e| events.push(format!("{:?}", e)));
+        scanner.feed(b"\x93", |e| events.push(format!("{:?}", e)));
+        assert_eq!(events, ["Valid(\"Γ\")"]);
+        events.clear();
+        scanner.feed(b"\xF0\x9F", |e| events.push(format!("{:?}", e)));
+        scanner.feed(b"\x98", |e| events.push(format!("{:?}", e)));
+        assert!(events.is_empty());
+        scanner.feed(b"x", |e| events.push(format!("{:?}", e)));
+        assert_eq!(events, ["Invalid([240, 159, 152])", "Valid(\"x\")"]);
+        events.clear();
+        scanner.feed(b"\xE2\x82", |e| events.push(format!("{:?}", e)));
+        scanner.finish(|e| events.push(format!("{:?}", e)));
+        assert_eq!(events, ["Invalid([226, 130])"]);
+        events.clear();
+        scanner.finish(|e| events.push(format!("{:?}", e)));
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn scanner_chunking() {
+        let inputs: [&[u8]; 7] = [
+            b"",
+            "aé 💩".as_bytes(),
+            b"\xFF",
+            b"a\xF0\x9F\x98",
+            b"\xED\xA0\x80\xCE\x93\xC0\x80",
+            b"\xF0\x9F\x98\xBA \xFF\xCE\x93#\xE2",
+            b"\xCE\xCE\x93\xF4\x90\x80\x80",
+        ];
+        for input in &inputs {
+            // The merged events are the sections of the whole input.
+            let expected = SplitUnicode::new(input).map(|s| match s {
+                Section::Unicode(s) => (true, s.as_bytes().to_vec()),
+                Section::NonUnicode(b) => (false, b.to_vec()),
+            }).collect::<Vec<_>>();
+            assert_eq!(scan(&[input]), expected);
+
+            for i in 0..input.len() + 1 {
+                for j in i..input.len() + 1 {
+                    assert_eq!(scan(&[&input[..i], &input[i..j], &input[j..]]), expected);
+                }
+            }
+            let bytes = input.chunks(1).collect::<Vec<_>>();
+            assert_eq!(scan(&bytes), expected);
+        }
+    }
 }
@@ -11,12 +11,17 @@
 /// The underlying OsString/OsStr implementation on Windows is a
 /// wrapper around the "WTF-8" encoding; see the `wtf8` module for more.
 
+use os_pattern::{DoubleEndedOsSearcher, OsPattern, OsSearcher, ReverseOsSearcher};
+use split_bytes;
 use utf8_sections::Utf8Sections;
 
+use std::ascii::AsciiExt;
 use std::borrow::Cow;
+use std::boxed::Box;
 use std::fmt::{self, Debug};
+use std::iter::FusedIterator;
 use wtf8::{self, Wtf8, Wtf8Buf};
-use core::str::pattern::{DoubleEndedSearcher, Pattern, ReverseSearcher, Searcher};
+use core::str::pattern::{DoubleEndedSearcher, Pattern, ReverseSearcher};
 use std::string::String;
 use std::result::Result;
 use std::option::Option;
@@ -52,6 +57,10 @@ impl Buf {
         unsafe { mem::transmute(self.inner.as_slice()) }
     }
 
+    pub fn as_mut_slice(&mut self) -> &mut Slice {
+        unsafe { mem::transmute(self.inner.as_mut_slice()) }
+    }
+
     pub fn with_capacity(capacity: usize) -> Self {
         Buf { inner: Wtf8Buf::with_capacity(capacity) }
     }
@@ -64,10 +73,18 @@ impl Buf {
         self.inner.reserve(additional)
     }
 
-    fn reserve_exact(&mut self, additional: usize) {
+    pub fn reserve_exact(&mut self, additional: usize) {
         self.inner.reserve_exact(additional)
     }
 
+    pub fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit()
+    }
+
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.inner.shrink_to(min_capacity)
+    }
+
     pub fn into_string(self) -> Result<String, Buf> {
         self.inner.into_string().map_err(|buf| Buf { inner: buf })
     }
@@ -80,6 +97,32 @@ impl Buf {
         self.inner.push_wtf8(&s.inner)
     }
 
+    pub fn into_box(self) -> Box<Slice> {
+        unsafe { mem::transmute(self.inner.into_box()) }
+    }
+
+    pub fn from_box(boxed: Box<Slice>) -> Buf {
+        let inner: Box<Wtf8> = unsafe { mem::transmute(boxed) };
+        Buf { inner: Wtf8Buf::from_box(inner) }
+    }
+
+    pub fn push_str(&mut self, s: &str) {
+        self.inner.push_str(s)
+    }
+
+    pub fn push_char(&mut self, c: char) {
+        self.inner.push_char(c)
+    }
+
+    pub fn retain<F, G>(&mut self, keep_char: F, mut keep_non_unicode: G)
+    where F: FnMut(char) -> bool, G: FnMut(&Slice) -> bool {
+        self.inner.retain(keep_char, |s| keep_non_unicode(Slice::from_wtf8(s)))
+    }
+
+    pub fn truncate(&mut self, new_len: usize) {
+        self.inner.truncate(new_len)
+    }
+
     pub fn clear(&mut self) {
         self.inner.clear()
     }
@@ -94,6 +137,23 @@ impl Slice {
         unsafe { mem::transmute(s) }
     }
 
+    // Panics if an OsSearcher cut the piece inside a code point
+    fn from_split_piece(piece: &[u8]) -> &Slice {
+        Self::from_wtf8(wtf8::from_split_piece(piece))
+    }
+
+    fn bytes(&self) -> &[u8] {
+        unsafe { mem::transmute(&self.inner) }
+    }
+
+    pub fn from_encoded_bytes(bytes: &[u8]) -> Option<&Slice> {
+        Wtf8::from_bytes(bytes).ok().map(Self::from_wtf8)
+    }
+
+    pub unsafe fn from_encoded_bytes_unchecked(bytes: &[u8]) -> &Slice {
+        Self::from_wtf8(Wtf8::from_bytes_unchecked(bytes))
+    }
+
     pub fn to_str(&self) -> Option<&str> {
         self.inner.as_str()
     }
@@ -116,6 +176,15 @@ impl Slice {
         self.inner.len()
     }
 
+    pub fn split_at(&self, mid: usize) -> Option<(&Slice, &Slice)> {
+        // is_code_point_boundary checks that the index is in [0, .len()]
+        if !wtf8::is_code_point_boundary(&self.inner, mid) { return None; }
+        unsafe {
+            Some((Self::from_wtf8(wtf8::slice_unchecked(&self.inner, 0, mid)),
+                  Self::from_wtf8(wtf8::slice_unchecked(&self.inner, mid, self.len()))))
+        }
+    }
+
     pub fn split_unicode<'a>(&'a self) -> SplitUnicode<'a> {
         SplitUnicode(self.inner.split_unicode())
     }
@@ -132,52 +201,80 @@ impl Slice {
         self.inner.ends_with_wtf8(&needle.inner)
     }
 
-    pub fn replace(&self, from: &Slice, to: &Slice) -> Buf {
-        Buf { inner: self.inner.replace(&from.inner, &to.inner) }
+    pub fn find_os(&self, needle: &Slice) -> Option<usize> {
+        self.inner.find_wtf8(&needle.inner)
+    }
+
+    pub fn rfind_os(&self, needle: &Slice) -> Option<usize> {
+        self.inner.rfind_wtf8(&needle.inner)
+    }
+
+    pub fn split_once_os(&self, needle: &Slice) -> Option<(&Slice, &Slice)> {
+        self.inner.split_once_wtf8(&needle.inner)
+            .map(|(a, b)| (Self::from_wtf8(a), Self::from_wtf8(b)))
+    }
+
+    pub fn rsplit_once_os(&self, needle: &Slice) -> Option<(&Slice, &Slice)> {
+        self.inner.rsplit_once_wtf8(&needle.inner)
+            .map(|(a, b)| (Self::from_wtf8(a), Self::from_wtf8(b)))
+    }
+
+    pub fn make_ascii_lowercase(&mut self) {
+        self.inner.make_ascii_lowercase()
+    }
+
+    pub fn make_ascii_uppercase(&mut self) {
+        self.inner.make_ascii_uppercase()
+    }
+
+    pub fn replace(&self, from: &Slice, to: &Slice) -> Option<Buf> {
+        self.inner.try_replace(&from.inner, &to.inner).map(|inner| Buf { inner: inner })
+    }
+
+    pub fn chars_lossy<'a>(&'a self) -> CharsLossy<'a> {
+        CharsLossy(self.inner.code_points())
     }
 
     pub fn utf8_sections<'a>(&'a self) -> Utf8Sections<'a> {
         self.inner.utf8_sections()
     }
 
-    pub fn split<'a, P>(&'a self, pat: P) -> Split<'a, P>
-    where P: Pattern<'a> + Clone {
-        Split { inner: self.inner.split(pat) }
+    pub fn split<'a, P>(&'a self, pat: P) -> Split<'a, P> where P: OsPattern<'a> {
+        Split { inner: split_bytes::Split::new(self.bytes(), pat) }
     }
 
     pub fn rsplit<'a, P>(&'a self, pat: P) -> RSplit<'a, P>
-    where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
-        RSplit { inner: self.inner.rsplit(pat) }
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
+        RSplit { inner: split_bytes::RSplit::new(self.bytes(), pat) }
     }
 
     pub fn split_terminator<'a, P>(&'a self, pat: P) -> SplitTerminator<'a, P>
-    where P: Pattern<'a> + Clone {
-        SplitTerminator { inner: self.inner.split_terminator(pat) }
+    where P: OsPattern<'a> {
+        SplitTerminator { inner: split_bytes::SplitTerminator::new(self.bytes(), pat) }
     }
 
     pub fn rsplit_terminator<'a, P>(&'a self, pat: P) -> RSplitTerminator<'a, P>
-    where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
-        RSplitTerminator { inner: self.inner.rsplit_terminator(pat) }
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
+        RSplitTerminator { inner: split_bytes::RSplitTerminator::new(self.bytes(), pat) }
     }
 
     pub fn splitn<'a, P>(&'a self, count: usize, pat: P) -> SplitN<'a, P>
-    where P: Pattern<'a> + Clone {
-        SplitN { inner: self.inner.splitn(count, pat) }
+    where P: OsPattern<'a> {
+        SplitN { inner: split_bytes::SplitN::new(self.bytes(), count, pat) }
     }
 
     pub fn rsplitn<'a, P>(&'a self, count: usize, pat: P) -> RSplitN<'a, P>
-    where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
-        RSplitN { inner: self.inner.rsplitn(count, pat) }
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
+        RSplitN { inner: split_bytes::RSplitN::new(self.bytes(), count, pat) }
     }
 
-    pub fn matches<'a, P>(&'a self, pat: P) -> Matches<'a, P>
-    where P: Pattern<'a> + Clone {
-        Matches { inner: self.inner.matches(pat) }
+    pub fn matches<'a, P>(&'a self, pat: P) -> Matches<'a, P> where P: OsPattern<'a> {
+        Matches { inner: split_bytes::Matches::new(self.bytes(), pat) }
     }
 
     pub fn rmatches<'a, P>(&'a self, pat: P) -> RMatches<'a, P>
-    where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
-        RMatches { inner: self.inner.rmatches(pat) }
+    where P: OsPattern<'a>, P::Searcher: ReverseOsSearcher<'a> {
+        RMatches { inner: split_bytes::RMatches::new(self.bytes(), pat) }
     }
 
     pub fn trim_matches<'a, P>(&'a self, pat: P) -> &'a Slice
@@ -221,6 +318,10 @@ impl<'a> Iterator for SplitUnicode<'a> {
     fn next(&mut self) -> Option<Section<'a>> {
         self.0.next().map(|x| x.into())
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
 }
 
 impl<'a> DoubleEndedIterator for SplitUnicode<'a> {
@@ -229,31 +330,52 @@ impl<'a> DoubleEndedIterator for SplitUnicode<'a> {
     }
 }
 
+impl<'a> FusedIterator for SplitUnicode<'a> {}
+
+#[derive(Clone)]
+pub struct CharsLossy<'a>(wtf8::Wtf8CodePoints<'a>);
+
+impl<'a> Iterator for CharsLossy<'a> {
+    type Item = char;
+    fn next(&mut self) -> Option<char> {
+        self.0.next().map(|c| c.to_char_lossy())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
 macro_rules! make_iterator {
     ($name:ident requires $bound:ident yielding $map:expr => $ret:ty) => {
-        pub struct $name<'a, P> where P: Pattern<'a> {
-            inner: wtf8::$name<'a, P>,
+        pub struct $name<'a, P> where P: OsPattern<'a> {
+            inner: split_bytes::$name<'a, P>,
         }
 
-        impl<'a, P> Clone for $name<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: Clone {
+        impl<'a, P> Clone for $name<'a, P> where P: OsPattern<'a>, P::Searcher: Clone {
             fn clone(&self) -> Self { $name { inner: self.inner.clone() } }
         }
 
-        impl<'a, P> Iterator for $name<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: $bound<'a> {
+        impl<'a, P> Iterator for $name<'a, P> where P: OsPattern<'a>, P::Searcher: $bound<'a> {
             type Item = $ret;
 
             fn next(&mut self) -> Option<$ret> {
                 self.inner.next().map($map)
             }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.inner.size_hint()
+            }
         }
+
+        impl<'a, P> FusedIterator for $name<'a, P>
+        where P: OsPattern<'a>, P::Searcher: $bound<'a> {}
     };
     ($name:ident requires $bound:ident is double ended yielding $map:expr => $ret:ty) => {
         make_iterator!{$name requires $bound yielding $map => $ret}
 
         impl<'a, P> DoubleEndedIterator for $name<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: DoubleEndedSearcher<'a> {
+        where P: OsPattern<'a>, P::Searcher: DoubleEndedOsSearcher<'a> {
             fn next_back(&mut self) -> Option<$ret> {
                 self.inner.next_back().map($map)
             }
@@ -261,18 +383,45 @@ macro_rules! make_iterator {
     }
 }
 
-make_iterator!{Split requires Searcher is double ended
-               yielding Slice::from_wtf8 => &'a Slice}
-make_iterator!{RSplit requires ReverseSearcher is double ended
-               yielding Slice::from_wtf8 => &'a Slice}
-make_iterator!{SplitTerminator requires Searcher is double ended
-               yielding Slice::from_wtf8 => &'a Slice}
-make_iterator!{RSplitTerminator requires ReverseSearcher is double ended
-               yielding Slice::from_wtf8 => &'a Slice}
-make_iterator!{SplitN requires Searcher yielding Slice::from_wtf8 => &'a Slice}
-make_iterator!{RSplitN requires ReverseSearcher yielding Slice::from_wtf8 => &'a Slice}
-make_iterator!{Matches requires Searcher is double ended yielding |x| x => &'a str}
-make_iterator!{RMatches requires ReverseSearcher is double ended yielding |x| x => &'a str}
+make_iterator!{Split requires OsSearcher is double ended
+               yielding Slice::from_split_piece => &'a Slice}
+make_iterator!{RSplit requires ReverseOsSearcher is double ended
+               yielding Slice::from_split_piece => &'a Slice}
+make_iterator!{SplitTerminator requires OsSearcher is double ended
+               yielding Slice::from_split_piece => &'a Slice}
+make_iterator!{RSplitTerminator requires ReverseOsSearcher is double ended
+               yielding Slice::from_split_piece => &'a Slice}
+make_iterator!{SplitN requires OsSearcher yielding Slice::from_split_piece => &'a Slice}
+make_iterator!{RSplitN requires ReverseOsSearcher yielding Slice::from_split_piece => &'a Slice}
+make_iterator!{Matches requires OsSearcher is double ended yielding Slice::from_split_piece => &'a Slice}
+make_iterator!{RMatches requires ReverseOsSearcher is double ended
+               yielding Slice::from_split_piece => &'a Slice}
+
+macro_rules! make_remainder {
+    ($($name:ident)*) => { $(
+        impl<'a, P> $name<'a, P> where P: OsPattern<'a> {
+            pub fn remainder(&self) -> Option<&'a Slice> {
+                self.inner.remainder().map(Slice::from_split_piece)
+            }
+        }
+    )* }
+}
+
+make_remainder!{Split RSplit SplitTerminator RSplitTerminator SplitN RSplitN}
+
+impl<'a, P> DoubleEndedIterator for SplitN<'a, P>
+where P: OsPattern<'a>, P::Searcher: DoubleEndedOsSearcher<'a> + Clone {
+    fn next_back(&mut self) -> Option<&'a Slice> {
+        self.inner.next_back().map(Slice::from_split_piece)
+    }
+}
+
+impl<'a, P> DoubleEndedIterator for RSplitN<'a, P>
+where P: OsPattern<'a>, P::Searcher: DoubleEndedOsSearcher<'a> + Clone {
+    fn next_back(&mut self) -> Option<&'a Slice> {
+        self.inner.next_back().map(Slice::from_split_piece)
+    }
+}
 
 pub mod os_str {
     use super::{Buf, Slice};
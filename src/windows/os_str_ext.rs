@@ -12,8 +12,10 @@
 
 use super::{OsString, OsStr};
 use super::Buf;
-use wtf8::Wtf8Buf;
-use sys_common::{FromInner, AsInner};
+use std::mem;
+use std::vec::Vec;
+use wtf8::{Wtf8, Wtf8Buf, Wtf8Error};
+use sys_common::{FromInner, IntoInner, AsInner};
 
 pub use wtf8::EncodeWide;
 
@@ -25,12 +27,53 @@ pub trait OsStringExt {
     /// This is lossless: calling `.encode_wide()` on the resulting string
     /// will always return the original code units.
     fn from_wide(wide: &[u16]) -> Self;
+
+    /// Creates an `OsString` from a potentially ill-formed UTF-16 slice of
+    /// 16-bit code units, replacing unpaired surrogates with U+FFFD.
+    ///
+    /// The resulting string is always valid Unicode.
+    fn from_wide_lossy(wide: &[u16]) -> Self;
+
+    /// Creates an `OsString` from the code units of `wide` up to the
+    /// first null, or from all of `wide` if it contains no null.
+    ///
+    /// This is useful for buffers filled in by Win32 functions.  Like
+    /// `from_wide`, it is lossless.
+    fn from_wide_null(wide: &[u16]) -> Self;
+
+    /// Creates an `OsString` from its WTF-8 representation.
+    fn from_wtf8_buf(buf: Wtf8Buf) -> Self;
+
+    /// Creates an `OsString` from bytes that are expected to be
+    /// well-formed WTF-8, such as the output of `as_encoded_bytes`.
+    ///
+    /// The vector is reused without copying.
+    fn from_wtf8(bytes: Vec<u8>) -> Result<Self, Wtf8Error> where Self: Sized;
+
+    /// Yields the underlying WTF-8 buffer of this `OsString`.
+    fn into_wtf8_buf(self) -> Wtf8Buf;
 }
 
 impl OsStringExt for OsString {
     fn from_wide(wide: &[u16]) -> OsString {
         FromInner::from_inner(Buf { inner: Wtf8Buf::from_wide(wide) })
     }
+    fn from_wide_lossy(wide: &[u16]) -> OsString {
+        FromInner::from_inner(Buf { inner: Wtf8Buf::from_wide_lossy(wide) })
+    }
+    fn from_wide_null(wide: &[u16]) -> OsString {
+        let len = wide.iter().position(|&c| c == 0).unwrap_or(wide.len());
+        OsString::from_wide(&wide[..len])
+    }
+    fn from_wtf8_buf(buf: Wtf8Buf) -> OsString {
+        FromInner::from_inner(Buf { inner: buf })
+    }
+    fn from_wtf8(bytes: Vec<u8>) -> Result<OsString, Wtf8Error> {
+        Wtf8Buf::from_bytes(bytes).map(OsString::from_wtf8_buf)
+    }
+    fn into_wtf8_buf(self) -> Wtf8Buf {
+        self.into_inner().inner
+    }
 }
 
 /// Windows-specific extensions to `OsStr`.
@@ -41,10 +84,29 @@ pub trait OsStrExt {
     /// This is lossless. Note that the encoding does not include a final
     /// null.
     fn encode_wide(&self) -> EncodeWide;
+
+    /// Creates an `OsStr` slice from its WTF-8 representation.
+    fn from_wtf8_slice(wtf8: &Wtf8) -> &Self;
+
+    /// Creates an `OsStr` slice from bytes that are expected to be
+    /// well-formed WTF-8, without copying.
+    fn from_wtf8(bytes: &[u8]) -> Result<&Self, Wtf8Error>;
+
+    /// Gets the underlying WTF-8 view of the `OsStr` slice.
+    fn as_wtf8(&self) -> &Wtf8;
 }
 
 impl OsStrExt for OsStr {
     fn encode_wide(&self) -> EncodeWide {
         self.as_inner().inner.encode_wide()
     }
+    fn from_wtf8_slice(wtf8: &Wtf8) -> &OsStr {
+        unsafe { mem::transmute(wtf8) }
+    }
+    fn from_wtf8(bytes: &[u8]) -> Result<&OsStr, Wtf8Error> {
+        Wtf8::from_bytes(bytes).map(OsStr::from_wtf8_slice)
+    }
+    fn as_wtf8(&self) -> &Wtf8 {
+        &self.as_inner().inner
+    }
 }
@@ -14,36 +14,44 @@
 //! [well-formedness](https://simonsapin.github.io/wtf-8/#well-formed),
 //! like the `String` and `&str` types do for UTF-8.
 //!
-//! Since [WTF-8 must not be used
-//! for interchange](https://simonsapin.github.io/wtf-8/#intended-audience),
-//! this library deliberately does not provide access to the underlying bytes
-//! of WTF-8 strings,
-//! nor can it decode WTF-8 from arbitrary bytes.
-//! WTF-8 strings can be obtained from UTF-8, UTF-16, or code points.
+//! This module is private; `Wtf8`, `Wtf8Buf`, `CodePoint` and `Wtf8Error`
+//! are re-exported from the crate root, and the other items are helpers
+//! for the rest of the crate.
+//! WTF-8 strings can be obtained from UTF-8, UTF-16, or code points,
+//! or decoded from bytes with `Wtf8::from_bytes`, which checks well-formedness.
+//!
+//! On Windows the prototype `OsStr` and `OsString` are backed by these types;
+//! see `windows::OsStrExt` and `windows::OsStringExt` for conversions, and
+//! `OsStr::as_encoded_bytes` for the underlying bytes.
 
 // this module is imported from @SimonSapin's repo and has tons of dead code on
 // unix (it's mostly used on windows), so don't worry about dead code here.
 #![allow(dead_code)]
 
+use os_pattern::{DoubleEndedOsSearcher, OsPattern, OsSearcher, ReverseOsSearcher, UnicodeSearcher};
 use split_bytes;
 use utf8_sections::{self, Utf8Sections};
 
 use core::str::next_code_point;
 
 use str::next_code_point_reverse;
-use slice_searcher::SliceSearcher;
+use slice_searcher::{ReverseSliceSearcher, SliceSearcher};
 
 use std::ascii::*;
 use std::borrow::{Borrow, Cow, ToOwned};
+use std::boxed::Box;
 use std::char;
+use std::cmp;
+#[cfg(feature = "std")]
+use std::error;
 use std::fmt;
 use std::hash::{Hash, Hasher};
-use std::iter::{self, FromIterator};
+use std::iter::{self, FromIterator, FusedIterator};
 use std::mem;
 use std::ops;
 use std::slice;
 use std::str;
-use core::str::pattern::{DoubleEndedSearcher, Pattern, ReverseSearcher, Searcher};
+use core::str::pattern::{DoubleEndedSearcher, Pattern, ReverseSearcher};
 use std::string::String;
 //use std::sys_common::AsInner;
 use std::vec::Vec;
@@ -60,7 +68,7 @@ const MAX_TWO_B: u32   =    0x800;
 const MAX_THREE_B: u32 =  0x10000;
 
 #[inline]
-fn encode_utf8_raw(code: u32, dst: &mut [u8]) -> Option<usize> {
+pub fn encode_utf8_raw(code: u32, dst: &mut [u8]) -> Option<usize> {
     // Marked #[inline] to allow llvm optimizing it away
     if code < MAX_ONE_B && !dst.is_empty() {
         dst[0] = code as u8;
@@ -279,6 +287,15 @@ impl Wtf8Buf {
         Wtf8Buf { bytes: <[_]>::to_vec(str.as_bytes()) }
     }
 
+    /// Creates a WTF-8 string from a byte vector, checking that the
+    /// bytes are well-formed WTF-8 (see `Wtf8::from_bytes`).
+    ///
+    /// The vector is reused without copying.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Wtf8Buf, Wtf8Error> {
+        try!(Wtf8::from_bytes(&bytes));
+        Ok(Wtf8Buf { bytes: bytes })
+    }
+
     /// Creates a WTF-8 string from a potentially ill-formed UTF-16 slice of 16-bit code units.
     ///
     /// This is lossless: calling `.encode_wide()` on the resulting string
@@ -300,6 +317,18 @@ impl Wtf8Buf {
         string
     }
 
+    /// Creates a WTF-8 string from a potentially ill-formed UTF-16 slice of 16-bit code units,
+    /// replacing unpaired surrogates with U+FFFD REPLACEMENT CHARACTER.
+    ///
+    /// The resulting string is always well-formed UTF-8.
+    pub fn from_wide_lossy(v: &[u16]) -> Wtf8Buf {
+        let mut string = Wtf8Buf::with_capacity(v.len());
+        for item in char::decode_utf16(v.iter().cloned()) {
+            string.push_char(item.unwrap_or('\u{FFFD}'));
+        }
+        string
+    }
+
     /// Copied from String::push
     /// This does **not** include the WTF-8 concatenation check.
     fn push_code_point_unchecked(&mut self, code_point: CodePoint) {
@@ -323,6 +352,11 @@ impl Wtf8Buf {
         unsafe { Wtf8::from_bytes_unchecked(&self.bytes) }
     }
 
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut Wtf8 {
+        unsafe { Wtf8::from_mut_bytes_unchecked(&mut self.bytes) }
+    }
+
     /// Reserves capacity for at least `additional` more bytes to be inserted
     /// in the given `Wtf8Buf`.
     /// The collection may reserve more space to avoid frequent reallocations.
@@ -347,6 +381,24 @@ impl Wtf8Buf {
         self.bytes.reserve_exact(additional)
     }
 
+    /// Shrinks the capacity of the string to match its length.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.bytes.shrink_to_fit()
+    }
+
+    /// Shrinks the capacity of the string to the larger of its length
+    /// and `min_capacity`.  Does nothing if the capacity is already
+    /// that small.
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        let capacity = cmp::max(self.bytes.len(), min_capacity);
+        if self.bytes.capacity() > capacity {
+            let mut bytes = Vec::with_capacity(capacity);
+            bytes.extend_from_slice(&self.bytes);
+            self.bytes = bytes;
+        }
+    }
+
     /// Returns the number of bytes that this string buffer can hold without reallocating.
     #[inline]
     pub fn capacity(&self) -> usize {
@@ -407,6 +459,65 @@ impl Wtf8Buf {
         self.push_code_point_unchecked(code_point)
     }
 
+    /// Retains only the characters for which `keep_char` returns true
+    /// and the runs of surrogates for which `keep_surrogates` returns
+    /// true.  This works in place, without allocating.
+    ///
+    /// Surrogates that become paired are replaced by a supplementary
+    /// code point.
+    pub fn retain<F, G>(&mut self, mut keep_char: F, mut keep_surrogates: G)
+    where F: FnMut(char) -> bool, G: FnMut(&Wtf8) -> bool {
+        let len = self.bytes.len();
+        let mut read = 0;
+        let mut write = 0;
+        while read < len {
+            let section_end = read + match str::from_utf8(&self.bytes[read..]) {
+                Ok(s) => s.len(),
+                Err(error) => error.valid_up_to(),
+            };
+            while read < section_end {
+                let (char_len, keep) = {
+                    let rest = unsafe { str::from_utf8_unchecked(&self.bytes[read..section_end]) };
+                    let c = rest.chars().next().unwrap();
+                    (c.len_utf8(), keep_char(c))
+                };
+                if keep {
+                    move_bytes(&mut self.bytes, read, write, char_len);
+                    write += char_len;
+                }
+                read += char_len;
+            }
+            if read == len { break; }
+
+            // Anything that isn't UTF-8 is a surrogate, which is
+            // always 3 bytes.
+            let mut run_end = read;
+            while run_end < len && self.bytes[run_end] == 0xED && self.bytes[run_end + 1] >= 0xA0 {
+                run_end += 3;
+            }
+            let (keep, lead, trail) = unsafe {
+                let run = Wtf8::from_bytes_unchecked(&self.bytes[read..run_end]);
+                let written = Wtf8::from_bytes_unchecked(&self.bytes[..write]);
+                (keep_surrogates(run), written.final_lead_surrogate(), run.initial_trail_surrogate())
+            };
+            if keep {
+                if let (Some(lead), Some(trail)) = (lead, trail) {
+                    // Replace newly paired surrogates by a supplementary
+                    // code point.  Since something was removed between
+                    // them, the 4 bytes will fit in the 6 that were used.
+                    write -= 3;
+                    read += 3;
+                    let code_point = decode_surrogate_pair(lead, trail) as u32;
+                    write += encode_utf8_raw(code_point, &mut self.bytes[write..]).unwrap();
+                }
+                move_bytes(&mut self.bytes, read, write, run_end - read);
+                write += run_end - read;
+            }
+            read = run_end;
+        }
+        self.bytes.truncate(write);
+    }
+
     /// Shortens a string to the specified length.
     ///
     /// # Panics
@@ -457,6 +568,20 @@ impl Wtf8Buf {
             }
         }
     }
+
+    /// Converts this `Wtf8Buf` into a boxed `Wtf8`, dropping any
+    /// excess capacity.
+    #[inline]
+    pub fn into_box(self) -> Box<Wtf8> {
+        unsafe { mem::transmute(self.bytes.into_boxed_slice()) }
+    }
+
+    /// Converts a boxed `Wtf8` into a `Wtf8Buf` without copying.
+    #[inline]
+    pub fn from_box(boxed: Box<Wtf8>) -> Wtf8Buf {
+        let bytes: Box<[u8]> = unsafe { mem::transmute(boxed) };
+        Wtf8Buf { bytes: bytes.into_vec() }
+    }
 }
 
 /// Create a new WTF-8 string from an iterator of code points.
@@ -487,6 +612,33 @@ impl Extend<CodePoint> for Wtf8Buf {
     }
 }
 
+/// An error returned when bytes are not well-formed WTF-8.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Wtf8Error {
+    valid_up_to: usize,
+}
+
+impl Wtf8Error {
+    /// Returns the length of the longest prefix of the input that is
+    /// well-formed WTF-8.
+    pub fn valid_up_to(&self) -> usize {
+        self.valid_up_to
+    }
+}
+
+impl fmt::Display for Wtf8Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "invalid WTF-8 sequence after {} bytes", self.valid_up_to)
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for Wtf8Error {
+    fn description(&self) -> &str {
+        "invalid WTF-8"
+    }
+}
+
 /// A borrowed slice of well-formed WTF-8 data.
 ///
 /// Similar to `&str`, but can additionally contain surrogate code points
@@ -547,12 +699,50 @@ impl Wtf8 {
         unsafe { Wtf8::from_bytes_unchecked(value.as_bytes()) }
     }
 
+    /// Creates a WTF-8 slice from a byte slice, checking that the
+    /// bytes are well-formed WTF-8.
+    ///
+    /// Well-formed WTF-8 is UTF-8 that may additionally contain
+    /// surrogate code points, as long as no lead surrogate is
+    /// immediately followed by a trail surrogate.
+    pub fn from_bytes(value: &[u8]) -> Result<&Wtf8, Wtf8Error> {
+        let mut rest = value;
+        let mut after_lead_surrogate = false;
+        loop {
+            let valid_up_to = match str::from_utf8(rest) {
+                Ok(_) => return Ok(unsafe { Wtf8::from_bytes_unchecked(value) }),
+                Err(error) => error.valid_up_to(),
+            };
+            if valid_up_to > 0 { after_lead_surrogate = false; }
+            rest = &rest[valid_up_to..];
+            let error = Wtf8Error { valid_up_to: value.len() - rest.len() };
+            if rest.len() < 3 { return Err(error); }
+            let is_lead = match (rest[0], rest[1], rest[2]) {
+                (0xED, 0xA0 ... 0xAF, 0x80 ... 0xBF) => true,
+                (0xED, 0xB0 ... 0xBF, 0x80 ... 0xBF) => false,
+                _ => return Err(error),
+            };
+            if after_lead_surrogate && !is_lead { return Err(error); }
+            after_lead_surrogate = is_lead;
+            rest = &rest[3..];
+        }
+    }
+
     /// Creates a WTF-8 slice from a WTF-8 byte slice.
     ///
     /// Since the byte slice is not checked for valid WTF-8, this functions is
     /// marked unsafe.
     #[inline]
-    unsafe fn from_bytes_unchecked(value: &[u8]) -> &Wtf8 {
+    pub unsafe fn from_bytes_unchecked(value: &[u8]) -> &Wtf8 {
+        mem::transmute(value)
+    }
+
+    /// Creates a mutable WTF-8 slice from a mutable WTF-8 byte slice.
+    ///
+    /// Since the byte slice is not checked for valid WTF-8, this functions is
+    /// marked unsafe.
+    #[inline]
+    unsafe fn from_mut_bytes_unchecked(value: &mut [u8]) -> &mut Wtf8 {
         mem::transmute(value)
     }
 
@@ -730,6 +920,40 @@ impl Wtf8 {
         return false;
     }
 
+    /// Returns the byte index of the first exact occurrence of
+    /// `needle` in `self`.
+    ///
+    /// Unlike `contains_wtf8`, this does not find surrogates that
+    /// are half of a surrogate pair in `self`, since that would not
+    /// be at a code point boundary.
+    pub fn find_wtf8(&self, needle: &Wtf8) -> Option<usize> {
+        // needle starts and ends on code point boundaries, so any
+        // match will too.
+        SliceSearcher::new(&self.bytes, &needle.bytes, false).next()
+    }
+
+    /// Returns the byte index of the last exact occurrence of
+    /// `needle` in `self`.  See `find_wtf8`.
+    pub fn rfind_wtf8(&self, needle: &Wtf8) -> Option<usize> {
+        ReverseSliceSearcher::new(&self.bytes, &needle.bytes, false).next()
+    }
+
+    /// Splits `self` around the first exact occurrence of `needle`.
+    /// See `find_wtf8`.
+    pub fn split_once_wtf8(&self, needle: &Wtf8) -> Option<(&Wtf8, &Wtf8)> {
+        self.find_wtf8(needle).map(|start| {
+            (&self[..start], &self[start + needle.len()..])
+        })
+    }
+
+    /// Splits `self` around the last exact occurrence of `needle`.
+    /// See `find_wtf8`.
+    pub fn rsplit_once_wtf8(&self, needle: &Wtf8) -> Option<(&Wtf8, &Wtf8)> {
+        self.rfind_wtf8(needle).map(|start| {
+            (&self[..start], &self[start + needle.len()..])
+        })
+    }
+
     /// Returns true if the ill-formed UTF-16 data corresponding to
     /// `needle` is a prefix of the data corresponding to `self`.
     pub fn starts_with_wtf8(&self, needle: &Wtf8) -> bool {
@@ -779,7 +1003,7 @@ impl Wtf8 {
     ///
     /// Note that patterns can only match UTF-8 sections.
     pub fn split<'a, P>(&'a self, pat: P) -> Split<'a, P>
-    where P: Pattern<'a> + Clone {
+    where P: Pattern<'a> + OsPattern<'a, Searcher = UnicodeSearcher<'a, P>> {
         Split { inner: split_bytes::Split::new(&self.bytes, pat) }
     }
 
@@ -789,7 +1013,8 @@ impl Wtf8 {
     ///
     /// Note that patterns can only match UTF-8 sections.
     pub fn rsplit<'a, P>(&'a self, pat: P) -> RSplit<'a, P>
-    where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
+    where P: Pattern<'a> + OsPattern<'a, Searcher = UnicodeSearcher<'a, P>>,
+          UnicodeSearcher<'a, P>: ReverseOsSearcher<'a> {
         RSplit { inner: split_bytes::RSplit::new(&self.bytes, pat) }
     }
 
@@ -798,7 +1023,7 @@ impl Wtf8 {
     ///
     /// Note that patterns can only match UTF-8 sections.
     pub fn split_terminator<'a, P>(&'a self, pat: P) -> SplitTerminator<'a, P>
-    where P: Pattern<'a> + Clone {
+    where P: Pattern<'a> + OsPattern<'a, Searcher = UnicodeSearcher<'a, P>> {
         SplitTerminator { inner: split_bytes::SplitTerminator::new(&self.bytes, pat) }
     }
 
@@ -807,7 +1032,8 @@ impl Wtf8 {
     ///
     /// Note that patterns can only match UTF-8 sections.
     pub fn rsplit_terminator<'a, P>(&'a self, pat: P) -> RSplitTerminator<'a, P>
-    where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
+    where P: Pattern<'a> + OsPattern<'a, Searcher = UnicodeSearcher<'a, P>>,
+          UnicodeSearcher<'a, P>: ReverseOsSearcher<'a> {
         RSplitTerminator { inner: split_bytes::RSplitTerminator::new(&self.bytes, pat) }
     }
 
@@ -817,7 +1043,7 @@ impl Wtf8 {
     ///
     /// Note that patterns can only match UTF-8 sections.
     pub fn splitn<'a, P>(&'a self, count: usize, pat: P) -> SplitN<'a, P>
-    where P: Pattern<'a> + Clone {
+    where P: Pattern<'a> + OsPattern<'a, Searcher = UnicodeSearcher<'a, P>> {
         SplitN { inner: split_bytes::SplitN::new(&self.bytes, count, pat) }
     }
 
@@ -827,7 +1053,8 @@ impl Wtf8 {
     ///
     /// Note that patterns can only match UTF-8 sections.
     pub fn rsplitn<'a, P>(&'a self, count: usize, pat: P) -> RSplitN<'a, P>
-    where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
+    where P: Pattern<'a> + OsPattern<'a, Searcher = UnicodeSearcher<'a, P>>,
+          UnicodeSearcher<'a, P>: ReverseOsSearcher<'a> {
         RSplitN { inner: split_bytes::RSplitN::new(&self.bytes, count, pat) }
     }
 
@@ -836,7 +1063,7 @@ impl Wtf8 {
     ///
     /// Note that patterns can only match UTF-8 sections.
     pub fn matches<'a, P>(&'a self, pat: P) -> Matches<'a, P>
-    where P: Pattern<'a> + Clone {
+    where P: Pattern<'a> + OsPattern<'a, Searcher = UnicodeSearcher<'a, P>> {
         Matches { inner: split_bytes::Matches::new(&self.bytes, pat) }
     }
 
@@ -845,7 +1072,8 @@ impl Wtf8 {
     ///
     /// Note that patterns can only match UTF-8 sections.
     pub fn rmatches<'a, P>(&'a self, pat: P) -> RMatches<'a, P>
-    where P: Pattern<'a> + Clone, P::Searcher: ReverseSearcher<'a> {
+    where P: Pattern<'a> + OsPattern<'a, Searcher = UnicodeSearcher<'a, P>>,
+          UnicodeSearcher<'a, P>: ReverseOsSearcher<'a> {
         RMatches { inner: split_bytes::RMatches::new(&self.bytes, pat) }
     }
 
@@ -877,9 +1105,16 @@ impl Wtf8 {
     }
 
     /// Replaces all occurrences of one string with another.
+    pub fn replace<T: AsRef<Wtf8>, U: AsRef<Wtf8>>(&self, from: T, to: U) -> Wtf8Buf {
+        self.try_replace(from, to).unwrap_or_else(|| self.to_owned())
+    }
+
+    /// Replaces all occurrences of one string with another, or
+    /// returns `None` without allocating if there are none.
     // TODO: Benchmark this against just round-tripping through UTF-16
     // once I have a decent byte substring searcher.
-    pub fn replace<T: AsRef<Wtf8>, U: AsRef<Wtf8>>(&self, from: T, to: U) -> Wtf8Buf {
+    pub fn try_replace<T: AsRef<Wtf8>, U: AsRef<Wtf8>>(&self, from: T, to: U)
+                                                       -> Option<Wtf8Buf> {
         let from = from.as_ref();
         let to = to.as_ref();
 
@@ -898,7 +1133,7 @@ impl Wtf8 {
         // Match only surrogates that could be part of non-BMP characters
         fn match_surrogates(source: &Wtf8,
                             surrogates: (Option<u16>, Option<u16>),
-                            to: &Wtf8) -> Wtf8Buf {
+                            to: &Wtf8) -> Option<Wtf8Buf> {
             let code_units = source.encode_wide();
             match surrogates {
                 (None, None) => {
@@ -909,10 +1144,11 @@ impl Wtf8 {
                         result.push(cu_to_cp(cu));
                         result.push_wtf8(to);
                     }
-                    return result;
+                    return Some(result);
                 }
                 (Some(from_cu), None) | (None, Some(from_cu)) => {
                     // One code unit
+                    if !code_units.clone().any(|cu| cu == from_cu) { return None; }
                     let mut result = Wtf8Buf::new();
                     for cu in code_units {
                         if cu == from_cu {
@@ -921,9 +1157,11 @@ impl Wtf8 {
                             result.push(cu_to_cp(cu));
                         }
                     }
-                    return result;
+                    return Some(result);
                 }
                 (Some(trail), Some(lead)) => {
+                    let mut pairs = code_units.clone().zip(code_units.clone().skip(1));
+                    if !pairs.any(|pair| pair == (trail, lead)) { return None; }
                     let mut code_units = code_units.peekable();
                     let mut result = Wtf8Buf::new();
                     while let Some(cu) = code_units.next() {
@@ -934,7 +1172,7 @@ impl Wtf8 {
                             result.push(cu_to_cp(cu));
                         }
                     }
-                    return result;
+                    return Some(result);
                 }
             }
         }
@@ -943,7 +1181,7 @@ impl Wtf8 {
         // representation, and possibly some surrogates on the ends
         fn match_fixed(mut source: &Wtf8,
                        middle: &Wtf8, (start, end): (Option<u16>, Option<u16>),
-                       to: &Wtf8) -> Wtf8Buf {
+                       to: &Wtf8) -> Option<Wtf8Buf> {
             // Search for the fixed middle string, and check if the
             // ends agree for each match.
 
@@ -962,6 +1200,7 @@ impl Wtf8 {
             }
 
             let mut result = Wtf8Buf::new();
+            let mut replaced = false;
             // Any extra trail surrogate at the beginning of our
             // source resulting from splitting a non-BMP character.
             let mut extra_surrogate = None;
@@ -1018,6 +1257,7 @@ impl Wtf8 {
                     if end_match == MatchType::None { continue; }
 
                     // We have an actual match!
+                    replaced = true;
 
                     // Add any leading non-matching stuff
                     if start_match != MatchType::Leftover {
@@ -1069,12 +1309,14 @@ impl Wtf8 {
                 break;
             }
 
+            if !replaced { return None; }
+
             // Add any extra stuff at the end
             if let Some(surrogate) = extra_surrogate {
                 result.push(cu_to_cp(surrogate));
             }
             result.push_wtf8(source);
-            result
+            Some(result)
         }
 
         // Split from into an optional trail surrogate, other
@@ -1231,6 +1473,14 @@ fn decode_surrogate(second_byte: u8, third_byte: u8) -> u16 {
     0xD800 | (second_byte as u16 & 0x3F) << 6 | third_byte as u16 & 0x3F
 }
 
+/// Moves `len` bytes from `from` to `to` within `bytes`, where `to <= from`.
+fn move_bytes(bytes: &mut [u8], from: usize, to: usize, len: usize) {
+    if from == to { return; }
+    for i in 0..len {
+        bytes[to + i] = bytes[from + i];
+    }
+}
+
 #[inline]
 fn decode_surrogate_pair(lead: u16, trail: u16) -> char {
     let code_point = 0x10000 + ((((lead - 0xD800) as u32) << 10) | (trail - 0xDC00) as u32);
@@ -1247,6 +1497,43 @@ pub fn is_code_point_boundary(slice: &Wtf8, index: usize) -> bool {
     }
 }
 
+/// Converts a piece of a `Wtf8` cut out at `OsSearcher` matches.
+///
+/// This is only for the split iterators in this crate: the check
+/// below is enough only because `piece` is known to come from valid
+/// WTF-8, so it must not be reachable with arbitrary bytes.
+///
+/// # Panics
+///
+/// Panics if the piece does not start and end on code point
+/// boundaries, which can only happen if the searcher breaks its
+/// contract.
+pub fn from_split_piece(piece: &[u8]) -> &Wtf8 {
+    assert!(is_code_point_piece(piece),
+            "OsSearcher match is not on a code point boundary");
+    // A piece of valid WTF-8 cut at code point boundaries is valid WTF-8.
+    unsafe { Wtf8::from_bytes_unchecked(piece) }
+}
+
+// Whether a piece of valid WTF-8 starts and ends on code point boundaries
+fn is_code_point_piece(piece: &[u8]) -> bool {
+    let is_continuation = |b: u8| b & 0xC0 == 0x80;
+    match piece.first() {
+        None => return true,
+        Some(&b) if is_continuation(b) => return false,
+        Some(_) => {}
+    }
+    // The first byte is not a continuation byte, so this is found.
+    let last = piece.iter().rposition(|&b| !is_continuation(b)).unwrap();
+    let width = match piece[last] {
+        0x00...0x7F => 1,
+        0xC0...0xDF => 2,
+        0xE0...0xEF => 3,
+        _ => 4,
+    };
+    piece.len() - last == width
+}
+
 /// Copied from core::str::raw::slice_unchecked
 #[inline]
 pub unsafe fn slice_unchecked(s: &Wtf8, begin: usize, end: usize) -> &Wtf8 {
@@ -1371,29 +1658,34 @@ impl AsciiExt for Wtf8 {
 
 macro_rules! make_iterator {
     ($name:ident requires $bound:ident yielding $map:expr => $ret:ty) => {
-        pub struct $name<'a, P> where P: Pattern<'a> {
+        pub struct $name<'a, P> where P: OsPattern<'a> {
             inner: split_bytes::$name<'a, P>,
         }
 
-        impl<'a, P> Clone for $name<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: Clone {
+        impl<'a, P> Clone for $name<'a, P> where P: OsPattern<'a>, P::Searcher: Clone {
             fn clone(&self) -> Self { $name { inner: self.inner.clone() } }
         }
 
-        impl<'a, P> Iterator for $name<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: $bound<'a> {
+        impl<'a, P> Iterator for $name<'a, P> where P: OsPattern<'a>, P::Searcher: $bound<'a> {
             type Item = $ret;
 
             fn next(&mut self) -> Option<$ret> {
                 self.inner.next().map($map)
             }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.inner.size_hint()
+            }
         }
+
+        impl<'a, P> FusedIterator for $name<'a, P>
+        where P: OsPattern<'a>, P::Searcher: $bound<'a> {}
     };
     ($name:ident requires $bound:ident is double ended yielding $map:expr => $ret:ty) => {
         make_iterator!{$name requires $bound yielding $map => $ret}
 
         impl<'a, P> DoubleEndedIterator for $name<'a, P>
-        where P: Pattern<'a> + Clone, P::Searcher: DoubleEndedSearcher<'a> {
+        where P: OsPattern<'a>, P::Searcher: DoubleEndedOsSearcher<'a> {
             fn next_back(&mut self) -> Option<$ret> {
                 self.inner.next_back().map($map)
             }
@@ -1426,6 +1718,10 @@ impl<'a> Iterator for SplitUnicode<'a> {
     fn next(&mut self) -> Option<Section<'a>> {
         self.0.next().map(|x| x.into())
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
 }
 
 impl<'a> DoubleEndedIterator for SplitUnicode<'a> {
@@ -1434,21 +1730,52 @@ impl<'a> DoubleEndedIterator for SplitUnicode<'a> {
     }
 }
 
+impl<'a> FusedIterator for SplitUnicode<'a> {}
+
+
+make_iterator!{Split requires OsSearcher is double ended
+               yielding from_split_piece => &'a Wtf8}
+make_iterator!{RSplit requires ReverseOsSearcher is double ended
+               yielding from_split_piece => &'a Wtf8}
+make_iterator!{SplitTerminator requires OsSearcher is double ended
+               yielding from_split_piece => &'a Wtf8}
+make_iterator!{RSplitTerminator requires ReverseOsSearcher is double ended
+               yielding from_split_piece => &'a Wtf8}
+make_iterator!{SplitN requires OsSearcher
+               yielding from_split_piece => &'a Wtf8}
+make_iterator!{RSplitN requires ReverseOsSearcher
+               yielding from_split_piece => &'a Wtf8}
+// These are only created with `str` patterns, which only match UTF-8.
+make_iterator!{Matches requires OsSearcher is double ended
+               yielding |x| unsafe { str::from_utf8_unchecked(x) } => &'a str}
+make_iterator!{RMatches requires ReverseOsSearcher is double ended
+               yielding |x| unsafe { str::from_utf8_unchecked(x) } => &'a str}
+
+macro_rules! make_remainder {
+    ($($name:ident)*) => { $(
+        impl<'a, P> $name<'a, P> where P: OsPattern<'a> {
+            pub fn remainder(&self) -> Option<&'a Wtf8> {
+                self.inner.remainder().map(from_split_piece)
+            }
+        }
+    )* }
+}
 
-make_iterator!{Split requires Searcher is double ended
-               yielding |s| unsafe { Wtf8::from_bytes_unchecked(s) } => &'a Wtf8}
-make_iterator!{RSplit requires ReverseSearcher is double ended
-               yielding |s| unsafe { Wtf8::from_bytes_unchecked(s) } => &'a Wtf8}
-make_iterator!{SplitTerminator requires Searcher is double ended
-               yielding |s| unsafe { Wtf8::from_bytes_unchecked(s) } => &'a Wtf8}
-make_iterator!{RSplitTerminator requires ReverseSearcher is double ended
-               yielding |s| unsafe { Wtf8::from_bytes_unchecked(s) } => &'a Wtf8}
-make_iterator!{SplitN requires Searcher
-               yielding |s| unsafe { Wtf8::from_bytes_unchecked(s) } => &'a Wtf8}
-make_iterator!{RSplitN requires ReverseSearcher
-               yielding |s| unsafe { Wtf8::from_bytes_unchecked(s) } => &'a Wtf8}
-make_iterator!{Matches requires Searcher is double ended yielding |x| x => &'a str}
-make_iterator!{RMatches requires ReverseSearcher is double ended yielding |x| x => &'a str}
+make_remainder!{Split RSplit SplitTerminator RSplitTerminator SplitN RSplitN}
+
+impl<'a, P> DoubleEndedIterator for SplitN<'a, P>
+where P: OsPattern<'a>, P::Searcher: DoubleEndedOsSearcher<'a> + Clone {
+    fn next_back(&mut self) -> Option<&'a Wtf8> {
+        self.inner.next_back().map(from_split_piece)
+    }
+}
+
+impl<'a, P> DoubleEndedIterator for RSplitN<'a, P>
+where P: OsPattern<'a>, P::Searcher: DoubleEndedOsSearcher<'a> + Clone {
+    fn next_back(&mut self) -> Option<&'a Wtf8> {
+        self.inner.next_back().map(from_split_piece)
+    }
+}
 
 
 #[cfg(test)]
@@ -1564,6 +1891,16 @@ mod tests {
                    b"a\xC3\xA9 \xED\xA0\xBD\xF0\x9F\x92\xA9");
     }
 
+    #[test]
+    fn wtf8buf_from_wide_lossy() {
+        assert_eq!(Wtf8Buf::from_wide_lossy(&[]).bytes, b"");
+        assert_eq!(Wtf8Buf::from_wide_lossy(
+                      &[0x61, 0xE9, 0x20, 0xD83D, 0xD83D, 0xDCA9]).bytes,
+                   b"a\xC3\xA9 \xEF\xBF\xBD\xF0\x9F\x92\xA9");
+        assert_eq!(Wtf8Buf::from_wide_lossy(&[0xDCA9, 0xD83D]).bytes,
+                   b"\xEF\xBF\xBD\xEF\xBF\xBD");
+    }
+
     #[test]
     fn wtf8buf_push_str() {
         let mut string = Wtf8Buf::new();
@@ -1677,6 +2014,24 @@ mod tests {
         assert_eq!(string.bytes, b"a");
     }
 
+    #[test]
+    fn code_point_pieces() {
+        assert!(is_code_point_piece(b""));
+        assert!(is_code_point_piece(b"a"));
+        assert!(is_code_point_piece("é💩".as_bytes()));
+        assert!(is_code_point_piece(b"\xED\xA0\xBD"));
+        assert!(!is_code_point_piece(&"é".as_bytes()[1..]));
+        assert!(!is_code_point_piece(&"aé".as_bytes()[..2]));
+        assert!(!is_code_point_piece(&"💩".as_bytes()[..3]));
+        assert!(!is_code_point_piece(b"\xED\xA0"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_piece_inside_code_point() {
+        from_split_piece(&"💩".as_bytes()[1..]);
+    }
+
     #[test]
     #[should_panic]
     fn wtf8buf_truncate_fail_code_point_boundary() {
@@ -1776,6 +2131,38 @@ mod tests {
         assert_eq!(&Wtf8::from_str("aé 💩").bytes, b"a\xC3\xA9 \xF0\x9F\x92\xA9");
     }
 
+    #[test]
+    fn wtf8_from_bytes() {
+        fn valid_up_to(bytes: &[u8]) -> Option<usize> {
+            Wtf8::from_bytes(bytes).err().map(|e| e.valid_up_to())
+        }
+
+        assert_eq!(&Wtf8::from_bytes(b"").unwrap().bytes, b"");
+        assert_eq!(&Wtf8::from_bytes(b"a\xC3\xA9 \xF0\x9F\x92\xA9").unwrap().bytes,
+                   b"a\xC3\xA9 \xF0\x9F\x92\xA9");
+        assert!(Wtf8::from_bytes(b"\xED\xA0\xBD").is_ok());
+        assert!(Wtf8::from_bytes(b"\xED\xB2\xA9a\xED\xA0\xBD").is_ok());
+        assert!(Wtf8::from_bytes(b"\xED\xA0\xBDa\xED\xB2\xA9").is_ok());
+        // Surrogate pairs must be encoded as a single code point
+        assert_eq!(valid_up_to(b"\xED\xA0\xBD\xED\xB2\xA9"), Some(3));
+        assert!(Wtf8::from_bytes(b"\xED\xB2\xA9\xED\xA0\xBD").is_ok());
+        assert_eq!(valid_up_to(b"\xFF"), Some(0));
+        assert_eq!(valid_up_to(b"a\xC3"), Some(1));
+        assert_eq!(valid_up_to(b"ab\xED\xA0"), Some(2));
+        assert_eq!(valid_up_to(b"\xC0\x80"), Some(0));
+        assert_eq!(valid_up_to(b"\xED\xA0\xBDa\xFF"), Some(4));
+    }
+
+    #[test]
+    fn wtf8buf_from_bytes() {
+        let bytes = b"a\xED\xA0\xBD".to_vec();
+        let ptr = bytes.as_ptr();
+        let string = Wtf8Buf::from_bytes(bytes).unwrap();
+        assert_eq!(string.bytes.as_ptr(), ptr);
+        assert_eq!(Wtf8Buf::from_bytes(b"a\xFF".to_vec()).map_err(|e| e.valid_up_to()),
+                   Err(1));
+    }
+
     #[test]
     fn wtf8_len() {
         assert_eq!(Wtf8::from_str("").len(), 0);
@@ -2081,6 +2468,62 @@ mod tests {
                       &[0xD83D, 0xDE3A, 0xD83D, 0xDE3A, 0xD83D        ]));
     }
 
+    #[test]
+    fn wtf8_find_wtf8() {
+        let string = Wtf8::from_str("a=é=💩");
+        assert_eq!(string.find_wtf8(Wtf8::from_str("=")), Some(1));
+        assert_eq!(string.rfind_wtf8(Wtf8::from_str("=")), Some(4));
+        assert_eq!(string.find_wtf8(Wtf8::from_str("")), Some(0));
+        assert_eq!(string.rfind_wtf8(Wtf8::from_str("")), Some(9));
+        assert_eq!(string.find_wtf8(Wtf8::from_str("b")), None);
+        assert_eq!(string.rfind_wtf8(Wtf8::from_str("b")), None);
+        assert_eq!(Wtf8::from_str("").rfind_wtf8(Wtf8::from_str("")), Some(0));
+        assert_eq!(Wtf8::from_str("aaa").rfind_wtf8(Wtf8::from_str("aa")), Some(1));
+
+        let mut needle = Wtf8Buf::new();
+        needle.push(CodePoint::from_u32(0xDCA9).unwrap());
+        assert_eq!(Wtf8::from_str("💩").find_wtf8(&needle), None);
+        assert_eq!(Wtf8::from_str("💩").rfind_wtf8(&needle), None);
+    }
+
+    #[test]
+    fn wtf8_split_once_wtf8() {
+        let string = Wtf8::from_str("a=é=💩");
+        assert_eq!(string.split_once_wtf8(Wtf8::from_str("=")),
+                   Some((Wtf8::from_str("a"), Wtf8::from_str("é=💩"))));
+        assert_eq!(string.split_once_wtf8(Wtf8::from_str("")),
+                   Some((Wtf8::from_str(""), string)));
+        assert_eq!(string.split_once_wtf8(Wtf8::from_str("b")), None);
+
+        let mut string = Wtf8Buf::from_str("a");
+        string.push(CodePoint::from_u32(0xD83D).unwrap());
+        string.push_str("b");
+        let mut needle = Wtf8Buf::new();
+        needle.push(CodePoint::from_u32(0xD83D).unwrap());
+        assert_eq!(string.split_once_wtf8(&needle),
+                   Some((Wtf8::from_str("a"), Wtf8::from_str("b"))));
+        assert_eq!(Wtf8::from_str("💩").split_once_wtf8(&needle), None);
+    }
+
+    #[test]
+    fn wtf8_rsplit_once_wtf8() {
+        let string = Wtf8::from_str("a=é=💩");
+        assert_eq!(string.rsplit_once_wtf8(Wtf8::from_str("=")),
+                   Some((Wtf8::from_str("a=é"), Wtf8::from_str("💩"))));
+        assert_eq!(string.rsplit_once_wtf8(Wtf8::from_str("")),
+                   Some((string, Wtf8::from_str(""))));
+        assert_eq!(string.rsplit_once_wtf8(Wtf8::from_str("b")), None);
+
+        let mut string = Wtf8Buf::from_str("a");
+        string.push(CodePoint::from_u32(0xDCA9).unwrap());
+        string.push_str("b");
+        let mut needle = Wtf8Buf::new();
+        needle.push(CodePoint::from_u32(0xDCA9).unwrap());
+        assert_eq!(string.rsplit_once_wtf8(&needle),
+                   Some((Wtf8::from_str("a"), Wtf8::from_str("b"))));
+        assert_eq!(Wtf8::from_str("💩").rsplit_once_wtf8(&needle), None);
+    }
+
     #[test]
     fn wtf8_starts_with_wtf8() {
         assert!(Wtf8::from_str("aé 💩").starts_with_wtf8(Wtf8::from_str("aé")));
@@ -2567,6 +3010,30 @@ mod tests {
                    fw([0x0040, 0x0393, 0x0393, 0x0042]));
     }
 
+    #[test]
+    fn wtf8_try_replace() {
+        fn fw<T: AsRef<[u16]>>(wide: T) -> Wtf8Buf { Wtf8Buf::from_wide(wide.as_ref()) }
+        let string = fw([0x0061, 0xD83D, 0xDE3A, 0xDE3B]);
+        assert_eq!(string.try_replace(fw([]), fw([0x0393])),
+                   Some(fw([0x0393, 0x0061, 0x0393, 0xD83D, 0x0393, 0xDE3A,
+                            0x0393, 0xDE3B, 0x0393])));
+        assert_eq!(string.try_replace(fw([0x0062]), fw([0x0393])), None);
+        assert_eq!(string.try_replace(fw([0x0061]), fw([0x0393])),
+                   Some(fw([0x0393, 0xD83D, 0xDE3A, 0xDE3B])));
+        // One surrogate
+        assert_eq!(string.try_replace(fw([0xD83E]), fw([0x0393])), None);
+        assert_eq!(string.try_replace(fw([0xD83D]), fw([0x0393])),
+                   Some(fw([0x0061, 0x0393, 0xDE3A, 0xDE3B])));
+        // Two surrogates
+        assert_eq!(string.try_replace(fw([0xDE3B, 0xD83D]), fw([0x0393])), None);
+        assert_eq!(fw([0xDE3B, 0xD83D]).try_replace(fw([0xDE3B, 0xD83D]), fw([0x0393])),
+                   Some(fw([0x0393])));
+        // Surrogates around a fixed middle
+        assert_eq!(string.try_replace(fw([0xDE3B, 0x0061]), fw([0x0393])), None);
+        assert_eq!(string.try_replace(fw([0xDE3A, 0xDE3B]), fw([0x0393])),
+                   Some(fw([0x0061, 0xD83D, 0x0393])));
+    }
+
     #[test]
     fn wtf8_replace_wtf8_and_two_surrogates() {
         // Replace something strting and ending with unpaired